@@ -0,0 +1,92 @@
+//! keygen, proving and verification of the two fibonacci layouts, for the
+//! same sequence length at each k, and of a batch of proofs verified one by
+//! one against at once
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fibo::{
+    fibonacci::{public_inputs::PublicInputs, row_based, single_column},
+    params_cache,
+    proving::{self, Proof},
+};
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{Circuit, ProvingKey},
+    poly::commitment::Params,
+};
+use rand_core::OsRng;
+
+const KS: [u32; 2] = [8, 12];
+/// proofs in the verified batch
+const BATCH: usize = 10;
+
+fn params(k: u32) -> Params<EqAffine> {
+    params_cache::load_or_generate(k, env!("CARGO_TARGET_TMPDIR")).unwrap()
+}
+
+fn bench_layout<C: Circuit<Fp>>(c: &mut Criterion, name: &str, circuit: impl Fn(usize) -> C) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+
+    for k in KS {
+        let params = params(k);
+        // the longest sequence the single column layout fits, which the row
+        // based one fits as well
+        let n = single_column::max_rows::<Fp>(k);
+        let one = Fp::from(1);
+        let publics = PublicInputs::new(one, one, n).to_instance_column();
+
+        let pk: ProvingKey<EqAffine> = proving::keygen(&params, &circuit(n)).unwrap();
+        let proof = proving::prove(&params, &pk, circuit(n), &[&publics], OsRng).unwrap();
+        println!(
+            "{} k = {} n = {}: proof size {} bytes",
+            name,
+            k,
+            n,
+            proof.as_bytes().len()
+        );
+
+        group.bench_function(BenchmarkId::new("keygen", k), |b| {
+            b.iter(|| proving::keygen(&params, &circuit(n)).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("prove", k), |b| {
+            b.iter(|| proving::prove(&params, &pk, circuit(n), &[&publics], OsRng).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("verify", k), |b| {
+            b.iter(|| verify(&params, &pk, &proof, &publics))
+        });
+
+        let batch: Vec<_> = (0..BATCH)
+            .map(|_| {
+                let proof = proving::prove(&params, &pk, circuit(n), &[&publics], OsRng).unwrap();
+                (proof, publics.clone())
+            })
+            .collect();
+        group.bench_function(BenchmarkId::new("verify_each_of_10", k), |b| {
+            b.iter(|| {
+                for (proof, publics) in &batch {
+                    verify(&params, &pk, proof, publics)
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("verify_batch_of_10", k), |b| {
+            b.iter(|| proving::verify_batch(&params, pk.get_vk(), &batch).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn verify(params: &Params<EqAffine>, pk: &ProvingKey<EqAffine>, proof: &Proof, publics: &[Fp]) {
+    proving::verify(params, pk.get_vk(), proof, &[publics]).unwrap()
+}
+
+fn row_based(c: &mut Criterion) {
+    bench_layout(c, "row_based", row_based::MyCircuit::<Fp>::new);
+}
+
+fn single_column(c: &mut Criterion) {
+    bench_layout(c, "single_column", single_column::MyCircuit::<Fp>::new);
+}
+
+criterion_group!(benches, row_based, single_column);
+criterion_main!(benches);
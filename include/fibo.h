@@ -0,0 +1,31 @@
+/* verifying proofs of the canonical fibonacci circuit, see src/ffi.rs.
+ * build the library with
+ * cargo rustc --release --lib --features ffi --crate-type cdylib */
+
+#ifndef FIBO_H
+#define FIBO_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define FIBO_OK 0
+#define FIBO_ERR_MALFORMED_PROOF -1
+#define FIBO_ERR_INSTANCE_COUNT -2
+#define FIBO_ERR_VERIFICATION -3
+#define FIBO_ERR_MALFORMED_PUBLICS -4
+#define FIBO_ERR_NULL -5
+#define FIBO_ERR_PANIC -6
+
+/* bytes of each public, the little endian repr of an Fp */
+#define FIBO_PUBLIC_BYTES 32
+
+/* verifies the proof_len bytes of a proof against publics_len publics, a, b
+ * and out, each FIBO_PUBLIC_BYTES long. returns FIBO_OK or a FIBO_ERR code */
+int32_t fibo_verify(const uint8_t *proof_ptr, size_t proof_len,
+                    const uint8_t *publics_ptr, size_t publics_len);
+
+/* the reason the last fibo_verify on this thread failed, or NULL after a
+ * success. valid until the next fibo_verify on the thread */
+const char *fibo_last_error_message(void);
+
+#endif
@@ -0,0 +1,196 @@
+//! a credential: the private age is at least the public threshold
+//!
+//! the age is range checked to a byte, then compared to the threshold by the
+//! lt chip. the threshold comes from the instance column, so one proving key
+//! serves every threshold
+
+use crate::{
+    error::FiboError,
+    fibonacci::min_k,
+    gadgets::{
+        byte_decompose::{self, ByteDecomposeChip, ByteDecomposeConfig},
+        lt::{LtChip, LtConfig},
+    },
+};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*};
+
+/// the bits of an age
+pub const BITS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct AgeConfig {
+    pub bytes: ByteDecomposeConfig,
+    pub lt: LtConfig,
+    /// the threshold
+    pub instance: Column<Instance>,
+}
+
+/// proves the private age is at least the threshold in the instance column
+#[derive(Debug, Default)]
+pub struct AgeCircuit<F> {
+    pub age: Value<F>,
+}
+
+impl<F: PrimeField> AgeCircuit<F> {
+    pub fn new(age: F) -> Self {
+        Self {
+            age: Value::known(age),
+        }
+    }
+
+    /// the smallest k fitting the byte table
+    pub fn min_k() -> u32 {
+        // the table is padded with one more row
+        min_k::<F, Self>(byte_decompose::TABLE_ROWS + 1)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for AgeCircuit<F> {
+    type Config = AgeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let table = meta.lookup_table_column();
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        AgeConfig {
+            bytes: ByteDecomposeChip::<F, 1>::configure(meta, [advice[0], advice[1]], table),
+            lt: LtChip::<F, BITS>::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        byte_decompose::load_table(layouter.namespace(|| "table"), config.bytes.table)?;
+
+        let [col_age, col_threshold, ..] = config.lt.advice;
+        let mut at = (0, "age");
+        let (age, threshold) = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    at = (0, "age");
+                    let age = region.assign_advice(|| "age", col_age, 0, || self.age)?;
+                    at = (0, "threshold");
+                    let threshold = region.assign_advice_from_instance(
+                        || "threshold",
+                        config.instance,
+                        0,
+                        col_threshold,
+                        0,
+                    )?;
+                    Ok((age, threshold))
+                },
+            )
+            .map_err(|e| FiboError::assign("load", at, e))?;
+
+        let bytes = ByteDecomposeChip::<F, 1>::construct(config.bytes);
+        bytes.assign(layouter.namespace(|| "age < 2^8"), &age)?;
+
+        let lt = LtChip::<F, BITS>::construct(config.lt);
+        let under = lt.lt(layouter.namespace(|| "age < threshold"), &age, &threshold)?;
+        layouter
+            .assign_region(
+                || "threshold <= age",
+                |mut region| region.constrain_constant(under.cell(), F::ZERO),
+            )
+            .map_err(|e| FiboError::assign("threshold <= age", (0, "lt"), e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    const K: u32 = 9;
+
+    #[test]
+    fn k() {
+        assert_eq!(AgeCircuit::<Fp>::min_k(), K);
+    }
+
+    #[test]
+    fn thresholds() {
+        mock_ok!(AgeCircuit::new(Fp::from(18)), K, vec![vec![Fp::from(18)]]);
+        mock_fails!(AgeCircuit::new(Fp::from(17)), K, vec![vec![Fp::from(18)]]);
+        mock_ok!(AgeCircuit::new(Fp::from(200)), K, vec![vec![Fp::from(18)]]);
+        // the same circuit for another threshold
+        mock_fails!(AgeCircuit::new(Fp::from(200)), K, vec![vec![Fp::from(201)]]);
+    }
+
+    #[test]
+    fn out_of_range() {
+        mock_fails!(AgeCircuit::new(-Fp::one()), K, vec![vec![Fp::from(18)]]);
+        mock_fails!(AgeCircuit::new(Fp::from(256)), K, vec![vec![Fp::from(18)]]);
+    }
+
+    /// the comparison alone, the age checked by nothing but the lt gate: for
+    /// age - threshold = low with top = 1, nothing bounds low
+    struct Unchecked {
+        age: Fp,
+    }
+
+    impl Circuit<Fp> for Unchecked {
+        type Config = AgeConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { age: self.age }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            AgeCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            byte_decompose::load_table(layouter.namespace(|| "table"), config.bytes.table)?;
+
+            let [col_a, col_b, col_low, col_top, col_lt] = config.lt.advice;
+            let lt = layouter.assign_region(
+                || "lt",
+                |mut region| {
+                    config.lt.selector.enable(&mut region, 0)?;
+                    let age = Value::known(self.age);
+                    region.assign_advice(|| "a", col_a, 0, || age)?;
+                    let threshold =
+                        region.assign_advice_from_instance(|| "b", config.instance, 0, col_b, 0)?;
+                    let low = age - threshold.value();
+                    region.assign_advice(|| "low", col_low, 0, || low)?;
+                    region.assign_advice(|| "top", col_top, 0, || Value::known(Fp::one()))?;
+                    region.assign_advice(|| "lt", col_lt, 0, || Value::known(Fp::zero()))
+                },
+            )?;
+            layouter.assign_region(
+                || "threshold <= age",
+                |mut region| region.constrain_constant(lt.cell(), Fp::zero()),
+            )
+        }
+    }
+
+    #[test]
+    fn range_check_is_mandatory() {
+        // p - 1 is no age, but without the range checks it clears any threshold
+        mock_ok!(Unchecked { age: -Fp::one() }, K, vec![vec![Fp::from(18)]]);
+        mock_fails!(AgeCircuit::new(-Fp::one()), K, vec![vec![Fp::from(18)]]);
+        // and so does 17 for 18, low being -1
+        mock_ok!(Unchecked { age: Fp::from(17) }, K, vec![vec![Fp::from(18)]]);
+    }
+}
@@ -0,0 +1,251 @@
+//! a hidden balance covers a public amount: balance >= amount, the balance
+//! left over published only as the commitment poseidon(balance - amount, salt)
+//!
+//! the comparison is the lt chip's, which range checks both sides to 64 bits,
+//! so the subtraction cannot wrap around the field
+
+use crate::{
+    error::FiboError,
+    gadgets::lt::{LtChip, LtConfig},
+    poseidon::{self, poseidon_hash, PoseidonConfig},
+};
+use halo2_proofs::{circuit::*, pasta::Fp, plonk::*, poly::Rotation};
+
+/// the bits of a balance
+pub const BITS: usize = 64;
+/// the k the range checks and the hash fit in
+pub const K: u32 = 8;
+
+/// instance row of the amount
+pub const AMOUNT_ROW: usize = 0;
+/// instance row of the commitment to the new balance
+pub const COMMITMENT_ROW: usize = 1;
+
+/// the commitment to the new balance, for applications to compute off the circuit
+pub fn commit(new_balance: u64, salt: Fp) -> Fp {
+    poseidon_hash([Fp::from(new_balance), salt])
+}
+
+#[derive(Debug, Clone)]
+pub struct BalanceConfig {
+    /// the poseidon state lives in the first three of the comparison columns
+    pub lt: LtConfig,
+    /// balance - amount = new balance on the first three columns
+    pub s_sub: Selector,
+    pub poseidon: PoseidonConfig,
+    pub instance: Column<Instance>,
+}
+
+/// proves the private balance covers the amount in the instance column,
+/// followed by the commitment to what is left
+#[derive(Debug, Default)]
+pub struct BalanceCircuit {
+    pub balance: Value<Fp>,
+    /// blinds the commitment, which a small balance could otherwise be found behind
+    pub salt: Value<Fp>,
+}
+
+impl BalanceCircuit {
+    pub fn new(balance: u64, salt: Fp) -> Self {
+        Self {
+            balance: Value::known(Fp::from(balance)),
+            salt: Value::known(salt),
+        }
+    }
+
+    /// the instance column for the amount and the commitment
+    pub fn instance(amount: u64, commitment: Fp) -> Vec<Fp> {
+        vec![Fp::from(amount), commitment]
+    }
+
+    /// the new balance committed to, witnessed by `new_of(balance - amount)`
+    fn synthesize_with(
+        &self,
+        config: BalanceConfig,
+        mut layouter: impl Layouter<Fp>,
+        new_of: impl FnOnce(Value<Fp>) -> Value<Fp>,
+    ) -> Result<(), FiboError> {
+        let [col_balance, col_amount, col_new, col_salt, _] = config.lt.advice;
+        let mut at = (0, "balance");
+        let (balance, amount, salt) = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    at = (0, "balance");
+                    let balance =
+                        region.assign_advice(|| "balance", col_balance, 0, || self.balance)?;
+                    at = (0, "amount");
+                    let amount = region.assign_advice_from_instance(
+                        || "amount",
+                        config.instance,
+                        AMOUNT_ROW,
+                        col_amount,
+                        0,
+                    )?;
+                    at = (0, "salt");
+                    let salt = region.assign_advice(|| "salt", col_salt, 0, || self.salt)?;
+                    Ok((balance, amount, salt))
+                },
+            )
+            .map_err(|e| FiboError::assign("load", at, e))?;
+
+        let lt = LtChip::<Fp, BITS>::construct(config.lt);
+        let short = lt.lt(layouter.namespace(|| "balance < amount"), &balance, &amount)?;
+
+        let new = new_of(balance.value().copied() - amount.value());
+        let mut at = (0, "selector");
+        let new = layouter
+            .assign_region(
+                || "new balance",
+                |mut region| {
+                    at = (0, "lt");
+                    region.constrain_constant(short.cell(), Fp::zero())?;
+                    at = (0, "selector");
+                    config.s_sub.enable(&mut region, 0)?;
+                    at = (0, "balance");
+                    balance.copy_advice(|| "balance", &mut region, col_balance, 0)?;
+                    at = (0, "amount");
+                    amount.copy_advice(|| "amount", &mut region, col_amount, 0)?;
+                    at = (0, "new");
+                    region.assign_advice(|| "new", col_new, 0, || new)
+                },
+            )
+            .map_err(|e| FiboError::assign("new balance", at, e))?;
+
+        let commitment = poseidon::hash(
+            &config.poseidon,
+            layouter.namespace(|| "commit"),
+            [new, salt],
+        )
+        .map_err(|e| FiboError::assign("commit", (0, "digest"), e))?;
+        layouter
+            .constrain_instance(commitment.cell(), config.instance, COMMITMENT_ROW)
+            .map_err(|e| FiboError::expose(COMMITMENT_ROW, e))
+    }
+}
+
+impl Circuit<Fp> for BalanceCircuit {
+    type Config = BalanceConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        let lt = LtChip::<Fp, BITS>::configure(meta, advice);
+        let s_sub = meta.selector();
+        meta.create_gate("new balance", |meta| {
+            let s = meta.query_selector(s_sub);
+            let [balance, amount, new] =
+                [0, 1, 2].map(|i| meta.query_advice(advice[i], Rotation::cur()));
+            vec![s * (balance - amount - new)]
+        });
+
+        BalanceConfig {
+            lt,
+            s_sub,
+            poseidon: poseidon::configure(meta, [advice[0], advice[1], advice[2]]),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, |new| new)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::dev::VerifyFailure;
+
+    fn salt() -> Fp {
+        Fp::from(0x5a17)
+    }
+
+    fn verifies<C: Circuit<Fp>>(circuit: &C, amount: u64, commitment: Fp) {
+        mock_ok!(
+            *circuit,
+            K,
+            vec![BalanceCircuit::instance(amount, commitment)]
+        );
+    }
+
+    fn fails<C: Circuit<Fp>>(circuit: &C, amount: u64, commitment: Fp) -> Vec<VerifyFailure> {
+        mock_fails!(
+            *circuit,
+            K,
+            vec![BalanceCircuit::instance(amount, commitment)]
+        )
+    }
+
+    #[test]
+    fn sufficient() {
+        let circuit = BalanceCircuit::new(1000, salt());
+        verifies(&circuit, 300, commit(700, salt()));
+        // all of it
+        verifies(&circuit, 1000, commit(0, salt()));
+        let max = BalanceCircuit::new(u64::MAX, salt());
+        verifies(&max, 1, commit(u64::MAX - 1, salt()));
+    }
+
+    #[test]
+    fn insufficient() {
+        // -1 has no commitment the prover could match, so try the one it would claim
+        let circuit = BalanceCircuit::new(1000, salt());
+        let wrapped = poseidon_hash([-Fp::one(), salt()]);
+        fails(&circuit, 1001, wrapped);
+    }
+
+    #[test]
+    fn commitment() {
+        let circuit = BalanceCircuit::new(1000, salt());
+        // another new balance, or the right one under another salt
+        fails(&circuit, 300, commit(701, salt()));
+        fails(&circuit, 300, commit(700, salt() + Fp::one()));
+    }
+
+    /// the balance with another new balance committed to
+    struct Tampered(BalanceCircuit);
+
+    impl Circuit<Fp> for Tampered {
+        type Config = BalanceConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(BalanceCircuit::default())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            BalanceCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let inflated = |new| new + Value::known(Fp::from(5000));
+            Ok(self.0.synthesize_with(config, layouter, inflated)?)
+        }
+    }
+
+    #[test]
+    fn other_new_balance() {
+        // 1000 - 300 committed as 5700: the hash is of 5700, the subtraction is not
+        let circuit = Tampered(BalanceCircuit::new(1000, salt()));
+        let failures = fails(&circuit, 300, commit(5700, salt()));
+        assert_eq!(failures.len(), 1, "{failures:?}");
+        assert!(matches!(
+            &failures[0],
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                if constraint.to_string().contains("new balance")
+        ));
+    }
+}
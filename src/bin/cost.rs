@@ -0,0 +1,45 @@
+//! compares what the fibonacci layouts cost for the same sequence
+
+use fibo::{
+    cost::{measure, CostTable, DebugFieldError},
+    fibonacci::{row_based, single_column, standard_plonk, two_column},
+    planner::WithV1,
+};
+use halo2_proofs::pasta::Fp;
+
+fn main() -> Result<(), DebugFieldError> {
+    let (k, n) = (4, 10);
+
+    let costs = [
+        (
+            "row_based",
+            measure(k, &row_based::MyCircuit::<Fp>::new(n))?,
+        ),
+        (
+            "single_column",
+            measure(k, &single_column::MyCircuit::<Fp>::new(n))?,
+        ),
+        (
+            "two_column",
+            measure(k, &two_column::MyCircuit::<Fp>::new(n))?,
+        ),
+        (
+            "standard_plonk",
+            measure(
+                k,
+                &standard_plonk::PlonkFiboCircuit::new(Fp::one(), Fp::one(), n),
+            )?,
+        ),
+        (
+            "row_based v1",
+            measure(k, &WithV1::new(row_based::MyCircuit::<Fp>::new(n)))?,
+        ),
+        (
+            "single_column v1",
+            measure(k, &WithV1::new(single_column::MyCircuit::<Fp>::new(n)))?,
+        ),
+    ];
+    println!("fibonacci up to n = {}, k = {}", n, k);
+    print!("{}", CostTable(&costs));
+    Ok(())
+}
@@ -0,0 +1,3 @@
+fn main() {
+    fibo::fibonacci::fibo3::run();
+}
@@ -0,0 +1,56 @@
+//! prints a csv of what proving a fibonacci layout costs at every k from the
+//! least it fits in up to --max-k, the sequence filling each size
+
+use clap::{Parser, ValueEnum};
+use fibo::{
+    fibonacci::{row_based, single_column},
+    sweep::{sweep, Layout, SweepRow, CSV_HEADER},
+};
+use halo2_proofs::{pasta::Fp, plonk::Circuit};
+use std::{error::Error, fmt::Debug, path::PathBuf};
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CircuitArg {
+    /// three advice columns, one region per row
+    RowBased,
+    /// one advice column and rotations
+    SingleColumn,
+}
+
+#[derive(Parser)]
+#[command(about = "sweep k for a fibonacci layout, as csv")]
+struct Cli {
+    #[arg(long, value_enum, default_value_t = CircuitArg::RowBased)]
+    circuit: CircuitArg,
+    #[arg(long, default_value_t = 12)]
+    max_k: u32,
+    #[arg(long, default_value = "params")]
+    params_dir: PathBuf,
+}
+
+fn run<C: Circuit<Fp> + Debug>(layout: Layout<C>, cli: &Cli) -> Result<(), Box<dyn Error>> {
+    println!("{}", CSV_HEADER);
+    let print = |row: &SweepRow| println!("{}", row.to_csv());
+    sweep(&layout, cli.max_k, &cli.params_dir, print)?;
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    match cli.circuit {
+        CircuitArg::RowBased => run(
+            Layout {
+                circuit: row_based::MyCircuit::<Fp>::new,
+                max_n: row_based::max_n::<Fp>,
+            },
+            &cli,
+        ),
+        CircuitArg::SingleColumn => run(
+            Layout {
+                circuit: single_column::MyCircuit::<Fp>::new,
+                max_n: single_column::max_rows::<Fp>,
+            },
+            &cli,
+        ),
+    }
+}
@@ -0,0 +1,56 @@
+//! the peak memory of proving the row based and single column fibonacci
+//! circuits at growing k, next to cost::estimate_prover_memory
+
+use fibo::{
+    cost::{estimate_prover_memory, DebugFieldError},
+    fibonacci::{public_inputs::PublicInputs, row_based, single_column},
+    memstats::{prover_peak, CountingAlloc},
+};
+use halo2_proofs::{
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+const KS: [u32; 3] = [8, 12, 14];
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1 << 20) as f64
+}
+
+fn row<C: Circuit<Fp>>(
+    name: &str,
+    k: u32,
+    circuit: impl Fn(usize) -> C,
+) -> Result<(), DebugFieldError> {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    let estimate = estimate_prover_memory(k, &cs)?;
+
+    let n = single_column::max_rows::<Fp>(k);
+    let publics = PublicInputs::new(Fp::one(), Fp::one(), n).to_instance_column();
+    let peak = prover_peak(k, circuit(n), &[&publics]);
+    println!(
+        "{:<14} | {:>2} | {:>10.2} | {:>10.2} | {:>5.2}",
+        name,
+        k,
+        mib(peak),
+        mib(estimate),
+        estimate as f64 / peak as f64
+    );
+    Ok(())
+}
+
+fn main() -> Result<(), DebugFieldError> {
+    println!(
+        "{:<14} | {:>2} | {:>10} | {:>10} | {:>5}",
+        "circuit", "k", "peak MiB", "est. MiB", "ratio"
+    );
+    for k in KS {
+        row("example1", k, row_based::MyCircuit::<Fp>::new)?;
+        row("fibo2", k, single_column::MyCircuit::<Fp>::new)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,36 @@
+//! renders both fibonacci layouts to png: plot_layout [k] [out dir]
+
+use fibo::{
+    fibonacci::{row_based, single_column},
+    layout::render,
+};
+use halo2_proofs::pasta::Fp;
+use std::{env, error::Error, path::PathBuf};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let k: u32 = args.next().map_or(Ok(4), |k| k.parse())?;
+    let dir = PathBuf::from(args.next().unwrap_or_else(|| ".".into()));
+
+    // the longest sequence fitting in 2^k rows
+    let n = single_column::max_rows::<Fp>(k);
+
+    let path = dir.join("row-based-layout.png");
+    render(
+        k,
+        &row_based::MyCircuit::<Fp>::new(n),
+        "Row Based Layout",
+        &path,
+    )?;
+    println!("{}", path.display());
+
+    let path = dir.join("single-column-layout.png");
+    render(
+        k,
+        &single_column::MyCircuit::<Fp>::new(n),
+        "Single Column Layout",
+        &path,
+    )?;
+    println!("{}", path.display());
+    Ok(())
+}
@@ -0,0 +1,3 @@
+fn main() {
+    fibo::fibonacci::single_column::run();
+}
@@ -0,0 +1,109 @@
+//! commit-reveal: the prover knows the x behind the public commitment
+//! c = poseidon(x)
+
+use halo2_proofs::{circuit::*, pasta::Fp, plonk::*};
+
+use crate::{
+    error::FiboError,
+    poseidon::{self, poseidon_hash, PoseidonConfig, WIDTH},
+};
+
+/// the k the poseidon rows fit in
+pub const K: u32 = 6;
+
+/// the commitment to x, for applications to compute off the circuit
+pub fn commit(x: Fp) -> Fp {
+    poseidon_hash([x])
+}
+
+#[derive(Debug, Clone)]
+pub struct CommitRevealConfig {
+    pub x: Column<Advice>,
+    pub poseidon: PoseidonConfig,
+    pub instance: Column<Instance>,
+}
+
+/// proves knowledge of the private x behind the commitment in the instance column
+#[derive(Debug, Default)]
+pub struct CommitRevealCircuit {
+    pub x: Value<Fp>,
+}
+
+impl CommitRevealCircuit {
+    pub fn new(x: Fp) -> Self {
+        Self { x: Value::known(x) }
+    }
+}
+
+impl Circuit<Fp> for CommitRevealCircuit {
+    type Config = CommitRevealConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let state = [(); WIDTH].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        CommitRevealConfig {
+            x: state[0],
+            poseidon: poseidon::configure(meta, state),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let x = layouter
+            .assign_region(
+                || "load x",
+                |mut region| region.assign_advice(|| "x", config.x, 0, || self.x),
+            )
+            .map_err(|e| FiboError::assign("load x", (0, "x"), e))?;
+
+        let c = poseidon::hash(&config.poseidon, layouter.namespace(|| "commit"), [x])?;
+        layouter
+            .constrain_instance(c.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock_fails, mock_ok,
+        proving::{keygen, prove, verify},
+    };
+    use halo2_proofs::{pasta::EqAffine, poly::commitment::Params};
+    use rand_core::OsRng;
+
+    #[test]
+    fn mock() {
+        let x = Fp::from(42);
+        let circuit = CommitRevealCircuit::new(x);
+        mock_ok!(circuit, K, vec![vec![commit(x)]]);
+        mock_fails!(circuit, K, vec![vec![commit(x + Fp::one())]]);
+    }
+
+    #[test]
+    fn real_proof() {
+        let params: Params<EqAffine> = Params::new(K);
+        let pk = keygen(&params, &CommitRevealCircuit::default()).unwrap();
+
+        let x = Fp::from(42);
+        let c = commit(x);
+        let proof = prove(&params, &pk, CommitRevealCircuit::new(x), &[&[c]], OsRng).unwrap();
+        assert!(verify(&params, pk.get_vk(), &proof, &[&[c]]).is_ok());
+
+        // the same proof against another commitment
+        let other = commit(Fp::from(43));
+        assert!(verify(&params, pk.get_vk(), &proof, &[&[other]]).is_err());
+    }
+}
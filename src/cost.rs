@@ -0,0 +1,295 @@
+//! what a circuit costs, as estimated by halo2's CircuitCost, the memory its
+//! prover takes and the least k it fits in
+
+use halo2_proofs::{
+    arithmetic::Field,
+    dev::{CircuitCost, MockProver},
+    pasta::{Eq, Fp},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use std::fmt::{self, Debug};
+use thiserror::Error as ThisError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cost {
+    pub k: u32,
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub lookups: usize,
+    pub permutation_columns: usize,
+    pub max_degree: usize,
+    /// rows used over all columns
+    pub rows: usize,
+    /// proof size added by each extra instance of the circuit, in bytes
+    pub marginal_proof_size: usize,
+    /// size of a proof for a single instance, in bytes
+    pub proof_size: usize,
+}
+
+/// a count halo2 only shows through Debug, missing from the Debug output of
+/// the version built against
+#[derive(Clone, Debug, PartialEq, Eq, ThisError)]
+#[error("the Debug output of {of} has no `{field}` to read")]
+pub struct DebugFieldError {
+    pub of: &'static str,
+    pub field: &'static str,
+}
+
+/// measures `circuit` laid out in 2^k rows. panics if it does not fit
+pub fn measure<C: Circuit<Fp> + Debug>(k: u32, circuit: &C) -> Result<Cost, DebugFieldError> {
+    let cost = CircuitCost::<Eq, C>::measure(k, circuit);
+
+    // CircuitCost keeps its counters private and only shows them through Debug
+    let debug = format!("{:?}", cost);
+    let field = |field| {
+        debug_field(&debug, field).ok_or(DebugFieldError {
+            of: "CircuitCost",
+            field,
+        })
+    };
+
+    Ok(Cost {
+        k,
+        advice_columns: field("num_advice_columns")?,
+        fixed_columns: field("num_fixed_columns")?,
+        instance_columns: field("num_instance_columns")?,
+        lookups: field("lookups")?,
+        permutation_columns: field("permutation_cols")?,
+        max_degree: field("max_deg")?,
+        rows: field("max_rows")?,
+        marginal_proof_size: cost.marginal_proof_size().into(),
+        proof_size: cost.proof_size(1).into(),
+    })
+}
+
+/// reads the number `name: 123` out of a Debug representation, for the
+/// halo2 types which expose nothing else. None if there is no such number
+pub(crate) fn debug_field(debug: &str, name: &str) -> Option<usize> {
+    let pattern = format!(" {}: ", name);
+    let start = debug.find(&pattern)? + pattern.len();
+    let digits = debug[start..].split(|c: char| !c.is_ascii_digit()).next()?;
+    digits.parse().ok()
+}
+
+/// the smallest k up to `k_max` at which MockProver lays `circuit` out
+/// against `instances`, whether or not they satisfy it. below the rows the
+/// constraint system and the instance columns need MockProver fails without
+/// synthesizing, so the search starts there
+pub fn find_min_k<C: Circuit<Fp>>(
+    circuit: &C,
+    instances: &[Vec<Fp>],
+    k_max: u32,
+) -> Result<u32, Error> {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    let instance_rows = instances.iter().map(Vec::len).max().unwrap_or(0);
+    let rows = cs
+        .minimum_rows()
+        .max(instance_rows + cs.blinding_factors() + 1);
+
+    let mut k = rows.next_power_of_two().trailing_zeros();
+    while k <= k_max {
+        match MockProver::run(k, circuit, instances.to_vec()) {
+            Ok(_) => return Ok(k),
+            Err(Error::NotEnoughRowsAvailable { .. } | Error::InstanceTooLarge) => k += 1,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(Error::NotEnoughRowsAvailable { current_k: k_max })
+}
+
+/// bytes of a pasta scalar, and of an affine point of the params
+const SCALAR_BYTES: usize = 32;
+const POINT_BYTES: usize = 64;
+
+/// predicts the peak bytes of generating the params and keys of a circuit
+/// configured into `cs` and proving it in 2^k rows
+///
+/// each column is held as its n values, its n coefficients and its values
+/// over the extended domain on which the quotient is evaluated: the advice,
+/// instance and fixed columns, the selectors which keygen turns into fixed
+/// columns, the permutation of each column in the permutation and its grand
+/// products, one per chunk of degree - 2 columns, and the permuted input,
+/// permuted table and product of each lookup. then there are the three
+/// lagrange polynomials of the extended domain the proving key keeps, the
+/// quotient, its pieces and the evaluator's scratch, each as large, the
+/// params' two vectors of n points and the copy of one the opening folds
+pub fn estimate_prover_memory<F: Field>(
+    k: u32,
+    cs: &ConstraintSystem<F>,
+) -> Result<usize, DebugFieldError> {
+    let debug = format!("{:?}", cs.pinned());
+    let missing = |field| DebugFieldError {
+        of: "PinnedConstraintSystem",
+        field,
+    };
+    let field = |field| debug_field(&debug, field).ok_or(missing(field));
+    let section = |start: &'static str, end: &str| {
+        let from = debug.find(start).ok_or(missing(start))?;
+        let to = debug[from..].find(end).ok_or(missing(start))?;
+        Ok(&debug[from..from + to])
+    };
+    let permutation_columns = section("permutation: ", "lookups: ")?
+        .matches("Column {")
+        .count();
+    let lookups = section("lookups: ", "constants: ")?
+        .matches("input_expressions")
+        .count();
+
+    let n = 1 << k;
+    let degree = cs.degree();
+    let extended = n * (degree - 1).next_power_of_two();
+    let products = permutation_columns.div_ceil((degree - 2).max(1));
+
+    let columns = field("num_advice_columns")?
+        + field("num_instance_columns")?
+        + field("num_fixed_columns")?
+        + field("num_selectors")?
+        + permutation_columns
+        + products
+        + 3 * lookups;
+    let polynomials = columns * (2 * n + extended) + 7 * extended;
+    Ok(polynomials * SCALAR_BYTES + 3 * n * POINT_BYTES)
+}
+
+/// a labelled line of the cost table
+type Line = (&'static str, fn(&Cost) -> usize);
+
+/// side by side costs of named circuits
+pub struct CostTable<'a>(pub &'a [(&'a str, Cost)]);
+
+impl fmt::Display for CostTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: [Line; 10] = [
+            ("k", |c| c.k as usize),
+            ("advice columns", |c| c.advice_columns),
+            ("fixed columns", |c| c.fixed_columns),
+            ("instance columns", |c| c.instance_columns),
+            ("lookups", |c| c.lookups),
+            ("permutation columns", |c| c.permutation_columns),
+            ("max degree", |c| c.max_degree),
+            ("rows", |c| c.rows),
+            ("marginal proof size", |c| c.marginal_proof_size),
+            ("proof size", |c| c.proof_size),
+        ];
+
+        write!(f, "{:<20}", "")?;
+        for (name, _) in self.0 {
+            write!(f, " | {:>14}", name)?;
+        }
+        writeln!(f)?;
+        for (label, value) in lines {
+            write!(f, "{:<20}", label)?;
+            for (_, cost) in self.0 {
+                write!(f, " | {:>14}", value(cost))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{
+        public_inputs::PublicInputs, row_based, single_column, standard_plonk, two_column,
+    };
+
+    #[test]
+    fn fibonacci_costs() {
+        let row_based = measure(4, &row_based::MyCircuit::new(10)).unwrap();
+        let single_column = measure(4, &single_column::MyCircuit::<Fp>::new(10)).unwrap();
+        let two_column = measure(4, &two_column::MyCircuit::<Fp>::new(10)).unwrap();
+        let plonk = measure(
+            4,
+            &standard_plonk::PlonkFiboCircuit::new(Fp::one(), Fp::one(), 10),
+        )
+        .unwrap();
+
+        // selectors are compressed into a fixed column
+        let columns = |c: &Cost| {
+            (
+                c.advice_columns,
+                c.fixed_columns,
+                c.instance_columns,
+                c.permutation_columns,
+            )
+        };
+        assert_eq!(columns(&row_based), (3, 1, 1, 4));
+        assert_eq!(columns(&single_column), (1, 1, 1, 2));
+        assert_eq!(columns(&two_column), (2, 1, 1, 3));
+        // the coefficients take five fixed columns where a selector takes one
+        assert_eq!(columns(&plonk), (3, 5, 1, 4));
+        assert_eq!(row_based.lookups, 0);
+        assert_eq!(single_column.lookups, 0);
+
+        // fewer columns make for smaller proofs, more of them for fewer rows
+        assert!(single_column.proof_size < two_column.proof_size);
+        assert!(two_column.proof_size < row_based.proof_size);
+        assert!(two_column.rows < row_based.rows);
+        assert!(row_based.rows < single_column.rows);
+        assert!(row_based.proof_size < plonk.proof_size);
+    }
+
+    #[test]
+    fn debug_fields() {
+        let debug = "Cost { max_rows: 12, max_deg: 3 }";
+        assert_eq!(debug_field(debug, "max_rows"), Some(12));
+        assert_eq!(debug_field(debug, "max_deg"), Some(3));
+        // a field halo2 renamed or dropped is an error, not a panic
+        assert_eq!(debug_field(debug, "lookups"), None);
+        assert_eq!(debug_field("Cost { max_rows: None }", "max_rows"), None);
+
+        let error = DebugFieldError {
+            of: "CircuitCost",
+            field: "lookups",
+        };
+        assert_eq!(
+            error.to_string(),
+            "the Debug output of CircuitCost has no `lookups` to read"
+        );
+    }
+
+    #[test]
+    fn prover_memory() {
+        fn estimate<C: Circuit<Fp>>(k: u32) -> usize {
+            let mut cs = ConstraintSystem::default();
+            C::configure(&mut cs);
+            estimate_prover_memory(k, &cs).unwrap()
+        }
+        let row_based = estimate::<row_based::MyCircuit<Fp>>;
+        let single_column = estimate::<single_column::MyCircuit<Fp>>;
+
+        // linear in the rows, and in the columns
+        assert_eq!(row_based(12), 4 * row_based(10));
+        assert!(single_column(12) < row_based(12));
+        // 13 columns of 2 + 2 polynomials of n, 7 extended, 3 vectors of points
+        assert_eq!(row_based(10), ((13 * 4 + 14) * 32 + 3 * 64) << 10);
+    }
+
+    #[test]
+    fn min_k() {
+        let instances = |n| vec![PublicInputs::new(Fp::one(), Fp::one(), n).to_instance_column()];
+        let row_based = |n| find_min_k(&row_based::MyCircuit::new(n), &instances(n), 20);
+        assert_eq!(row_based(10).unwrap(), 4);
+        // 98 rows, and the 6 halo2 reserves
+        assert_eq!(row_based(100).unwrap(), 7);
+        assert_eq!(row_based(100).unwrap(), row_based::min_k_for_rows::<Fp>(98));
+
+        let single_column = find_min_k(&single_column::MyCircuit::new(100), &instances(100), 20);
+        assert_eq!(single_column.unwrap(), 7);
+
+        assert!(matches!(
+            find_min_k(&row_based::MyCircuit::new(100), &instances(100), 6),
+            Err(Error::NotEnoughRowsAvailable { current_k: 6 })
+        ));
+        // an instance column too long for the circuit's rows
+        let long = vec![vec![Fp::one(); 40]];
+        assert_eq!(
+            find_min_k(&row_based::MyCircuit::new(10), &long, 20).unwrap(),
+            6
+        );
+    }
+}
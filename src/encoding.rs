@@ -0,0 +1,101 @@
+//! 0x prefixed hex of bytes, and of field elements as the 32 byte little
+//! endian repr Fp::from_repr takes
+
+use halo2_proofs::pasta::{group::ff::PrimeField, Fp};
+use thiserror::Error;
+
+/// bytes of the repr of an Fp
+pub const FP_BYTES: usize = 32;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum HexError {
+    #[error("hex must start with 0x: {0}")]
+    MissingPrefix(String),
+    #[error("hex has an odd number of digits, {0}")]
+    OddLength(usize),
+    #[error("'{c}' at {index} is not a hex digit")]
+    InvalidChar { c: char, index: usize },
+    #[error("a field element is {FP_BYTES} bytes, got {0}")]
+    Length(usize),
+    #[error("{0} is not below the modulus of Fp")]
+    NotInField(String),
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("0x{digits}")
+}
+
+pub fn from_hex(text: &str) -> Result<Vec<u8>, HexError> {
+    let digits = text
+        .strip_prefix("0x")
+        .ok_or_else(|| HexError::MissingPrefix(text.to_owned()))?;
+    // the index is in text, after the prefix
+    let value = |(index, c): (usize, char)| {
+        c.to_digit(16)
+            .map(|d| d as u8)
+            .ok_or(HexError::InvalidChar {
+                c,
+                index: index + 2,
+            })
+    };
+    let digits = digits
+        .char_indices()
+        .map(value)
+        .collect::<Result<Vec<_>, _>>()?;
+    if !digits.len().is_multiple_of(2) {
+        return Err(HexError::OddLength(digits.len()));
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect())
+}
+
+pub fn fp_to_hex(value: &Fp) -> String {
+    to_hex(&value.to_repr())
+}
+
+pub fn fp_from_hex(text: &str) -> Result<Fp, HexError> {
+    let bytes = from_hex(text)?;
+    let repr: [u8; FP_BYTES] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| HexError::Length(bytes.len()))?;
+    Option::from(Fp::from_repr(repr)).ok_or_else(|| HexError::NotInField(text.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(to_hex(&[0, 1, 0xab, 0xff]), "0x0001abff");
+        assert_eq!(from_hex("0x0001ABff").unwrap(), [0, 1, 0xab, 0xff]);
+        assert!(from_hex("0x").unwrap().is_empty());
+
+        for value in [Fp::zero(), Fp::from(55), -Fp::one()] {
+            assert_eq!(fp_from_hex(&fp_to_hex(&value)).unwrap(), value);
+        }
+        // little endian
+        assert!(fp_to_hex(&Fp::from(0x1234)).starts_with("0x3412"));
+    }
+
+    #[test]
+    fn rejected() {
+        assert_eq!(
+            from_hex("abcd"),
+            Err(HexError::MissingPrefix("abcd".to_owned()))
+        );
+        assert_eq!(from_hex("0xabc"), Err(HexError::OddLength(3)));
+        assert_eq!(
+            from_hex("0xabcg"),
+            Err(HexError::InvalidChar { c: 'g', index: 5 })
+        );
+        assert_eq!(fp_from_hex("0x0102"), Err(HexError::Length(2)));
+
+        let p = fp_to_hex(&-Fp::one()).replacen("0x00", "0x01", 1);
+        assert_eq!(fp_from_hex(&p), Err(HexError::NotInField(p)));
+    }
+}
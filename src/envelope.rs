@@ -0,0 +1,351 @@
+//! a proof bundled with what its verifier needs: the circuit, k, n and the
+//! instance columns, in a json file
+//!
+//! each instance value is the hex of its repr, as encoding::fp_to_hex writes
+//! it, and the proof is base64. a manifest is a json list of the paths of
+//! envelopes, relative to the manifest, to verify as a batch
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    encoding::{fp_from_hex, fp_to_hex},
+    proving::Proof,
+};
+use halo2_proofs::pasta::Fp;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// the circuits an envelope may name, with the rows of each instance column
+pub const CIRCUITS: [(&str, &[usize]); 2] = [("row_based", &[3]), ("single_column", &[3])];
+
+#[derive(Debug, Error)]
+pub enum EnvelopeError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("malformed envelope: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("unknown circuit \"{0}\"")]
+    UnknownCircuit(String),
+    /// the rows of each instance column
+    #[error("circuit \"{circuit}\" takes instance columns of {expected:?} rows, got {got:?}")]
+    InstanceArity {
+        circuit: String,
+        expected: Vec<usize>,
+        got: Vec<usize>,
+    },
+    #[error("instance column {column}, row {row} is not a field element: {value}")]
+    Instance {
+        column: usize,
+        row: usize,
+        value: String,
+    },
+    #[error("the proof is not valid base64")]
+    Base64,
+    /// the envelopes of a manifest must share the circuit, k and n
+    #[error("{path}: a proof of {got}, the batch is of {expected}")]
+    MixedBatch {
+        path: String,
+        expected: String,
+        got: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEnvelope {
+    /// one of CIRCUITS
+    pub circuit: String,
+    pub k: u32,
+    /// index of the fibonacci number proven, which fixes the circuit's rows
+    pub n: usize,
+    pub instances: Vec<Vec<String>>,
+    pub proof: String,
+}
+
+impl ProofEnvelope {
+    pub fn new(circuit: &str, k: u32, n: usize, instances: &[Vec<Fp>], proof: &Proof) -> Self {
+        let instances = instances
+            .iter()
+            .map(|column| column.iter().map(fp_to_hex).collect())
+            .collect();
+        Self {
+            circuit: circuit.to_owned(),
+            k,
+            n,
+            instances,
+            proof: base64::encode(proof.as_bytes()),
+        }
+    }
+
+    /// checks the circuit is known, the instance columns fit it and every
+    /// value decodes
+    pub fn validate(&self) -> Result<(), EnvelopeError> {
+        self.instances()?;
+        self.proof()?;
+        Ok(())
+    }
+
+    pub fn instances(&self) -> Result<Vec<Vec<Fp>>, EnvelopeError> {
+        let expected = CIRCUITS
+            .iter()
+            .find(|(name, _)| *name == self.circuit)
+            .map(|(_, rows)| rows.to_vec())
+            .ok_or_else(|| EnvelopeError::UnknownCircuit(self.circuit.clone()))?;
+        let got: Vec<_> = self.instances.iter().map(Vec::len).collect();
+        if got != expected {
+            return Err(EnvelopeError::InstanceArity {
+                circuit: self.circuit.clone(),
+                expected,
+                got,
+            });
+        }
+
+        self.instances
+            .iter()
+            .enumerate()
+            .map(|(column, values)| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(row, value)| {
+                        fp_from_hex(value).map_err(|_| EnvelopeError::Instance {
+                            column,
+                            row,
+                            value: value.clone(),
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn proof(&self) -> Result<Proof, EnvelopeError> {
+        let bytes = base64::decode(&self.proof).ok_or(EnvelopeError::Base64)?;
+        Ok(Proof::from_bytes(&bytes))
+    }
+
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<(), EnvelopeError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// reads and validates an envelope
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self, EnvelopeError> {
+        let envelope: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        envelope.validate()?;
+        Ok(envelope)
+    }
+
+    /// what fixes the verifying key of the proof
+    fn shape(&self) -> String {
+        format!("{} at k = {}, n = {}", self.circuit, self.k, self.n)
+    }
+}
+
+/// reads and validates the envelopes a manifest lists, in its order, all of
+/// them of the same circuit, k and n
+pub fn load_manifest(path: impl AsRef<Path>) -> Result<Vec<ProofEnvelope>, EnvelopeError> {
+    let path = path.as_ref();
+    let paths: Vec<PathBuf> = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut envelopes: Vec<ProofEnvelope> = vec![];
+    for path in paths {
+        let path = dir.join(path);
+        let envelope = ProofEnvelope::load_json(&path)?;
+        if let Some(first) = envelopes.first() {
+            if first.shape() != envelope.shape() {
+                return Err(EnvelopeError::MixedBatch {
+                    path: path.display().to_string(),
+                    expected: first.shape(),
+                    got: envelope.shape(),
+                });
+            }
+        }
+        envelopes.push(envelope);
+    }
+    Ok(envelopes)
+}
+
+/// the standard alphabet, padded
+mod base64 {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub fn encode(bytes: &[u8]) -> String {
+        let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let mut group = [0; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    out.push(ALPHABET[(bits >> (18 - 6 * i)) as usize & 63] as char);
+                } else {
+                    out.push('=');
+                }
+            }
+        }
+        out
+    }
+
+    pub fn decode(text: &str) -> Option<Vec<u8>> {
+        let text = text.as_bytes();
+        if !text.len().is_multiple_of(4) {
+            return None;
+        }
+        let mut out = Vec::with_capacity(text.len() / 4 * 3);
+        for (i, chunk) in text.chunks(4).enumerate() {
+            let last = i == text.len() / 4 - 1;
+            let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+            if padding > 2 || (padding > 0 && !last) {
+                return None;
+            }
+
+            let mut bits = 0u32;
+            for &c in &chunk[..4 - padding] {
+                let digit = ALPHABET.iter().position(|&a| a == c)?;
+                bits = bits << 6 | digit as u32;
+            }
+            bits <<= 6 * padding;
+            let bytes = bits.to_be_bytes();
+            out.extend_from_slice(&bytes[1..4 - padding]);
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::public_inputs::PublicInputs;
+
+    fn envelope() -> ProofEnvelope {
+        let publics = PublicInputs::new(Fp::one(), Fp::one(), 10);
+        let proof = Proof::from_bytes(&[0, 1, 2, 3, 254, 255, 7]);
+        ProofEnvelope::new("row_based", 4, 10, &[publics.to_instance_column()], &proof)
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proof.json");
+        let envelope = envelope();
+        envelope.save_json(&path).unwrap();
+
+        let loaded = ProofEnvelope::load_json(&path).unwrap();
+        assert_eq!(loaded, envelope);
+        assert_eq!(
+            loaded.proof().unwrap().as_bytes(),
+            [0, 1, 2, 3, 254, 255, 7]
+        );
+        let publics = PublicInputs::new(Fp::one(), Fp::one(), 10);
+        assert_eq!(loaded.instances().unwrap(), [publics.to_instance_column()]);
+    }
+
+    #[test]
+    fn base64() {
+        for (bytes, text) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64::encode(bytes), text);
+            assert_eq!(base64::decode(text).unwrap(), bytes);
+        }
+        for text in ["Zm9", "Zm9v!", "Z===", "Zg==Zm9v", "Zm9v\n"] {
+            assert_eq!(base64::decode(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn unknown_circuit() {
+        let mut envelope = envelope();
+        envelope.circuit = "tribonacci".to_owned();
+        assert!(matches!(
+            envelope.validate(),
+            Err(EnvelopeError::UnknownCircuit(name)) if name == "tribonacci"
+        ));
+    }
+
+    #[test]
+    fn instance_arity() {
+        let mut envelope = envelope();
+        envelope.instances[0].pop();
+        let error = envelope.validate().unwrap_err();
+        assert!(matches!(
+            &error,
+            EnvelopeError::InstanceArity { expected, got, .. } if *expected == [3] && *got == [2]
+        ));
+
+        envelope.instances = vec![];
+        assert!(matches!(
+            envelope.validate(),
+            Err(EnvelopeError::InstanceArity { .. })
+        ));
+    }
+
+    #[test]
+    fn tampered() {
+        let mut envelope = envelope();
+        envelope.proof.insert(2, '*');
+        assert!(matches!(envelope.validate(), Err(EnvelopeError::Base64)));
+
+        // the modulus itself is out of the field
+        let mut envelope = self::envelope();
+        envelope.instances[0][2] = fp_to_hex(&-Fp::one()).replacen("00", "01", 1);
+        assert!(matches!(
+            envelope.validate(),
+            Err(EnvelopeError::Instance {
+                column: 0,
+                row: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("proofs")).unwrap();
+        let mut other = envelope();
+        other.n = 9;
+        envelope()
+            .save_json(dir.path().join("proofs/a.json"))
+            .unwrap();
+        envelope()
+            .save_json(dir.path().join("proofs/b.json"))
+            .unwrap();
+        other.save_json(dir.path().join("proofs/c.json")).unwrap();
+
+        // relative to the manifest
+        let manifest = dir.path().join("manifest.json");
+        fs::write(&manifest, "[\"proofs/a.json\", \"proofs/b.json\"]").unwrap();
+        assert_eq!(load_manifest(&manifest).unwrap(), [envelope(), envelope()]);
+
+        fs::write(&manifest, "[\"proofs/a.json\", \"proofs/c.json\"]").unwrap();
+        let error = load_manifest(&manifest).unwrap_err();
+        assert!(matches!(error, EnvelopeError::MixedBatch { .. }));
+        assert!(error.to_string().contains("row_based at k = 4, n = 9"));
+
+        fs::write(&manifest, "[\"proofs/d.json\"]").unwrap();
+        assert!(matches!(
+            load_manifest(&manifest),
+            Err(EnvelopeError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn malformed_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proof.json");
+        fs::write(&path, "{\"circuit\": \"row_based\"}").unwrap();
+        assert!(matches!(
+            ProofEnvelope::load_json(&path),
+            Err(EnvelopeError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+//! the halo2 errors of the chips, along with where they happened
+
+use halo2_proofs::plonk::Error;
+use thiserror::Error;
+
+/// where an assignment was in its region: (offset, column)
+pub type At = (usize, &'static str);
+
+#[derive(Debug, Error)]
+pub enum FiboError {
+    /// an assignment inside a region failed
+    #[error("region \"{region}\", row {offset}, column {column}: {source}")]
+    Assign {
+        region: String,
+        offset: usize,
+        column: &'static str,
+        #[source]
+        source: Error,
+    },
+    /// a table holds its seeds and at least one step, `min` rows in all
+    #[error("the table needs at least {min} rows, got {rows}")]
+    TooFewRows { rows: usize, min: usize },
+    /// a lookup table takes more rows than 2^k leaves to tables once the
+    /// blinding rows are reserved
+    #[error("a table of {rows} rows does not fit in 2^{k} rows, which leave {max} for it")]
+    TableTooLarge { rows: usize, k: u32, max: usize },
+    /// a range chip asked for a number of bits its table has no room for
+    #[error("the table holds values of 1 to {max} bits, not {bits}")]
+    UnsupportedBits { bits: usize, max: usize },
+    /// two lists a chip pairs up row by row are of different lengths
+    #[error("the lists differ in length: {a} against {b}")]
+    LengthMismatch { a: usize, b: usize },
+    /// constraining a cell to the instance column failed
+    #[error("exposing a cell at instance row {row}: {source}")]
+    Expose {
+        row: usize,
+        #[source]
+        source: Error,
+    },
+}
+
+impl FiboError {
+    pub fn assign(region: impl Into<String>, (offset, column): At, source: Error) -> Self {
+        Self::Assign {
+            region: region.into(),
+            offset,
+            column,
+            source,
+        }
+    }
+
+    pub fn expose(row: usize, source: Error) -> Self {
+        Self::Expose { row, source }
+    }
+}
+
+/// synthesize still has to return a halo2 error, which carries no context
+impl From<FiboError> for Error {
+    fn from(e: FiboError) -> Self {
+        match e {
+            FiboError::Assign { source, .. } | FiboError::Expose { source, .. } => source,
+            FiboError::TooFewRows { .. }
+            | FiboError::UnsupportedBits { .. }
+            | FiboError::LengthMismatch { .. } => Error::Synthesis,
+            FiboError::TableTooLarge { k, .. } => Error::NotEnoughRowsAvailable { current_k: k },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_has_context() {
+        let e = FiboError::assign("next row", (0, "c"), Error::Synthesis);
+        let message = e.to_string();
+        assert!(message.contains("region \"next row\""));
+        assert!(message.contains("row 0, column c"));
+        assert!(matches!(Error::from(e), Error::Synthesis));
+    }
+}
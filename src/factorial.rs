@@ -0,0 +1,272 @@
+//! n! with the running product in one advice column and the index in another
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// instance row of n
+pub const N_ROW: usize = 0;
+/// instance row of the claimed n!
+pub const OUT_ROW: usize = 1;
+
+/// the two cells of the last row: (idx, prod)
+type LastRow<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
+
+#[derive(Debug, Clone)]
+pub struct FactorialConfig {
+    pub prod: Column<Advice>,
+    pub idx: Column<Advice>,
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct FactorialChip<F: Field> {
+    config: FactorialConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> FactorialChip<F> {
+    pub fn construct(config: FactorialConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> FactorialConfig {
+        let prod = meta.advice_column();
+        let idx = meta.advice_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(prod);
+        meta.enable_equality(idx);
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        meta.create_gate("factorial", |meta| {
+            let s = meta.query_selector(selector);
+            let prod_cur = meta.query_advice(prod, Rotation::cur());
+            let prod_next = meta.query_advice(prod, Rotation::next());
+            let idx_cur = meta.query_advice(idx, Rotation::cur());
+            let idx_next = meta.query_advice(idx, Rotation::next());
+            let one = Expression::Constant(F::ONE);
+            vec![
+                s.clone() * (prod_cur * (idx_cur.clone() + one.clone()) - prod_next),
+                s * (idx_cur + one - idx_next),
+            ]
+        });
+
+        FactorialConfig {
+            prod,
+            idx,
+            selector,
+            instance,
+        }
+    }
+
+    /// | row | prod | idx | selector |
+    /// |  0  |   1  |  1  |     1    |
+    /// |  1  |   2  |  2  |     1    |
+    /// |  2  |   6  |  3  |     1    |
+    /// |  3  |  24  |  4  |     1    |
+    /// |  4  |  120 |  5  |     0    |
+    ///
+    /// row r holds (r + 1)! and r + 1, the first row being constants. returns
+    /// the last row
+    pub fn assign(&self, layouter: impl Layouter<F>, n: usize) -> Result<LastRow<F>, FiboError> {
+        self.assign_with(layouter, n, |_, idx| idx + Value::known(F::ONE))
+    }
+
+    /// as assign, the index of each row after the first witnessed by
+    /// `next_idx(row, idx of the row before)`
+    fn assign_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: usize,
+        next_idx: impl Fn(usize, Value<F>) -> Value<F>,
+    ) -> Result<LastRow<F>, FiboError> {
+        if n == 0 {
+            return Err(FiboError::TooFewRows { rows: n, min: 1 });
+        }
+
+        let mut at = (0, "idx");
+        layouter
+            .assign_region(
+                || "factorial table",
+                |mut region| {
+                    let FactorialConfig { prod, idx, .. } = self.config;
+
+                    at = (0, "idx");
+                    let mut idx_cell =
+                        region.assign_advice_from_constant(|| "idx", idx, 0, F::ONE)?;
+                    at = (0, "prod");
+                    let mut prod_cell =
+                        region.assign_advice_from_constant(|| "prod", prod, 0, F::ONE)?;
+
+                    for row in 1..n {
+                        at = (row - 1, "selector");
+                        self.config.selector.enable(&mut region, row - 1)?;
+
+                        at = (row, "prod");
+                        let prod_val = prod_cell.value().copied()
+                            * (idx_cell.value().copied() + Value::known(F::ONE));
+                        prod_cell = region.assign_advice(|| "prod", prod, row, || prod_val)?;
+
+                        at = (row, "idx");
+                        let idx_val = next_idx(row, idx_cell.value().copied());
+                        idx_cell = region.assign_advice(|| "idx", idx, row, || idx_val)?;
+                    }
+
+                    Ok((idx_cell, prod_cell))
+                },
+            )
+            .map_err(|e| FiboError::assign("factorial table", at, e))
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field> Chip<F> for FactorialChip<F> {
+    type Config = FactorialConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves n! for the n in the instance column, the instance being [n, n!]
+#[derive(Debug, Default)]
+pub struct FactorialCircuit<F> {
+    /// the number of rows of the table, n >= 1
+    pub n: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> FactorialCircuit<F> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for FactorialCircuit<F> {
+    type Config = FactorialConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        FactorialChip::configure(meta, instance, constant)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FactorialChip::construct(config);
+
+        let (idx, prod) = chip.assign(layouter.namespace(|| "table"), self.n)?;
+        chip.expose_public(layouter.namespace(|| "n"), &idx, N_ROW)?;
+        chip.expose_public(layouter.namespace(|| "out"), &prod, OUT_ROW)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn five() {
+        let circuit = FactorialCircuit::<Fp>::new(5);
+        mock_ok!(circuit, 4, vec![vec![Fp::from(5), Fp::from(120)]]);
+        mock_fails!(circuit, 4, vec![vec![Fp::from(5), Fp::from(121)]]);
+        // n is bound by the last index
+        mock_fails!(circuit, 4, vec![vec![Fp::from(6), Fp::from(120)]]);
+
+        mock_ok!(
+            FactorialCircuit::<Fp>::new(1),
+            4,
+            vec![vec![Fp::from(1), Fp::from(1)]]
+        );
+    }
+
+    /// the factorial table with the index at `row` bumped by one more, the
+    /// products following the tampered index
+    struct Tampered {
+        row: usize,
+    }
+
+    impl Circuit<Fp> for Tampered {
+        type Config = FactorialConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { row: self.row }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FactorialCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FactorialChip::construct(config);
+            let (idx, prod) = chip.assign_with(layouter.namespace(|| "table"), 5, |row, idx| {
+                let step = if row == self.row { 2 } else { 1 };
+                idx + Value::known(Fp::from(step))
+            })?;
+            chip.expose_public(layouter.namespace(|| "n"), &idx, N_ROW)?;
+            chip.expose_public(layouter.namespace(|| "out"), &prod, OUT_ROW)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tampered_index() {
+        // 1, 2, 4, 5, 6: the products 1, 2, 6, 30, 180 follow the gate's first constraint
+        let circuit = Tampered { row: 2 };
+        mock_fails!(circuit, 4, vec![vec![Fp::from(6), Fp::from(180)]]);
+        mock_fails!(circuit, 4, vec![vec![Fp::from(5), Fp::from(120)]]);
+    }
+
+    #[test]
+    fn empty() {
+        let circuit = FactorialCircuit::<Fp>::new(0);
+        let result = MockProver::run(4, &circuit, vec![vec![Fp::from(0), Fp::from(1)]]);
+        assert!(matches!(result, Err(Error::Synthesis)));
+    }
+}
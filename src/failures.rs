@@ -0,0 +1,379 @@
+//! helpers to reason about why MockProver rejected a circuit, and to explain
+//! it to a reader
+
+use std::fmt::{Debug, Write};
+
+use crate::cost::debug_field;
+use halo2_proofs::{
+    dev::{metadata, FailureLocation, MockProver, VerifyFailure},
+    pasta::Fp,
+};
+
+/// whether a copy constraint to the cell at `row` of `column` failed. only
+/// meant for cells living outside any region, like those of instance columns
+pub fn permutation_failure_at(
+    failures: &[VerifyFailure],
+    column: impl Into<metadata::Column>,
+    row: usize,
+) -> bool {
+    let column = column.into();
+    failures.iter().any(|failure| {
+        matches!(
+            failure,
+            VerifyFailure::Permutation {
+                column: c,
+                location: FailureLocation::OutsideRegion { row: r },
+            } if *c == column && *r == row
+        )
+    })
+}
+
+/// renders `failures` of `prover` for a reader: grouped by gate and region,
+/// regions as `"name" #index` with the rows they start on, the values of the
+/// cells concerned read from the prover's assignments, and the likely cause
+/// of each group. a prover whose Debug output can not be read, as after a
+/// change to its layout upstream, gets each failure as halo2 displays it
+pub fn explain_failures(prover: &MockProver<Fp>, failures: &[VerifyFailure]) -> String {
+    Tables::read(prover)
+        .and_then(|tables| explain_grouped(&tables, failures))
+        .unwrap_or_else(|| explain_plain(failures))
+}
+
+/// the failures as halo2 displays them, one paragraph each
+fn explain_plain(failures: &[VerifyFailure]) -> String {
+    let mut out = format!("{} verification failure(s)\n", failures.len());
+    for failure in failures {
+        writeln!(out, "\n{}", failure).unwrap();
+    }
+    out
+}
+
+fn explain_grouped(tables: &Tables, failures: &[VerifyFailure]) -> Option<String> {
+    let mut groups: Vec<Group> = vec![];
+    for failure in failures {
+        let (heading, line, cause) = explain(tables, failure)?;
+        let group = match groups.iter().position(|g| g.heading == heading) {
+            Some(i) => &mut groups[i],
+            None => {
+                groups.push(Group {
+                    heading,
+                    lines: vec![],
+                    causes: vec![],
+                });
+                groups.last_mut().unwrap()
+            }
+        };
+        group.lines.push(line);
+        if !group.causes.contains(&cause) {
+            group.causes.push(cause);
+        }
+    }
+
+    let mut out = format!("{} verification failure(s)\n", failures.len());
+    for group in groups {
+        writeln!(out, "\n{}", group.heading).unwrap();
+        for line in group.lines {
+            writeln!(out, "  {}", line).unwrap();
+        }
+        for cause in group.causes {
+            writeln!(out, "  likely cause: {}", cause).unwrap();
+        }
+    }
+    Some(out)
+}
+
+struct Group {
+    heading: String,
+    lines: Vec<String>,
+    causes: Vec<&'static str>,
+}
+
+/// the heading of the failure's group, a line about it and its likely cause.
+/// None if a region or column of it can not be read
+fn explain(tables: &Tables, failure: &VerifyFailure) -> Option<(String, String, &'static str)> {
+    Some(match failure {
+        VerifyFailure::ConstraintNotSatisfied {
+            constraint,
+            location,
+            cell_values,
+        } => {
+            let gate = name_of(constraint);
+            let mut line = format!("{} not satisfied {}", constraint, tables.at(location));
+            for (cell, value) in cell_values {
+                write!(line, "\n    {} = {}", cell, value).unwrap();
+            }
+            (
+                format!("gate \"{}\" {}", gate, tables.place(location)?),
+                line,
+                "the witness does not satisfy the gate, see the values of its cells",
+            )
+        }
+        VerifyFailure::CellNotAssigned {
+            gate,
+            region,
+            column,
+            offset,
+            ..
+        } => (
+            format!("gate \"{}\" in {}", name_of(gate), tables.region(region)?),
+            format!(
+                "{:?} column queried at offset {} is unassigned",
+                column.column_type(),
+                offset
+            ),
+            "the gate queries a cell the region does not assign",
+        ),
+        VerifyFailure::InstanceCellNotAssigned {
+            gate, region, row, ..
+        } => (
+            format!("gate \"{}\" in {}", name_of(gate), tables.region(region)?),
+            format!("instance row {} is queried but not given", row),
+            "fewer public inputs than the gate queries",
+        ),
+        VerifyFailure::ConstraintPoisoned { constraint } => (
+            format!("gate \"{}\"", name_of(constraint)),
+            format!("{} reads a poisoned cell", constraint),
+            "the gate is enabled on a row among the blinding rows",
+        ),
+        VerifyFailure::Lookup {
+            lookup_index,
+            location,
+        } => (
+            format!("lookup {} {}", lookup_index, tables.place(location)?),
+            format!("input not in the table {}", tables.at(location)),
+            "an input the lookup is enabled on is missing from its table",
+        ),
+        VerifyFailure::Permutation { column, location } => {
+            let (kind, index) = column_of(column)?;
+            let value = tables.value(&kind, index, tables.row(location));
+            let line = format!(
+                "copy constraint not satisfied {}, holding {}",
+                tables.at(location),
+                value
+            );
+            match location {
+                FailureLocation::OutsideRegion { .. } if kind == "instance" => (
+                    format!("instance column {}", index),
+                    line,
+                    "public input doesn't match witnessed value",
+                ),
+                FailureLocation::OutsideRegion { .. } => (
+                    format!("{} column {}", kind, index),
+                    line,
+                    "a cell outside any region differs from the one copied to it",
+                ),
+                FailureLocation::InRegion { region, .. } => (
+                    tables.region(region)?,
+                    format!("{} column {} {}", kind, index, line),
+                    "the cell differs from the one it is copied to or from",
+                ),
+            }
+        }
+    })
+}
+
+/// the first quoted string of a Debug representation, the name of a gate or
+/// region for the halo2 metadata which keeps it private
+fn quoted(debug: &str) -> &str {
+    debug.split('"').nth(1).unwrap_or("")
+}
+
+fn name_of(value: &impl Debug) -> String {
+    quoted(&format!("{:?}", value)).to_owned()
+}
+
+/// the lowercase type and the index of a column
+fn column_of(column: &metadata::Column) -> Option<(String, usize)> {
+    // Column { column_type: Advice, index: 2 }
+    let debug = format!("{:?}", column);
+    let kind = debug
+        .split("column_type: ")
+        .nth(1)
+        .and_then(|rest| rest.split(',').next())
+        .unwrap_or("")
+        .to_lowercase();
+    Some((kind, debug_field(&debug, "index")?))
+}
+
+/// the cells of a MockProver and the rows its regions start on, which it
+/// only shows through Debug
+struct Tables {
+    fixed: Vec<Vec<String>>,
+    advice: Vec<Vec<String>>,
+    instance: Vec<Vec<String>>,
+    region_starts: Vec<Option<usize>>,
+}
+
+impl Tables {
+    /// None if the Debug output lacks a field it reads
+    fn read(prover: &MockProver<Fp>) -> Option<Self> {
+        let debug = format!("{:?}", prover);
+        let section = |start: &str, end: &str| {
+            let from = debug.find(start)? + start.len();
+            Some(&debug[from..from + debug[from..].find(end)?])
+        };
+        let region_starts = section("regions: [", "], current_region: ")?
+            .split("rows: ")
+            .skip(1)
+            .map(|rest| rest.strip_prefix("Some((")?.split(',').next()?.parse().ok())
+            .collect();
+        Some(Self {
+            fixed: columns(section("fixed: [", "], advice: ")?),
+            advice: columns(section("advice: [", "], instance: ")?),
+            instance: columns(section("instance: [", "], selectors: ")?),
+            region_starts,
+        })
+    }
+
+    /// `"name" #index, from row r`
+    fn region(&self, region: &metadata::Region) -> Option<String> {
+        let debug = format!("{:?}", region);
+        let index = debug_field(&debug, "index")?;
+        Some(match self.region_starts.get(index).copied().flatten() {
+            Some(start) => format!(
+                "region \"{}\" #{}, from row {}",
+                quoted(&debug),
+                index,
+                start
+            ),
+            None => format!("region \"{}\" #{}", quoted(&debug), index),
+        })
+    }
+
+    fn place(&self, location: &FailureLocation) -> Option<String> {
+        Some(match location {
+            FailureLocation::InRegion { region, .. } => format!("in {}", self.region(region)?),
+            FailureLocation::OutsideRegion { .. } => "outside any region".to_owned(),
+        })
+    }
+
+    /// the circuit row of a location, if its region was laid out
+    fn row(&self, location: &FailureLocation) -> Option<usize> {
+        match location {
+            FailureLocation::InRegion { region, offset } => {
+                let index = debug_field(&format!("{:?}", region), "index")?;
+                Some(self.region_starts.get(index).copied().flatten()? + offset)
+            }
+            FailureLocation::OutsideRegion { row } => Some(*row),
+        }
+    }
+
+    fn at(&self, location: &FailureLocation) -> String {
+        match (location, self.row(location)) {
+            (FailureLocation::InRegion { offset, .. }, Some(row)) => {
+                format!("at offset {} (row {})", offset, row)
+            }
+            (FailureLocation::InRegion { offset, .. }, None) => format!("at offset {}", offset),
+            (FailureLocation::OutsideRegion { row }, _) => format!("at row {}", row),
+        }
+    }
+
+    fn value(&self, kind: &str, index: usize, row: Option<usize>) -> String {
+        let table = match kind {
+            "fixed" => &self.fixed,
+            "advice" => &self.advice,
+            _ => &self.instance,
+        };
+        row.and_then(|row| table.get(index)?.get(row))
+            .cloned()
+            .unwrap_or_else(|| "an unknown value".to_owned())
+    }
+}
+
+/// the values of `[cell, ...], [cell, ...]`, one vector per column
+fn columns(table: &str) -> Vec<Vec<String>> {
+    table
+        .split("], [")
+        .map(|column| {
+            column
+                .trim_matches(|c| c == '[' || c == ']')
+                .split(", ")
+                .map(cell_value)
+                .collect()
+        })
+        .collect()
+}
+
+/// a cell as Debug shows it, with values below 2^64 in decimal
+fn cell_value(cell: &str) -> String {
+    match cell {
+        "Unassigned" => "nothing, unassigned".to_owned(),
+        "Padding" => "0, padding".to_owned(),
+        cell if cell.starts_with("Poison") => "a poisoned blinding value".to_owned(),
+        cell => {
+            let hex = cell.trim_start_matches("Assigned(").trim_end_matches(')');
+            let digits = hex.trim_start_matches("0x").trim_start_matches('0');
+            match digits.len() {
+                0 => "0".to_owned(),
+                1..=16 => u64::from_str_radix(digits, 16)
+                    .map_or_else(|_| hex.to_owned(), |value| value.to_string()),
+                _ => hex.to_owned(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{public_inputs::PublicInputs, row_based::MyCircuit};
+
+    #[test]
+    fn wrong_output() {
+        let mut publics = PublicInputs::new(Fp::one(), Fp::one(), 10);
+        publics.out = Fp::from(65);
+        let circuit = MyCircuit::<Fp>::new(10);
+        let prover = MockProver::run(4, &circuit, vec![publics.to_instance_column()]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        let text = explain_failures(&prover, &failures);
+
+        assert!(text.starts_with("2 verification failure(s)\n"));
+        assert!(text.contains(
+            "instance column 0\n  copy constraint not satisfied at row 2, holding 65\n  \
+             likely cause: public input doesn't match witnessed value\n"
+        ));
+        // the cell of out, c of the last row
+        assert!(text.contains("region \"fib step 10/10\" #7, from row 7\n"));
+        assert!(text.contains(
+            "advice column 2 copy constraint not satisfied at offset 0 (row 7), holding 55"
+        ));
+    }
+
+    #[test]
+    fn plain_fallback() {
+        let mut publics = PublicInputs::new(Fp::one(), Fp::one(), 10);
+        publics.out = Fp::from(65);
+        let circuit = MyCircuit::<Fp>::new(10);
+        let prover = MockProver::run(4, &circuit, vec![publics.to_instance_column()]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert!(Tables::read(&prover).is_some());
+
+        // what explain_failures falls back to once the tables can not be read
+        let text = explain_plain(&failures);
+        assert!(text.starts_with("2 verification failure(s)\n\n"));
+        for failure in &failures {
+            assert!(text.contains(&failure.to_string()), "{text}");
+        }
+    }
+
+    #[test]
+    fn cell_values() {
+        assert_eq!(
+            cell_value(
+                "Assigned(0x0000000000000000000000000000000000000000000000000000000000000037)"
+            ),
+            "55"
+        );
+        assert_eq!(
+            cell_value(
+                "Assigned(0x0000000000000000000000000000000000000000000000000000000000000000)"
+            ),
+            "0"
+        );
+        let large = "0x40000000000000000000000000000000224698fc094cf91b992d30ed00000000";
+        assert_eq!(cell_value(&format!("Assigned({large})")), large);
+        assert_eq!(cell_value("Padding"), "0, padding");
+        assert_eq!(cell_value("Poison(12)"), "a poisoned blinding value");
+        assert_eq!(columns("[Unassigned, Padding], [Poison(3)]").len(), 2);
+    }
+}
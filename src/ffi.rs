@@ -0,0 +1,300 @@
+//! a C interface verifying proofs of the canonical fibonacci circuit, the row
+//! based one proving the 10th number at k = 4, as `fibo prove` does by default
+//!
+//! halo2_proofs 0.3 can not read a verifying key from bytes, so the params and
+//! the key are generated on the first call and kept for the later ones. the
+//! declarations for C are in include/fibo.h, and the library is built with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib`
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+    sync::OnceLock,
+};
+
+use crate::{
+    fibonacci::{public_inputs::PublicInputs, row_based::MyCircuit},
+    proving::{self, Proof},
+};
+use halo2_proofs::{
+    pasta::{group::ff::PrimeField, EqAffine, Fp},
+    plonk::{keygen_vk, Error, VerifyingKey},
+    poly::commitment::Params,
+};
+
+/// k of the canonical circuit
+pub const K: u32 = 4;
+/// the index of the fibonacci number the canonical circuit proves
+pub const N: usize = 10;
+
+/// the proof verified
+pub const FIBO_OK: i32 = 0;
+/// the proof could not be read
+pub const FIBO_ERR_MALFORMED_PROOF: i32 = -1;
+/// the publics are not the 3 of a, b and out
+pub const FIBO_ERR_INSTANCE_COUNT: i32 = -2;
+/// the proof was read but does not verify against the publics
+pub const FIBO_ERR_VERIFICATION: i32 = -3;
+/// a public is not the canonical encoding of a field element
+pub const FIBO_ERR_MALFORMED_PUBLICS: i32 = -4;
+/// a pointer was null
+pub const FIBO_ERR_NULL: i32 = -5;
+/// the verifier panicked
+pub const FIBO_ERR_PANIC: i32 = -6;
+
+/// bytes of each public, the little endian repr of an Fp
+pub const PUBLIC_BYTES: usize = 32;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn canonical() -> &'static (Params<EqAffine>, VerifyingKey<EqAffine>) {
+    static KEYS: OnceLock<(Params<EqAffine>, VerifyingKey<EqAffine>)> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        let params = Params::new(K);
+        let vk = keygen_vk(&params, &MyCircuit::<Fp>::new(N)).unwrap();
+        (params, vk)
+    })
+}
+
+fn parse_publics(bytes: &[u8]) -> Result<Vec<Fp>, (i32, String)> {
+    bytes
+        .chunks(PUBLIC_BYTES)
+        .enumerate()
+        .map(|(i, bytes)| {
+            let repr = bytes.try_into().unwrap();
+            Option::from(Fp::from_repr(repr)).ok_or((
+                FIBO_ERR_MALFORMED_PUBLICS,
+                format!("public {i} is not a field element"),
+            ))
+        })
+        .collect()
+}
+
+/// the bytes of `count` publics, if they are as many as the circuit takes
+fn publics_bytes(count: usize) -> Result<usize, (i32, String)> {
+    let expected = PublicInputs::new(Fp::one(), Fp::one(), N)
+        .to_instance_column()
+        .len();
+    if count != expected {
+        return Err((
+            FIBO_ERR_INSTANCE_COUNT,
+            format!("expected {expected} publics, got {count}"),
+        ));
+    }
+    count
+        .checked_mul(PUBLIC_BYTES)
+        .ok_or((FIBO_ERR_INSTANCE_COUNT, format!("{count} publics overflow")))
+}
+
+/// checks the pointers and lengths, and only then reads the bytes behind them
+///
+/// # Safety
+///
+/// as for fibo_verify
+unsafe fn verify_raw(
+    proof_ptr: *const u8,
+    proof_len: usize,
+    publics_ptr: *const u8,
+    publics_len: usize,
+) -> Result<(), (i32, String)> {
+    if proof_ptr.is_null() || publics_ptr.is_null() {
+        return Err((FIBO_ERR_NULL, "null pointer".to_owned()));
+    }
+    let publics_bytes = publics_bytes(publics_len)?;
+    // no slice may span more than isize::MAX bytes
+    if proof_len > isize::MAX as usize {
+        return Err((
+            FIBO_ERR_MALFORMED_PROOF,
+            format!("a proof of {proof_len} bytes is too long"),
+        ));
+    }
+    let proof = slice::from_raw_parts(proof_ptr, proof_len);
+    let publics = slice::from_raw_parts(publics_ptr, publics_bytes);
+    verify(proof, publics)
+}
+
+fn verify(proof: &[u8], publics: &[u8]) -> Result<(), (i32, String)> {
+    let publics = parse_publics(publics)?;
+
+    let (params, vk) = canonical();
+    let proof = Proof::from_bytes(proof);
+    proving::verify(params, vk, &proof, &[&publics]).map_err(|e| match e {
+        Error::Transcript(e) => (FIBO_ERR_MALFORMED_PROOF, format!("reading the proof: {e}")),
+        e => (
+            FIBO_ERR_VERIFICATION,
+            format!("the proof does not verify: {e}"),
+        ),
+    })
+}
+
+/// verifies the `proof_len` bytes of a proof of the canonical circuit
+/// against `publics_len` publics, a, b and out, each the 32 byte little
+/// endian repr of an Fp. returns FIBO_OK or one of the negative FIBO_ERR
+/// codes, the reason then being in fibo_last_error_message
+///
+/// # Safety
+///
+/// `proof_ptr` must point to `proof_len` readable bytes, and `publics_ptr` to
+/// `publics_len * 32`. the pointers are checked for null and publics_len for
+/// 3 before either is read
+#[no_mangle]
+pub unsafe extern "C" fn fibo_verify(
+    proof_ptr: *const u8,
+    proof_len: usize,
+    publics_ptr: *const u8,
+    publics_len: usize,
+) -> i32 {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        verify_raw(proof_ptr, proof_len, publics_ptr, publics_len)
+    }))
+    .unwrap_or_else(|_| Err((FIBO_ERR_PANIC, "the verifier panicked".to_owned())));
+    match result {
+        Ok(()) => {
+            set_last_error(None);
+            FIBO_OK
+        }
+        Err((code, message)) => {
+            set_last_error(Some(message));
+            code
+        }
+    }
+}
+
+/// the reason the last fibo_verify on this thread failed, or null after a
+/// success. the string stays valid until the next fibo_verify on the thread
+#[no_mangle]
+pub extern "C" fn fibo_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+fn set_last_error(message: Option<String>) {
+    // the messages are ours and hold no nul
+    let message = message.map(|message| CString::new(message).unwrap());
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibonacci::vectors::nth, proving::keygen};
+    use rand_core::OsRng;
+    use std::ffi::CStr;
+
+    fn publics(out: Fp) -> Vec<u8> {
+        [Fp::one(), Fp::one(), out]
+            .iter()
+            .flat_map(|value| value.to_repr())
+            .collect()
+    }
+
+    fn proof() -> Vec<u8> {
+        let params: Params<EqAffine> = Params::new(K);
+        let circuit = MyCircuit::<Fp>::new(N);
+        let pk = keygen(&params, &circuit).unwrap();
+        let instance = PublicInputs::new(Fp::one(), Fp::one(), N).to_instance_column();
+        let proof = proving::prove(&params, &pk, circuit, &[&instance], OsRng).unwrap();
+        proof.to_bytes()
+    }
+
+    fn call(proof: &[u8], publics: &[u8], count: usize) -> (i32, Option<String>) {
+        let code = unsafe { fibo_verify(proof.as_ptr(), proof.len(), publics.as_ptr(), count) };
+        let message = fibo_last_error_message();
+        let message = (!message.is_null()).then(|| {
+            unsafe { CStr::from_ptr(message) }
+                .to_str()
+                .unwrap()
+                .to_owned()
+        });
+        (code, message)
+    }
+
+    #[test]
+    fn verifies() {
+        let proof = proof();
+        assert_eq!(call(&proof, &publics(nth(10)), 3), (FIBO_OK, None));
+
+        let (code, message) = call(&proof, &publics(Fp::from(56)), 3);
+        assert_eq!(code, FIBO_ERR_VERIFICATION);
+        assert!(message.unwrap().starts_with("the proof does not verify"));
+
+        // a success clears the message
+        assert_eq!(call(&proof, &publics(nth(10)), 3), (FIBO_OK, None));
+    }
+
+    #[test]
+    fn errors() {
+        let proof = proof();
+        let (code, message) = call(&proof[..proof.len() / 2], &publics(nth(10)), 3);
+        assert_eq!(code, FIBO_ERR_MALFORMED_PROOF);
+        assert!(message.unwrap().starts_with("reading the proof"));
+
+        let (code, message) = call(&proof, &publics(nth(10))[..2 * PUBLIC_BYTES], 2);
+        assert_eq!(code, FIBO_ERR_INSTANCE_COUNT);
+        assert_eq!(message.unwrap(), "expected 3 publics, got 2");
+
+        let mut publics = publics(nth(10));
+        publics[2 * PUBLIC_BYTES..].fill(0xff);
+        let (code, message) = call(&proof, &publics, 3);
+        assert_eq!(code, FIBO_ERR_MALFORMED_PUBLICS);
+        assert_eq!(message.unwrap(), "public 2 is not a field element");
+
+        let code = unsafe { fibo_verify(ptr::null(), 0, publics.as_ptr(), 3) };
+        assert_eq!(code, FIBO_ERR_NULL);
+        let code = unsafe { fibo_verify(proof.as_ptr(), proof.len(), ptr::null(), 3) };
+        assert_eq!(code, FIBO_ERR_NULL);
+    }
+
+    #[test]
+    fn lengths_checked_before_reading() {
+        // neither count fits the buffers, so reading them first would be UB
+        let proof = proof();
+        let publics = publics(nth(10));
+        for count in [4, usize::MAX / PUBLIC_BYTES + 1, usize::MAX] {
+            let (code, message) = call(&proof, &publics, count);
+            assert_eq!(code, FIBO_ERR_INSTANCE_COUNT);
+            assert_eq!(message.unwrap(), format!("expected 3 publics, got {count}"));
+        }
+        let code = unsafe { fibo_verify(proof.as_ptr(), usize::MAX, publics.as_ptr(), 3) };
+        assert_eq!(code, FIBO_ERR_MALFORMED_PROOF);
+    }
+
+    #[test]
+    fn header_matches() {
+        let header = include_str!("../include/fibo.h");
+        let define = |name: &str| -> i64 {
+            let prefix = format!("#define {name} ");
+            let line = header
+                .lines()
+                .find_map(|line| line.strip_prefix(&prefix))
+                .unwrap_or_else(|| panic!("include/fibo.h defines no {name}"));
+            line.trim().parse().unwrap()
+        };
+
+        let codes = [
+            ("FIBO_OK", FIBO_OK),
+            ("FIBO_ERR_MALFORMED_PROOF", FIBO_ERR_MALFORMED_PROOF),
+            ("FIBO_ERR_INSTANCE_COUNT", FIBO_ERR_INSTANCE_COUNT),
+            ("FIBO_ERR_VERIFICATION", FIBO_ERR_VERIFICATION),
+            ("FIBO_ERR_MALFORMED_PUBLICS", FIBO_ERR_MALFORMED_PUBLICS),
+            ("FIBO_ERR_NULL", FIBO_ERR_NULL),
+            ("FIBO_ERR_PANIC", FIBO_ERR_PANIC),
+        ];
+        for (name, code) in codes {
+            assert_eq!(define(name), code as i64, "{name}");
+        }
+        assert_eq!(define("FIBO_PUBLIC_BYTES"), PUBLIC_BYTES as i64);
+        // and nothing the header defines, bar its include guard, is missing here
+        let defines = header
+            .lines()
+            .filter(|line| line.starts_with("#define FIBO_") && *line != "#define FIBO_H");
+        assert_eq!(defines.count(), codes.len() + 1);
+    }
+}
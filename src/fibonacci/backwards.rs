@@ -0,0 +1,285 @@
+//! fibonacci run in reverse: from two consecutive numbers, the seeds they
+//! grew from
+//!
+//! the gate is the row based one, s · (c − b − a), but each row copies b and
+//! c in from a and b of the row before and witnesses a = c − b, so the
+//! sequence is walked down instead of up
+
+use std::marker::PhantomData;
+
+use super::Step;
+use crate::{error::FiboError, util::ACell};
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// instance rows of the two numbers claimed, then of the seeds they give
+pub const LAST_ROWS: [usize; 2] = [0, 1];
+pub const SEED_ROWS: [usize; 2] = [2, 3];
+
+/// the two numbers `steps` before x and y, x coming right before y, computed
+/// natively
+pub fn fib_back<F: Field>(x: F, y: F, steps: usize) -> (F, F) {
+    let (mut a, mut b) = (x, y);
+    for _ in 0..steps {
+        (a, b) = (b - a, a);
+    }
+    (a, b)
+}
+
+#[derive(Debug, Clone)]
+pub struct BackwardsConfig {
+    pub advice: [Column<Advice>; 3],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct BackwardsChip<F: Field> {
+    config: BackwardsConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> BackwardsChip<F> {
+    pub fn construct(config: BackwardsConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        instance: Column<Instance>,
+    ) -> BackwardsConfig {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let selector = meta.selector();
+
+        meta.enable_equality(instance);
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("sub", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, b, c] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![s * (c - b - a)]
+        });
+
+        BackwardsConfig {
+            advice,
+            selector,
+            instance,
+        }
+    }
+
+    /// the row of x and y, copied into b and c from the instance, and the
+    /// number before them in a. returns (a, b)
+    pub fn assign_last_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(ACell<F>, ACell<F>), FiboError> {
+        let [col_a, col_b, col_c] = self.config.advice;
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "last row",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "b");
+                    let b_cell: ACell<F> = region
+                        .assign_advice_from_instance(
+                            || "x",
+                            self.config.instance,
+                            LAST_ROWS[0],
+                            col_b,
+                            0,
+                        )?
+                        .into();
+                    at = (0, "c");
+                    let c_cell: ACell<F> = region
+                        .assign_advice_from_instance(
+                            || "y",
+                            self.config.instance,
+                            LAST_ROWS[1],
+                            col_c,
+                            0,
+                        )?
+                        .into();
+
+                    at = (0, "a");
+                    let a_val = c_cell.value_copied() - b_cell.value_copied();
+                    let a_cell = region.assign_advice(|| "a", col_a, 0, || a_val)?;
+                    Ok((a_cell.into(), b_cell))
+                },
+            )
+            .map_err(|e| FiboError::assign("last row", at, e))
+    }
+
+    /// the row before, from a and b of the row after it: b here is a there,
+    /// and c here is b there, the copies going the other way from the row based
+    /// chip's. returns (a, b) of this row
+    pub fn assign_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        step: Step,
+        next_a: &ACell<F>,
+        next_b: &ACell<F>,
+    ) -> Result<(ACell<F>, ACell<F>), FiboError> {
+        let [col_a, col_b, col_c] = self.config.advice;
+        let name = step.to_string();
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || name.clone(),
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "b");
+                    let b_cell = next_a.copy_to(&mut region, col_b, 0, "b")?;
+                    at = (0, "c");
+                    next_b.copy_to(&mut region, col_c, 0, "c")?;
+
+                    at = (0, "a");
+                    let a_val = next_b.value_copied() - next_a.value_copied();
+                    let a_cell = region.assign_advice(|| "a", col_a, 0, || a_val)?;
+                    Ok((a_cell.into(), b_cell))
+                },
+            )
+            .map_err(|e| FiboError::assign(name, at, e))
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &ACell<F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field> Chip<F> for BackwardsChip<F> {
+    type Config = BackwardsConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves the instance seeds are the two numbers `steps` before the instance
+/// x and y. the instance column holds x, y, then the seeds
+#[derive(Debug, Default)]
+pub struct BackwardsCircuit<F> {
+    /// steps down from x and y, at least 1
+    pub steps: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> BackwardsCircuit<F> {
+    pub fn new(steps: usize) -> Self {
+        Self {
+            steps,
+            _marker: PhantomData,
+        }
+    }
+
+    /// x, y and the seeds they give, as the instance column
+    pub fn instance(x: F, y: F, steps: usize) -> Vec<F> {
+        let (a, b) = fib_back(x, y, steps);
+        vec![x, y, a, b]
+    }
+}
+
+impl<F: Field> Circuit<F> for BackwardsCircuit<F> {
+    type Config = BackwardsConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.steps)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        BackwardsChip::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = BackwardsChip::construct(config);
+
+        let (mut a, mut b) = chip.assign_last_row(layouter.namespace(|| "last row"))?;
+        for i in 2..=self.steps {
+            let step = Step {
+                index: i,
+                of: self.steps,
+            };
+            (a, b) = chip.assign_row(layouter.namespace(|| "row before"), step, &a, &b)?;
+        }
+
+        chip.expose_public(layouter.namespace(|| "a"), &a, SEED_ROWS[0])?;
+        chip.expose_public(layouter.namespace(|| "b"), &b, SEED_ROWS[1])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{fib, vectors::nth},
+        mock_fails, mock_ok,
+    };
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn recovers_seeds() {
+        // 55 and 89 are the 10th and 11th, 9 steps past 1 and 1
+        let (x, y) = (nth(10), nth(11));
+        assert_eq!(fib_back(x, y, 9), (Fp::one(), Fp::one()));
+        let instance = BackwardsCircuit::instance(x, y, 9);
+        assert_eq!(instance[2..], [Fp::one(), Fp::one()]);
+        mock_ok!(BackwardsCircuit::<Fp>::new(9), 4, vec![instance]);
+
+        // and back up again
+        let (a, b) = fib_back(Fp::from(2), Fp::from(7), 5);
+        assert_eq!(fib(a, b, 6), Fp::from(2));
+        assert_eq!(fib(a, b, 7), Fp::from(7));
+    }
+
+    #[test]
+    fn off_trajectory() {
+        // any two numbers have a past: 55 and 90 verify too, just not from 1, 1
+        let (x, y) = (nth(10), nth(11) + Fp::one());
+        let instance = BackwardsCircuit::instance(x, y, 9);
+        assert_ne!(instance[2..], [Fp::one(), Fp::one()]);
+        mock_ok!(BackwardsCircuit::<Fp>::new(9), 4, vec![instance]);
+
+        // 35 and -20, whose 10th and 11th numbers are 55 and 90 again
+        let (a, b) = fib_back(x, y, 9);
+        assert_eq!((a, b), (Fp::from(35), -Fp::from(20)));
+        assert_eq!(fib(a, b, 10), x);
+        assert_eq!(fib(a, b, 11), y);
+    }
+
+    #[test]
+    fn wrong_seeds() {
+        let circuit = BackwardsCircuit::<Fp>::new(9);
+        let (x, y) = (nth(10), nth(11));
+        mock_fails!(circuit, 4, vec![vec![x, y, Fp::one(), Fp::from(2)]]);
+        mock_fails!(circuit, 4, vec![vec![x, y, Fp::from(2), Fp::one()]]);
+        // the seeds of one step fewer
+        let instance = BackwardsCircuit::instance(x, y, 8);
+        mock_fails!(circuit, 4, vec![instance]);
+    }
+}
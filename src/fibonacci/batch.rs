@@ -0,0 +1,263 @@
+//! B fibonacci sequences side by side, each in three advice columns of its
+//! own, so B times the columns in the rows of one
+//!
+//! each row is a region holding the (a, b, c) of every sequence, under the one
+//! selector of the "add" gate. the instance column holds the public inputs of
+//! the sequences one after the other: sequence i has its a, b and out at rows
+//! 3i + A_ROW, 3i + B_ROW and 3i + OUT_ROW. B = 1 is the row based circuit
+
+use std::marker::PhantomData;
+
+use super::{
+    min_k,
+    public_inputs::{PublicInputs, A_ROW, B_ROW, OUT_ROW},
+};
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// the instance rows of each sequence
+pub const ROWS_PER_SEQUENCE: usize = 3;
+
+/// the instance row of the public input at `row` of the i-th sequence
+pub fn instance_row(i: usize, row: usize) -> usize {
+    ROWS_PER_SEQUENCE * i + row
+}
+
+/// the instance column of the sequences' public inputs, in order
+pub fn instance<F: Field>(publics: &[PublicInputs<F>]) -> Vec<F> {
+    publics
+        .iter()
+        .flat_map(|publics| publics.to_instance_column())
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchFiboConfig<const B: usize> {
+    /// a, b and c of each sequence
+    pub advice: [[Column<Advice>; 3]; B],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// proves the n-th number of each of B sequences, from the seeds in the
+/// instance column
+#[derive(Debug, Default)]
+pub struct BatchFiboCircuit<F, const B: usize> {
+    /// index of the fibonacci numbers to prove, a and b being the 1st and 2nd. n >= 3
+    pub n: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const B: usize> BatchFiboCircuit<F, B> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            _marker: PhantomData,
+        }
+    }
+
+    /// the smallest k fitting the n - 2 rows, and the instance rows
+    pub fn min_k(&self) -> u32 {
+        min_k::<F, Self>((self.n - 2).max(ROWS_PER_SEQUENCE * B))
+    }
+
+    /// a region of one row: every sequence copies in its two previous numbers,
+    /// or takes its seeds from the instance column on the first row
+    fn assign_row(
+        &self,
+        config: &BatchFiboConfig<B>,
+        mut layouter: impl Layouter<F>,
+        prev: Option<&[[AssignedCell<F, F>; 2]; B]>,
+    ) -> Result<[[AssignedCell<F, F>; 2]; B], FiboError> {
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "row",
+                |mut region| {
+                    at = (0, "selector");
+                    config.selector.enable(&mut region, 0)?;
+
+                    let mut next = Vec::with_capacity(B);
+                    for (i, [col_a, col_b, col_c]) in config.advice.into_iter().enumerate() {
+                        let (a, b) = match prev {
+                            None => {
+                                at = (0, "a");
+                                let a = region.assign_advice_from_instance(
+                                    || "a",
+                                    config.instance,
+                                    instance_row(i, A_ROW),
+                                    col_a,
+                                    0,
+                                )?;
+                                at = (0, "b");
+                                let b = region.assign_advice_from_instance(
+                                    || "b",
+                                    config.instance,
+                                    instance_row(i, B_ROW),
+                                    col_b,
+                                    0,
+                                )?;
+                                (a, b)
+                            }
+                            Some(prev) => {
+                                let [pre_b, pre_c] = &prev[i];
+                                at = (0, "a");
+                                let a = pre_b.copy_advice(|| "a", &mut region, col_a, 0)?;
+                                at = (0, "b");
+                                let b = pre_c.copy_advice(|| "b", &mut region, col_b, 0)?;
+                                (a, b)
+                            }
+                        };
+
+                        at = (0, "c");
+                        let c_val = a.value().copied() + b.value();
+                        let c = region.assign_advice(|| "c", col_c, 0, || c_val)?;
+                        next.push([b, c]);
+                    }
+
+                    Ok(next.try_into().unwrap())
+                },
+            )
+            .map_err(|e| FiboError::assign("row", at, e))
+    }
+
+    fn assign(
+        &self,
+        config: BatchFiboConfig<B>,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), FiboError> {
+        if self.n < 3 {
+            return Err(FiboError::TooFewRows {
+                rows: self.n,
+                min: 3,
+            });
+        }
+
+        // the first row already holds the 3rd numbers
+        let mut prev = self.assign_row(&config, layouter.namespace(|| "first row"), None)?;
+        for _ in 3..self.n {
+            prev = self.assign_row(&config, layouter.namespace(|| "next row"), Some(&prev))?;
+        }
+
+        for (i, [_, out]) in prev.iter().enumerate() {
+            let row = instance_row(i, OUT_ROW);
+            layouter
+                .constrain_instance(out.cell(), config.instance, row)
+                .map_err(|e| FiboError::expose(row, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: Field, const B: usize> Circuit<F> for BatchFiboCircuit<F, B> {
+    type Config = BatchFiboConfig<B>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); B].map(|_| [(); 3].map(|_| meta.advice_column()));
+        let instance = meta.instance_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        for column in advice.iter().flatten() {
+            meta.enable_equality(*column);
+        }
+        meta.enable_equality(instance);
+
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(selector);
+            advice
+                .iter()
+                .map(|&[col_a, col_b, col_c]| {
+                    let a = meta.query_advice(col_a, Rotation::cur());
+                    let b = meta.query_advice(col_b, Rotation::cur());
+                    let c = meta.query_advice(col_c, Rotation::cur());
+                    s.clone() * (a + b - c)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        BatchFiboConfig {
+            advice,
+            selector,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.assign(config, layouter)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cost::measure, failures::permutation_failure_at, fibonacci::row_based};
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::VerifyFailure, pasta::Fp};
+
+    fn publics(seeds: &[(u64, u64)], n: usize) -> Vec<PublicInputs<Fp>> {
+        seeds
+            .iter()
+            .map(|&(a, b)| PublicInputs::new(Fp::from(a), Fp::from(b), n))
+            .collect()
+    }
+
+    fn verifies<const B: usize>(n: usize, instance: Vec<Fp>) {
+        let circuit = BatchFiboCircuit::<Fp, B>::new(n);
+        mock_ok!(circuit, circuit.min_k(), vec![instance]);
+    }
+
+    fn fails<const B: usize>(n: usize, instance: Vec<Fp>) -> Vec<VerifyFailure> {
+        let circuit = BatchFiboCircuit::<Fp, B>::new(n);
+        mock_fails!(circuit, circuit.min_k(), vec![instance])
+    }
+
+    #[test]
+    fn one_sequence() {
+        let publics = publics(&[(1, 1)], 10);
+        let instance = instance(&publics);
+        assert_eq!(instance, publics[0].to_instance_column());
+        verifies::<1>(10, instance.clone());
+
+        // laid out as the row based circuit
+        let batch = measure(4, &BatchFiboCircuit::<Fp, 1>::new(10)).unwrap();
+        let row_based = measure(4, &row_based::MyCircuit::<Fp>::new(10)).unwrap();
+        assert_eq!(batch, row_based);
+    }
+
+    #[test]
+    fn four_sequences() {
+        let seeds = [(1, 1), (2, 3), (0, 1), (5, 8)];
+        let publics = publics(&seeds, 12);
+        verifies::<4>(12, instance(&publics));
+    }
+
+    #[test]
+    fn one_wrong_output() {
+        let seeds = [(1, 1), (2, 3), (0, 1), (5, 8)];
+        let mut publics = publics(&seeds, 12);
+        publics[2].out += Fp::one();
+        let failures = fails::<4>(12, instance(&publics));
+        for i in 0..4 {
+            let failed =
+                permutation_failure_at(&failures, (Any::Instance, 0), instance_row(i, OUT_ROW));
+            assert_eq!(failed, i == 2, "{i}");
+        }
+    }
+
+    #[test]
+    fn cost() {
+        let one = measure(5, &BatchFiboCircuit::<Fp, 1>::new(20)).unwrap();
+        let four = measure(5, &BatchFiboCircuit::<Fp, 4>::new(20)).unwrap();
+        assert_eq!(four.rows, one.rows);
+        assert_eq!(four.advice_columns, 4 * one.advice_columns);
+        // the extra columns are committed to and opened in the one proof
+        assert!(four.proof_size > one.proof_size);
+        assert!(four.proof_size < 4 * one.proof_size);
+    }
+}
@@ -0,0 +1,229 @@
+//! fibonacci in a single advice column with the seeds 1 and 1 built into the
+//! circuit rather than read from the instance column
+//!
+//! the seed cells are pinned by region.constrain_constant, which copies them to
+//! cells of a fixed column the layouter fills with the constants. that column
+//! has to be enabled with meta.enable_constant in configure. the instance
+//! column carries only the output
+
+use std::marker::PhantomData;
+
+use super::{min_k, single_column::FiboConfig};
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// instance row of the output, the only one
+pub const OUT_ROW: usize = 0;
+
+#[derive(Debug, Clone)]
+pub struct ConstantSeedsConfig {
+    pub fibo: FiboConfig,
+    /// holds the seeds the first two cells are pinned to
+    pub constant: Column<Fixed>,
+}
+
+/// proves the instance column holds the rows-th fibonacci number from 1, 1
+#[derive(Debug, Default)]
+pub struct ConstantSeedsCircuit<F> {
+    /// number of rows in the fibonacci table, the last one being the public output
+    pub rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> ConstantSeedsCircuit<F> {
+    pub fn new(rows: usize) -> Self {
+        Self {
+            rows,
+            _marker: PhantomData,
+        }
+    }
+
+    /// the smallest k fitting a table of `rows` rows
+    pub fn min_k(&self) -> u32 {
+        min_k::<F, Self>(self.rows.max(3))
+    }
+
+    /// the table witnessed from the seeds a and b, which are pinned to 1 and 1
+    /// whatever they are
+    fn assign_with(
+        &self,
+        config: &ConstantSeedsConfig,
+        mut layouter: impl Layouter<F>,
+        (a, b): (Value<F>, Value<F>),
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        if self.rows < 3 {
+            return Err(FiboError::TooFewRows {
+                rows: self.rows,
+                min: 3,
+            });
+        }
+
+        let FiboConfig {
+            advice, selector, ..
+        } = config.fibo;
+        let mut at = (0, "advice");
+        layouter
+            .assign_region(
+                || "entire fibonacci table",
+                |mut region| {
+                    at = (0, "advice");
+                    let mut a_cell = region.assign_advice(|| "a", advice, 0, || a)?;
+                    region.constrain_constant(a_cell.cell(), F::ONE)?;
+                    at = (1, "advice");
+                    let mut b_cell = region.assign_advice(|| "b", advice, 1, || b)?;
+                    region.constrain_constant(b_cell.cell(), F::ONE)?;
+
+                    for n in 0..self.rows - 2 {
+                        at = (n, "selector");
+                        selector.enable(&mut region, n)?;
+                    }
+
+                    for n in 2..self.rows {
+                        at = (n, "advice");
+                        let c_val = a_cell.value().copied() + b_cell.value();
+                        let c_cell = region.assign_advice(|| "c", advice, n, || c_val)?;
+                        a_cell = b_cell;
+                        b_cell = c_cell;
+                    }
+
+                    Ok(b_cell)
+                },
+            )
+            .map_err(|e| FiboError::assign("entire fibonacci table", at, e))
+    }
+
+    fn synthesize_with(
+        &self,
+        config: ConstantSeedsConfig,
+        mut layouter: impl Layouter<F>,
+        seeds: (Value<F>, Value<F>),
+    ) -> Result<(), FiboError> {
+        let out = self.assign_with(&config, layouter.namespace(|| "table"), seeds)?;
+        layouter
+            .constrain_instance(out.cell(), config.fibo.instance, OUT_ROW)
+            .map_err(|e| FiboError::expose(OUT_ROW, e))
+    }
+}
+
+impl<F: Field> Circuit<F> for ConstantSeedsCircuit<F> {
+    type Config = ConstantSeedsConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.rows)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = meta.advice_column();
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        let selector = meta.selector();
+
+        // enable the equality, and the constants for constrain_constant
+        meta.enable_equality(advice);
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(advice, Rotation::cur());
+            let b = meta.query_advice(advice, Rotation::next());
+            let c = meta.query_advice(advice, Rotation(2));
+            vec![s * (a + b - c)]
+        });
+
+        ConstantSeedsConfig {
+            fibo: FiboConfig {
+                advice,
+                selector,
+                instance,
+            },
+            constant,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        let one = Value::known(F::ONE);
+        Ok(self.synthesize_with(config, layouter, (one, one))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{fib, single_column, vectors},
+        mock_fails, mock_ok,
+        summary::describe,
+    };
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn output_only() {
+        let circuit = ConstantSeedsCircuit::<Fp>::new(10);
+        assert_eq!(circuit.min_k(), 4);
+        mock_ok!(circuit, 4, vec![vec![vectors::nth(10)]]);
+        mock_fails!(circuit, 4, vec![vec![vectors::nth(10) + Fp::one()]]);
+        assert_eq!(fib(Fp::one(), Fp::one(), 10), vectors::nth(10));
+    }
+
+    /// the table witnessed from other seeds than the constants
+    struct Seeded {
+        rows: usize,
+        seeds: (u64, u64),
+    }
+
+    impl Circuit<Fp> for Seeded {
+        type Config = ConstantSeedsConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                rows: self.rows,
+                seeds: self.seeds,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            ConstantSeedsCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let (a, b) = self.seeds;
+            let seeds = (Value::known(Fp::from(a)), Value::known(Fp::from(b)));
+            let circuit = ConstantSeedsCircuit::new(self.rows);
+            Ok(circuit.synthesize_with(config, layouter, seeds)?)
+        }
+    }
+
+    #[test]
+    fn other_seeds() {
+        let circuit = Seeded {
+            rows: 10,
+            seeds: (1, 1),
+        };
+        mock_ok!(circuit, 4, vec![vec![vectors::nth(10)]]);
+
+        // the sequence from 2, 3 adds up, but its seeds are not the constants
+        let out = fib(Fp::from(2), Fp::from(3), 10);
+        let circuit = Seeded {
+            rows: 10,
+            seeds: (2, 3),
+        };
+        mock_fails!(circuit, 4, vec![vec![out]]);
+    }
+
+    #[test]
+    fn extra_fixed_column() {
+        let summary = describe::<Fp, ConstantSeedsCircuit<Fp>>(4).unwrap();
+        let instance_seeds = describe::<Fp, single_column::MyCircuit<Fp>>(4).unwrap();
+        assert_eq!(summary.fixed_columns, instance_seeds.fixed_columns + 1);
+        assert_eq!(summary.advice_columns, instance_seeds.advice_columns);
+        assert_eq!(summary.gates, vec!["add"]);
+        assert!(summary.to_string().contains("fixed columns      1"));
+    }
+}
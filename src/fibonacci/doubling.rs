@@ -0,0 +1,249 @@
+//! the n-th fibonacci number by fast doubling, one row per bit of n rather
+//! than one per number
+//!
+//! from (F(k), F(k+1)) = (a, b) a row steps to
+//!
+//!   F(2k) = a·(2b - a),  F(2k+1) = a² + b²
+//!
+//! and on a set bit one further, to (F(2k+1), F(2k) + F(2k+1)). the bits of
+//! the public n are decomposed most significant first from (F(0), F(1)) = (0, 1),
+//! so one key serves every n below 2^BITS. F(1) = F(2) = 1, as for fib(1, 1, n)
+
+use super::min_k;
+use crate::{
+    error::FiboError,
+    gadgets::decompose::{DecomposeChip, DecomposeConfig},
+};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// instance row of n
+pub const N_ROW: usize = 0;
+/// instance row of the claimed F(n)
+pub const OUT_ROW: usize = 1;
+
+#[derive(Debug, Clone)]
+pub struct DoublingConfig {
+    pub decompose: DecomposeConfig,
+    /// a, b and the bit
+    pub advice: [Column<Advice>; 3],
+    /// on every row but the last: the next row doubles this one
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// proves the instance column holds n followed by F(n), for any n below 2^BITS
+#[derive(Debug, Default)]
+pub struct DoublingCircuit<F, const BITS: usize> {
+    pub n: Value<F>,
+}
+
+impl<F: PrimeField, const BITS: usize> DoublingCircuit<F, BITS> {
+    pub fn new(n: u64) -> Self {
+        Self {
+            n: Value::known(F::from(n)),
+        }
+    }
+
+    /// the instance column for n and F(n)
+    pub fn instance(n: u64, out: F) -> Vec<F> {
+        vec![F::from(n), out]
+    }
+
+    /// the smallest k fitting the decomposition and the doubling rows
+    pub fn min_k() -> u32 {
+        min_k::<F, Self>(BITS + 1)
+    }
+
+    fn assign(
+        &self,
+        config: DoublingConfig,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), FiboError> {
+        let [col_a, col_b, col_bit] = config.advice;
+        let n = layouter
+            .assign_region(
+                || "load n",
+                |mut region| {
+                    region.assign_advice_from_instance(|| "n", config.instance, N_ROW, col_a, 0)
+                },
+            )
+            .map_err(|e| FiboError::assign("load n", (0, "a"), e))?;
+
+        let decompose = DecomposeChip::<F, BITS>::construct(config.decompose);
+        let bits = decompose.assign(layouter.namespace(|| "n < 2^BITS"), &n)?;
+
+        let mut at = (0, "a");
+        let out = layouter
+            .assign_region(
+                || "doubling",
+                |mut region| {
+                    at = (0, "a");
+                    let mut a = region.assign_advice_from_constant(|| "a", col_a, 0, F::ZERO)?;
+                    at = (0, "b");
+                    let mut b = region.assign_advice_from_constant(|| "b", col_b, 0, F::ONE)?;
+
+                    for (row, bit) in bits.iter().rev().enumerate() {
+                        at = (row, "selector");
+                        config.selector.enable(&mut region, row)?;
+                        at = (row, "bit");
+                        bit.copy_advice(|| "bit", &mut region, col_bit, row)?;
+
+                        let (fa, fb) = (a.value().copied(), b.value().copied());
+                        let double = fa * (fb + fb - fa);
+                        let double_one = fa * fa + fb * fb;
+                        let set = bit.value().map(|bit| *bit == F::ONE);
+                        let next = set.zip(double).zip(double_one).map(|((set, c), d)| {
+                            if set {
+                                (d, c + d)
+                            } else {
+                                (c, d)
+                            }
+                        });
+
+                        at = (row + 1, "a");
+                        a = region.assign_advice(|| "a", col_a, row + 1, || next.map(|n| n.0))?;
+                        at = (row + 1, "b");
+                        b = region.assign_advice(|| "b", col_b, row + 1, || next.map(|n| n.1))?;
+                    }
+
+                    Ok(a)
+                },
+            )
+            .map_err(|e| FiboError::assign("doubling", at, e))?;
+
+        layouter
+            .constrain_instance(out.cell(), config.instance, OUT_ROW)
+            .map_err(|e| FiboError::expose(OUT_ROW, e))
+    }
+}
+
+impl<F: PrimeField, const BITS: usize> Circuit<F> for DoublingCircuit<F, BITS> {
+    type Config = DoublingConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        let [a, b, bit] = advice;
+        meta.create_gate("double", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, b, bit] = [a, b, bit].map(|column| meta.query_advice(column, Rotation::cur()));
+            let a_next = meta.query_advice(advice[0], Rotation::next());
+            let b_next = meta.query_advice(advice[1], Rotation::next());
+
+            // F(2k) and F(2k+1)
+            let double = a.clone() * (b.clone() + b.clone() - a.clone());
+            let double_one = a.clone() * a + b.clone() * b;
+            vec![
+                s.clone()
+                    * (double.clone() + bit.clone() * (double_one.clone() - double.clone())
+                        - a_next),
+                s * (double_one + bit * double - b_next),
+            ]
+        });
+
+        DoublingConfig {
+            decompose: DecomposeChip::<F, BITS>::configure(meta),
+            advice,
+            selector,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.assign(config, layouter)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cost::measure,
+        fibonacci::{fib, public_inputs::PublicInputs, single_column},
+        mock_fails, mock_ok,
+    };
+    use halo2_proofs::pasta::Fp;
+
+    type Doubling = DoublingCircuit<Fp, 8>;
+
+    /// the circuit of n, its k and an instance claiming `out`
+    fn doubling(n: u64, out: Fp) -> (Doubling, u32, Vec<Vec<Fp>>) {
+        (
+            Doubling::new(n),
+            Doubling::min_k(),
+            vec![Doubling::instance(n, out)],
+        )
+    }
+
+    fn verifies(n: u64, out: Fp) {
+        let (circuit, k, instances) = doubling(n, out);
+        mock_ok!(circuit, k, instances);
+    }
+
+    fn fails(n: u64, out: Fp) {
+        let (circuit, k, instances) = doubling(n, out);
+        mock_fails!(circuit, k, instances);
+    }
+
+    #[test]
+    fn additive_output() {
+        // 10 = 0b1010 and 13 = 0b1101 both double and double-and-add, 255 and
+        // 128 only the one or the other, after the leading zeros
+        for n in [3, 4, 10, 13, 21, 64, 128, 255] {
+            let publics = PublicInputs::new(Fp::one(), Fp::one(), n);
+            let additive = single_column::MyCircuit::<Fp>::new(n);
+            let k = single_column::min_k_for_rows::<Fp>(n);
+            mock_ok!(additive, k, vec![publics.to_instance_column()]);
+
+            verifies(n as u64, publics.out);
+            fails(n as u64, publics.out + Fp::one());
+        }
+    }
+
+    #[test]
+    fn small_n() {
+        verifies(0, Fp::zero());
+        verifies(1, Fp::one());
+        verifies(2, Fp::one());
+        fails(2, Fp::from(2));
+    }
+
+    #[test]
+    fn n_too_large() {
+        // 256 needs a ninth bit
+        fails(256, fib(Fp::one(), Fp::one(), 256));
+    }
+
+    #[test]
+    fn rows() {
+        // F(10^6) wraps around the field many times over, the same way on both
+        let n = 1_000_000;
+        let out = fib(Fp::one(), Fp::one(), n);
+        let circuit = DoublingCircuit::<Fp, 20>::new(n as u64);
+        let k = DoublingCircuit::<Fp, 20>::min_k();
+        let instance = DoublingCircuit::<Fp, 20>::instance(n as u64, out);
+        mock_ok!(circuit, k, vec![instance]);
+
+        let doubling = measure(k, &circuit).unwrap();
+        let additive = measure(20, &single_column::MyCircuit::<Fp>::new(n)).unwrap();
+        assert_eq!(k, 5);
+        // n on a row of its own, then the 20 doublings after (0, 1)
+        assert_eq!(doubling.rows, 22);
+        assert_eq!(additive.rows, n);
+    }
+}
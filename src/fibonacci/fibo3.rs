@@ -1,30 +1,34 @@
+//! same layout as single_column, but the circuit is a unit struct
+
 use std::marker::PhantomData;
 
+use super::public_inputs::{PublicInputs, A_ROW, B_ROW, OUT_ROW};
+use crate::error::FiboError;
 use halo2_proofs::{
     arithmetic::Field, circuit::*, dev::MockProver, pasta::Fp, plonk::*, poly::Rotation,
 };
 
 #[derive(Debug, Clone)]
-struct FiboConfig {
+pub struct FiboConfig {
     pub advice: Column<Advice>,
     pub selector: Selector,
     pub instance: Column<Instance>,
 }
 
-struct FiboChip<F: Field> {
+pub struct FiboChip<F: Field> {
     config: FiboConfig,
     _marker: PhantomData<F>,
 }
 
 impl<F: Field> FiboChip<F> {
-    fn construct(config: FiboConfig) -> Self {
+    pub fn construct(config: FiboConfig) -> Self {
         Self {
             config,
             _marker: PhantomData,
         }
     }
 
-    fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> FiboConfig {
+    pub fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> FiboConfig {
         let advice = meta.advice_column();
         let selector = meta.selector();
 
@@ -57,46 +61,57 @@ impl<F: Field> FiboChip<F> {
     /// |  7  |    21    |     1     |      
     /// |  8  |    34    |     0     |      
     /// |  9  |    55    |     0     |      
-    fn assign(
+    pub fn assign(
         &self,
         mut layouter: impl Layouter<F>,
         rows: usize,
-    ) -> Result<AssignedCell<F, F>, Error> {
-        layouter.assign_region(
-            || "entire fibonacci table",
-            |mut region| {
-                self.config.selector.enable(&mut region, 0)?;
-                self.config.selector.enable(&mut region, 1)?;
-
-                let mut a_cell = region.assign_advice_from_instance(
-                    || "1",
-                    self.config.instance,
-                    0,
-                    self.config.advice,
-                    0,
-                )?;
-                let mut b_cell = region.assign_advice_from_instance(
-                    || "1",
-                    self.config.instance,
-                    1,
-                    self.config.advice,
-                    1,
-                )?;
-
-                for n in 2..rows {
-                    if n < rows - 2 {
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        if rows < 3 {
+            return Err(FiboError::TooFewRows { rows, min: 3 });
+        }
+
+        let mut at = (0, "advice");
+        layouter
+            .assign_region(
+                || "entire fibonacci table",
+                |mut region| {
+                    at = (0, "advice");
+                    let mut a_cell = region.assign_advice_from_instance(
+                        || "1",
+                        self.config.instance,
+                        A_ROW,
+                        self.config.advice,
+                        0,
+                    )?;
+                    at = (1, "advice");
+                    let mut b_cell = region.assign_advice_from_instance(
+                        || "1",
+                        self.config.instance,
+                        B_ROW,
+                        self.config.advice,
+                        1,
+                    )?;
+
+                    // the gate at row n reaches row n + 2
+                    for n in 0..rows - 2 {
+                        at = (n, "selector");
                         self.config.selector.enable(&mut region, n)?;
                     }
-                    let c_val = a_cell.value().copied() + b_cell.value();
 
-                    let c_cell = region.assign_advice(|| "c", self.config.advice, n, || c_val)?;
-                    a_cell = b_cell;
-                    b_cell = c_cell;
-                }
+                    for n in 2..rows {
+                        at = (n, "advice");
+                        let c_val = a_cell.value().copied() + b_cell.value();
 
-                Ok(b_cell)
-            },
-        )
+                        let c_cell =
+                            region.assign_advice(|| "c", self.config.advice, n, || c_val)?;
+                        a_cell = b_cell;
+                        b_cell = c_cell;
+                    }
+
+                    Ok(b_cell)
+                },
+            )
+            .map_err(|e| FiboError::assign("entire fibonacci table", at, e))
     }
 
     pub fn expose_public(
@@ -104,20 +119,22 @@ impl<F: Field> FiboChip<F> {
         mut layouter: impl Layouter<F>,
         cell: &AssignedCell<F, F>,
         row: usize,
-    ) -> Result<(), Error> {
-        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
     }
 }
 
-#[derive(Default)]
-struct MyCircuit;
+#[derive(Debug, Default)]
+pub struct MyCircuit;
 
 impl<F: Field> Circuit<F> for MyCircuit {
     type Config = FiboConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -136,26 +153,25 @@ impl<F: Field> Circuit<F> for MyCircuit {
         let out_cell = chip.assign(layouter.namespace(|| "entire region"), 10)?;
 
         // SAME: assign_advice_from_instance
-        chip.expose_public(layouter.namespace(|| "out"), &out_cell, 2)?;
+        chip.expose_public(layouter.namespace(|| "out"), &out_cell, OUT_ROW)?;
 
         Ok(())
     }
 }
 
-fn main() {
+pub fn run() {
     let k = 4;
     let a = Fp::from(1);
-    let out = Fp::from(55);
     let circuit = MyCircuit;
 
-    let mut publics = vec![a, a, out];
+    let mut publics = PublicInputs::new(a, a, 10);
 
-    let prover = MockProver::run(k, &circuit, vec![publics.clone()]).unwrap();
+    let prover = MockProver::run(k, &circuit, vec![publics.to_instance_column()]).unwrap();
     prover.assert_satisfied();
 
     // wrong out
-    publics[2] += Fp::from(10);
-    let _prover = MockProver::run(k, &circuit, vec![publics.clone()]).unwrap();
+    publics.out += Fp::from(10);
+    let _prover = MockProver::run(k, &circuit, vec![publics.to_instance_column()]).unwrap();
     // uncomment the following line will fail
     // _prover.assert_satisfied();
 }
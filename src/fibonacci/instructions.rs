@@ -0,0 +1,149 @@
+//! the instructions shared by the fibonacci chips, and a circuit generic over them
+
+use std::marker::PhantomData;
+
+use super::{
+    public_inputs::{A_ROW, B_ROW, OUT_ROW},
+    Step,
+};
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*};
+
+type ResultNum<N> = Result<N, Error>;
+
+/// what a chip needs to prove a fibonacci sequence, whatever its layout
+pub trait FiboInstructions<F: Field>: Chip<F> + Sized {
+    // a number of the sequence
+    type Num: Clone;
+
+    fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> Self::Config;
+
+    fn construct(config: Self::Config) -> Self;
+
+    // load the two seeds as private inputs
+    fn load_initial(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> ResultNum<(Self::Num, Self::Num)>;
+
+    // return the number after prev and cur, constrained to follow them
+    fn advance(
+        &self,
+        layouter: impl Layouter<F>,
+        step: Step,
+        prev: &Self::Num,
+        cur: &Self::Num,
+    ) -> ResultNum<Self::Num>;
+
+    // expose a number as public input to the circuit
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: &Self::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+/// proves the n-th number from the seeds a and b, with any chip implementing
+/// the instructions. the instance column holds a, b and out
+#[derive(Debug, Default)]
+pub struct FiboCircuit<F, C> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    /// index of the number to prove, a and b being the 1st and 2nd. n >= 3
+    pub n: usize,
+    _chip: PhantomData<C>,
+}
+
+impl<F: Field, C> FiboCircuit<F, C> {
+    pub fn new(a: F, b: F, n: usize) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+            _chip: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, C: FiboInstructions<F>> Circuit<F> for FiboCircuit<F, C> {
+    type Config = C::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: Value::unknown(),
+            b: Value::unknown(),
+            n: self.n,
+            _chip: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        C::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = C::construct(config);
+
+        let (a, b) = chip.load_initial(layouter.namespace(|| "load seeds"), self.a, self.b)?;
+        chip.expose_public(layouter.namespace(|| "expose a"), &a, A_ROW)?;
+        chip.expose_public(layouter.namespace(|| "expose b"), &b, B_ROW)?;
+
+        let (mut prev, mut cur) = (a, b);
+        for i in 3..=self.n {
+            let step = Step {
+                index: i,
+                of: self.n,
+            };
+            let next = chip.advance(layouter.namespace(|| "advance"), step, &prev, &cur)?;
+            prev = cur;
+            cur = next;
+        }
+
+        chip.expose_public(layouter.namespace(|| "expose out"), &cur, OUT_ROW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{public_inputs::PublicInputs, row_based, single_column},
+        mock_fails, mock_ok,
+    };
+    use halo2_proofs::pasta::Fp;
+
+    fn verifies<C: FiboInstructions<Fp>>(n: usize, publics: PublicInputs<Fp>) {
+        let circuit = FiboCircuit::<Fp, C>::new(publics.a, publics.b, n);
+        mock_ok!(circuit, 6, vec![publics.to_instance_column()]);
+    }
+
+    fn fails<C: FiboInstructions<Fp>>(n: usize, publics: PublicInputs<Fp>) {
+        let circuit = FiboCircuit::<Fp, C>::new(publics.a, publics.b, n);
+        mock_fails!(circuit, 6, vec![publics.to_instance_column()]);
+    }
+
+    #[test]
+    fn same_outputs_for_both_chips() {
+        for (a, b, n) in [(1, 1, 3), (1, 1, 10), (2, 7, 12)] {
+            let publics = PublicInputs::new(Fp::from(a), Fp::from(b), n);
+
+            verifies::<row_based::FiboChip<Fp>>(n, publics);
+            verifies::<single_column::FiboChip<Fp>>(n, publics);
+
+            let wrong = PublicInputs {
+                out: publics.out + Fp::from(1),
+                ..publics
+            };
+            fails::<row_based::FiboChip<Fp>>(n, wrong);
+            fails::<single_column::FiboChip<Fp>>(n, wrong);
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! any second order linear recurrence c = p·a + q·b, the coefficients p and q
+//! sitting in fixed columns so the gate does not change with them
+
+use super::{
+    public_inputs::{A_ROW, B_ROW, OUT_ROW},
+    Step,
+};
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// the n-th number of the recurrence c = p·a + q·b seeded with a and b as 1st
+/// and 2nd, computed natively
+pub fn lin_rec<F: Field>(p: F, q: F, a: F, b: F, n: usize) -> F {
+    let (mut a, mut b) = (a, b);
+    for _ in 2..n {
+        (a, b) = (b, p * a + q * b);
+    }
+    if n == 1 {
+        a
+    } else {
+        b
+    }
+}
+
+/// the three cells of the first row: (a, b, c)
+type FirstRow<F> = (AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>);
+
+#[derive(Debug, Clone)]
+pub struct LinRecConfig {
+    pub advice: [Column<Advice>; 3],
+    /// p and q
+    pub coeffs: [Column<Fixed>; 2],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct LinRecChip<F: Field> {
+    config: LinRecConfig,
+    p: F,
+    q: F,
+}
+
+impl<F: Field> LinRecChip<F> {
+    pub fn construct(config: LinRecConfig, p: F, q: F) -> Self {
+        Self { config, p, q }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> LinRecConfig {
+        let col_a = meta.advice_column();
+        let col_b = meta.advice_column();
+        let col_c = meta.advice_column();
+        let col_p = meta.fixed_column();
+        let col_q = meta.fixed_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(col_a);
+        meta.enable_equality(col_b);
+        meta.enable_equality(col_c);
+        meta.enable_equality(instance);
+
+        meta.create_gate("linear recurrence", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            let p = meta.query_fixed(col_p);
+            let q = meta.query_fixed(col_q);
+            vec![s * (p * a + q * b - c)]
+        });
+
+        LinRecConfig {
+            advice: [col_a, col_b, col_c],
+            coeffs: [col_p, col_q],
+            selector,
+            instance,
+        }
+    }
+
+    /// enables the gate on the row at `offset` and loads its coefficients
+    fn enable(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
+        self.config.selector.enable(region, offset)?;
+        let [col_p, col_q] = self.config.coeffs;
+        region.assign_fixed(|| "p", col_p, offset, || Value::known(self.p))?;
+        region.assign_fixed(|| "q", col_q, offset, || Value::known(self.q))?;
+        Ok(())
+    }
+
+    pub fn assign_first_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<FirstRow<F>, FiboError> {
+        let mut at = (0, "coefficients");
+        layouter
+            .assign_region(
+                || "first row",
+                |mut region| {
+                    at = (0, "coefficients");
+                    self.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    let a_cell = region.assign_advice(|| "a", self.config.advice[0], 0, || a)?;
+                    at = (0, "b");
+                    let b_cell = region.assign_advice(|| "b", self.config.advice[1], 0, || b)?;
+
+                    at = (0, "c");
+                    let c_val = a * Value::known(self.p) + b * Value::known(self.q);
+                    let c_cell =
+                        region.assign_advice(|| "c", self.config.advice[2], 0, || c_val)?;
+                    Ok((a_cell, b_cell, c_cell))
+                },
+            )
+            .map_err(|e| FiboError::assign("first row", at, e))
+    }
+
+    /// the row of the step-th number, from the two before it
+    pub fn assign_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        step: Step,
+        pre_b: &AssignedCell<F, F>,
+        pre_c: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let name = step.to_string();
+        let mut at = (0, "coefficients");
+        layouter
+            .assign_region(
+                || name.clone(),
+                |mut region| {
+                    at = (0, "coefficients");
+                    self.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    pre_b.copy_advice(|| "a", &mut region, self.config.advice[0], 0)?;
+                    at = (0, "b");
+                    pre_c.copy_advice(|| "b", &mut region, self.config.advice[1], 0)?;
+
+                    at = (0, "c");
+                    let c_val = pre_b.value().copied() * Value::known(self.p)
+                        + pre_c.value().copied() * Value::known(self.q);
+                    region.assign_advice(|| "c", self.config.advice[2], 0, || c_val)
+                },
+            )
+            .map_err(|e| FiboError::assign(name, at, e))
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field> Chip<F> for LinRecChip<F> {
+    type Config = LinRecConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LinRecCircuit<F> {
+    /// the coefficients are part of the circuit, not of the witness
+    pub p: F,
+    pub q: F,
+    pub a: Value<F>,
+    pub b: Value<F>,
+    /// index of the number to prove, a and b being the 1st and 2nd. n >= 3
+    pub n: usize,
+}
+
+impl<F: Field> LinRecCircuit<F> {
+    pub fn new(p: F, q: F, a: F, b: F, n: usize) -> Self {
+        Self {
+            p,
+            q,
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for LinRecCircuit<F> {
+    type Config = LinRecConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            p: self.p,
+            q: self.q,
+            a: Value::unknown(),
+            b: Value::unknown(),
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        LinRecChip::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = LinRecChip::construct(config, self.p, self.q);
+
+        let (a_cell, mut pre_b, mut pre_c) =
+            chip.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
+        chip.expose_public(layouter.namespace(|| "a"), &a_cell, A_ROW)?;
+        chip.expose_public(layouter.namespace(|| "b"), &pre_b, B_ROW)?;
+
+        for i in 4..=self.n {
+            let step = Step {
+                index: i,
+                of: self.n,
+            };
+            let c_cell =
+                chip.assign_row(layouter.namespace(|| "next row"), step, &pre_b, &pre_c)?;
+            pre_b = pre_c;
+            pre_c = c_cell;
+        }
+
+        chip.expose_public(layouter.namespace(|| "out"), &pre_c, OUT_ROW)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{fib, public_inputs::PublicInputs, vectors::nth},
+        mock_fails, mock_ok,
+    };
+    use halo2_proofs::pasta::Fp;
+
+    fn mock(
+        p: u64,
+        q: u64,
+        a: u64,
+        b: u64,
+        n: usize,
+        out: Fp,
+    ) -> (LinRecCircuit<Fp>, Vec<Vec<Fp>>) {
+        let [p, q, a, b] = [p, q, a, b].map(Fp::from);
+        let publics = PublicInputs { a, b, out };
+        (
+            LinRecCircuit::new(p, q, a, b, n),
+            vec![publics.to_instance_column()],
+        )
+    }
+
+    fn verifies(p: u64, q: u64, a: u64, b: u64, n: usize, out: Fp) {
+        let (circuit, instances) = mock(p, q, a, b, n, out);
+        mock_ok!(circuit, 4, instances);
+    }
+
+    fn fails(p: u64, q: u64, a: u64, b: u64, n: usize, out: Fp) {
+        let (circuit, instances) = mock(p, q, a, b, n, out);
+        mock_fails!(circuit, 4, instances);
+    }
+
+    #[test]
+    fn fibonacci() {
+        let one = Fp::from(1);
+        assert_eq!(lin_rec(one, one, one, one, 10), fib(one, one, 10));
+        verifies(1, 1, 1, 1, 10, nth(10));
+        fails(1, 1, 1, 1, 10, nth(10) + one);
+    }
+
+    #[test]
+    fn pell() {
+        // P(n) = 2 P(n - 1) + P(n - 2): the 2 goes with b, the newer number
+        let pell = [0, 1, 2, 5, 12, 29, 70, 169, 408, 985];
+        let [zero, one, two] = [0, 1, 2].map(Fp::from);
+        for (i, p) in pell.into_iter().enumerate() {
+            assert_eq!(lin_rec(one, two, zero, one, i + 1), Fp::from(p));
+        }
+        verifies(1, 2, 0, 1, 10, Fp::from(985));
+    }
+
+    #[test]
+    fn wrong_coefficients() {
+        // the 10th pell number, claimed by the fibonacci circuit, and back
+        fails(1, 1, 0, 1, 10, Fp::from(985));
+        fails(1, 2, 0, 1, 10, nth(9));
+        // p and q swapped
+        fails(2, 1, 0, 1, 10, Fp::from(985));
+    }
+}
@@ -1,3 +1,91 @@
-pub mod fibo1;
-pub mod fibo2;
-pub mod fibo3;
\ No newline at end of file
+use std::fmt;
+
+use crate::error::FiboError;
+use halo2_proofs::{
+    arithmetic::Field,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+pub mod backwards;
+pub mod batch;
+pub mod constant_seeds;
+pub mod doubling;
+pub mod fibo3;
+pub mod instructions;
+pub mod linrec;
+pub mod product;
+pub mod public_inputs;
+pub mod public_n;
+pub mod reference;
+pub mod row_based;
+pub mod single_column;
+pub mod standard_plonk;
+pub mod tribonacci;
+pub mod two_column;
+pub mod two_instance;
+pub mod vectors;
+pub mod wrapping;
+
+/// the n-th number of the sequence seeded with a and b as 1st and 2nd, computed natively
+///
+/// this is the number mod p: past reference::max_exact_index it is no longer
+/// the integer one, which reference::fib_exact gives
+pub fn fib<F: Field>(a: F, b: F, n: usize) -> F {
+    let (mut a, mut b) = (a, b);
+    for _ in 2..n {
+        (a, b) = (b, a + b);
+    }
+    if n == 1 {
+        a
+    } else {
+        b
+    }
+}
+
+/// the layouts prove the n-th number from a, b and at least one sum of them,
+/// so n below 3 is an error rather than a circuit
+pub fn check_n(n: usize) -> Result<(), FiboError> {
+    if n < 3 {
+        return Err(FiboError::TooFewRows { rows: n, min: 3 });
+    }
+    Ok(())
+}
+
+/// the region of the index-th number of a sequence of `of`, from the first one
+/// past the first row, which names it "fib step index/of"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub index: usize,
+    pub of: usize,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fib step {}/{}", self.index, self.of)
+    }
+}
+
+/// a circuit whose sequence length is part of its type, and so of its keys
+pub trait FixedLength {
+    /// the index of the number proven, a and b being the 1st and 2nd
+    const ROWS: usize;
+}
+
+/// the rows of 2^k left to the circuit C once halo2 has reserved its blinding rows
+pub fn usable_rows<F: Field, C: Circuit<F>>(k: u32) -> usize {
+    let mut meta = ConstraintSystem::<F>::default();
+    C::configure(&mut meta);
+    (1 << k) - (meta.blinding_factors() + 1)
+}
+
+/// the smallest k for which the circuit C has `rows` usable rows
+pub fn min_k<F: Field, C: Circuit<F>>(rows: usize) -> u32 {
+    let mut meta = ConstraintSystem::<F>::default();
+    C::configure(&mut meta);
+
+    let mut k = 1;
+    while (1 << k) < meta.minimum_rows() || usable_rows::<F, C>(k) < rows {
+        k += 1;
+    }
+    k
+}
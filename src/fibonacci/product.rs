@@ -0,0 +1,189 @@
+//! the multiplicative fibonacci sequence c = a·b, over three advice columns like row_based
+
+use std::marker::PhantomData;
+
+use super::{
+    instructions::{FiboCircuit, FiboInstructions},
+    Step,
+};
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// the n-th number of the sequence c = a·b seeded with a and b as 1st and
+/// 2nd, computed natively
+pub fn product_fib<F: Field>(a: F, b: F, n: usize) -> F {
+    let (mut a, mut b) = (a, b);
+    for _ in 2..n {
+        (a, b) = (b, a * b);
+    }
+    if n == 1 {
+        a
+    } else {
+        b
+    }
+}
+
+/// proves the n-th number of the multiplicative sequence
+pub type ProductCircuit<F> = FiboCircuit<F, ProductChip<F>>;
+
+#[derive(Debug, Clone)]
+pub struct ProductConfig {
+    pub advice: [Column<Advice>; 3],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct ProductChip<F: Field> {
+    config: ProductConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for ProductChip<F> {
+    type Config = ProductConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> FiboInstructions<F> for ProductChip<F> {
+    type Num = AssignedCell<F, F>;
+
+    fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> ProductConfig {
+        let col_a = meta.advice_column();
+        let col_b = meta.advice_column();
+        let col_c = meta.advice_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(col_a);
+        meta.enable_equality(col_b);
+        meta.enable_equality(col_c);
+        meta.enable_equality(instance);
+
+        meta.create_gate("mul", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            vec![s * (a * b - c)]
+        });
+
+        ProductConfig {
+            advice: [col_a, col_b, col_c],
+            selector,
+            instance,
+        }
+    }
+
+    fn construct(config: ProductConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn load_initial(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        layouter.assign_region(
+            || "load initial",
+            |mut region| {
+                let a_cell = region.assign_advice(|| "a", self.config.advice[0], 0, || a)?;
+                let b_cell = region.assign_advice(|| "b", self.config.advice[1], 0, || b)?;
+                Ok((a_cell, b_cell))
+            },
+        )
+    }
+
+    fn advance(
+        &self,
+        mut layouter: impl Layouter<F>,
+        step: Step,
+        prev: &AssignedCell<F, F>,
+        cur: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || step.to_string(),
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                prev.copy_advice(|| "a", &mut region, self.config.advice[0], 0)?;
+                cur.copy_advice(|| "b", &mut region, self.config.advice[1], 0)?;
+
+                let c_val = prev.value().copied() * cur.value();
+                region.assign_advice(|| "c", self.config.advice[2], 0, || c_val)
+            },
+        )
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        num: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(num.cell(), self.config.instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibonacci::public_inputs::PublicInputs, mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    fn circuit(n: usize, publics: PublicInputs<Fp>) -> ProductCircuit<Fp> {
+        ProductCircuit::new(publics.a, publics.b, n)
+    }
+
+    fn publics(a: u64, b: u64, n: usize) -> PublicInputs<Fp> {
+        let (a, b) = (Fp::from(a), Fp::from(b));
+        PublicInputs {
+            a,
+            b,
+            out: product_fib(a, b, n),
+        }
+    }
+
+    #[test]
+    fn small() {
+        // 2, 3, 6, 18, 108, 1944
+        assert_eq!(publics(2, 3, 6).out, Fp::from(1944));
+        let publics = publics(2, 3, 6);
+        mock_ok!(circuit(6, publics), 4, vec![publics.to_instance_column()]);
+
+        let wrong = PublicInputs {
+            out: Fp::from(1945),
+            ..publics
+        };
+        mock_fails!(circuit(6, wrong), 4, vec![wrong.to_instance_column()]);
+    }
+
+    #[test]
+    fn wraps_around() {
+        // the n-th number is 2^e2 · 3^e3, e2 and e3 following fibonacci
+        let n = 20;
+        let (mut e2, mut e3) = ((1u64, 0u64), (0u64, 1u64));
+        for _ in 2..n {
+            e2 = (e2.1, e2.0 + e2.1);
+            e3 = (e3.1, e3.0 + e3.1);
+        }
+        // far more than the 255 bits of the modulus
+        assert!(e2.1 > 1000);
+        let expected = Fp::from(2).pow_vartime([e2.1]) * Fp::from(3).pow_vartime([e3.1]);
+
+        let publics = publics(2, 3, n);
+        assert_eq!(publics.out, expected);
+        mock_ok!(circuit(n, publics), 5, vec![publics.to_instance_column()]);
+
+        // the number without reducing mod p does not fit in u128, let alone the field
+        assert!(2u128.checked_pow(e2.1 as u32).is_none());
+    }
+}
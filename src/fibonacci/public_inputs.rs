@@ -0,0 +1,78 @@
+//! the layout of the instance column shared by the fibonacci circuits
+
+use super::fib;
+use halo2_proofs::arithmetic::Field;
+
+/// instance row of the first seed
+pub const A_ROW: usize = 0;
+/// instance row of the second seed
+pub const B_ROW: usize = 1;
+/// instance row of the claimed n-th number
+pub const OUT_ROW: usize = 2;
+
+/// with the seeds and the output in instance columns of their own: the column
+/// of the seeds, a and b at A_ROW and B_ROW
+pub const SEEDS_COLUMN: usize = 0;
+/// the column of the output, out at its first row
+pub const OUTPUTS_COLUMN: usize = 1;
+
+/// what the verifier knows: the seeds and the claimed n-th number
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicInputs<F> {
+    pub a: F,
+    pub b: F,
+    pub out: F,
+}
+
+impl<F: Field> PublicInputs<F> {
+    /// the honest public inputs for the n-th number from the seeds a and b
+    pub fn new(a: F, b: F, n: usize) -> Self {
+        Self {
+            a,
+            b,
+            out: fib(a, b, n),
+        }
+    }
+
+    /// the instance column, each input at its row
+    pub fn to_instance_column(&self) -> Vec<F> {
+        let mut column = vec![F::ZERO; 3];
+        column[A_ROW] = self.a;
+        column[B_ROW] = self.b;
+        column[OUT_ROW] = self.out;
+        column
+    }
+
+    /// the instance columns of the seeds and of the output, in that order
+    pub fn to_instance_columns(&self) -> Vec<Vec<F>> {
+        let mut columns = vec![vec![F::ZERO; 2], vec![self.out]];
+        columns[SEEDS_COLUMN][A_ROW] = self.a;
+        columns[SEEDS_COLUMN][B_ROW] = self.b;
+        columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{row_based, single_column},
+        mock_fails, mock_ok,
+    };
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn wrong_order() {
+        let (a, b) = (Fp::from(2), Fp::from(3));
+        let publics = PublicInputs::new(a, b, 10);
+        let reversed = vec![publics.out, publics.b, publics.a];
+
+        let row_based = row_based::MyCircuit::new(10);
+        mock_ok!(row_based, 4, vec![publics.to_instance_column()]);
+        mock_fails!(row_based, 4, vec![reversed.clone()]);
+
+        let single_column = single_column::MyCircuit::<Fp>::new(10);
+        mock_ok!(single_column, 4, vec![publics.to_instance_column()]);
+        mock_fails!(single_column, 4, vec![reversed]);
+    }
+}
@@ -0,0 +1,314 @@
+//! the n-th fibonacci number from 1, 1 for a public n up to MAX, so one
+//! proving key serves every n
+//!
+//! row r holds idx = r + 1 and, until idx reaches n, a = F(idx) and
+//! b = F(idx + 1). done is set on the row where idx - n is zero, by the is zero
+//! chip, and stays set, freezing a and b from there on. the last row then
+//! holds a = F(n), and must be done, which rejects an n past MAX
+//!
+//! | row | idx |  a   |  b   | n | done |
+//! |  0  |  1  |  1   |  1   | 4 |  0   |
+//! |  1  |  2  |  1   |  2   | 4 |  0   |
+//! |  2  |  3  |  2   |  3   | 4 |  0   |
+//! |  3  |  4  |  3   |  5   | 4 |  1   |
+//! |  4  |  5  |  3   |  5   | 4 |  1   |
+
+use super::min_k;
+use crate::{
+    error::FiboError,
+    gadgets::is_zero::{IsZeroChip, IsZeroConfig},
+};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// instance row of n
+pub const N_ROW: usize = 0;
+/// instance row of the claimed F(n)
+pub const OUT_ROW: usize = 1;
+
+#[derive(Debug, Clone)]
+pub struct PublicNConfig<F> {
+    /// idx, a, b, n and done
+    pub advice: [Column<Advice>; 5],
+    /// idx - n
+    pub is_zero: IsZeroConfig<F>,
+    /// on the first row: done is whether n = 1
+    pub s_first: Selector,
+    /// on every row but the first: the step, or the copy once done
+    pub s_next: Selector,
+    /// on every row: the is zero gate
+    pub s_all: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// proves the instance column holds n followed by F(n), for any n from 1 to MAX
+#[derive(Debug, Default)]
+pub struct PublicNCircuit<F, const MAX: usize> {
+    pub n: Value<F>,
+}
+
+impl<F: PrimeField, const MAX: usize> PublicNCircuit<F, MAX> {
+    pub fn new(n: u64) -> Self {
+        Self {
+            n: Value::known(F::from(n)),
+        }
+    }
+
+    /// the instance column for n and F(n)
+    pub fn instance(n: u64, out: F) -> Vec<F> {
+        vec![F::from(n), out]
+    }
+
+    /// the smallest k fitting the MAX rows
+    pub fn min_k() -> u32 {
+        min_k::<F, Self>(MAX)
+    }
+
+    fn assign(
+        &self,
+        config: PublicNConfig<F>,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), FiboError> {
+        if MAX < 1 {
+            return Err(FiboError::TooFewRows { rows: MAX, min: 1 });
+        }
+
+        let [col_idx, col_a, col_b, col_n, col_done] = config.advice;
+        let is_zero = IsZeroChip::construct(config.is_zero.clone());
+        let one = Value::known(F::ONE);
+
+        let mut at = (0, "idx");
+        let (a, done) = layouter
+            .assign_region(
+                || "fibonacci up to n",
+                |mut region| {
+                    let (mut a, mut b, mut done) = (one, one, Value::known(F::ZERO));
+                    let mut cells = None;
+
+                    for row in 0..MAX {
+                        at = (row, "selector");
+                        config.s_all.enable(&mut region, row)?;
+                        if row == 0 {
+                            config.s_first.enable(&mut region, row)?;
+                        } else {
+                            config.s_next.enable(&mut region, row)?;
+                        }
+
+                        at = (row, "n");
+                        let n = region.assign_advice_from_instance(
+                            || "n",
+                            config.instance,
+                            N_ROW,
+                            col_n,
+                            row,
+                        )?;
+                        let idx = Value::known(F::from(row as u64 + 1));
+                        at = (row, "value inv");
+                        is_zero.assign(&mut region, row, idx - n.value())?;
+
+                        // the step, unless done on the row before
+                        if row > 0 {
+                            let step = done.map(|done| done == F::ZERO);
+                            (a, b) = (
+                                step.zip(a).zip(b).map(|((s, a), b)| if s { b } else { a }),
+                                step.zip(a)
+                                    .zip(b)
+                                    .map(|((s, a), b)| if s { a + b } else { b }),
+                            );
+                        }
+                        let at_n = n.value().map(|n| F::from(row as u64 + 1) == *n);
+                        done = done.zip(at_n).map(|(done, at_n)| {
+                            if done == F::ONE || at_n {
+                                F::ONE
+                            } else {
+                                F::ZERO
+                            }
+                        });
+
+                        at = (row, "idx");
+                        let a_cell = if row == 0 {
+                            region.assign_advice_from_constant(|| "idx", col_idx, 0, F::ONE)?;
+                            at = (row, "a");
+                            let a_cell =
+                                region.assign_advice_from_constant(|| "a", col_a, 0, F::ONE)?;
+                            at = (row, "b");
+                            region.assign_advice_from_constant(|| "b", col_b, 0, F::ONE)?;
+                            a_cell
+                        } else {
+                            region.assign_advice(|| "idx", col_idx, row, || idx)?;
+                            at = (row, "a");
+                            let a_cell = region.assign_advice(|| "a", col_a, row, || a)?;
+                            at = (row, "b");
+                            region.assign_advice(|| "b", col_b, row, || b)?;
+                            a_cell
+                        };
+                        at = (row, "done");
+                        let done_cell = region.assign_advice(|| "done", col_done, row, || done)?;
+                        cells = Some((a_cell, done_cell));
+                    }
+
+                    Ok(cells.unwrap())
+                },
+            )
+            .map_err(|e| FiboError::assign("fibonacci up to n", at, e))?;
+
+        layouter
+            .assign_region(
+                || "n <= MAX",
+                |mut region| region.constrain_constant(done.cell(), F::ONE),
+            )
+            .map_err(|e| FiboError::assign("n <= MAX", (0, "done"), e))?;
+        layouter
+            .constrain_instance(a.cell(), config.instance, OUT_ROW)
+            .map_err(|e| FiboError::expose(OUT_ROW, e))
+    }
+}
+
+impl<F: PrimeField, const MAX: usize> Circuit<F> for PublicNCircuit<F, MAX> {
+    type Config = PublicNConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let value_inv = meta.advice_column();
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        let s_first = meta.selector();
+        let s_next = meta.selector();
+        let s_all = meta.selector();
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        let [idx, a, b, n, done] = advice;
+        let is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(s_all),
+            |meta| meta.query_advice(idx, Rotation::cur()) - meta.query_advice(n, Rotation::cur()),
+            value_inv,
+        );
+
+        meta.create_gate("first", |meta| {
+            let s = meta.query_selector(s_first);
+            let done = meta.query_advice(done, Rotation::cur());
+            vec![s * (done - is_zero.expr())]
+        });
+
+        meta.create_gate("next", |meta| {
+            let s = meta.query_selector(s_next);
+            let [idx_prev, a_prev, b_prev, done_prev] =
+                [idx, a, b, done].map(|column| meta.query_advice(column, Rotation::prev()));
+            let [idx, a, b, done] =
+                [idx, a, b, done].map(|column| meta.query_advice(column, Rotation::cur()));
+            let one = Expression::Constant(F::ONE);
+            let step = one.clone() - done_prev.clone();
+            vec![
+                s.clone() * (idx_prev + one - idx),
+                // a' = b, b' = a + b, or a and b as they were
+                s.clone()
+                    * (done_prev.clone() * a_prev.clone() + step.clone() * b_prev.clone() - a),
+                s.clone()
+                    * (done_prev.clone() * b_prev.clone() + step.clone() * (a_prev + b_prev) - b),
+                s * (done_prev + step * is_zero.expr() - done),
+            ]
+        });
+
+        PublicNConfig {
+            advice,
+            is_zero,
+            s_first,
+            s_next,
+            s_all,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.assign(config, layouter)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{fib, vectors::nth},
+        mock_fails, mock_ok, proving,
+    };
+    use halo2_proofs::{
+        pasta::{EqAffine, Fp},
+        poly::commitment::Params,
+    };
+    use rand_core::OsRng;
+
+    const MAX: usize = 20;
+    type Circuit = PublicNCircuit<Fp, MAX>;
+
+    fn out(n: usize) -> Fp {
+        fib(Fp::one(), Fp::one(), n)
+    }
+
+    fn verifies(n: u64, out: Fp) {
+        mock_ok!(
+            Circuit::new(n),
+            Circuit::min_k(),
+            vec![Circuit::instance(n, out)]
+        );
+    }
+
+    fn fails(n: u64, out: Fp) {
+        mock_fails!(
+            Circuit::new(n),
+            Circuit::min_k(),
+            vec![Circuit::instance(n, out)]
+        );
+    }
+
+    #[test]
+    fn edges() {
+        verifies(1, Fp::one());
+        verifies(2, Fp::one());
+        verifies(MAX as u64, out(MAX));
+        assert_eq!(out(MAX), nth(MAX));
+    }
+
+    #[test]
+    fn past_max() {
+        // F(MAX + 1) is what the table would reach next, but it stops at MAX
+        fails(MAX as u64 + 1, out(MAX + 1));
+        fails(MAX as u64 + 1, out(MAX));
+        fails(0, Fp::zero());
+    }
+
+    #[test]
+    fn wrong_n() {
+        verifies(7, nth(7));
+        // F(7) claimed for 8, and F(8) for 7
+        fails(8, nth(7));
+        fails(7, nth(8));
+    }
+
+    #[test]
+    fn one_key() {
+        let k = Circuit::min_k();
+        let params: Params<EqAffine> = Params::new(k);
+        let pk = proving::keygen(&params, &Circuit::default()).unwrap();
+
+        for n in [1, 5, 12, MAX] {
+            let instance = Circuit::instance(n as u64, out(n));
+            let proof =
+                proving::prove(&params, &pk, Circuit::new(n as u64), &[&instance], OsRng).unwrap();
+            assert!(proving::verify(&params, pk.get_vk(), &proof, &[&instance]).is_ok());
+
+            let wrong = Circuit::instance(n as u64 + 1, out(n));
+            assert!(proving::verify(&params, pk.get_vk(), &proof, &[&wrong]).is_err());
+        }
+    }
+}
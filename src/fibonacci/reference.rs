@@ -0,0 +1,183 @@
+//! the integer fibonacci numbers, exactly, to hold the circuits against
+//!
+//! the circuits add in Fp, so once a number passes the modulus p, from the
+//! 368th on with seeds 1 and 1, what they prove is that number reduced mod p.
+//! max_exact_index is the last n whose number is still the integer one
+
+use std::cmp::Ordering;
+
+use halo2_proofs::pasta::{group::ff::PrimeField, Fp};
+
+/// the modulus of Fp, little endian limbs
+pub const MODULUS: [u64; 4] = [
+    0x992d30ed00000001,
+    0x224698fc094cf91b,
+    0x0000000000000000,
+    0x4000000000000000,
+];
+
+/// a natural number of little endian limbs, just enough of one to add
+/// fibonacci numbers and reduce them mod p
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigUint(Vec<u64>);
+
+impl BigUint {
+    pub fn from_u64(value: u64) -> Self {
+        Self(vec![value])
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let len = self.0.len().max(other.0.len());
+        let mut sum = Vec::with_capacity(len + 1);
+        let mut carry = false;
+        for i in 0..len {
+            let x = self.0.get(i).copied().unwrap_or(0);
+            let y = other.0.get(i).copied().unwrap_or(0);
+            let (s, c1) = x.overflowing_add(y);
+            let (s, c2) = s.overflowing_add(carry as u64);
+            sum.push(s);
+            carry = c1 || c2;
+        }
+        if carry {
+            sum.push(1);
+        }
+        Self(sum)
+    }
+
+    /// the bits up to the highest set one
+    pub fn bits(&self) -> usize {
+        let top = self.0.iter().rposition(|&limb| limb != 0);
+        top.map_or(0, |i| 64 * (i + 1) - self.0[i].leading_zeros() as usize)
+    }
+
+    /// the number as Fp, if it is below the modulus
+    pub fn to_fp(&self) -> Option<Fp> {
+        if self.bits() > 256 {
+            return None;
+        }
+        let limbs: [u64; 4] = std::array::from_fn(|i| self.0.get(i).copied().unwrap_or(0));
+        (cmp_limbs(&limbs, &MODULUS) == Ordering::Less).then(|| from_limbs(limbs))
+    }
+
+    /// the number mod p, by long division a bit at a time
+    pub fn reduce(&self) -> Fp {
+        let mut r = [0u64; 4];
+        for bit in (0..self.bits()).rev() {
+            // r < p < 2^255, so doubling it does not overflow
+            for i in (1..4).rev() {
+                r[i] = r[i] << 1 | r[i - 1] >> 63;
+            }
+            r[0] = r[0] << 1 | (self.0[bit / 64] >> (bit % 64)) & 1;
+            if cmp_limbs(&r, &MODULUS) != Ordering::Less {
+                let mut borrow = false;
+                for (limb, p) in r.iter_mut().zip(MODULUS) {
+                    let (d, b1) = limb.overflowing_sub(p);
+                    let (d, b2) = d.overflowing_sub(borrow as u64);
+                    *limb = d;
+                    borrow = b1 || b2;
+                }
+            }
+        }
+        from_limbs(r)
+    }
+}
+
+fn cmp_limbs(x: &[u64; 4], y: &[u64; 4]) -> Ordering {
+    x.iter().rev().cmp(y.iter().rev())
+}
+
+/// limbs below the modulus as Fp
+fn from_limbs(limbs: [u64; 4]) -> Fp {
+    let mut repr = [0; 32];
+    for (bytes, limb) in repr.chunks_mut(8).zip(limbs) {
+        bytes.copy_from_slice(&limb.to_le_bytes());
+    }
+    Fp::from_repr(repr).unwrap()
+}
+
+/// the n-th number of the sequence seeded with a and b as 1st and 2nd, as an
+/// integer
+pub fn fib_exact(a: u64, b: u64, n: usize) -> BigUint {
+    let (mut a, mut b) = (BigUint::from_u64(a), BigUint::from_u64(b));
+    for _ in 2..n {
+        (a, b) = (b.clone(), a.add(&b));
+    }
+    if n == 1 {
+        a
+    } else {
+        b
+    }
+}
+
+/// the largest n whose fibonacci number from 1 and 1 is below p, so that a
+/// circuit proving it proves the integer number
+pub fn max_exact_index() -> usize {
+    let (mut n, mut a, mut b) = (2, BigUint::from_u64(1), BigUint::from_u64(1));
+    loop {
+        (a, b) = (b.clone(), a.add(&b));
+        if b.to_fp().is_none() {
+            return n;
+        }
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{fib, public_inputs::PublicInputs, row_based::MyCircuit},
+        mock_ok,
+    };
+
+    /// mocks the row based circuit of the n-th number claiming `out`
+    fn proves(n: usize, out: Fp) {
+        let (circuit, k) = MyCircuit::with_min_k(n, 12).unwrap();
+        let publics = PublicInputs {
+            a: Fp::one(),
+            b: Fp::one(),
+            out,
+        };
+        mock_ok!(circuit, k, vec![publics.to_instance_column()]);
+    }
+
+    #[test]
+    fn modulus() {
+        let p_minus_one = BigUint(vec![MODULUS[0] - 1, MODULUS[1], MODULUS[2], MODULUS[3]]);
+        assert_eq!(p_minus_one.to_fp(), Some(-Fp::one()));
+        assert_eq!(BigUint(MODULUS.to_vec()).to_fp(), None);
+        assert_eq!(BigUint(MODULUS.to_vec()).reduce(), Fp::zero());
+        assert_eq!(
+            BigUint(MODULUS.to_vec()).add(&p_minus_one).reduce(),
+            -Fp::one()
+        );
+    }
+
+    #[test]
+    fn bound() {
+        let max = max_exact_index();
+        assert_eq!(max, 367);
+        assert!(fib_exact(1, 1, max).to_fp().is_some());
+        assert!(fib_exact(1, 1, max + 1).to_fp().is_none());
+        // p is just over 2^254
+        assert_eq!(fib_exact(1, 1, max + 1).bits(), 255);
+    }
+
+    #[test]
+    fn exact_below_bound() {
+        for n in [3, 100, 200, max_exact_index()] {
+            let exact = fib_exact(1, 1, n).to_fp().unwrap();
+            assert_eq!(exact, fib(Fp::one(), Fp::one(), n));
+            proves(n, exact);
+        }
+    }
+
+    #[test]
+    fn reduced_above_bound() {
+        for n in [max_exact_index() + 1, 400, 500] {
+            let exact = fib_exact(1, 1, n);
+            assert!(exact.to_fp().is_none());
+            proves(n, exact.reduce());
+        }
+    }
+}
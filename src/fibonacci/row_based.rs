@@ -0,0 +1,969 @@
+//! fibonacci over three advice columns (a, b, c), one region per row
+//!
+//! the sums are in Fp, so for n past reference::max_exact_index the circuit
+//! proves the n-th number mod p, not the integer one
+
+use std::marker::PhantomData;
+
+use super::{
+    check_n,
+    instructions::FiboInstructions,
+    min_k,
+    public_inputs::{PublicInputs, A_ROW, B_ROW, OUT_ROW},
+    usable_rows, FixedLength, Step,
+};
+pub use crate::util::ACell;
+use crate::{cost, error::FiboError, trace};
+use halo2_proofs::{
+    arithmetic::Field, circuit::*, dev::MockProver, pasta::Fp, plonk::*, poly::Rotation,
+};
+
+/// the three cells of the first row: (a, b, c)
+type FirstRow<F> = (ACell<F>, ACell<F>, ACell<F>);
+
+#[derive(Debug, Clone)]
+pub struct FiboConfig {
+    pub advice: [Column<Advice>; 3],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct FiboChip<F: Field> {
+    config: FiboConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> FiboChip<F> {
+    pub fn construct(config: FiboConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> FiboConfig {
+        let col_a = meta.advice_column();
+        let col_b = meta.advice_column();
+        let col_c = meta.advice_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(col_a);
+        meta.enable_equality(col_b);
+        meta.enable_equality(col_c);
+        meta.enable_equality(instance);
+
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            vec![s * (a + b - c)]
+        });
+
+        FiboConfig {
+            advice: [col_a, col_b, col_c],
+            selector,
+            instance,
+        }
+    }
+
+    pub fn assign_first_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<FirstRow<F>, FiboError> {
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "first row",
+                |mut region| {
+                    let _span = tracing::debug_span!("region", name = "first row").entered();
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+                    at = (0, "a");
+                    let a_cell = region
+                        .assign_advice(|| "a", self.config.advice[0], 0, || a)
+                        .map(ACell)?;
+
+                    at = (0, "b");
+                    let b_cell = region
+                        .assign_advice(|| "b", self.config.advice[1], 0, || b)
+                        .map(ACell)?;
+
+                    at = (0, "c");
+                    let c_val = a.and_then(|a| b.map(|b| a + b));
+                    let c_cell = region
+                        .assign_advice(|| "c", self.config.advice[2], 0, || c_val)
+                        .map(ACell)?;
+                    Ok((a_cell, b_cell, c_cell))
+                },
+            )
+            .map_err(|e| FiboError::assign("first row", at, e))
+    }
+
+    /// same as assign_first_row, but a and b are copied from their instance
+    /// rows, so they cannot differ from the public seeds
+    pub fn assign_first_row_from_instance(
+        &self,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<FirstRow<F>, FiboError> {
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "first row",
+                |mut region| {
+                    let _span = tracing::debug_span!("region", name = "first row").entered();
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+                    at = (0, "a");
+                    let a_cell = region
+                        .assign_advice_from_instance(
+                            || "a",
+                            self.config.instance,
+                            A_ROW,
+                            self.config.advice[0],
+                            0,
+                        )
+                        .map(ACell)?;
+
+                    at = (0, "b");
+                    let b_cell = region
+                        .assign_advice_from_instance(
+                            || "b",
+                            self.config.instance,
+                            B_ROW,
+                            self.config.advice[1],
+                            0,
+                        )
+                        .map(ACell)?;
+
+                    at = (0, "c");
+                    let c_val = a_cell.value_copied() + b_cell.value_copied();
+                    let c_cell = region
+                        .assign_advice(|| "c", self.config.advice[2], 0, || c_val)
+                        .map(ACell)?;
+                    Ok((a_cell, b_cell, c_cell))
+                },
+            )
+            .map_err(|e| FiboError::assign("first row", at, e))
+    }
+
+    /// the row of the step-th number, from the two before it
+    pub fn assign_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        step: Step,
+        pre_b: &ACell<F>,
+        pre_c: &ACell<F>,
+    ) -> Result<ACell<F>, FiboError> {
+        let name = step.to_string();
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || name.clone(),
+                |mut region| {
+                    let _span = tracing::debug_span!("region", name = name.as_str()).entered();
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    pre_b.copy_to(&mut region, self.config.advice[0], 0, "a")?;
+
+                    at = (0, "b");
+                    pre_c.copy_to(&mut region, self.config.advice[1], 0, "b")?; // what if offset not 0: NotEnoughRowsAvailable
+
+                    at = (0, "c");
+                    let c_val = pre_b.value_copied() + pre_c.value_copied();
+                    #[cfg(test)]
+                    let c_val = tamper::apply(step, c_val);
+
+                    let c_cell = region
+                        .assign_advice(|| "c", self.config.advice[2], 0, || c_val)
+                        .map(ACell)?;
+
+                    Ok(c_cell)
+                },
+            )
+            .map_err(|e| FiboError::assign(name, at, e))
+    }
+
+    /// the gate of the single region layout, making b of the next row the
+    /// sum of b and c of this one
+    pub fn configure_step(meta: &mut ConstraintSystem<F>, config: &FiboConfig) -> Selector {
+        let step = meta.selector();
+        let [_, col_b, col_c] = config.advice;
+
+        meta.create_gate("step", |meta| {
+            let s = meta.query_selector(step);
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            let b_next = meta.query_advice(col_b, Rotation::next());
+            vec![s * (b + c - b_next)]
+        });
+
+        step
+    }
+
+    /// | row |  a  |  b  |  c  | add | step |
+    /// |  0  |  1  |  1  |  2  |  1  |   1  |
+    /// |  1  |  2  |  3  |  5  |  1  |   1  |
+    /// |  2  |  5  |  8  |  13 |  1  |   0  |
+    ///
+    /// the whole sequence up to the n-th number in one region, from the seeds
+    /// in the instance column. a is copied from c of the row above, so each
+    /// row adds two numbers instead of one. returns the n-th number
+    pub fn assign_all(
+        &self,
+        mut layouter: impl Layouter<F>,
+        step: Selector,
+        n: usize,
+    ) -> Result<ACell<F>, FiboError> {
+        check_n(n)?;
+        // row r holds the numbers 2r + 1 to 2r + 3
+        let rows = n / 2;
+        let mut at = (0, "a");
+        layouter
+            .assign_region(
+                || "entire sequence",
+                |mut region| {
+                    let [col_a, col_b, col_c] = self.config.advice;
+
+                    at = (0, "a");
+                    let mut a_cell = region
+                        .assign_advice_from_instance(|| "a", self.config.instance, A_ROW, col_a, 0)
+                        .map(ACell)?;
+                    at = (0, "b");
+                    let mut b_cell = region
+                        .assign_advice_from_instance(|| "b", self.config.instance, B_ROW, col_b, 0)
+                        .map(ACell)?;
+
+                    let mut c_cell = None;
+                    for row in 0..rows {
+                        at = (row, "selector");
+                        self.config.selector.enable(&mut region, row)?;
+                        if row + 1 < rows {
+                            step.enable(&mut region, row)?;
+                        }
+
+                        if row > 0 {
+                            let prev_c: &ACell<F> = c_cell.as_ref().unwrap();
+                            at = (row, "a");
+                            a_cell = prev_c.copy_to(&mut region, col_a, row, "a")?;
+
+                            at = (row, "b");
+                            let b_val = b_cell.value_copied() + prev_c.value_copied();
+                            b_cell = region
+                                .assign_advice(|| "b", col_b, row, || b_val)
+                                .map(ACell)?;
+                        }
+
+                        at = (row, "c");
+                        let c_val = a_cell.value_copied() + b_cell.value_copied();
+                        c_cell = Some(
+                            region
+                                .assign_advice(|| "c", col_c, row, || c_val)
+                                .map(ACell)?,
+                        );
+                    }
+
+                    // an odd n ends in c of the last row, an even one in b
+                    Ok(match c_cell {
+                        Some(c_cell) if n % 2 == 1 => c_cell,
+                        _ => b_cell,
+                    })
+                },
+            )
+            .map_err(|e| FiboError::assign("entire sequence", at, e))
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &ACell<F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field> Chip<F> for FiboChip<F> {
+    type Config = FiboConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> FiboInstructions<F> for FiboChip<F> {
+    type Num = ACell<F>;
+
+    fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> FiboConfig {
+        Self::configure(meta, instance)
+    }
+
+    fn construct(config: FiboConfig) -> Self {
+        Self::construct(config)
+    }
+
+    fn load_initial(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<(ACell<F>, ACell<F>), Error> {
+        layouter.assign_region(
+            || "load initial",
+            |mut region| {
+                let a_cell = region
+                    .assign_advice(|| "a", self.config.advice[0], 0, || a)
+                    .map(ACell)?;
+                let b_cell = region
+                    .assign_advice(|| "b", self.config.advice[1], 0, || b)
+                    .map(ACell)?;
+                Ok((a_cell, b_cell))
+            },
+        )
+    }
+
+    fn advance(
+        &self,
+        layouter: impl Layouter<F>,
+        step: Step,
+        prev: &ACell<F>,
+        cur: &ACell<F>,
+    ) -> Result<ACell<F>, Error> {
+        Ok(self.assign_row(layouter, step, prev, cur)?)
+    }
+
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: &ACell<F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        Ok(self.expose_public(layouter, num, row)?)
+    }
+}
+
+/// where the first row takes the seeds from
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Seeds<F> {
+    /// copied from the instance column
+    #[default]
+    Instance,
+    /// witnessed, then constrained to the instance column
+    Private(Value<F>, Value<F>),
+}
+
+/// witnesses a number one off in a step, as a dishonest prover would, for
+/// tests to see where the failure lands
+#[cfg(test)]
+mod tamper {
+    use super::Step;
+    use halo2_proofs::{arithmetic::Field, circuit::Value};
+    use std::cell::Cell;
+
+    thread_local! {
+        static STEP: Cell<Option<usize>> = const { Cell::new(None) };
+    }
+
+    /// runs `f` with the c of the step-th number off by one
+    pub fn at<T>(step: usize, f: impl FnOnce() -> T) -> T {
+        STEP.set(Some(step));
+        let value = f();
+        STEP.set(None);
+        value
+    }
+
+    pub fn apply<F: Field>(step: Step, c: Value<F>) -> Value<F> {
+        if STEP.get() == Some(step.index) {
+            c + Value::known(F::ONE)
+        } else {
+            c
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MyCircuit<F> {
+    pub seeds: Seeds<F>,
+    /// index of the fibonacci number to prove, a and b being the 1st and 2nd. n >= 3
+    pub n: usize,
+}
+
+impl<F: Field> MyCircuit<F> {
+    /// the n-th number from the seeds in the instance column
+    pub fn new(n: usize) -> Self {
+        Self {
+            seeds: Seeds::Instance,
+            n,
+        }
+    }
+
+    /// the n-th number from the seeds a and b, witnessed like any other number
+    pub fn with_private_seeds(a: F, b: F, n: usize) -> Self {
+        Self {
+            seeds: Seeds::Private(Value::known(a), Value::known(b)),
+            n,
+        }
+    }
+
+    /// same as new, but checks n is at least 3 and the sequence fits in 2^k
+    /// rows
+    pub fn with_k(n: usize, k: u32) -> Result<Self, FiboError> {
+        check_n(n)?;
+        let rows = n - 2;
+        if k < min_k_for_rows::<F>(rows) {
+            let max = max_n::<F>(k).saturating_sub(2);
+            return Err(FiboError::TableTooLarge { rows, k, max });
+        }
+        Ok(Self::new(n))
+    }
+}
+
+impl MyCircuit<Fp> {
+    /// same as new, along with the least k up to `k_max` fitting the n-th number,
+    /// as found by cost::find_min_k. n below 3 fails with Error::Synthesis
+    pub fn with_min_k(n: usize, k_max: u32) -> Result<(Self, u32), Error> {
+        check_n(n)?;
+        let circuit = Self::new(n);
+        // any instance column of the right length lays the circuit out
+        let instances = vec![PublicInputs::new(Fp::one(), Fp::one(), n).to_instance_column()];
+        let k = cost::find_min_k(&circuit, &instances, k_max)?;
+        Ok((circuit, k))
+    }
+}
+
+/// the smallest k fitting `rows` rows of the sequence, n - 2 for the n-th
+/// number since each row is a region of its own holding three numbers
+pub fn min_k_for_rows<F: Field>(rows: usize) -> u32 {
+    // the instance column holds a, b and out
+    min_k::<F, MyCircuit<F>>(rows.max(3))
+}
+
+/// the largest n whose rows fit in a circuit of size 2^k
+pub fn max_n<F: Field>(k: u32) -> usize {
+    usable_rows::<F, MyCircuit<F>>(k) + 2
+}
+
+impl<F: Field> Circuit<F> for MyCircuit<F> {
+    type Config = FiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the number of steps and where the seeds come from shape the
+        // circuit, so they must survive keygen
+        let seeds = match self.seeds {
+            Seeds::Instance => Seeds::Instance,
+            Seeds::Private(..) => Seeds::Private(Value::unknown(), Value::unknown()),
+        };
+        Self { seeds, n: self.n }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let _span = tracing::info_span!("configure").entered();
+        // we can define the instance here to share between chips
+        let instance = meta.instance_column();
+        FiboChip::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let _span = tracing::info_span!("synthesize", pass = trace::pass()).entered();
+        // the first row already holds the 3rd number, out of reach of n < 3
+        check_n(self.n)?;
+        let chip = FiboChip::construct(config);
+
+        let (mut pre_b, mut pre_c) = match self.seeds {
+            Seeds::Instance => {
+                let (_, b_cell, c_cell) =
+                    chip.assign_first_row_from_instance(layouter.namespace(|| "first row"))?;
+                (b_cell, c_cell)
+            }
+            Seeds::Private(a, b) => {
+                let (a_cell, b_cell, c_cell) =
+                    chip.assign_first_row(layouter.namespace(|| "first row"), a, b)?;
+
+                // without these the seeds are free: any a, b would do
+                chip.expose_public(layouter.namespace(|| "a"), &a_cell, A_ROW)?;
+                chip.expose_public(layouter.namespace(|| "b"), &b_cell, B_ROW)?;
+                (b_cell, c_cell)
+            }
+        };
+
+        for i in 4..=self.n {
+            let step = Step {
+                index: i,
+                of: self.n,
+            };
+            let c_cell =
+                chip.assign_row(layouter.namespace(|| "next row"), step, &pre_b, &pre_c)?;
+            pre_b = pre_c;
+            pre_c = c_cell;
+        }
+
+        chip.expose_public(layouter.namespace(|| "out"), &pre_c, OUT_ROW)?;
+
+        Ok(())
+    }
+}
+
+/// the n-th number like MyCircuit, but laid out by assign_all in a single
+/// region, taking about half the rows
+#[derive(Debug, Default)]
+pub struct SingleRegionCircuit<F> {
+    /// index of the fibonacci number to prove, a and b being the 1st and 2nd. n >= 3
+    pub n: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SingleRegionCircuit<F> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for SingleRegionCircuit<F> {
+    type Config = (FiboConfig, Selector);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        let config = FiboChip::configure(meta, instance);
+        let step = FiboChip::configure_step(meta, &config);
+        (config, step)
+    }
+
+    fn synthesize(
+        &self,
+        (config, step): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FiboChip::construct(config);
+
+        let out = chip.assign_all(layouter.namespace(|| "sequence"), step, self.n)?;
+        chip.expose_public(layouter.namespace(|| "out"), &out, OUT_ROW)?;
+
+        Ok(())
+    }
+}
+
+/// MyCircuit for the ROWS-th number, fixed by the type
+#[derive(Debug, Default)]
+pub struct FixedCircuit<F, const ROWS: usize> {
+    pub seeds: Seeds<F>,
+}
+
+impl<F: Field, const ROWS: usize> FixedCircuit<F, ROWS> {
+    pub fn new() -> Self {
+        Self {
+            seeds: Seeds::Instance,
+        }
+    }
+
+    pub fn with_private_seeds(a: F, b: F) -> Self {
+        Self {
+            seeds: Seeds::Private(Value::known(a), Value::known(b)),
+        }
+    }
+
+    fn inner(&self) -> MyCircuit<F> {
+        MyCircuit {
+            seeds: self.seeds,
+            n: ROWS,
+        }
+    }
+}
+
+impl<F, const ROWS: usize> FixedLength for FixedCircuit<F, ROWS> {
+    const ROWS: usize = ROWS;
+}
+
+impl<F: Field, const ROWS: usize> Circuit<F> for FixedCircuit<F, ROWS> {
+    type Config = FiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            seeds: self.inner().without_witnesses().seeds,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.inner().synthesize(config, layouter)
+    }
+}
+
+pub fn run() {
+    let k = 4;
+    let a = Fp::from(1);
+    let b = Fp::from(1);
+    let circuit = MyCircuit::<Fp>::new(10);
+
+    let mut publics = PublicInputs::new(a, b, 10);
+
+    let prover = MockProver::run(k, &circuit, vec![publics.to_instance_column()]).unwrap();
+    prover.assert_satisfied();
+    println!("the 10th fibonacci number is {:?}", publics.out);
+
+    // wrong out
+    publics.out += Fp::from(10);
+    let prover = MockProver::run(k, &circuit, vec![publics.to_instance_column()]).unwrap();
+    if let Err(failures) = prover.verify() {
+        println!("a wrong out fails with {:?}", failures);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{fib, vectors},
+        harness::{assert_vk_independent, pinned_vk},
+        mock_fails, mock_ok,
+    };
+    use halo2_proofs::dev::VerifyFailure;
+
+    fn mock(k: u32, n: usize) {
+        let (a, b) = (Fp::from(1), Fp::from(1));
+        let publics = PublicInputs::new(a, b, n);
+        mock_ok!(
+            MyCircuit::<Fp>::new(n),
+            k,
+            vec![publics.to_instance_column()]
+        );
+    }
+
+    #[test]
+    fn steps() {
+        mock(4, 3);
+        mock(4, 10);
+        // 38 one-row regions no longer fit in the 26 usable rows of k = 5
+        mock(6, 40);
+    }
+
+    fn failures(circuit: MyCircuit<Fp>, instance: Vec<Fp>) -> Vec<VerifyFailure> {
+        mock_fails!(circuit, 4, vec![instance])
+    }
+
+    /// the public inputs of the 10th number from 1, 1, claiming out instead
+    fn claiming(out: Fp) -> Vec<Fp> {
+        let one = Fp::from(1);
+        let publics = PublicInputs {
+            out,
+            ..PublicInputs::new(one, one, 10)
+        };
+        publics.to_instance_column()
+    }
+
+    #[test]
+    fn correct_publics() {
+        mock_ok!(
+            MyCircuit::<Fp>::new(10),
+            4,
+            vec![claiming(vectors::nth(10))]
+        );
+    }
+
+    #[test]
+    fn other_field() {
+        // nothing in the chip is particular to the scalar field of pallas
+        use halo2_proofs::pasta::Fq;
+
+        let publics = PublicInputs::new(Fq::one(), Fq::one(), 10);
+        let prover = MockProver::run(4, &MyCircuit::new(10), vec![publics.to_instance_column()]);
+        assert_eq!(prover.unwrap().verify(), Ok(()));
+    }
+
+    #[test]
+    fn wrong_out() {
+        // the last row computes 55, and copies it to the instance
+        mock_fails!(
+            MyCircuit::<Fp>::new(10),
+            4,
+            vec![claiming(Fp::from(65))],
+            "fib step 10/10"
+        );
+    }
+
+    #[test]
+    fn tampered_step() {
+        // every step after the 7th adds up, from the wrong number on
+        let publics = PublicInputs::new(Fp::one(), Fp::one(), 10);
+        let circuit = MyCircuit::<Fp>::new(10);
+        let failures = tamper::at(7, || {
+            mock_fails!(
+                circuit,
+                4,
+                vec![publics.to_instance_column()],
+                "fib step 7/10"
+            )
+        });
+        // the gate of the 7th, and both ends of the copy of out, no longer 55
+        assert_eq!(failures.len(), 3);
+        mock_ok!(circuit, 4, vec![publics.to_instance_column()]);
+    }
+
+    #[test]
+    fn wrong_out_location() {
+        use crate::failures::permutation_failure_at;
+
+        let failures = failures(MyCircuit::new(10), claiming(Fp::from(65)));
+
+        // exposed with expose_public at the out row of the instance column
+        assert!(permutation_failure_at(
+            &failures,
+            (Any::Instance, 0),
+            OUT_ROW
+        ));
+        assert!(!permutation_failure_at(
+            &failures,
+            (Any::Instance, 0),
+            A_ROW
+        ));
+    }
+
+    #[test]
+    fn wrong_seeds() {
+        let one = Fp::from(1);
+        let circuit = MyCircuit::with_private_seeds(Fp::from(2), one, 10);
+        assert!(!failures(circuit, claiming(vectors::nth(10))).is_empty());
+        let circuit = MyCircuit::with_private_seeds(one, Fp::from(2), 10);
+        assert!(!failures(circuit, claiming(vectors::nth(10))).is_empty());
+    }
+
+    #[test]
+    fn seeds_are_constrained() {
+        use crate::failures::permutation_failure_at;
+
+        // the right out for the seeds 2, 3, but claiming they are 1, 1
+        let (one, two, three) = (Fp::from(1), Fp::from(2), Fp::from(3));
+        let circuit = MyCircuit::with_private_seeds(two, three, 10);
+        let publics = PublicInputs {
+            a: one,
+            b: one,
+            ..PublicInputs::new(two, three, 10)
+        };
+        let failures = failures(circuit, publics.to_instance_column());
+
+        assert!(permutation_failure_at(&failures, (Any::Instance, 0), A_ROW));
+        assert!(permutation_failure_at(&failures, (Any::Instance, 0), B_ROW));
+        assert!(!permutation_failure_at(
+            &failures,
+            (Any::Instance, 0),
+            OUT_ROW
+        ));
+    }
+
+    #[test]
+    fn seeds_from_instance() {
+        use crate::failures::permutation_failure_at;
+
+        // no seeds to witness, so whatever the instance holds is used
+        let (one, two, three) = (Fp::from(1), Fp::from(2), Fp::from(3));
+        let publics = PublicInputs::new(two, three, 10);
+        mock_ok!(
+            MyCircuit::<Fp>::new(10),
+            4,
+            vec![publics.to_instance_column()]
+        );
+
+        // the claim of seeds_are_constrained then only fails at the output
+        let publics = PublicInputs {
+            a: one,
+            b: one,
+            ..publics
+        };
+        let failures = failures(MyCircuit::new(10), publics.to_instance_column());
+        assert!(!permutation_failure_at(
+            &failures,
+            (Any::Instance, 0),
+            A_ROW
+        ));
+        assert!(!permutation_failure_at(
+            &failures,
+            (Any::Instance, 0),
+            B_ROW
+        ));
+        assert!(permutation_failure_at(
+            &failures,
+            (Any::Instance, 0),
+            OUT_ROW
+        ));
+    }
+
+    #[test]
+    fn short_instance() {
+        // the missing out is padded with zero
+        let one = Fp::from(1);
+        assert!(!failures(MyCircuit::new(10), vec![one, one]).is_empty());
+    }
+
+    #[test]
+    fn prove() {
+        use crate::proving::prove_and_verify;
+
+        let (a, b) = (Fp::from(1), Fp::from(1));
+        let mut publics = PublicInputs::new(a, b, 10);
+        let instance = publics.to_instance_column();
+        assert!(prove_and_verify(4, MyCircuit::new(10), &[&instance]).is_ok());
+
+        // wrong out
+        publics.out += Fp::from(10);
+        let instance = publics.to_instance_column();
+        assert!(prove_and_verify(4, MyCircuit::new(10), &[&instance]).is_err());
+    }
+
+    #[test]
+    fn min_k() {
+        let one = Fp::from(1);
+        for n in [3, 10, 12, 13, 40, 62] {
+            let k = min_k_for_rows::<Fp>(n - 2);
+            let circuit = MyCircuit::new(n);
+            let instance = PublicInputs::new(one, one, n).to_instance_column();
+
+            mock_ok!(circuit, k, vec![instance.clone()]);
+            assert!(MockProver::run(k - 1, &circuit, vec![instance]).is_err());
+
+            assert!(MyCircuit::<Fp>::with_k(n, k).is_ok());
+            assert!(matches!(
+                MyCircuit::<Fp>::with_k(n, k - 1),
+                Err(FiboError::TableTooLarge { .. })
+            ));
+            assert_eq!(MyCircuit::with_min_k(n, 20).unwrap().1, k);
+            assert!(max_n::<Fp>(k) >= n);
+            assert_eq!(min_k_for_rows::<Fp>(max_n::<Fp>(k) - 2), k);
+        }
+    }
+
+    #[test]
+    fn too_short() {
+        for n in [0, 1, 2] {
+            assert!(matches!(
+                MyCircuit::<Fp>::with_k(n, 4),
+                Err(FiboError::TooFewRows { rows, min: 3 }) if rows == n
+            ));
+            assert!(matches!(
+                MyCircuit::<Fp>::with_min_k(n, 10),
+                Err(Error::Synthesis)
+            ));
+            let instance = PublicInputs::new(Fp::one(), Fp::one(), n).to_instance_column();
+            assert!(matches!(
+                MockProver::run(4, &MyCircuit::<Fp>::new(n), vec![instance]),
+                Err(Error::Synthesis)
+            ));
+        }
+    }
+
+    #[test]
+    fn steps_exceed_k() {
+        let one = Fp::from(1);
+        let circuit = MyCircuit::new(40);
+        let publics = PublicInputs::new(one, one, 40);
+        assert_eq!(publics.out, vectors::nth(40));
+        assert!(MockProver::run(5, &circuit, vec![publics.to_instance_column()]).is_err());
+    }
+
+    /// the pinned verifying key at k = 6 of a circuit satisfied by the public
+    /// inputs of its ROWS-th number from a and b
+    fn fixed_vk<C: Circuit<Fp> + FixedLength>(circuit: C, a: u64, b: u64) -> String {
+        let publics = PublicInputs::new(Fp::from(a), Fp::from(b), C::ROWS);
+        mock_ok!(circuit, 6, vec![publics.to_instance_column()]);
+        pinned_vk(6, &circuit)
+    }
+
+    #[test]
+    fn vk_ignores_witness() {
+        let seeds = |a: u64, b: u64| MyCircuit::with_private_seeds(Fp::from(a), Fp::from(b), 10);
+        let unknown = seeds(1, 1).without_witnesses();
+        assert!(matches!(unknown.seeds, Seeds::Private(..)));
+        assert_vk_independent(4, &[unknown, seeds(1, 1), seeds(7, 9)]);
+    }
+
+    #[test]
+    fn fixed_rows() {
+        let (two, seven) = (Fp::from(2), Fp::from(7));
+        let vk5 = fixed_vk(FixedCircuit::<Fp, 5>::new(), 1, 1);
+        let vk10 = fixed_vk(FixedCircuit::<Fp, 10>::new(), 1, 1);
+        let vk50 = fixed_vk(FixedCircuit::<Fp, 50>::new(), 1, 1);
+        assert_ne!(vk5, vk10);
+        assert_ne!(vk10, vk50);
+        assert_eq!(vk10, fixed_vk(FixedCircuit::<Fp, 10>::new(), 2, 7));
+
+        // copying the seeds from the instance or constraining them to it
+        // afterwards is the same permutation
+        let private = |a, b| FixedCircuit::<Fp, 10>::with_private_seeds(a, b);
+        assert_eq!(vk10, fixed_vk(private(Fp::from(1), Fp::from(1)), 1, 1));
+        assert_eq!(vk10, fixed_vk(private(two, seven), 2, 7));
+    }
+
+    fn single_region(k: u32, n: usize, publics: PublicInputs<Fp>) -> Result<(), Error> {
+        let circuit = SingleRegionCircuit::<Fp>::new(n);
+        let prover = MockProver::run(k, &circuit, vec![publics.to_instance_column()])?;
+        let honest = publics.out == fib(publics.a, publics.b, n);
+        assert_eq!(prover.verify().is_ok(), honest);
+        Ok(())
+    }
+
+    #[test]
+    fn single_region_steps() {
+        let (two, seven) = (Fp::from(2), Fp::from(7));
+        for n in 3..=21 {
+            let publics = PublicInputs::new(two, seven, n);
+            single_region(4, n, publics).unwrap();
+
+            let out = publics.out + Fp::from(1);
+            single_region(4, n, PublicInputs { out, ..publics }).unwrap();
+        }
+    }
+
+    #[test]
+    fn single_region_too_short() {
+        // n = 1 would leave no row, and so no c, to end in
+        for n in [0, 1, 2] {
+            let publics = PublicInputs::new(Fp::one(), Fp::one(), n);
+            assert!(matches!(
+                single_region(4, n, publics),
+                Err(Error::Synthesis)
+            ));
+        }
+    }
+
+    #[test]
+    fn single_region_fits_more() {
+        let one = Fp::from(1);
+
+        // one region per row: n - 2 rows, out of the 10 usable rows of k = 4
+        let publics = PublicInputs::new(one, one, 12).to_instance_column();
+        assert!(MockProver::run(4, &MyCircuit::new(12), vec![publics]).is_ok());
+        let publics = PublicInputs::new(one, one, 13).to_instance_column();
+        assert!(MockProver::run(4, &MyCircuit::new(13), vec![publics]).is_err());
+
+        // two numbers a row: n / 2 rows
+        single_region(4, 21, PublicInputs::new(one, one, 21)).unwrap();
+        assert!(single_region(4, 22, PublicInputs::new(one, one, 22)).is_err());
+    }
+}
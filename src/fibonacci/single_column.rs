@@ -0,0 +1,599 @@
+//! fibonacci in a single advice column, using rotations within one region
+//!
+//! as in row_based, n past reference::max_exact_index proves the number mod p
+
+use std::marker::PhantomData;
+
+use super::{
+    check_n,
+    instructions::FiboInstructions,
+    min_k,
+    public_inputs::{PublicInputs, A_ROW, B_ROW, OUT_ROW},
+    usable_rows, FixedLength, Step,
+};
+use crate::{cost, error::FiboError, trace, util::ACell};
+use halo2_proofs::{
+    arithmetic::Field, circuit::*, dev::MockProver, pasta::Fp, plonk::*, poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct FiboConfig {
+    pub advice: Column<Advice>,
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct FiboChip<F: Field> {
+    config: FiboConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> FiboChip<F> {
+    pub fn construct(config: FiboConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> FiboConfig {
+        let advice = meta.advice_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(advice);
+        meta.enable_equality(instance);
+
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(advice, Rotation::cur());
+            let b = meta.query_advice(advice, Rotation::next());
+            let c = meta.query_advice(advice, Rotation(2));
+            vec![s * (a + b - c)]
+        });
+
+        FiboConfig {
+            advice,
+            selector,
+            instance,
+        }
+    }
+
+    /// | row |  advice  | selector |   instance  |
+    /// |  0  |    1     |     1    |       1     |
+    /// |  1  |    1     |     1    |       1     |
+    /// |  2  |    2     |     1    |      55     |
+    ///                  .
+    ///                  .
+    ///                  .
+    /// |  7  |    21    |     1     |      
+    /// |  8  |    34    |     0     |      
+    /// |  9  |    55    |     0     |      
+    ///
+    /// the gate at row n reaches down to row n + 2, so it is enabled on the first rows - 2 rows
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        rows: usize,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        if rows < 3 {
+            return Err(FiboError::TooFewRows { rows, min: 3 });
+        }
+
+        let mut at = (0, "advice");
+        layouter
+            .assign_region(
+                || "entire fibonacci table",
+                |mut region| {
+                    let _span =
+                        tracing::debug_span!("region", name = "entire fibonacci table").entered();
+                    at = (0, "advice");
+                    let mut a_cell = region.assign_advice_from_instance(
+                        || "1",
+                        self.config.instance,
+                        A_ROW,
+                        self.config.advice,
+                        0,
+                    )?;
+                    at = (1, "advice");
+                    let mut b_cell = region.assign_advice_from_instance(
+                        || "1",
+                        self.config.instance,
+                        B_ROW,
+                        self.config.advice,
+                        1,
+                    )?;
+
+                    for n in 0..rows - 2 {
+                        at = (n, "selector");
+                        self.config.selector.enable(&mut region, n)?;
+                    }
+
+                    for n in 2..rows {
+                        at = (n, "advice");
+                        let c_val = a_cell.value().copied() + b_cell.value();
+
+                        let c_cell =
+                            region.assign_advice(|| "c", self.config.advice, n, || c_val)?;
+                        a_cell = b_cell;
+                        b_cell = c_cell;
+                    }
+
+                    Ok(b_cell)
+                },
+            )
+            .map_err(|e| FiboError::assign("entire fibonacci table", at, e))
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field> Chip<F> for FiboChip<F> {
+    type Config = FiboConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// step by step, each step is its own three row region copying in the two
+/// previous numbers, as ACells like the row based chip
+impl<F: Field> FiboInstructions<F> for FiboChip<F> {
+    type Num = ACell<F>;
+
+    fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> FiboConfig {
+        Self::configure(meta, instance)
+    }
+
+    fn construct(config: FiboConfig) -> Self {
+        Self::construct(config)
+    }
+
+    fn load_initial(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<(ACell<F>, ACell<F>), Error> {
+        layouter.assign_region(
+            || "load initial",
+            |mut region| {
+                let a_cell = region.assign_advice(|| "a", self.config.advice, 0, || a)?;
+                let b_cell = region.assign_advice(|| "b", self.config.advice, 1, || b)?;
+                Ok((a_cell.into(), b_cell.into()))
+            },
+        )
+    }
+
+    fn advance(
+        &self,
+        mut layouter: impl Layouter<F>,
+        step: Step,
+        prev: &ACell<F>,
+        cur: &ACell<F>,
+    ) -> Result<ACell<F>, Error> {
+        layouter.assign_region(
+            || step.to_string(),
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                prev.copy_to(&mut region, self.config.advice, 0, "a")?;
+                cur.copy_to(&mut region, self.config.advice, 1, "b")?;
+
+                let c_val = prev.value_copied() + cur.value_copied();
+                region
+                    .assign_advice(|| "c", self.config.advice, 2, || c_val)
+                    .map(ACell)
+            },
+        )
+    }
+
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: &ACell<F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        Ok(self.expose_public(layouter, num, row)?)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MyCircuit<F> {
+    /// number of rows in the fibonacci table, the last one being the public output
+    pub rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> MyCircuit<F> {
+    pub fn new(rows: usize) -> Self {
+        Self {
+            rows,
+            _marker: PhantomData,
+        }
+    }
+
+    /// same as new, but checks the table has at least 3 rows and fits in 2^k
+    /// rows
+    pub fn with_k(rows: usize, k: u32) -> Result<Self, FiboError> {
+        check_n(rows)?;
+        if k < min_k_for_rows::<F>(rows) {
+            let max = max_rows::<F>(k);
+            return Err(FiboError::TableTooLarge { rows, k, max });
+        }
+        Ok(Self::new(rows))
+    }
+}
+
+impl MyCircuit<Fp> {
+    /// same as new, along with the least k up to `k_max` fitting a table of `rows` rows,
+    /// as found by cost::find_min_k
+    pub fn with_min_k(rows: usize, k_max: u32) -> Result<(Self, u32), Error> {
+        let circuit = Self::new(rows);
+        // any instance column of the right length lays the circuit out
+        let instances = vec![PublicInputs::new(Fp::one(), Fp::one(), rows).to_instance_column()];
+        let k = cost::find_min_k(&circuit, &instances, k_max)?;
+        Ok((circuit, k))
+    }
+}
+
+/// the maximum number of fibonacci rows fitting in a circuit of size 2^k,
+/// i.e. the rows left once halo2 has reserved its blinding rows
+pub fn max_rows<F: Field>(k: u32) -> usize {
+    usable_rows::<F, MyCircuit<F>>(k)
+}
+
+/// the smallest k fitting a table of `rows` rows, all in the one region
+pub fn min_k_for_rows<F: Field>(rows: usize) -> u32 {
+    // the instance column holds a, b and out
+    min_k::<F, MyCircuit<F>>(rows.max(3))
+}
+
+impl<F: Field> Circuit<F> for MyCircuit<F> {
+    type Config = FiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.rows)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let _span = tracing::info_span!("configure").entered();
+        // we can define the instance here to share between chips
+        let instance = meta.instance_column();
+        FiboChip::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let _span = tracing::info_span!("synthesize", pass = trace::pass()).entered();
+        let chip = FiboChip::construct(config);
+
+        let out_cell = chip.assign(layouter.namespace(|| "entire region"), self.rows)?;
+
+        // SAME: assign_advice_from_instance
+        chip.expose_public(layouter.namespace(|| "out"), &out_cell, OUT_ROW)?;
+
+        Ok(())
+    }
+}
+
+/// MyCircuit with a table of ROWS rows, fixed by the type
+#[derive(Debug, Default)]
+pub struct FixedCircuit<F, const ROWS: usize> {
+    _marker: PhantomData<F>,
+}
+
+impl<F, const ROWS: usize> FixedLength for FixedCircuit<F, ROWS> {
+    const ROWS: usize = ROWS;
+}
+
+impl<F: Field, const ROWS: usize> Circuit<F> for FixedCircuit<F, ROWS> {
+    type Config = FiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        MyCircuit::new(ROWS).synthesize(config, layouter)
+    }
+}
+
+pub fn run() {
+    let k = 4;
+    let a = Fp::from(1);
+    let circuit = MyCircuit::<Fp>::new(10);
+
+    let mut publics = PublicInputs::new(a, a, 10);
+
+    let prover = MockProver::run(k, &circuit, vec![publics.to_instance_column()]).unwrap();
+    prover.assert_satisfied();
+    println!("the 10th fibonacci number is {:?}", publics.out);
+
+    // wrong out
+    publics.out += Fp::from(10);
+    let prover = MockProver::run(k, &circuit, vec![publics.to_instance_column()]).unwrap();
+    if let Err(failures) = prover.verify() {
+        println!("a wrong out fails with {:?}", failures);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibonacci::vectors::nth, harness::pinned_vk, mock_fails, mock_ok};
+    use halo2_proofs::dev::VerifyFailure;
+
+    fn mock(k: u32, rows: usize) -> Result<MockProver<Fp>, Error> {
+        let a = Fp::from(1);
+        let circuit = MyCircuit::<Fp>::new(rows);
+        let publics = PublicInputs::new(a, a, rows);
+        MockProver::run(k, &circuit, vec![publics.to_instance_column()])
+    }
+
+    /// the table of `rows` from 1, 1 against its honest public inputs
+    fn satisfied(k: u32, rows: usize) {
+        let a = Fp::from(1);
+        let publics = PublicInputs::new(a, a, rows);
+        mock_ok!(
+            MyCircuit::<Fp>::new(rows),
+            k,
+            vec![publics.to_instance_column()]
+        );
+    }
+
+    fn publics(a: u64, b: u64, out: Fp) -> Vec<Fp> {
+        let (a, b) = (Fp::from(a), Fp::from(b));
+        PublicInputs { a, b, out }.to_instance_column()
+    }
+
+    #[test]
+    fn rows() {
+        satisfied(4, 3);
+        satisfied(5, 13);
+    }
+
+    fn failures(instance: Vec<Fp>) -> Vec<VerifyFailure> {
+        mock_fails!(MyCircuit::<Fp>::new(10), 4, vec![instance])
+    }
+
+    #[test]
+    fn wrong_out() {
+        let circuit = MyCircuit::<Fp>::new(10);
+        mock_fails!(
+            circuit,
+            4,
+            vec![publics(1, 1, Fp::from(65))],
+            "entire fibonacci table"
+        );
+    }
+
+    #[test]
+    fn wrong_publics_location() {
+        use crate::failures::permutation_failure_at;
+
+        let wrong_out = failures(publics(1, 1, Fp::from(65)));
+        assert!(permutation_failure_at(
+            &wrong_out,
+            (Any::Instance, 0),
+            OUT_ROW
+        ));
+
+        // the seeds are copied from the instance with assign_advice_from_instance,
+        // so they always match it and a wrong seed shows up at the output
+        let wrong_a = failures(publics(2, 1, nth(10)));
+        assert!(permutation_failure_at(
+            &wrong_a,
+            (Any::Instance, 0),
+            OUT_ROW
+        ));
+        assert!(!permutation_failure_at(&wrong_a, (Any::Instance, 0), A_ROW));
+    }
+
+    #[test]
+    fn wrong_seeds() {
+        assert!(!failures(publics(2, 1, nth(10))).is_empty());
+        assert!(!failures(publics(1, 2, nth(10))).is_empty());
+    }
+
+    #[test]
+    fn short_instance() {
+        // the missing out is padded with zero
+        let one = Fp::from(1);
+        assert!(!failures(vec![one, one]).is_empty());
+    }
+
+    #[test]
+    fn rows_exceed_k() {
+        assert_eq!(max_rows::<Fp>(4), 10);
+        assert!(matches!(
+            mock(4, 13),
+            Err(Error::NotEnoughRowsAvailable { .. })
+        ));
+    }
+
+    /// runs assign directly, keeping the message of the error it returns
+    #[derive(Default)]
+    struct Assign {
+        rows: usize,
+        error: std::cell::RefCell<Option<String>>,
+    }
+
+    impl Circuit<Fp> for Assign {
+        type Config = FiboConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                rows: self.rows,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> FiboConfig {
+            let instance = meta.instance_column();
+            FiboChip::configure(meta, instance)
+        }
+
+        fn synthesize(&self, config: FiboConfig, layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = FiboChip::construct(config);
+            chip.assign(layouter, self.rows)
+                .inspect_err(|e| *self.error.borrow_mut() = Some(e.to_string()))?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn error_names_region() {
+        let circuit = Assign {
+            rows: 13,
+            ..Assign::default()
+        };
+        let result = MockProver::run(4, &circuit, vec![publics(1, 1, Fp::one())]);
+        assert!(matches!(result, Err(Error::NotEnoughRowsAvailable { .. })));
+
+        // the gate of row 10 is the first thing past the 10 usable rows
+        let message = circuit.error.take().unwrap();
+        assert!(
+            message.contains("region \"entire fibonacci table\""),
+            "{}",
+            message
+        );
+        assert!(message.contains("row 10, column selector"), "{}", message);
+    }
+
+    #[test]
+    fn too_few_rows() {
+        for rows in 0..3 {
+            let circuit = Assign {
+                rows,
+                ..Assign::default()
+            };
+            let result = MockProver::run(4, &circuit, vec![publics(1, 1, Fp::one())]);
+            assert!(matches!(result, Err(Error::Synthesis)));
+
+            let message = circuit.error.take().unwrap();
+            assert!(message.contains("at least 3 rows"), "{}", message);
+        }
+
+        // the seeds and a single sum, under a single gate
+        for rows in 3..5 {
+            satisfied(4, rows);
+        }
+    }
+
+    #[test]
+    fn rows_at_limit() {
+        for k in 4..7 {
+            let rows = max_rows::<Fp>(k);
+            satisfied(k, rows);
+            assert!(matches!(
+                mock(k, rows + 1),
+                Err(Error::NotEnoughRowsAvailable { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn prove() {
+        use crate::proving;
+        use halo2_proofs::{pasta::EqAffine, poly::commitment::Params};
+        use rand_core::OsRng;
+
+        let params: Params<EqAffine> = Params::new(4);
+        let circuit = MyCircuit::<Fp>::new(10);
+        let pk = proving::keygen(&params, &circuit).unwrap();
+
+        let right = publics(1, 1, nth(10));
+        let proof = proving::prove(&params, &pk, circuit, &[&right], OsRng).unwrap();
+        assert!(proving::verify(&params, pk.get_vk(), &proof, &[&right]).is_ok());
+
+        // the proof for 55 does not verify against a claim of 65
+        let wrong = publics(1, 1, Fp::from(65));
+        assert!(proving::verify(&params, pk.get_vk(), &proof, &[&wrong]).is_err());
+    }
+
+    #[test]
+    fn min_k() {
+        for rows in [3, 10, 11, 26, 27, 60] {
+            let k = min_k_for_rows::<Fp>(rows);
+            satisfied(k, rows);
+            assert!(mock(k - 1, rows).is_err());
+
+            assert!(MyCircuit::<Fp>::with_k(rows, k).is_ok());
+            let error = MyCircuit::<Fp>::with_k(rows, k - 1).unwrap_err();
+            assert!(matches!(
+                error,
+                FiboError::TableTooLarge { rows: r, k: e, max }
+                    if r == rows && e == k - 1 && max == max_rows::<Fp>(k - 1)
+            ));
+            assert_eq!(MyCircuit::with_min_k(rows, 20).unwrap().1, k);
+        }
+    }
+
+    #[test]
+    fn with_k_too_short() {
+        for rows in [0, 1, 2] {
+            assert!(matches!(
+                MyCircuit::<Fp>::with_k(rows, 4),
+                Err(FiboError::TooFewRows { rows: r, min: 3 }) if r == rows
+            ));
+        }
+    }
+
+    /// the pinned verifying key at k = 6 of a circuit satisfied by the public
+    /// inputs of its ROWS-th number from a and b
+    fn fixed_vk<C: Circuit<Fp> + FixedLength>(circuit: C, a: u64, b: u64) -> String {
+        let publics = PublicInputs::new(Fp::from(a), Fp::from(b), C::ROWS);
+        mock_ok!(circuit, 6, vec![publics.to_instance_column()]);
+        pinned_vk(6, &circuit)
+    }
+
+    #[test]
+    fn fixed_rows() {
+        let vk5 = fixed_vk(FixedCircuit::<Fp, 5>::default(), 1, 1);
+        let vk10 = fixed_vk(FixedCircuit::<Fp, 10>::default(), 1, 1);
+        let vk50 = fixed_vk(FixedCircuit::<Fp, 50>::default(), 1, 1);
+        assert_ne!(vk5, vk10);
+        assert_ne!(vk10, vk50);
+        assert_eq!(vk10, fixed_vk(FixedCircuit::<Fp, 10>::default(), 2, 7));
+    }
+
+    // --features=dev-graph
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn plot_fibo2() {
+        use plotters::prelude::*;
+
+        let root = BitMapBackend::new("fib-2-layout.png", (1024, 3096)).into_drawing_area();
+        root.fill(&WHITE).unwrap();
+        let root = root.titled("Fib 2 Layout", ("sans-serif", 60)).unwrap();
+
+        let circuit = MyCircuit::<Fp>::new(10);
+        halo2_proofs::dev::CircuitLayout::default()
+            .render(4, &circuit, &root)
+            .unwrap();
+    }
+}
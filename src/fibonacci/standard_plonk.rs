@@ -0,0 +1,88 @@
+//! fibonacci on the general purpose plonk gate, each c = a + b being a row
+//! with q_l = q_r = 1 and q_o = -1 where the custom gates hardcode a + b - c
+
+use super::{
+    instructions::{FiboCircuit, FiboInstructions},
+    Step,
+};
+use crate::gadgets::standard_plonk::{StandardPlonkChip, StandardPlonkConfig};
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*};
+
+/// proves the n-th number of the sequence on the standard plonk gate
+pub type PlonkFiboCircuit<F> = FiboCircuit<F, StandardPlonkChip<F>>;
+
+impl<F: Field> FiboInstructions<F> for StandardPlonkChip<F> {
+    type Num = AssignedCell<F, F>;
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        instance: Column<Instance>,
+    ) -> StandardPlonkConfig {
+        StandardPlonkChip::configure(meta, instance)
+    }
+
+    fn construct(config: StandardPlonkConfig) -> Self {
+        StandardPlonkChip::construct(config)
+    }
+
+    fn load_initial(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        // all the coefficients zero leave a and b free
+        let values = [a, b, Value::known(F::ZERO)];
+        let [a, b, _] = self.raw(layouter, [F::ZERO; 5], values)?;
+        Ok((a, b))
+    }
+
+    /// one row of the plonk gate, not a region of its own to name after the step
+    fn advance(
+        &self,
+        layouter: impl Layouter<F>,
+        _step: Step,
+        prev: &AssignedCell<F, F>,
+        cur: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        Ok(self.add(layouter, prev, cur)?)
+    }
+
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        Ok(StandardPlonkChip::expose_public(self, layouter, num, row)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{fib, public_inputs::PublicInputs, vectors},
+        mock_fails, mock_ok,
+    };
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn end_to_end() {
+        let (a, b) = (Fp::one(), Fp::one());
+        let out = fib(a, b, 10);
+        assert_eq!(out, vectors::nth(10));
+        let circuit = PlonkFiboCircuit::new(a, b, 10);
+        mock_ok!(
+            circuit,
+            4,
+            vec![PublicInputs { a, b, out }.to_instance_column()]
+        );
+        let wrong = PublicInputs {
+            a,
+            b,
+            out: out + Fp::one(),
+        };
+        mock_fails!(circuit, 4, vec![wrong.to_instance_column()]);
+    }
+}
@@ -0,0 +1,256 @@
+//! tribonacci in a single advice column, each number the sum of the three before it
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{
+    arithmetic::Field, circuit::*, dev::MockProver, pasta::Fp, plonk::*, poly::Rotation,
+};
+
+/// instance rows of the three seeds, then of the claimed n-th number
+pub const SEED_ROWS: [usize; 3] = [0, 1, 2];
+pub const OUT_ROW: usize = 3;
+
+/// the n-th tribonacci number seeded with a, b and c as 1st, 2nd and 3rd, computed natively
+pub fn trib<F: Field>(a: F, b: F, c: F, n: usize) -> F {
+    let mut seq = [a, b, c];
+    for _ in 3..n {
+        seq = [seq[1], seq[2], seq[0] + seq[1] + seq[2]];
+    }
+    seq[n.clamp(1, 3) - 1]
+}
+
+#[derive(Debug, Clone)]
+pub struct TriboConfig {
+    pub advice: Column<Advice>,
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct TriboChip<F: Field> {
+    config: TriboConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> TriboChip<F> {
+    pub fn construct(config: TriboConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> TriboConfig {
+        let advice = meta.advice_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(advice);
+        meta.enable_equality(instance);
+
+        meta.create_gate("add3", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(advice, Rotation::cur());
+            let b = meta.query_advice(advice, Rotation::next());
+            let c = meta.query_advice(advice, Rotation(2));
+            let d = meta.query_advice(advice, Rotation(3));
+            vec![s * (a + b + c - d)]
+        });
+
+        TriboConfig {
+            advice,
+            selector,
+            instance,
+        }
+    }
+
+    /// | row | advice | selector |
+    /// |  0  |   0    |    1     |
+    /// |  1  |   0    |    1     |
+    /// |  2  |   1    |    1     |
+    /// |  3  |   1    |    1     |
+    ///              .
+    ///              .
+    /// |  7  |   13   |    0     |
+    /// |  8  |   24   |    0     |
+    /// |  9  |   44   |    0     |
+    ///
+    /// the gate at row n reaches down to row n + 3, so it is enabled on the first rows - 3 rows
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        rows: usize,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        if rows < 4 {
+            return Err(FiboError::TooFewRows { rows, min: 4 });
+        }
+
+        let mut at = (0, "advice");
+        layouter
+            .assign_region(
+                || "entire tribonacci table",
+                |mut region| {
+                    let mut seq = Vec::with_capacity(rows);
+                    for (offset, row) in SEED_ROWS.into_iter().enumerate() {
+                        at = (offset, "advice");
+                        seq.push(region.assign_advice_from_instance(
+                            || "seed",
+                            self.config.instance,
+                            row,
+                            self.config.advice,
+                            offset,
+                        )?);
+                    }
+
+                    for n in 0..rows - 3 {
+                        at = (n, "selector");
+                        self.config.selector.enable(&mut region, n)?;
+                    }
+
+                    for n in 3..rows {
+                        at = (n, "advice");
+                        let d_val =
+                            seq[n - 3].value().copied() + seq[n - 2].value() + seq[n - 1].value();
+                        seq.push(region.assign_advice(|| "d", self.config.advice, n, || d_val)?);
+                    }
+
+                    Ok(seq.pop().unwrap())
+                },
+            )
+            .map_err(|e| FiboError::assign("entire tribonacci table", at, e))
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field> Chip<F> for TriboChip<F> {
+    type Config = TriboConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TriboCircuit<F> {
+    /// number of rows in the tribonacci table, the last one being the public output
+    pub rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> TriboCircuit<F> {
+    pub fn new(rows: usize) -> Self {
+        Self {
+            rows,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for TriboCircuit<F> {
+    type Config = TriboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.rows)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        TriboChip::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = TriboChip::construct(config);
+
+        let out_cell = chip.assign(layouter.namespace(|| "entire region"), self.rows)?;
+        chip.expose_public(layouter.namespace(|| "out"), &out_cell, OUT_ROW)?;
+
+        Ok(())
+    }
+}
+
+pub fn run() {
+    // four rotations of the one column leave 9 usable rows at k = 4
+    let k = 5;
+    let (zero, one) = (Fp::from(0), Fp::from(1));
+    let circuit = TriboCircuit::<Fp>::new(10);
+
+    let publics = vec![zero, zero, one, trib(zero, zero, one, 10)];
+    let prover = MockProver::run(k, &circuit, vec![publics.clone()]).unwrap();
+    prover.assert_satisfied();
+    println!("the 10th tribonacci number is {:?}", publics[OUT_ROW]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibonacci::vectors::nth, mock_fails, mock_ok};
+
+    #[test]
+    fn tenth() {
+        let [zero, one] = [0, 1].map(Fp::from);
+        assert_eq!(trib(zero, zero, one, 10), Fp::from(44));
+        mock_ok!(
+            TriboCircuit::<Fp>::new(10),
+            5,
+            vec![vec![zero, zero, one, Fp::from(44)]]
+        );
+
+        let [two, three, five] = [2, 3, 5].map(Fp::from);
+        let out = trib(two, three, five, 10);
+        mock_ok!(
+            TriboCircuit::<Fp>::new(10),
+            5,
+            vec![vec![two, three, five, out]]
+        );
+    }
+
+    #[test]
+    fn wrong_out() {
+        let [zero, one] = [0, 1].map(Fp::from);
+        let circuit = TriboCircuit::<Fp>::new(10);
+        mock_fails!(circuit, 5, vec![vec![zero, zero, one, Fp::from(45)]]);
+        // the 10th fibonacci number
+        mock_fails!(circuit, 5, vec![vec![zero, zero, one, nth(10)]]);
+    }
+
+    #[test]
+    fn usable_rows() {
+        use crate::fibonacci::usable_rows;
+        assert_eq!(usable_rows::<Fp, TriboCircuit<Fp>>(4), 9);
+    }
+
+    #[test]
+    fn tail() {
+        // just the seeds and one sum, under a single gate
+        let [zero, one] = [0, 1].map(Fp::from);
+        mock_ok!(
+            TriboCircuit::<Fp>::new(4),
+            5,
+            vec![vec![zero, zero, one, one]]
+        );
+
+        let circuit = TriboCircuit::<Fp>::new(3);
+        let result = MockProver::run(5, &circuit, vec![vec![zero, zero, one, one]]);
+        assert!(matches!(result, Err(Error::Synthesis)));
+    }
+}
@@ -0,0 +1,234 @@
+//! fibonacci over two advice columns (a, b), two numbers a row, using rotations within one region
+
+use std::marker::PhantomData;
+
+use super::public_inputs::{A_ROW, B_ROW, OUT_ROW};
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct FiboConfig {
+    pub advice: [Column<Advice>; 2],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct FiboChip<F: Field> {
+    config: FiboConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> FiboChip<F> {
+    pub fn construct(config: FiboConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> FiboConfig {
+        let col_a = meta.advice_column();
+        let col_b = meta.advice_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(col_a);
+        meta.enable_equality(col_b);
+        meta.enable_equality(instance);
+
+        // the next row continues the sequence: a' = a + b, b' = b + a'
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let a_next = meta.query_advice(col_a, Rotation::next());
+            let b_next = meta.query_advice(col_b, Rotation::next());
+            vec![
+                s.clone() * (a + b.clone() - a_next.clone()),
+                s * (b + a_next - b_next),
+            ]
+        });
+
+        FiboConfig {
+            advice: [col_a, col_b],
+            selector,
+            instance,
+        }
+    }
+
+    /// | row |  a  |  b  | selector |
+    /// |  0  |  1  |  1  |     1    |
+    /// |  1  |  2  |  3  |     1    |
+    /// |  2  |  5  |  8  |     1    |
+    /// |  3  |  13 |  21 |     1    |
+    /// |  4  |  34 |  55 |     0    |
+    ///
+    /// row r holds the numbers 2r + 1 and 2r + 2, so the n-th number is in the
+    /// last of n / 2 rows, rounded up. returns it
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: usize,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        if n < 3 {
+            return Err(FiboError::TooFewRows { rows: n, min: 3 });
+        }
+
+        let rows = n.div_ceil(2);
+        let mut at = (0, "a");
+        layouter
+            .assign_region(
+                || "entire fibonacci table",
+                |mut region| {
+                    let [col_a, col_b] = self.config.advice;
+
+                    at = (0, "a");
+                    let mut a_cell = region.assign_advice_from_instance(
+                        || "a",
+                        self.config.instance,
+                        A_ROW,
+                        col_a,
+                        0,
+                    )?;
+                    at = (0, "b");
+                    let mut b_cell = region.assign_advice_from_instance(
+                        || "b",
+                        self.config.instance,
+                        B_ROW,
+                        col_b,
+                        0,
+                    )?;
+
+                    for row in 1..rows {
+                        at = (row - 1, "selector");
+                        self.config.selector.enable(&mut region, row - 1)?;
+
+                        at = (row, "a");
+                        let a_val = a_cell.value().copied() + b_cell.value();
+                        a_cell = region.assign_advice(|| "a", col_a, row, || a_val)?;
+
+                        at = (row, "b");
+                        let b_val = b_cell.value().copied() + a_cell.value();
+                        b_cell = region.assign_advice(|| "b", col_b, row, || b_val)?;
+                    }
+
+                    // an odd n ends in a of the last row, an even one in b
+                    Ok(if n % 2 == 1 { a_cell } else { b_cell })
+                },
+            )
+            .map_err(|e| FiboError::assign("entire fibonacci table", at, e))
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field> Chip<F> for FiboChip<F> {
+    type Config = FiboConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MyCircuit<F> {
+    /// index of the fibonacci number to prove, a and b being the 1st and 2nd. n >= 3
+    pub n: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> MyCircuit<F> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for MyCircuit<F> {
+    type Config = FiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        FiboChip::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FiboChip::construct(config);
+
+        let out_cell = chip.assign(layouter.namespace(|| "entire region"), self.n)?;
+        chip.expose_public(layouter.namespace(|| "out"), &out_cell, OUT_ROW)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibonacci::public_inputs::PublicInputs, mock_fails, mock_ok};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    fn mock(n: usize, publics: PublicInputs<Fp>) -> Result<MockProver<Fp>, Error> {
+        MockProver::run(4, &MyCircuit::new(n), vec![publics.to_instance_column()])
+    }
+
+    #[test]
+    fn steps() {
+        let (two, seven) = (Fp::from(2), Fp::from(7));
+        for n in 3..=20 {
+            let publics = PublicInputs::new(two, seven, n);
+            mock_ok!(MyCircuit::new(n), 4, vec![publics.to_instance_column()]);
+        }
+        // 11 rows no longer fit in the 10 usable rows of k = 4
+        let one = Fp::from(1);
+        assert!(mock(21, PublicInputs::new(one, one, 21)).is_err());
+    }
+
+    #[test]
+    fn wrong_publics() {
+        let one = Fp::from(1);
+        let publics = PublicInputs::new(one, one, 10);
+        let out = publics.out + one;
+        let wrong = PublicInputs { out, ..publics };
+        mock_fails!(MyCircuit::new(10), 4, vec![wrong.to_instance_column()]);
+
+        // the seeds come from the instance, so other seeds make for another out
+        let a = Fp::from(2);
+        let wrong = PublicInputs { a, ..publics };
+        mock_fails!(MyCircuit::new(10), 4, vec![wrong.to_instance_column()]);
+    }
+
+    #[test]
+    fn too_few_numbers() {
+        let one = Fp::from(1);
+        for n in 0..3 {
+            let publics = PublicInputs::new(one, one, 3);
+            assert!(matches!(mock(n, publics), Err(Error::Synthesis)));
+        }
+    }
+}
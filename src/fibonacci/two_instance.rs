@@ -0,0 +1,170 @@
+//! the row based fibonacci with two instance columns, the seeds in one and the
+//! output in the other
+//!
+//! the chip reads the seeds from the column it is configured with, and the
+//! output is constrained to the outputs column by naming it in
+//! constrain_instance. MockProver::run and the prover then take one vector
+//! per instance column, in the order configure created the columns
+
+use std::marker::PhantomData;
+
+use super::{
+    row_based::{FiboChip, FiboConfig},
+    Step,
+};
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*};
+
+#[derive(Debug, Clone)]
+pub struct TwoInstanceConfig {
+    /// the chip's instance column is the one of the seeds
+    pub fibo: FiboConfig,
+    pub outputs: Column<Instance>,
+}
+
+/// proves the output in the second instance column is the n-th number from
+/// the seeds in the first
+#[derive(Debug, Default)]
+pub struct TwoInstanceCircuit<F> {
+    /// index of the fibonacci number to prove, a and b being the 1st and 2nd. n >= 3
+    pub n: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> TwoInstanceCircuit<F> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for TwoInstanceCircuit<F> {
+    type Config = TwoInstanceConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        // the seeds column first, then the outputs column
+        let seeds = meta.instance_column();
+        let outputs = meta.instance_column();
+        meta.enable_equality(outputs);
+        TwoInstanceConfig {
+            fibo: FiboChip::configure(meta, seeds),
+            outputs,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FiboChip::construct(config.fibo);
+
+        // the first row already holds the 3rd number
+        let (_, mut pre_b, mut pre_c) =
+            chip.assign_first_row_from_instance(layouter.namespace(|| "first row"))?;
+        for i in 4..=self.n {
+            let step = Step {
+                index: i,
+                of: self.n,
+            };
+            let c_cell =
+                chip.assign_row(layouter.namespace(|| "next row"), step, &pre_b, &pre_c)?;
+            pre_b = pre_c;
+            pre_c = c_cell;
+        }
+
+        // the row of out in its own column
+        let row = 0;
+        layouter
+            .constrain_instance(pre_c.cell(), config.outputs, row)
+            .map_err(|e| FiboError::expose(row, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        failures::permutation_failure_at,
+        fibonacci::public_inputs::{PublicInputs, OUTPUTS_COLUMN, SEEDS_COLUMN},
+        mock_fails, mock_ok, proving,
+    };
+    use halo2_proofs::{
+        dev::MockProver,
+        pasta::{EqAffine, Fp},
+        poly::commitment::Params,
+    };
+    use rand_core::OsRng;
+
+    fn publics() -> PublicInputs<Fp> {
+        PublicInputs::new(Fp::from(2), Fp::from(3), 10)
+    }
+
+    fn mock(instances: Vec<Vec<Fp>>) -> Result<MockProver<Fp>, Error> {
+        MockProver::run(4, &TwoInstanceCircuit::<Fp>::new(10), instances)
+    }
+
+    #[test]
+    fn columns() {
+        let columns = publics().to_instance_columns();
+        assert_eq!(columns[SEEDS_COLUMN], vec![Fp::from(2), Fp::from(3)]);
+        assert_eq!(columns[OUTPUTS_COLUMN], vec![publics().out]);
+        mock_ok!(TwoInstanceCircuit::<Fp>::new(10), 4, columns);
+    }
+
+    #[test]
+    fn wrong_output() {
+        let mut publics = publics();
+        publics.out += Fp::one();
+        let circuit = TwoInstanceCircuit::<Fp>::new(10);
+        let failures = mock_fails!(circuit, 4, publics.to_instance_columns());
+        // the copy failing is the one into the second column
+        assert!(permutation_failure_at(&failures, (Any::Instance, 1), 0));
+    }
+
+    #[test]
+    fn swapped() {
+        let mut columns = publics().to_instance_columns();
+        columns.swap(SEEDS_COLUMN, OUTPUTS_COLUMN);
+        mock_fails!(TwoInstanceCircuit::<Fp>::new(10), 4, columns);
+    }
+
+    #[test]
+    fn one_vector() {
+        // all three numbers in the one column the single column circuits take
+        let instance = vec![publics().to_instance_column()];
+        let error = mock(instance.clone()).unwrap_err();
+        assert!(matches!(error, Error::InvalidInstances));
+        assert_eq!(
+            error.to_string(),
+            "Provided instances do not match the circuit"
+        );
+
+        // and the same from the prover
+        let params: Params<EqAffine> = Params::new(4);
+        let circuit = TwoInstanceCircuit::<Fp>::new(10);
+        let pk = proving::keygen(&params, &circuit).unwrap();
+        let result = proving::prove(&params, &pk, circuit, &[&instance[0]], OsRng);
+        assert!(matches!(result, Err(Error::InvalidInstances)));
+    }
+
+    #[test]
+    fn prove() {
+        let params: Params<EqAffine> = Params::new(4);
+        let circuit = TwoInstanceCircuit::<Fp>::new(10);
+        let pk = proving::keygen(&params, &circuit).unwrap();
+
+        let columns = publics().to_instance_columns();
+        let instances: Vec<&[Fp]> = columns.iter().map(Vec::as_slice).collect();
+        let proof = proving::prove(&params, &pk, circuit, &instances, OsRng).unwrap();
+        assert!(proving::verify(&params, pk.get_vk(), &proof, &instances).is_ok());
+    }
+}
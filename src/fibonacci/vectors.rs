@@ -0,0 +1,95 @@
+//! the first 100 fibonacci numbers as field elements, for tests to take their
+//! expected outputs from
+//!
+//! the table is computed at compile time in u128, which holds the numbers up
+//! to the 186th, so every entry is below the modulus and goes in as it is
+
+use halo2_proofs::pasta::Fp;
+
+/// the numbers the table holds
+pub const COUNT: usize = 100;
+
+const fn numbers() -> [u128; COUNT] {
+    let mut numbers = [1; COUNT];
+    let mut i = 2;
+    while i < COUNT {
+        numbers[i] = numbers[i - 1] + numbers[i - 2];
+        i += 1;
+    }
+    numbers
+}
+
+const fn to_fp(value: u128) -> Fp {
+    Fp::from_raw([value as u64, (value >> 64) as u64, 0, 0])
+}
+
+const fn table() -> [Fp; COUNT] {
+    let numbers = numbers();
+    let mut table = [to_fp(0); COUNT];
+    let mut i = 0;
+    while i < COUNT {
+        table[i] = to_fp(numbers[i]);
+        i += 1;
+    }
+    table
+}
+
+/// the n-th number of 1, 1, 2, 3, 5, ... at n - 1
+pub const FIBONACCI: [Fp; COUNT] = table();
+
+/// the n-th fibonacci number, 1-based as everywhere in the examples
+pub fn nth(n: usize) -> Fp {
+    assert!(
+        (1..=COUNT).contains(&n),
+        "the table holds the numbers 1 to {COUNT}, not {n}"
+    );
+    FIBONACCI[n - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{fib, reference::fib_exact};
+
+    #[test]
+    fn table() {
+        assert_eq!(nth(1), Fp::one());
+        assert_eq!(nth(2), Fp::one());
+        assert_eq!(nth(10), Fp::from(55));
+        for n in [3, 20, 64, 93, 99, COUNT] {
+            assert_eq!(nth(n), fib_exact(1, 1, n).reduce(), "{n}");
+            assert_eq!(nth(n), fib(Fp::one(), Fp::one(), n));
+        }
+        // the 100th is past u64 already
+        assert!(fib_exact(1, 1, COUNT).bits() > 64);
+    }
+
+    #[test]
+    fn past_u128() {
+        assert!(fib_exact(1, 1, 186).bits() <= 128);
+        // the 187th is the first past u128, and the 500th is past the modulus
+        for n in [187, 200, 370, 500] {
+            assert!(fib_exact(1, 1, n).bits() > 128);
+            assert_eq!(
+                fib(Fp::one(), Fp::one(), n),
+                fib_exact(1, 1, n).reduce(),
+                "{n}"
+            );
+        }
+        assert!(fib_exact(1, 1, 500).bits() > 256);
+    }
+
+    #[test]
+    fn other_seeds() {
+        for n in [1, 2, 10, 150, 400] {
+            let expected = fib_exact(2, 7, n).reduce();
+            assert_eq!(fib(Fp::from(2), Fp::from(7), n), expected, "{n}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "1 to 100, not 101")]
+    fn past_the_table() {
+        nth(COUNT + 1);
+    }
+}
@@ -0,0 +1,425 @@
+//! fibonacci mod 2^64, as u64::wrapping_add computes it, rather than mod p
+//!
+//! each step witnesses the overflow bit o of a + b and constrains
+//! c + o·2^64 = a + b, with c, and the seeds, decomposed into 8 bytes. with
+//! everything below 2^64 the sum is below p, so the field never wraps and the
+//! equation holds over the integers
+
+use super::{
+    public_inputs::{A_ROW, B_ROW, OUT_ROW},
+    Step,
+};
+use crate::{
+    error::FiboError,
+    fibonacci::min_k,
+    gadgets::byte_decompose::{load_table, ByteDecomposeChip, ByteDecomposeConfig, TABLE_ROWS},
+};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+use std::marker::PhantomData;
+
+/// the bytes of a u64
+const BYTES: usize = 8;
+
+/// the cells of the seeds: (a, b)
+type Seeds<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
+
+/// the n-th number of the sequence seeded with a and b as 1st and 2nd, each
+/// the wrapping sum of the two before it
+pub fn fib_u64(a: u64, b: u64, n: usize) -> u64 {
+    let (mut a, mut b) = (a, b);
+    for _ in 2..n {
+        (a, b) = (b, a.wrapping_add(b));
+    }
+    if n == 1 {
+        a
+    } else {
+        b
+    }
+}
+
+/// the low 128 bits of a field element
+fn low_u128<F: PrimeField>(value: F) -> u128 {
+    let repr = value.to_repr();
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&repr.as_ref()[..16]);
+    u128::from_le_bytes(bytes)
+}
+
+/// the overflow bit and the wrapped sum of a and b, as the honest prover
+/// witnesses them
+fn wrapping_add<F: PrimeField>(a: Value<F>, b: Value<F>) -> (Value<F>, Value<F>) {
+    let sum = a.zip(b).map(|(a, b)| low_u128(a) + low_u128(b));
+    let o = sum.map(|sum| F::from((sum >> 64) as u64));
+    let c = sum.map(|sum| F::from(sum as u64));
+    (o, c)
+}
+
+#[derive(Debug, Clone)]
+pub struct WrappingConfig {
+    /// a, b, c and the overflow bit
+    pub advice: [Column<Advice>; 4],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+    pub bytes: ByteDecomposeConfig,
+}
+
+pub struct WrappingChip<F: PrimeField> {
+    config: WrappingConfig,
+    bytes: ByteDecomposeChip<F, BYTES>,
+}
+
+impl<F: PrimeField> WrappingChip<F> {
+    pub fn construct(config: WrappingConfig) -> Self {
+        let bytes = ByteDecomposeChip::construct(config.bytes.clone());
+        Self { config, bytes }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> WrappingConfig {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let selector = meta.selector();
+
+        meta.enable_equality(instance);
+        for column in &advice[..3] {
+            meta.enable_equality(*column);
+        }
+
+        meta.create_gate("wrapping add", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, b, c, o] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let two_64 = Expression::Constant(F::from_u128(1 << 64));
+            let one = Expression::Constant(F::ONE);
+            vec![
+                s.clone() * o.clone() * (one - o.clone()),
+                s * (a + b - c - o * two_64),
+            ]
+        });
+
+        let bytes = [(); 2].map(|_| meta.advice_column());
+        let table = meta.lookup_table_column();
+        let bytes = ByteDecomposeChip::<F, BYTES>::configure(meta, bytes, table);
+
+        WrappingConfig {
+            advice,
+            selector,
+            instance,
+            bytes,
+        }
+    }
+
+    pub fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), FiboError> {
+        load_table(layouter, self.config.bytes.table)
+    }
+
+    /// the seeds, copied from the instance and checked to fit a u64
+    pub fn assign_seeds(&self, mut layouter: impl Layouter<F>) -> Result<Seeds<F>, FiboError> {
+        let [col_a, col_b, ..] = self.config.advice;
+        let mut at = (0, "a");
+        let (a, b) = layouter
+            .assign_region(
+                || "seeds",
+                |mut region| {
+                    at = (0, "a");
+                    let a = region.assign_advice_from_instance(
+                        || "a",
+                        self.config.instance,
+                        A_ROW,
+                        col_a,
+                        0,
+                    )?;
+                    at = (0, "b");
+                    let b = region.assign_advice_from_instance(
+                        || "b",
+                        self.config.instance,
+                        B_ROW,
+                        col_b,
+                        0,
+                    )?;
+                    Ok((a, b))
+                },
+            )
+            .map_err(|e| FiboError::assign("seeds", at, e))?;
+
+        self.bytes.assign(layouter.namespace(|| "a bytes"), &a)?;
+        self.bytes.assign(layouter.namespace(|| "b bytes"), &b)?;
+        Ok((a, b))
+    }
+
+    /// c = a + b mod 2^64, checked to fit a u64
+    pub fn assign_step(
+        &self,
+        layouter: impl Layouter<F>,
+        step: Step,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        self.assign_step_with(layouter, step, a, b, |o, c| (o, c))
+    }
+
+    /// as assign_step, the overflow bit and c witnessed by `witness_of(o, c)`
+    fn assign_step_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        step: Step,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        witness_of: impl Fn(Value<F>, Value<F>) -> (Value<F>, Value<F>),
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let [col_a, col_b, col_c, col_o] = self.config.advice;
+        let (o, c) = wrapping_add(a.value().copied(), b.value().copied());
+        let (o, c) = witness_of(o, c);
+        let name = step.to_string();
+        let mut at = (0, "selector");
+        let c = layouter
+            .assign_region(
+                || name.clone(),
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    at = (0, "b");
+                    b.copy_advice(|| "b", &mut region, col_b, 0)?;
+
+                    at = (0, "o");
+                    region.assign_advice(|| "o", col_o, 0, || o)?;
+                    at = (0, "c");
+                    region.assign_advice(|| "c", col_c, 0, || c)
+                },
+            )
+            .map_err(|e| FiboError::assign(name, at, e))?;
+
+        self.bytes.assign(layouter.namespace(|| "c bytes"), &c)?;
+        Ok(c)
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: PrimeField> Chip<F> for WrappingChip<F> {
+    type Config = WrappingConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves the n-th number mod 2^64 from the seeds, all three in the instance
+/// column as the other fibonacci circuits take them
+#[derive(Debug, Default)]
+pub struct WrappingCircuit<F> {
+    /// index of the number to prove, a and b being the 1st and 2nd. n >= 3
+    pub n: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> WrappingCircuit<F> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            _marker: PhantomData,
+        }
+    }
+
+    /// the smallest k fitting the byte table and the n-th number
+    pub fn min_k(&self) -> u32 {
+        // a byte decomposition of every number, and a row for each step
+        let rows = self.n * BYTES + self.n;
+        min_k::<F, Self>(rows.max(TABLE_ROWS + 1))
+    }
+
+    /// the seeds, a and b, and the numbers after them, the one of `index`
+    /// witnessed with its overflow bit and c given by `witness_of`
+    fn synthesize_with(
+        &self,
+        config: WrappingConfig,
+        mut layouter: impl Layouter<F>,
+        witness_of: impl Fn(usize, Value<F>, Value<F>) -> (Value<F>, Value<F>),
+    ) -> Result<(), FiboError> {
+        let chip = WrappingChip::construct(config);
+        chip.load_table(layouter.namespace(|| "table"))?;
+
+        let (mut a, mut b) = chip.assign_seeds(layouter.namespace(|| "seeds"))?;
+        for i in 3..=self.n {
+            let step = Step {
+                index: i,
+                of: self.n,
+            };
+            let c =
+                chip.assign_step_with(layouter.namespace(|| "step"), step, &a, &b, |o, c| {
+                    witness_of(i, o, c)
+                })?;
+            (a, b) = (b, c);
+        }
+
+        chip.expose_public(layouter.namespace(|| "out"), &b, OUT_ROW)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for WrappingCircuit<F> {
+    type Config = WrappingConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        WrappingChip::configure(meta, instance)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, |_, o, c| (o, c))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{fib, vectors::nth},
+        mock_fails, mock_ok,
+    };
+    use halo2_proofs::{dev::VerifyFailure, pasta::Fp};
+
+    fn instance(a: u64, b: u64, out: u64) -> Vec<Vec<Fp>> {
+        vec![vec![Fp::from(a), Fp::from(b), Fp::from(out)]]
+    }
+
+    /// the overflow bit and c of a step
+    type Witness = (Value<Fp>, Value<Fp>);
+
+    /// the circuit of the n-th number, the `index`-th witnessed by `witness_of`
+    struct Tampered {
+        n: usize,
+        index: usize,
+        witness_of: fn(Value<Fp>, Value<Fp>) -> Witness,
+    }
+
+    impl Circuit<Fp> for Tampered {
+        type Config = WrappingConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { ..*self }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            WrappingCircuit::<Fp>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let circuit = WrappingCircuit::<Fp>::new(self.n);
+            Ok(circuit.synthesize_with(config, layouter, |i, o, c| {
+                if i == self.index {
+                    (self.witness_of)(o, c)
+                } else {
+                    (o, c)
+                }
+            })?)
+        }
+    }
+
+    #[test]
+    fn overflows() {
+        // the 93rd is the last below 2^64
+        assert_eq!(Fp::from(fib_u64(1, 1, 93)), nth(93));
+        assert!(fib_u64(1, 1, 94) < fib_u64(1, 1, 93));
+        assert_ne!(Fp::from(fib_u64(1, 1, 94)), fib(Fp::one(), Fp::one(), 94));
+
+        let circuit = WrappingCircuit::<Fp>::new(100);
+        let k = circuit.min_k();
+        assert_eq!(k, 10);
+        mock_ok!(circuit, k, instance(1, 1, fib_u64(1, 1, 100)));
+        // the field sum of the 100th is out of the u64 range
+        let out = nth(100);
+        mock_fails!(circuit, k, vec![vec![Fp::one(), Fp::one(), out]]);
+
+        // from the top of the range, the 3rd number already wraps
+        mock_ok!(WrappingCircuit::<Fp>::new(3), 9, instance(u64::MAX, 2, 1));
+    }
+
+    /// 2^64 as a witness
+    fn two_64() -> Value<Fp> {
+        Value::known(Fp::from_u128(1 << 64))
+    }
+
+    #[test]
+    fn wrong_overflow_bit() {
+        // the 94th overflows: a bit of 0 alone breaks the sum
+        let flipped = Tampered {
+            n: 94,
+            index: 94,
+            witness_of: |o, c| (o - o, c),
+        };
+        mock_fails!(
+            flipped,
+            10,
+            instance(1, 1, fib_u64(1, 1, 94)),
+            "wrapping add"
+        );
+
+        // and the 93rd does not: a bit of 1 keeping the sum takes c below 0,
+        // which is p - something, far past 8 bytes
+        let flipped = Tampered {
+            n: 93,
+            index: 93,
+            witness_of: |o, c| (o + Value::known(Fp::one()), c - two_64()),
+        };
+        mock_fails!(
+            flipped,
+            10,
+            instance(1, 1, fib_u64(1, 1, 93)),
+            "byte decompose"
+        );
+
+        // a bit of 2 keeping the sum is no bit at all
+        let two = Tampered {
+            n: 10,
+            index: 10,
+            witness_of: |o, c| (o + Value::known(Fp::from(2)), c - two_64() - two_64()),
+        };
+        mock_fails!(two, 10, instance(1, 1, fib_u64(1, 1, 10)), "wrapping add");
+    }
+
+    #[test]
+    fn c_past_u64() {
+        // the 94th without its overflow: c the whole sum, which the gate takes
+        let unwrapped = Tampered {
+            n: 94,
+            index: 94,
+            witness_of: |o, c| (o - o, c + two_64()),
+        };
+        let sum = Fp::from(fib_u64(1, 1, 94)) + Fp::from_u128(1 << 64);
+        assert_eq!(sum, nth(94));
+        // its 8 bytes sum to the low 64 bits only, which the copy back to c catches
+        let instance = vec![vec![Fp::one(), Fp::one(), sum]];
+        let failures = mock_fails!(unwrapped, 10, instance, "byte decompose");
+        assert!(!failures
+            .iter()
+            .any(|f| matches!(f, VerifyFailure::ConstraintNotSatisfied { .. })));
+
+        // seeds past u64 fail the same check
+        let seeds = vec![vec![Fp::from_u128(1 << 64), Fp::one(), Fp::one()]];
+        mock_fails!(WrappingCircuit::<Fp>::new(3), 9, seeds, "byte decompose");
+    }
+}
@@ -0,0 +1,283 @@
+//! the running sum of private values, for proving hidden amounts add up to a total
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// the cells of the values, and the total
+pub type Summed<F> = (Vec<AssignedCell<F, F>>, AssignedCell<F, F>);
+
+#[derive(Debug, Clone)]
+pub struct AccumulatorConfig {
+    pub value: Column<Advice>,
+    pub sum: Column<Advice>,
+    pub selector: Selector,
+}
+
+pub struct AccumulatorChip<F: Field> {
+    config: AccumulatorConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> AccumulatorChip<F> {
+    pub fn construct(config: AccumulatorConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// `constant` holds the zero the sum starts from
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        [value, sum]: [Column<Advice>; 2],
+        constant: Column<Fixed>,
+    ) -> AccumulatorConfig {
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(value);
+        meta.enable_equality(sum);
+        meta.enable_constant(constant);
+
+        meta.create_gate("accumulate", |meta| {
+            let s = meta.query_selector(selector);
+            let value = meta.query_advice(value, Rotation::cur());
+            let sum_cur = meta.query_advice(sum, Rotation::cur());
+            let sum_next = meta.query_advice(sum, Rotation::next());
+            vec![s * (sum_cur + value - sum_next)]
+        });
+
+        AccumulatorConfig {
+            value,
+            sum,
+            selector,
+        }
+    }
+
+    /// | row | value |        sum        | selector |
+    /// |  0  |  v_0  |         0         |     1    |
+    /// |  1  |  v_1  |        v_0        |     1    |
+    /// | ... |       |                   |          |
+    /// |  n  |       | v_0 + … + v_(n-1) |     0    |
+    ///
+    /// the first sum being the constant 0
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Value<F>],
+    ) -> Result<Summed<F>, FiboError> {
+        self.assign_with(layouter, values, |_, sum| sum)
+    }
+
+    /// as assign, calling `on_row(region, row)` on each row of a value, for
+    /// circuits laying out cells of their own alongside the values
+    pub fn assign_rows(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Value<F>],
+        on_row: impl FnMut(&mut Region<'_, F>, usize) -> Result<(), Error>,
+    ) -> Result<Summed<F>, FiboError> {
+        self.assign_inner(layouter, values, |_, sum| sum, on_row)
+    }
+
+    /// as assign, the sum of each row after the first witnessed by
+    /// `sum_at(row, sum)`, the rows after it following the true sum
+    fn assign_with(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Value<F>],
+        sum_at: impl Fn(usize, Value<F>) -> Value<F>,
+    ) -> Result<Summed<F>, FiboError> {
+        self.assign_inner(layouter, values, sum_at, |_, _| Ok(()))
+    }
+
+    fn assign_inner(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<F>],
+        sum_at: impl Fn(usize, Value<F>) -> Value<F>,
+        mut on_row: impl FnMut(&mut Region<'_, F>, usize) -> Result<(), Error>,
+    ) -> Result<Summed<F>, FiboError> {
+        let AccumulatorConfig {
+            value,
+            sum,
+            selector,
+        } = self.config;
+
+        let mut at = (0, "sum");
+        layouter
+            .assign_region(
+                || "accumulate",
+                |mut region| {
+                    at = (0, "sum");
+                    let mut sum_cell =
+                        region.assign_advice_from_constant(|| "sum", sum, 0, F::ZERO)?;
+                    let mut total = Value::known(F::ZERO);
+
+                    let mut cells = Vec::with_capacity(values.len());
+                    for (row, v) in values.iter().enumerate() {
+                        at = (row, "selector");
+                        selector.enable(&mut region, row)?;
+
+                        at = (row, "value");
+                        cells.push(region.assign_advice(|| "value", value, row, || *v)?);
+
+                        at = (row + 1, "sum");
+                        total = total + v;
+                        let sum_val = sum_at(row + 1, total);
+                        sum_cell = region.assign_advice(|| "sum", sum, row + 1, || sum_val)?;
+
+                        at = (row, "row");
+                        on_row(&mut region, row)?;
+                    }
+
+                    Ok((cells, sum_cell))
+                },
+            )
+            .map_err(|e| FiboError::assign("accumulate", at, e))
+    }
+}
+
+impl<F: Field> Chip<F> for AccumulatorChip<F> {
+    type Config = AccumulatorConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SumConfig {
+    pub accumulator: AccumulatorConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes the sum of the private values
+#[derive(Debug, Default)]
+pub struct SumCircuit<F> {
+    pub values: Vec<Value<F>>,
+}
+
+impl<F: Field> SumCircuit<F> {
+    pub fn new(values: &[F]) -> Self {
+        Self {
+            values: values.iter().copied().map(Value::known).collect(),
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for SumCircuit<F> {
+    type Config = SumConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![Value::unknown(); self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 2].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        SumConfig {
+            accumulator: AccumulatorChip::configure(meta, advice, constant),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = AccumulatorChip::construct(config.accumulator);
+        let (_, total) = chip.assign(layouter.namespace(|| "sum"), &self.values)?;
+        layouter
+            .constrain_instance(total.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::min_k;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    fn values(values: impl IntoIterator<Item = u64>) -> Vec<Fp> {
+        values.into_iter().map(Fp::from).collect()
+    }
+
+    #[test]
+    fn simple() {
+        let circuit = SumCircuit::new(&values([3, 4, 5]));
+        mock_ok!(circuit, 4, vec![vec![Fp::from(12)]]);
+        mock_fails!(circuit, 4, vec![vec![Fp::from(13)]]);
+        mock_ok!(SumCircuit::new(&[]), 4, vec![vec![Fp::from(0)]]);
+    }
+
+    #[test]
+    fn hundred() {
+        let circuit = SumCircuit::new(&values(1..=100));
+        // the 101 rows of the sum
+        let k = min_k::<Fp, SumCircuit<Fp>>(101);
+        assert_eq!(k, 7);
+        mock_ok!(circuit, k, vec![vec![Fp::from(5050)]]);
+        assert!(MockProver::run(k - 1, &circuit, vec![vec![Fp::from(5050)]]).is_err());
+    }
+
+    /// the sum of 3, 4 and 5 with the sum after 3 witnessed one more
+    struct Fudged;
+
+    impl Circuit<Fp> for Fudged {
+        type Config = SumConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            SumCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = AccumulatorChip::construct(config.accumulator);
+            let values = values([3, 4, 5])
+                .into_iter()
+                .map(Value::known)
+                .collect::<Vec<_>>();
+            let (_, total) =
+                chip.assign_with(layouter.namespace(|| "sum"), &values, |row, sum| {
+                    if row == 1 {
+                        sum + Value::known(Fp::one())
+                    } else {
+                        sum
+                    }
+                })?;
+            layouter.constrain_instance(total.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn fudged_sum() {
+        // the total is still 12, the rows around the fudged sum break
+        let failures = mock_fails!(Fudged, 4, vec![vec![Fp::from(12)]]);
+        assert_eq!(failures.len(), 2);
+    }
+}
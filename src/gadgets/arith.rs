@@ -0,0 +1,305 @@
+//! add and mul as two gates on the same three advice columns, each row enabling
+//! the selector of the one operation it holds
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct ArithConfig {
+    /// a, b and out
+    pub advice: [Column<Advice>; 3],
+    pub s_add: Selector,
+    pub s_mul: Selector,
+}
+
+pub struct ArithChip<F: Field> {
+    config: ArithConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> ArithChip<F> {
+    pub fn construct(config: ArithConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 3]) -> ArithConfig {
+        let s_add = meta.selector();
+        let s_mul = meta.selector();
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(s_add);
+            let [a, b, out] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![s * (a + b - out)]
+        });
+        meta.create_gate("mul", |meta| {
+            let s = meta.query_selector(s_mul);
+            let [a, b, out] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![s * (a * b - out)]
+        });
+
+        ArithConfig {
+            advice,
+            s_add,
+            s_mul,
+        }
+    }
+
+    pub fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        layouter
+            .assign_region(
+                || "load private",
+                |mut region| region.assign_advice(|| "private", self.config.advice[0], 0, || value),
+            )
+            .map_err(|e| FiboError::assign("load private", (0, "private"), e))
+    }
+
+    /// out on one row with a and b, enabling `selector` only
+    fn op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &'static str,
+        selector: Selector,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        out: Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let [col_a, col_b, col_out] = self.config.advice;
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || name,
+                |mut region| {
+                    at = (0, "selector");
+                    selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    at = (0, "b");
+                    b.copy_advice(|| "b", &mut region, col_b, 0)?;
+
+                    at = (0, "out");
+                    region.assign_advice(|| "out", col_out, 0, || out)
+                },
+            )
+            .map_err(|e| FiboError::assign(name, at, e))
+    }
+
+    pub fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let out = a.value().copied() + b.value();
+        self.op(layouter, "add", self.config.s_add, a, b, out)
+    }
+
+    pub fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let out = a.value().copied() * b.value();
+        self.op(layouter, "mul", self.config.s_mul, a, b, out)
+    }
+}
+
+impl<F: Field> Chip<F> for ArithChip<F> {
+    type Config = ArithConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MulAddConfig {
+    pub arith: ArithConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes a·b + c for the private a, b and c
+#[derive(Debug, Default)]
+pub struct MulAddCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub c: Value<F>,
+}
+
+impl<F: Field> MulAddCircuit<F> {
+    pub fn new(a: F, b: F, c: F) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            c: Value::known(c),
+        }
+    }
+
+    /// loads a, b and c
+    fn load(
+        &self,
+        chip: &ArithChip<F>,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<[AssignedCell<F, F>; 3], FiboError> {
+        Ok([
+            chip.load_private(layouter.namespace(|| "a"), self.a)?,
+            chip.load_private(layouter.namespace(|| "b"), self.b)?,
+            chip.load_private(layouter.namespace(|| "c"), self.c)?,
+        ])
+    }
+}
+
+impl<F: Field> Circuit<F> for MulAddCircuit<F> {
+    type Config = MulAddConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        MulAddConfig {
+            arith: ArithChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = ArithChip::construct(config.arith);
+        let [a, b, c] = self.load(&chip, layouter.namespace(|| "load"))?;
+        let ab = chip.mul(layouter.namespace(|| "a·b"), &a, &b)?;
+        let out = chip.add(layouter.namespace(|| "a·b + c"), &ab, &c)?;
+        layouter
+            .constrain_instance(out.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn mul_add() {
+        for [a, b, c] in [[3, 4, 5], [0, 7, 1], [9, 1, 0]] {
+            let circuit = MulAddCircuit::new(Fp::from(a), Fp::from(b), Fp::from(c));
+            mock_ok!(circuit, 4, vec![vec![Fp::from(a * b + c)]]);
+            // a + b + c and (a + b)·c
+            mock_fails!(circuit, 4, vec![vec![Fp::from(a + b + c)]]);
+            mock_fails!(circuit, 4, vec![vec![Fp::from((a + b) * c)]]);
+        }
+    }
+
+    /// a·b + c with the product row under the add selector, or followed by a
+    /// garbage row under neither
+    struct Tampered {
+        circuit: MulAddCircuit<Fp>,
+        wrong_selector: bool,
+    }
+
+    impl Circuit<Fp> for Tampered {
+        type Config = MulAddConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                circuit: MulAddCircuit::default(),
+                wrong_selector: self.wrong_selector,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MulAddCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = ArithChip::construct(config.arith.clone());
+            let [a, b, c] = self.circuit.load(&chip, layouter.namespace(|| "load"))?;
+
+            let ab = if self.wrong_selector {
+                let out = a.value().copied() * b.value();
+                chip.op(
+                    layouter.namespace(|| "a·b"),
+                    "mul",
+                    config.arith.s_add,
+                    &a,
+                    &b,
+                    out,
+                )?
+            } else {
+                let ab = chip.mul(layouter.namespace(|| "a·b"), &a, &b)?;
+                layouter.assign_region(
+                    || "garbage",
+                    |mut region| {
+                        for (column, value) in config.arith.advice.into_iter().zip([1, 2, 42]) {
+                            region.assign_advice(
+                                || "garbage",
+                                column,
+                                0,
+                                || Value::known(Fp::from(value)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+                ab
+            };
+            let out = chip.add(layouter.namespace(|| "a·b + c"), &ab, &c)?;
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn wrong_selector() {
+        let circuit = Tampered {
+            circuit: MulAddCircuit::new(Fp::from(3), Fp::from(4), Fp::from(5)),
+            wrong_selector: true,
+        };
+        // 3·4 on a row checking 3 + 4
+        mock_fails!(circuit, 4, vec![vec![Fp::from(17)]]);
+    }
+
+    #[test]
+    fn unselected_row() {
+        // 1, 2, 42 is neither a sum nor a product, and nothing checks it
+        let circuit = Tampered {
+            circuit: MulAddCircuit::new(Fp::from(3), Fp::from(4), Fp::from(5)),
+            wrong_selector: false,
+        };
+        mock_ok!(circuit, 4, vec![vec![Fp::from(17)]]);
+    }
+}
@@ -0,0 +1,312 @@
+//! and, or and not over boolean cells, one selector per operation on the same columns
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct BoolConfig {
+    /// a, b and out
+    pub advice: [Column<Advice>; 3],
+    /// a is boolean
+    pub s_bool: Selector,
+    pub s_and: Selector,
+    pub s_or: Selector,
+    pub s_not: Selector,
+}
+
+pub struct BoolChip<F: Field> {
+    config: BoolConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> BoolChip<F> {
+    pub fn construct(config: BoolConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 3]) -> BoolConfig {
+        let s_bool = meta.selector();
+        let s_and = meta.selector();
+        let s_or = meta.selector();
+        let s_not = meta.selector();
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let one = || Expression::Constant(F::ONE);
+        let cur = |meta: &mut VirtualCells<'_, F>| {
+            advice.map(|column| meta.query_advice(column, Rotation::cur()))
+        };
+
+        meta.create_gate("bool", |meta| {
+            let s = meta.query_selector(s_bool);
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            vec![s * a.clone() * (one() - a)]
+        });
+        meta.create_gate("and", |meta| {
+            let s = meta.query_selector(s_and);
+            let [a, b, out] = cur(meta);
+            vec![s * (out - a * b)]
+        });
+        meta.create_gate("or", |meta| {
+            let s = meta.query_selector(s_or);
+            let [a, b, out] = cur(meta);
+            vec![s * (out - (a.clone() + b.clone() - a * b))]
+        });
+        meta.create_gate("not", |meta| {
+            let s = meta.query_selector(s_not);
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[2], Rotation::cur());
+            vec![s * (out - (one() - a))]
+        });
+
+        BoolConfig {
+            advice,
+            s_bool,
+            s_and,
+            s_or,
+            s_not,
+        }
+    }
+
+    /// loads a private value, constrained to be 0 or 1
+    pub fn load_private(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        self.load(layouter, value, true)
+    }
+
+    fn load(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        checked: bool,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "load bit",
+                |mut region| {
+                    if checked {
+                        at = (0, "selector");
+                        self.config.s_bool.enable(&mut region, 0)?;
+                    }
+                    at = (0, "a");
+                    region.assign_advice(|| "bit", self.config.advice[0], 0, || value)
+                },
+            )
+            .map_err(|e| FiboError::assign("load bit", at, e))
+    }
+
+    /// out = f(a, b) on one row enabled by `selector`, b left empty for not
+    fn op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &'static str,
+        selector: Selector,
+        a: &AssignedCell<F, F>,
+        b: Option<&AssignedCell<F, F>>,
+        out: Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let [col_a, col_b, col_out] = self.config.advice;
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || name,
+                |mut region| {
+                    at = (0, "selector");
+                    selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    if let Some(b) = b {
+                        at = (0, "b");
+                        b.copy_advice(|| "b", &mut region, col_b, 0)?;
+                    }
+
+                    at = (0, "out");
+                    region.assign_advice(|| "out", col_out, 0, || out)
+                },
+            )
+            .map_err(|e| FiboError::assign(name, at, e))
+    }
+
+    pub fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let out = a.value().copied() * b.value();
+        self.op(layouter, "and", self.config.s_and, a, Some(b), out)
+    }
+
+    pub fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let (a_val, b_val) = (a.value().copied(), b.value().copied());
+        let out = a_val + b_val - a_val * b_val;
+        self.op(layouter, "or", self.config.s_or, a, Some(b), out)
+    }
+
+    pub fn not(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let out = Value::known(F::ONE) - a.value();
+        self.op(layouter, "not", self.config.s_not, a, None, out)
+    }
+}
+
+impl<F: Field> Chip<F> for BoolChip<F> {
+    type Config = BoolConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FormulaConfig {
+    pub bool: BoolConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes (a and b) or not c for the private bits a, b and c
+#[derive(Debug, Default)]
+pub struct FormulaCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub c: Value<F>,
+}
+
+impl<F: Field> FormulaCircuit<F> {
+    pub fn new(a: F, b: F, c: F) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            c: Value::known(c),
+        }
+    }
+
+    fn evaluate(
+        &self,
+        chip: &BoolChip<F>,
+        mut layouter: impl Layouter<F>,
+        checked: bool,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let a = chip.load(layouter.namespace(|| "a"), self.a, checked)?;
+        let b = chip.load(layouter.namespace(|| "b"), self.b, checked)?;
+        let c = chip.load(layouter.namespace(|| "c"), self.c, checked)?;
+
+        let a_and_b = chip.and(layouter.namespace(|| "a and b"), &a, &b)?;
+        let not_c = chip.not(layouter.namespace(|| "not c"), &c)?;
+        chip.or(layouter.namespace(|| "or"), &a_and_b, &not_c)
+    }
+}
+
+impl<F: Field> Circuit<F> for FormulaCircuit<F> {
+    type Config = FormulaConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        FormulaConfig {
+            bool: BoolChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = BoolChip::construct(config.bool);
+        let out = self.evaluate(&chip, layouter.namespace(|| "formula"), true)?;
+        layouter
+            .constrain_instance(out.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn truth_table() {
+        let bit = |bit: bool| Fp::from(bit as u64);
+        for bits in 0..8 {
+            let [a, b, c] = [bits & 1 == 1, bits & 2 == 2, bits & 4 == 4];
+            let out = (a && b) || !c;
+            let circuit = FormulaCircuit::new(bit(a), bit(b), bit(c));
+            mock_ok!(circuit, 4, vec![vec![bit(out)]]);
+            mock_fails!(circuit, 4, vec![vec![bit(!out)]]);
+        }
+    }
+
+    /// the formula with its inputs loaded without the check that they are bits
+    struct Unchecked(FormulaCircuit<Fp>);
+
+    impl Circuit<Fp> for Unchecked {
+        type Config = FormulaConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(FormulaCircuit::default())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FormulaCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = BoolChip::construct(config.bool);
+            let out = self
+                .0
+                .evaluate(&chip, layouter.namespace(|| "formula"), false)?;
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn non_boolean_input() {
+        // 2 · 1/2 = 1, so "2 and 1/2" is true, and or-ed with not 1 the formula holds
+        let two = Fp::from(2);
+        let circuit = FormulaCircuit::new(two, two.invert().unwrap(), Fp::one());
+        mock_fails!(circuit, 4, vec![vec![Fp::one()]]);
+        mock_ok!(Unchecked(circuit), 4, vec![vec![Fp::one()]]);
+    }
+}
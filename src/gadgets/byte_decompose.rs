@@ -0,0 +1,352 @@
+//! a value as its N little-endian bytes, each looked up in a table of 0..256
+//! and recomposed by a running sum: acc = 256·acc_prev + byte
+//!
+//! the table column is passed in, so every decomposition of a circuit shares
+//! the one table, loaded once by `load_table`
+
+use std::marker::PhantomData;
+
+use crate::{error::FiboError, fibonacci::min_k};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// one row for each byte
+pub const TABLE_ROWS: usize = 1 << 8;
+
+/// the N little-endian bytes of the value. the bytes from N on are dropped
+pub fn to_bytes<F: PrimeField, const N: usize>(value: F) -> [F; N] {
+    let repr = value.to_repr();
+    std::array::from_fn(|i| F::from(repr.as_ref().get(i).copied().unwrap_or(0) as u64))
+}
+
+/// fills the table with 0..256, one byte a row
+pub fn load_table<F: PrimeField>(
+    mut layouter: impl Layouter<F>,
+    table: TableColumn,
+) -> Result<(), FiboError> {
+    let mut at = (0, "table");
+    layouter
+        .assign_table(
+            || "byte table",
+            |mut t| {
+                for offset in 0..TABLE_ROWS {
+                    at = (offset, "table");
+                    let value = Value::known(F::from(offset as u64));
+                    t.assign_cell(|| "byte", table, offset, || value)?;
+                }
+                Ok(())
+            },
+        )
+        .map_err(|e| FiboError::assign("byte table", at, e))
+}
+
+#[derive(Debug, Clone)]
+pub struct ByteDecomposeConfig {
+    pub byte: Column<Advice>,
+    /// the running sum of the bytes so far
+    pub acc: Column<Advice>,
+    /// on every row: the byte is in the table
+    pub q_byte: Selector,
+    /// on every row but the first: the running sum
+    pub q_sum: Selector,
+    pub table: TableColumn,
+}
+
+#[derive(Debug)]
+pub struct ByteDecomposeChip<F: PrimeField, const N: usize> {
+    config: ByteDecomposeConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField, const N: usize> ByteDecomposeChip<F, N> {
+    pub fn construct(config: ByteDecomposeConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// `table` is filled by `load_table`, once for all the chips using it
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        [byte, acc]: [Column<Advice>; 2],
+        table: TableColumn,
+    ) -> ByteDecomposeConfig {
+        let q_byte = meta.complex_selector();
+        let q_sum = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(byte);
+        meta.enable_equality(acc);
+
+        // unselected rows look up 0, which is in the table
+        meta.lookup(|meta| {
+            let q_byte = meta.query_selector(q_byte);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            vec![(q_byte * byte, table)]
+        });
+
+        meta.create_gate("byte running sum", |meta| {
+            let q_sum = meta.query_selector(q_sum);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![q_sum * (acc_prev * F::from(256) + byte - acc)]
+        });
+
+        ByteDecomposeConfig {
+            byte,
+            acc,
+            q_byte,
+            q_sum,
+            table,
+        }
+    }
+
+    /// | row |  byte |          acc          | q_byte | q_sum |
+    /// |  0  | b_N-1 |         b_N-1         |    1   |   0   |
+    /// |  1  | b_N-2 |   256·b_N-1 + b_N-2   |    1   |   1   |
+    ///                         .
+    ///                         .
+    /// | N-1 |  b_0  |         value         |    1   |   1   |
+    ///
+    /// returns the bytes of the value, least significant first. the value must
+    /// be below 2^(8·N), so a value of fewer bytes has its high bytes at 0
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, FiboError> {
+        self.assign_with(layouter, value, |bytes| bytes)
+    }
+
+    /// as assign, the bytes, least significant first, witnessed by `bytes_of(bytes)`
+    fn assign_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        bytes_of: impl FnOnce([Value<F>; N]) -> [Value<F>; N],
+    ) -> Result<Vec<AssignedCell<F, F>>, FiboError> {
+        if N == 0 {
+            return Err(FiboError::TooFewRows { rows: 0, min: 1 });
+        }
+
+        let bytes = value.value().map(|value| to_bytes::<F, N>(*value));
+        let bytes = bytes_of(bytes.transpose_array());
+
+        let mut at = (0, "byte");
+        layouter
+            .assign_region(
+                || "byte decompose",
+                |mut region| {
+                    let mut cells = Vec::with_capacity(N);
+                    let mut acc = Value::known(F::ZERO);
+                    let mut acc_cell = None;
+
+                    for (row, byte) in bytes.iter().rev().enumerate() {
+                        at = (row, "q_byte");
+                        self.config.q_byte.enable(&mut region, row)?;
+                        if row > 0 {
+                            at = (row, "q_sum");
+                            self.config.q_sum.enable(&mut region, row)?;
+                        }
+
+                        at = (row, "byte");
+                        let byte_cell =
+                            region.assign_advice(|| "byte", self.config.byte, row, || *byte)?;
+
+                        at = (row, "acc");
+                        acc = acc * Value::known(F::from(256)) + byte;
+                        let cell = region.assign_advice(|| "acc", self.config.acc, row, || acc)?;
+                        if row == 0 {
+                            // the sum starts at the top byte
+                            region.constrain_equal(cell.cell(), byte_cell.cell())?;
+                        }
+
+                        cells.push(byte_cell);
+                        acc_cell = Some(cell);
+                    }
+
+                    at = (N - 1, "acc");
+                    region.constrain_equal(acc_cell.unwrap().cell(), value.cell())?;
+
+                    cells.reverse();
+                    Ok(cells)
+                },
+            )
+            .map_err(|e| FiboError::assign("byte decompose", at, e))
+    }
+}
+
+impl<F: PrimeField, const N: usize> Chip<F> for ByteDecomposeChip<F, N> {
+    type Config = ByteDecomposeConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves the private value fits in N bytes
+#[derive(Debug, Default)]
+pub struct FitsCircuit<F, const N: usize> {
+    pub value: Value<F>,
+}
+
+/// a private value below 2^248, which any canonical pasta field element
+/// witness of 31 bytes is
+pub type CanonicalCircuit<F> = FitsCircuit<F, 31>;
+
+impl<F: PrimeField, const N: usize> FitsCircuit<F, N> {
+    pub fn new(value: F) -> Self {
+        Self {
+            value: Value::known(value),
+        }
+    }
+
+    /// the smallest k fitting the table, which takes far more rows than the bytes
+    pub fn min_k() -> u32 {
+        // the table is padded with one more row
+        min_k::<F, Self>(TABLE_ROWS.max(N) + 1)
+    }
+
+    /// the value decomposed, its bytes witnessed by `bytes_of`
+    fn synthesize_with(
+        &self,
+        config: ByteDecomposeConfig,
+        mut layouter: impl Layouter<F>,
+        bytes_of: impl FnOnce([Value<F>; N]) -> [Value<F>; N],
+    ) -> Result<(), FiboError> {
+        load_table(layouter.namespace(|| "table"), config.table)?;
+
+        let value = layouter
+            .assign_region(
+                || "load",
+                |mut region| region.assign_advice(|| "value", config.acc, 0, || self.value),
+            )
+            .map_err(|e| FiboError::assign("load", (0, "value"), e))?;
+
+        let chip = ByteDecomposeChip::<F, N>::construct(config);
+        chip.assign_with(layouter.namespace(|| "bytes"), &value, bytes_of)?;
+        Ok(())
+    }
+}
+
+impl<F: PrimeField, const N: usize> Circuit<F> for FitsCircuit<F, N> {
+    type Config = ByteDecomposeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 2].map(|_| meta.advice_column());
+        let table = meta.lookup_table_column();
+        ByteDecomposeChip::<F, N>::configure(meta, advice, table)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, |bytes| bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{arithmetic::Field, dev::VerifyFailure, pasta::Fp};
+
+    #[test]
+    fn k() {
+        assert_eq!(CanonicalCircuit::<Fp>::min_k(), 9);
+    }
+
+    #[test]
+    fn exact_bytes() {
+        assert_eq!(
+            to_bytes::<Fp, 4>(Fp::from(0x1234_5678)),
+            [0x78, 0x56, 0x34, 0x12].map(Fp::from)
+        );
+        mock_ok!(FitsCircuit::<Fp, 4>::new(Fp::from(0x1234_5678)), 9, vec![]);
+        mock_ok!(
+            FitsCircuit::<Fp, 4>::new(Fp::from(u32::MAX as u64)),
+            9,
+            vec![]
+        );
+        // the fifth byte has no row to go to
+        mock_fails!(FitsCircuit::<Fp, 4>::new(Fp::from(1 << 32)), 9, vec![]);
+    }
+
+    #[test]
+    fn fewer_bytes() {
+        for value in [0, 1, 0xff, 0x1234] {
+            mock_ok!(FitsCircuit::<Fp, 4>::new(Fp::from(value)), 9, vec![]);
+        }
+    }
+
+    /// the decomposition with its bytes witnessed by `bytes_of`
+    struct Tampered<const N: usize> {
+        circuit: FitsCircuit<Fp, N>,
+        bytes_of: fn([Value<Fp>; N]) -> [Value<Fp>; N],
+    }
+
+    impl<const N: usize> Circuit<Fp> for Tampered<N> {
+        type Config = ByteDecomposeConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                circuit: FitsCircuit::default(),
+                bytes_of: self.bytes_of,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FitsCircuit::<Fp, N>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            Ok(self
+                .circuit
+                .synthesize_with(config, layouter, self.bytes_of)?)
+        }
+    }
+
+    #[test]
+    fn nonzero_high_byte() {
+        // 0x1234 with its top byte at 1 sums to 0x0100_1234
+        let circuit = Tampered::<4> {
+            circuit: FitsCircuit::new(Fp::from(0x1234)),
+            bytes_of: |[b0, b1, b2, _]| [b0, b1, b2, Value::known(Fp::one())],
+        };
+        mock_fails!(circuit, 9, vec![]);
+    }
+
+    #[test]
+    fn tampered_byte() {
+        // 0x11·256 + 0x134 is still 0x1234, but 0x134 is no byte
+        let circuit = Tampered::<2> {
+            circuit: FitsCircuit::new(Fp::from(0x1234)),
+            bytes_of: |_| [0x134, 0x11].map(|b| Value::known(Fp::from(b))),
+        };
+        let failures = mock_fails!(circuit, 9, vec![]);
+        assert_eq!(failures.len(), 1, "{failures:?}");
+        assert!(matches!(failures[0], VerifyFailure::Lookup { .. }));
+    }
+
+    #[test]
+    fn canonical() {
+        let two_248 = Fp::from(2).pow_vartime([248]);
+        mock_ok!(CanonicalCircuit::new(two_248 - Fp::one()), 9, vec![]);
+        mock_fails!(CanonicalCircuit::new(two_248), 9, vec![]);
+        mock_fails!(CanonicalCircuit::new(-Fp::one()), 9, vec![]);
+    }
+}
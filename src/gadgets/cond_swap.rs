@@ -0,0 +1,208 @@
+//! (l, r) = (b, a) if flag else (a, b), for a boolean flag
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// the two cells out of a swap: (l, r)
+type Swapped<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
+
+#[derive(Debug, Clone)]
+pub struct CondSwapConfig {
+    /// a, b, flag, l and r on one row
+    pub advice: [Column<Advice>; 5],
+    pub selector: Selector,
+}
+
+pub struct CondSwapChip<F: Field> {
+    config: CondSwapConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> CondSwapChip<F> {
+    pub fn construct(config: CondSwapConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+    ) -> CondSwapConfig {
+        let selector = meta.selector();
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("cond swap", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, b, flag, l, r] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let one = Expression::Constant(F::ONE);
+            let not_flag = one - flag.clone();
+            vec![
+                s.clone() * (flag.clone() * b.clone() + not_flag.clone() * a.clone() - l),
+                s.clone() * (flag.clone() * a + not_flag.clone() * b - r),
+                s * flag * not_flag,
+            ]
+        });
+
+        CondSwapConfig { advice, selector }
+    }
+
+    /// copies a, b and flag onto a row and returns (l, r)
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        flag: &AssignedCell<F, F>,
+    ) -> Result<Swapped<F>, FiboError> {
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "cond swap",
+                |mut region| {
+                    let [col_a, col_b, col_flag, col_l, col_r] = self.config.advice;
+
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    at = (0, "b");
+                    b.copy_advice(|| "b", &mut region, col_b, 0)?;
+                    at = (0, "flag");
+                    flag.copy_advice(|| "flag", &mut region, col_flag, 0)?;
+
+                    // l = a + flag·(b - a), r = b + flag·(a - b)
+                    let diff = b.value().copied() - a.value();
+                    let shift = flag.value().copied() * diff;
+
+                    at = (0, "l");
+                    let l =
+                        region.assign_advice(|| "l", col_l, 0, || a.value().copied() + shift)?;
+                    at = (0, "r");
+                    let r =
+                        region.assign_advice(|| "r", col_r, 0, || b.value().copied() - shift)?;
+                    Ok((l, r))
+                },
+            )
+            .map_err(|e| FiboError::assign("cond swap", at, e))
+    }
+}
+
+impl<F: Field> Chip<F> for CondSwapChip<F> {
+    type Config = CondSwapConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    #[derive(Debug, Clone)]
+    struct SwapConfig {
+        swap: CondSwapConfig,
+        instance: Column<Instance>,
+    }
+
+    /// swaps the private a and b by the private flag, exposing (l, r)
+    #[derive(Default)]
+    struct SwapCircuit {
+        a: Value<Fp>,
+        b: Value<Fp>,
+        flag: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for SwapCircuit {
+        type Config = SwapConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 5].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            SwapConfig {
+                swap: CondSwapChip::configure(meta, advice),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let [col_a, col_b, col_flag, ..] = config.swap.advice;
+            let (a, b, flag) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", col_a, 0, || self.a)?;
+                    let b = region.assign_advice(|| "b", col_b, 0, || self.b)?;
+                    let flag = region.assign_advice(|| "flag", col_flag, 0, || self.flag)?;
+                    Ok((a, b, flag))
+                },
+            )?;
+
+            let chip = CondSwapChip::construct(config.swap);
+            let (l, r) = chip.assign(layouter.namespace(|| "swap"), &a, &b, &flag)?;
+            layouter.constrain_instance(l.cell(), config.instance, 0)?;
+            layouter.constrain_instance(r.cell(), config.instance, 1)
+        }
+    }
+
+    /// swaps 3 and 5 if flag is 1
+    fn swap(flag: u64) -> SwapCircuit {
+        SwapCircuit {
+            a: Value::known(Fp::from(3)),
+            b: Value::known(Fp::from(5)),
+            flag: Value::known(Fp::from(flag)),
+        }
+    }
+
+    fn verifies(flag: u64, l: u64, r: u64) {
+        mock_ok!(swap(flag), 4, vec![vec![Fp::from(l), Fp::from(r)]]);
+    }
+
+    fn fails(flag: u64, l: u64, r: u64) {
+        mock_fails!(swap(flag), 4, vec![vec![Fp::from(l), Fp::from(r)]]);
+    }
+
+    #[test]
+    fn keeps() {
+        verifies(0, 3, 5);
+        fails(0, 5, 3);
+    }
+
+    #[test]
+    fn swaps() {
+        verifies(1, 5, 3);
+        fails(1, 3, 5);
+    }
+
+    #[test]
+    fn non_boolean_flag() {
+        // with flag = 2, l = 3 + 2·2 = 7 and r = 5 - 2·2 = 1 satisfy the swap constraints
+        fails(2, 7, 1);
+    }
+}
@@ -0,0 +1,451 @@
+//! a value as its N little-endian bits, recomposed by a running sum
+//!
+//! the bits are laid out from the most significant down, so each row doubles
+//! the sum of the rows above: acc = 2·acc_prev + bit
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// the N little-endian bits of the value, as 0 or 1. the bits from N on are dropped
+pub fn to_bits<F: PrimeField, const N: usize>(value: F) -> [F; N] {
+    std::array::from_fn(|i| F::from(bit(&value, i) as u64))
+}
+
+/// the i-th little-endian bit of the value
+pub fn bit<F: PrimeField>(value: &F, i: usize) -> bool {
+    let repr = value.to_repr();
+    (repr.as_ref()[i / 8] >> (i % 8)) & 1 == 1
+}
+
+#[derive(Debug, Clone)]
+pub struct DecomposeConfig {
+    pub bit: Column<Advice>,
+    /// the running sum of the bits so far
+    pub acc: Column<Advice>,
+    /// on every row: the bit is boolean
+    pub q_bit: Selector,
+    /// on every row but the first: the running sum
+    pub q_sum: Selector,
+}
+
+pub struct DecomposeChip<F: PrimeField, const N: usize> {
+    config: DecomposeConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField, const N: usize> DecomposeChip<F, N> {
+    pub fn construct(config: DecomposeConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> DecomposeConfig {
+        let bit = meta.advice_column();
+        let acc = meta.advice_column();
+        let q_bit = meta.selector();
+        let q_sum = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(bit);
+        meta.enable_equality(acc);
+
+        meta.create_gate("bool", |meta| {
+            let q_bit = meta.query_selector(q_bit);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            vec![q_bit * bit.clone() * (Expression::Constant(F::ONE) - bit)]
+        });
+
+        meta.create_gate("running sum", |meta| {
+            let q_sum = meta.query_selector(q_sum);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![q_sum * (acc_prev * F::from(2) + bit - acc)]
+        });
+
+        DecomposeConfig {
+            bit,
+            acc,
+            q_bit,
+            q_sum,
+        }
+    }
+
+    /// | row |  bit  |       acc       | q_bit | q_sum |
+    /// |  0  | b_N-1 |      b_N-1      |   1   |   0   |
+    /// |  1  | b_N-2 | 2·b_N-1 + b_N-2 |   1   |   1   |
+    ///                     .
+    ///                     .
+    /// | N-1 |  b_0  |      value      |   1   |   1   |
+    ///
+    /// returns the bits of the value, least significant first
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, FiboError> {
+        let bits = value.value().map(|value| to_bits::<F, N>(*value));
+        self.assign_bits(layouter, value, bits.transpose_array())
+    }
+
+    /// as assign, with the bits witnessed least significant first
+    fn assign_bits(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        bits: [Value<F>; N],
+    ) -> Result<Vec<AssignedCell<F, F>>, FiboError> {
+        let mut at = (0, "bit");
+        layouter
+            .assign_region(
+                || "decompose",
+                |mut region| {
+                    let mut cells = Vec::with_capacity(N);
+                    let mut acc = Value::known(F::ZERO);
+                    let mut acc_cell = None;
+
+                    for (row, bit) in bits.iter().rev().enumerate() {
+                        at = (row, "q_bit");
+                        self.config.q_bit.enable(&mut region, row)?;
+                        if row > 0 {
+                            at = (row, "q_sum");
+                            self.config.q_sum.enable(&mut region, row)?;
+                        }
+
+                        at = (row, "bit");
+                        let bit_cell =
+                            region.assign_advice(|| "bit", self.config.bit, row, || *bit)?;
+
+                        at = (row, "acc");
+                        acc = acc * Value::known(F::from(2)) + bit;
+                        let cell = region.assign_advice(|| "acc", self.config.acc, row, || acc)?;
+                        if row == 0 {
+                            // the sum starts at the top bit
+                            region.constrain_equal(cell.cell(), bit_cell.cell())?;
+                        }
+
+                        cells.push(bit_cell);
+                        acc_cell = Some(cell);
+                    }
+
+                    if let Some(acc_cell) = acc_cell {
+                        at = (N - 1, "acc");
+                        region.constrain_equal(acc_cell.cell(), value.cell())?;
+                    }
+
+                    cells.reverse();
+                    Ok(cells)
+                },
+            )
+            .map_err(|e| FiboError::assign("decompose", at, e))
+    }
+
+    /// Σ bit_i·2^i of the bits, least significant first, copied in and
+    /// recomposed by the same running sum
+    pub fn compose(
+        &self,
+        layouter: impl Layouter<F>,
+        bits: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        self.compose_with(layouter, bits, |_, acc| acc)
+    }
+
+    /// as compose, the running sum of each row witnessed by `acc_at(row, acc)`
+    fn compose_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bits: &[AssignedCell<F, F>],
+        acc_at: impl Fn(usize, Value<F>) -> Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        if bits.is_empty() {
+            return Err(FiboError::TooFewRows { rows: 0, min: 1 });
+        }
+
+        let mut at = (0, "bit");
+        layouter
+            .assign_region(
+                || "compose",
+                |mut region| {
+                    let mut acc = Value::known(F::ZERO);
+                    let mut acc_cell = None;
+
+                    for (row, bit) in bits.iter().rev().enumerate() {
+                        at = (row, "q_bit");
+                        self.config.q_bit.enable(&mut region, row)?;
+                        if row > 0 {
+                            at = (row, "q_sum");
+                            self.config.q_sum.enable(&mut region, row)?;
+                        }
+
+                        at = (row, "bit");
+                        let bit_cell =
+                            bit.copy_advice(|| "bit", &mut region, self.config.bit, row)?;
+
+                        at = (row, "acc");
+                        acc = acc_at(row, acc * Value::known(F::from(2)) + bit.value());
+                        let cell = region.assign_advice(|| "acc", self.config.acc, row, || acc)?;
+                        if row == 0 {
+                            region.constrain_equal(cell.cell(), bit_cell.cell())?;
+                        }
+                        acc_cell = Some(cell);
+                    }
+
+                    Ok(acc_cell.unwrap())
+                },
+            )
+            .map_err(|e| FiboError::assign("compose", at, e))
+    }
+}
+
+impl<F: PrimeField, const N: usize> Chip<F> for DecomposeChip<F, N> {
+    type Config = DecomposeConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves the private value fits in N bits
+#[derive(Debug, Default)]
+pub struct DecomposeCircuit<F, const N: usize> {
+    pub value: Value<F>,
+}
+
+/// proves the private value fits in a u64
+pub type U64Circuit<F> = DecomposeCircuit<F, 64>;
+
+impl<F: PrimeField, const N: usize> DecomposeCircuit<F, N> {
+    pub fn new(value: F) -> Self {
+        Self {
+            value: Value::known(value),
+        }
+    }
+}
+
+/// loads the private value into the acc column
+fn load<F: PrimeField>(
+    config: &DecomposeConfig,
+    mut layouter: impl Layouter<F>,
+    value: Value<F>,
+) -> Result<AssignedCell<F, F>, FiboError> {
+    layouter
+        .assign_region(
+            || "load value",
+            |mut region| region.assign_advice(|| "value", config.acc, 0, || value),
+        )
+        .map_err(|e| FiboError::assign("load value", (0, "acc"), e))
+}
+
+impl<F: PrimeField, const N: usize> Circuit<F> for DecomposeCircuit<F, N> {
+    type Config = DecomposeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        DecomposeChip::<F, N>::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let value = load(&config, layouter.namespace(|| "value"), self.value)?;
+        let chip = DecomposeChip::<F, N>::construct(config);
+        chip.assign(layouter.namespace(|| "bits"), &value)?;
+        Ok(())
+    }
+}
+
+/// decomposes the private value into N bits and back, the recomposed cell
+/// constrained equal to the value
+#[derive(Debug, Default)]
+pub struct RoundTripCircuit<F, const N: usize> {
+    pub value: Value<F>,
+}
+
+impl<F: PrimeField, const N: usize> RoundTripCircuit<F, N> {
+    pub fn new(value: F) -> Self {
+        Self {
+            value: Value::known(value),
+        }
+    }
+}
+
+/// constrains the recomposed cell equal to the value
+fn round_trip<F: PrimeField>(
+    mut layouter: impl Layouter<F>,
+    value: &AssignedCell<F, F>,
+    composed: &AssignedCell<F, F>,
+) -> Result<(), FiboError> {
+    layouter
+        .assign_region(
+            || "round trip",
+            |mut region| region.constrain_equal(value.cell(), composed.cell()),
+        )
+        .map_err(|e| FiboError::assign("round trip", (0, "acc"), e))
+}
+
+impl<F: PrimeField, const N: usize> Circuit<F> for RoundTripCircuit<F, N> {
+    type Config = DecomposeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        DecomposeChip::<F, N>::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let value = load(&config, layouter.namespace(|| "value"), self.value)?;
+        let chip = DecomposeChip::<F, N>::construct(config);
+
+        let bits = chip.assign(layouter.namespace(|| "decompose"), &value)?;
+        let composed = chip.compose(layouter.namespace(|| "compose"), &bits)?;
+        round_trip(layouter.namespace(|| "round trip"), &value, &composed)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn bits() {
+        let bits = to_bits::<Fp, 8>(Fp::from(0b1011_0010));
+        let expected = [0, 1, 0, 0, 1, 1, 0, 1].map(Fp::from);
+        assert_eq!(bits, expected);
+    }
+
+    #[test]
+    fn u64() {
+        for value in [0, 1, 0xdead_beef, u64::MAX] {
+            mock_ok!(U64Circuit::new(Fp::from(value)), 7, vec![]);
+        }
+    }
+
+    #[test]
+    fn too_big() {
+        let big = Fp::from(u64::MAX) + Fp::one();
+        mock_fails!(U64Circuit::new(big), 7, vec![]);
+        mock_fails!(U64Circuit::new(-Fp::one()), 7, vec![]);
+        mock_ok!(DecomposeCircuit::<_, 65>::new(big), 7, vec![]);
+    }
+
+    /// decomposes 6 into 4 bits with `bits` witnessed instead
+    struct Tampered {
+        bits: [u64; 4],
+    }
+
+    impl Circuit<Fp> for Tampered {
+        type Config = DecomposeConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { bits: self.bits }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            DecomposeChip::<Fp, 4>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let value = load(
+                &config,
+                layouter.namespace(|| "value"),
+                Value::known(Fp::from(6)),
+            )?;
+            let chip = DecomposeChip::<Fp, 4>::construct(config);
+            let bits = self.bits.map(|bit| Value::known(Fp::from(bit)));
+            chip.assign_bits(layouter.namespace(|| "bits"), &value, bits)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn flipped_bit() {
+        mock_ok!(Tampered { bits: [0, 1, 1, 0] }, 7, vec![]);
+        mock_fails!(Tampered { bits: [1, 1, 1, 0] }, 7, vec![]);
+        mock_fails!(Tampered { bits: [0, 1, 1, 1] }, 7, vec![]);
+        // 6 = 2·3, a 3 standing in for the two ones
+        mock_fails!(Tampered { bits: [0, 3, 0, 0] }, 7, vec![]);
+    }
+
+    /// the round trip of 6 in 4 bits, the running sum of `row` bumped by one
+    struct TamperedSum {
+        row: usize,
+    }
+
+    impl Circuit<Fp> for TamperedSum {
+        type Config = DecomposeConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { row: self.row }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            DecomposeChip::<Fp, 4>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let six = Value::known(Fp::from(6));
+            let value = load(&config, layouter.namespace(|| "value"), six)?;
+            let chip = DecomposeChip::<Fp, 4>::construct(config);
+
+            let bits = chip.assign(layouter.namespace(|| "decompose"), &value)?;
+            let composed =
+                chip.compose_with(layouter.namespace(|| "compose"), &bits, |row, acc| {
+                    if row == self.row {
+                        acc + Value::known(Fp::one())
+                    } else {
+                        acc
+                    }
+                })?;
+            round_trip(layouter.namespace(|| "round trip"), &value, &composed)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recomposes() {
+        // both ways through 64 bits take 129 rows
+        let circuit = RoundTripCircuit::<_, 64>::new(Fp::from(0xdead_beef));
+        mock_ok!(circuit, 8, vec![]);
+
+        // no tampering past the last row
+        mock_ok!(TamperedSum { row: 4 }, 7, vec![]);
+        for row in 0..4 {
+            mock_fails!(TamperedSum { row }, 7, vec![]);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! a = q·b + r with r < b, for N bit divisors and quotients
+//!
+//! without the quotient range checked, any a makes r = 0 hold with q = a/b in
+//! the field, so q is decomposed into N bits and r compared to b, which range
+//! checks both of them as well
+
+use std::marker::PhantomData;
+
+use super::{
+    decompose::DecomposeChip,
+    lt::{LtChip, LtConfig},
+};
+use crate::error::FiboError;
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// the quotient and the remainder
+type QuoRem<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
+
+/// the low 128 bits of the value
+fn to_u128<F: PrimeField>(value: &F) -> u128 {
+    let repr = value.to_repr();
+    repr.as_ref()
+        .iter()
+        .take(16)
+        .rev()
+        .fold(0, |acc, byte| (acc << 8) | *byte as u128)
+}
+
+#[derive(Debug, Clone)]
+pub struct DivModConfig {
+    /// a, b, q and r, the comparison using one more column
+    pub advice: [Column<Advice>; 5],
+    pub selector: Selector,
+    pub lt: LtConfig,
+}
+
+#[derive(Debug)]
+pub struct DivModChip<F: PrimeField, const N: usize> {
+    config: DivModConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField, const N: usize> DivModChip<F, N> {
+    pub fn construct(config: DivModConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// the comparison shares the advice columns
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 5]) -> DivModConfig {
+        let selector = meta.selector();
+        let lt = LtChip::<F, N>::configure(meta, advice);
+
+        meta.create_gate("div mod", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, b, q, r] = [0, 1, 2, 3].map(|i| meta.query_advice(advice[i], Rotation::cur()));
+            vec![s * (q * b + r - a)]
+        });
+
+        DivModConfig {
+            advice,
+            selector,
+            lt,
+        }
+    }
+
+    /// (a / b, a % b) of a by the N bit divisor b. a must be below 2^(2·N)
+    /// to have a quotient of N bits
+    pub fn div_mod(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<QuoRem<F>, FiboError> {
+        self.div_mod_with(layouter, a, b, |q, r| (q, r))
+    }
+
+    /// as div_mod, q and r witnessed by `quo_rem(q, r)`
+    fn div_mod_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        quo_rem: impl FnOnce(Value<F>, Value<F>) -> (Value<F>, Value<F>),
+    ) -> Result<QuoRem<F>, FiboError> {
+        let native = a.value().zip(b.value()).map(|(a, b)| {
+            let (a, b) = (to_u128(a), to_u128(b));
+            // a zero divisor leaves r = a, for r < b to reject
+            let (q, r) = a.checked_div(b).map_or((0, a), |q| (q, a % b));
+            (F::from_u128(q), F::from_u128(r))
+        });
+        let (q, r) = native.unzip();
+        let (q, r) = quo_rem(q, r);
+
+        let [col_a, col_b, col_q, col_r, _] = self.config.advice;
+        let mut at = (0, "selector");
+        let (q, r) = layouter
+            .assign_region(
+                || "div mod",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    at = (0, "b");
+                    b.copy_advice(|| "b", &mut region, col_b, 0)?;
+
+                    at = (0, "q");
+                    let q = region.assign_advice(|| "q", col_q, 0, || q)?;
+                    at = (0, "r");
+                    let r = region.assign_advice(|| "r", col_r, 0, || r)?;
+                    Ok((q, r))
+                },
+            )
+            .map_err(|e| FiboError::assign("div mod", at, e))?;
+
+        let decompose = DecomposeChip::<F, N>::construct(self.config.lt.decompose.clone());
+        decompose.assign(layouter.namespace(|| "range check q"), &q)?;
+
+        let lt = LtChip::<F, N>::construct(self.config.lt.clone());
+        let r_lt_b = lt.lt(layouter.namespace(|| "r < b"), &r, b)?;
+        layouter
+            .assign_region(
+                || "r < b holds",
+                |mut region| region.constrain_constant(r_lt_b.cell(), F::ONE),
+            )
+            .map_err(|e| FiboError::assign("r < b holds", (0, "lt"), e))?;
+
+        Ok((q, r))
+    }
+}
+
+impl<F: PrimeField, const N: usize> Chip<F> for DivModChip<F, N> {
+    type Config = DivModConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DivisibleConfig {
+    pub div_mod: DivModConfig,
+    /// the divisor, then the remainder
+    pub instance: Column<Instance>,
+}
+
+/// exposes the remainder of the private a by the public divisor, 0 for a
+/// proof that the divisor divides a
+#[derive(Debug, Default)]
+pub struct DivisibleCircuit<F, const N: usize> {
+    pub a: Value<F>,
+}
+
+impl<F: PrimeField, const N: usize> DivisibleCircuit<F, N> {
+    pub fn new(a: F) -> Self {
+        Self { a: Value::known(a) }
+    }
+
+    /// the instance column for the divisor and the remainder
+    pub fn instance(divisor: u64, remainder: u64) -> Vec<F> {
+        vec![F::from(divisor), F::from(remainder)]
+    }
+
+    /// a divided, q and r witnessed by `quo_rem`
+    fn synthesize_with(
+        &self,
+        config: DivisibleConfig,
+        mut layouter: impl Layouter<F>,
+        quo_rem: impl FnOnce(Value<F>, Value<F>) -> (Value<F>, Value<F>),
+    ) -> Result<(), FiboError> {
+        let [col_a, col_b, ..] = config.div_mod.advice;
+        let mut at = (0, "a");
+        let (a, b) = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    at = (0, "a");
+                    let a = region.assign_advice(|| "a", col_a, 0, || self.a)?;
+                    at = (0, "b");
+                    let b =
+                        region.assign_advice_from_instance(|| "b", config.instance, 0, col_b, 0)?;
+                    Ok((a, b))
+                },
+            )
+            .map_err(|e| FiboError::assign("load", at, e))?;
+
+        let chip = DivModChip::<F, N>::construct(config.div_mod);
+        let (_, r) = chip.div_mod_with(layouter.namespace(|| "a / b"), &a, &b, quo_rem)?;
+        layouter
+            .constrain_instance(r.cell(), config.instance, 1)
+            .map_err(|e| FiboError::expose(1, e))
+    }
+}
+
+impl<F: PrimeField, const N: usize> Circuit<F> for DivisibleCircuit<F, N> {
+    type Config = DivisibleConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        DivisibleConfig {
+            div_mod: DivModChip::<F, N>::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, |q, r| (q, r))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{arithmetic::Field, dev::VerifyFailure, pasta::Fp};
+
+    const K: u32 = 7;
+
+    fn instance(b: u64, r: u64) -> Vec<Vec<Fp>> {
+        vec![DivisibleCircuit::<Fp, 16>::instance(b, r)]
+    }
+
+    fn verifies(a: u64, b: u64, r: u64) {
+        mock_ok!(
+            DivisibleCircuit::<Fp, 16>::new(Fp::from(a)),
+            K,
+            instance(b, r)
+        );
+    }
+
+    fn fails(a: u64, b: u64, r: u64) {
+        mock_fails!(
+            DivisibleCircuit::<Fp, 16>::new(Fp::from(a)),
+            K,
+            instance(b, r)
+        );
+    }
+
+    #[test]
+    fn divisible() {
+        for a in [0, 7, 14, 7 * 9_000] {
+            verifies(a, 7, 0);
+        }
+        fails(15, 7, 0);
+    }
+
+    #[test]
+    fn remainder() {
+        verifies(15, 7, 1);
+        verifies(100, 7, 2);
+        verifies(6, 7, 6);
+        fails(100, 7, 3);
+    }
+
+    #[test]
+    fn zero_divisor() {
+        fails(15, 0, 15);
+        fails(15, 0, 0);
+    }
+
+    type QuoRemOf = fn(Value<Fp>, Value<Fp>) -> (Value<Fp>, Value<Fp>);
+
+    /// the division of the private a with q and r witnessed by `quo_rem`
+    struct Tampered {
+        a: u64,
+        quo_rem: QuoRemOf,
+    }
+
+    impl Circuit<Fp> for Tampered {
+        type Config = DivisibleConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { ..*self }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            DivisibleCircuit::<Fp, 16>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let circuit = DivisibleCircuit::<Fp, 16>::new(Fp::from(self.a));
+            Ok(circuit.synthesize_with(config, layouter, self.quo_rem)?)
+        }
+    }
+
+    #[test]
+    fn wraparound() {
+        // q = 15/7 in the field has q·7 + 0 = 15, but q is no 16 bit value
+        let circuit = Tampered {
+            a: 15,
+            quo_rem: |_, _| {
+                let q = Fp::from(15) * Fp::from(7).invert().unwrap();
+                (Value::known(q), Value::known(Fp::zero()))
+            },
+        };
+        let failures = mock_fails!(circuit, K, instance(7, 0));
+        // the gate holds, it is the range check that rejects it
+        assert!(!failures.iter().any(|failure| matches!(
+            failure,
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                if constraint.to_string().contains("div mod")
+        )));
+    }
+
+    #[test]
+    fn remainder_too_large() {
+        // 15 = 1·7 + 8 holds, but 8 is no remainder of 7
+        let circuit = Tampered {
+            a: 15,
+            quo_rem: |_, _| (Value::known(Fp::one()), Value::known(Fp::from(8))),
+        };
+        mock_fails!(circuit, K, instance(7, 8));
+    }
+}
@@ -0,0 +1,380 @@
+//! unsigned fixed point numbers of N bits, a value v standing for v·2^-16
+//!
+//! the product of two numbers carries the scale twice, so mul divides it by
+//! 2^16 with the div mod chip, truncating, whose quotient range check is what
+//! rejects a product out of N bits
+
+use std::marker::PhantomData;
+
+use super::{
+    decompose::DecomposeChip,
+    div_mod::{DivModChip, DivModConfig},
+};
+use crate::error::FiboError;
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// the bits after the point
+pub const SCALE_BITS: usize = 16;
+/// 1.0
+pub const ONE: u64 = 1 << SCALE_BITS;
+
+/// x to the nearest point of the grid
+pub fn to_fixed(x: f64) -> u64 {
+    (x * ONE as f64).round() as u64
+}
+
+pub fn to_f64(value: u128) -> f64 {
+    value as f64 / ONE as f64
+}
+
+/// a·b truncated to the grid, as mul computes it
+pub fn mul(a: u128, b: u128) -> u128 {
+    (a * b) >> SCALE_BITS
+}
+
+/// x·(1 + r)^n, truncated after each step
+pub fn growth(x: u64, r: u64, n: usize) -> u128 {
+    let rate = (ONE + r) as u128;
+    (0..n).fold(x as u128, |acc, _| mul(acc, rate))
+}
+
+#[derive(Debug, Clone)]
+pub struct FixedPointConfig {
+    /// a, b and out of the add and mul rows, shared with the division
+    pub div_mod: DivModConfig,
+    pub s_add: Selector,
+    pub s_mul: Selector,
+}
+
+#[derive(Debug)]
+pub struct FixedPointChip<F: PrimeField, const N: usize> {
+    config: FixedPointConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField, const N: usize> FixedPointChip<F, N> {
+    pub fn construct(config: FixedPointConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// the circuit must enable a constant column for the scale
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+    ) -> FixedPointConfig {
+        let div_mod = DivModChip::<F, N>::configure(meta, advice);
+        let s_add = meta.selector();
+        let s_mul = meta.selector();
+
+        let cur = |meta: &mut VirtualCells<'_, F>| {
+            [0, 1, 2].map(|i| meta.query_advice(advice[i], Rotation::cur()))
+        };
+
+        meta.create_gate("fixed add", |meta| {
+            let s = meta.query_selector(s_add);
+            let [a, b, out] = cur(meta);
+            vec![s * (a + b - out)]
+        });
+        meta.create_gate("fixed mul", |meta| {
+            let s = meta.query_selector(s_mul);
+            let [a, b, out] = cur(meta);
+            vec![s * (a * b - out)]
+        });
+
+        FixedPointConfig {
+            div_mod,
+            s_add,
+            s_mul,
+        }
+    }
+
+    /// loads a private number, range checked to N bits
+    pub fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let column = self.config.div_mod.advice[0];
+        let cell = layouter
+            .assign_region(
+                || "load",
+                |mut region| region.assign_advice(|| "value", column, 0, || value),
+            )
+            .map_err(|e| FiboError::assign("load", (0, "value"), e))?;
+        self.range_check(layouter.namespace(|| "range check"), &cell)?;
+        Ok(cell)
+    }
+
+    /// the cell is below 2^N
+    pub fn range_check(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+    ) -> Result<(), FiboError> {
+        let decompose = DecomposeChip::<F, N>::construct(self.config.div_mod.lt.decompose.clone());
+        decompose.assign(layouter, cell)?;
+        Ok(())
+    }
+
+    /// out = f(a, b) on one row enabled by `selector`
+    fn op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &'static str,
+        selector: Selector,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        out: Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let [col_a, col_b, col_out, ..] = self.config.div_mod.advice;
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || name,
+                |mut region| {
+                    at = (0, "selector");
+                    selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    at = (0, "b");
+                    b.copy_advice(|| "b", &mut region, col_b, 0)?;
+
+                    at = (0, "out");
+                    region.assign_advice(|| "out", col_out, 0, || out)
+                },
+            )
+            .map_err(|e| FiboError::assign(name, at, e))
+    }
+
+    /// a + b, both at the same scale. the sum is not range checked
+    pub fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let out = a.value().copied() + b.value();
+        self.op(layouter, "fixed add", self.config.s_add, a, b, out)
+    }
+
+    /// a·b truncated to the grid, range checked to N bits. a and b must be of
+    /// N bits for the product not to wrap around the field
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let out = a.value().copied() * b.value();
+        let product = self.op(
+            layouter.namespace(|| "product"),
+            "fixed mul",
+            self.config.s_mul,
+            a,
+            b,
+            out,
+        )?;
+
+        let column = self.config.div_mod.advice[1];
+        let scale = layouter
+            .assign_region(
+                || "scale",
+                |mut region| {
+                    region.assign_advice_from_constant(|| "scale", column, 0, F::from(ONE))
+                },
+            )
+            .map_err(|e| FiboError::assign("scale", (0, "scale"), e))?;
+
+        let div_mod = DivModChip::<F, N>::construct(self.config.div_mod.clone());
+        let (q, _) = div_mod.div_mod(layouter.namespace(|| "rescale"), &product, &scale)?;
+        Ok(q)
+    }
+
+    /// 1.0, as a constant of the circuit
+    pub fn one(&self, mut layouter: impl Layouter<F>) -> Result<AssignedCell<F, F>, FiboError> {
+        let column = self.config.div_mod.advice[0];
+        layouter
+            .assign_region(
+                || "one",
+                |mut region| region.assign_advice_from_constant(|| "one", column, 0, F::from(ONE)),
+            )
+            .map_err(|e| FiboError::assign("one", (0, "one"), e))
+    }
+}
+
+impl<F: PrimeField, const N: usize> Chip<F> for FixedPointChip<F, N> {
+    type Config = FixedPointConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// the bits of the numbers of the growth circuit: 16 before the point
+pub const GROWTH_BITS: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct GrowthConfig {
+    pub fixed: FixedPointConfig,
+    /// r, then the grown x
+    pub instance: Column<Instance>,
+}
+
+/// exposes x·(1 + r)^n for the private x and the public r, n being part of
+/// the circuit as it sets the number of multiplications
+#[derive(Debug, Default)]
+pub struct GrowthCircuit<F> {
+    pub x: Value<F>,
+    pub n: usize,
+}
+
+impl<F: PrimeField> GrowthCircuit<F> {
+    pub fn new(x: u64, n: usize) -> Self {
+        Self {
+            x: Value::known(F::from(x)),
+            n,
+        }
+    }
+
+    /// the instance column for the rate and the grown x
+    pub fn instance(r: u64, grown: u128) -> Vec<F> {
+        vec![F::from(r), F::from_u128(grown)]
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for GrowthCircuit<F> {
+    type Config = GrowthConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            x: Value::unknown(),
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        GrowthConfig {
+            fixed: FixedPointChip::<F, GROWTH_BITS>::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let column = config.fixed.div_mod.advice[1];
+        let chip = FixedPointChip::<F, GROWTH_BITS>::construct(config.fixed);
+        let x = chip.load_private(layouter.namespace(|| "x"), self.x)?;
+
+        let r = layouter
+            .assign_region(
+                || "r",
+                |mut region| {
+                    region.assign_advice_from_instance(|| "r", config.instance, 0, column, 0)
+                },
+            )
+            .map_err(|e| FiboError::assign("r", (0, "r"), e))?;
+        chip.range_check(layouter.namespace(|| "range check r"), &r)?;
+
+        let one = chip.one(layouter.namespace(|| "one"))?;
+        let rate = chip.add(layouter.namespace(|| "1 + r"), &one, &r)?;
+
+        let mut grown = x;
+        for i in 0..self.n {
+            grown = chip.mul(layouter.namespace(|| format!("step {i}")), &grown, &rate)?;
+        }
+
+        layouter
+            .constrain_instance(grown.cell(), config.instance, 1)
+            .map_err(|e| FiboError::expose(1, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    const K: u32 = 10;
+
+    fn verifies(x: u64, r: u64, n: usize, grown: u128) {
+        mock_ok!(
+            GrowthCircuit::<Fp>::new(x, n),
+            K,
+            vec![GrowthCircuit::<Fp>::instance(r, grown)]
+        );
+    }
+
+    fn fails(x: u64, r: u64, n: usize, grown: u128) {
+        mock_fails!(
+            GrowthCircuit::<Fp>::new(x, n),
+            K,
+            vec![GrowthCircuit::<Fp>::instance(r, grown)]
+        );
+    }
+
+    #[test]
+    fn mul_within_one_ulp() {
+        let ulp = 1.0 / ONE as f64;
+        for (a, b) in [
+            (1.5, 2.25),
+            (3.25, 2.7),
+            (1.1, 0.3),
+            (0.001, 0.5),
+            (255.75, 255.75),
+        ] {
+            let product = mul(to_fixed(a) as u128, to_fixed(b) as u128);
+            let reference = to_f64(to_fixed(a) as u128) * to_f64(to_fixed(b) as u128);
+            assert!((to_f64(product) - reference).abs() < ulp, "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn compound_growth() {
+        // 1000 at 5% for 5 years, truncating once a year
+        let (x, r, n) = (to_fixed(1000.0), to_fixed(0.05), 5);
+        let grown = growth(x, r, n);
+        let reference = to_f64(x as u128) * (1.0 + to_f64(r as u128)).powi(n as i32);
+        assert!((to_f64(grown) - reference).abs() < n as f64 / ONE as f64);
+
+        verifies(x, r, n, grown);
+        fails(x, r, n, grown + 1);
+        fails(x, r + 1, n, grown);
+    }
+
+    #[test]
+    fn no_steps() {
+        let x = to_fixed(42.5);
+        verifies(x, to_fixed(0.1), 0, x as u128);
+    }
+
+    #[test]
+    fn overflow() {
+        // 20000 doubled twice needs 17 bits before the point, 16 there are
+        let (x, r) = (to_fixed(20000.0), to_fixed(1.0));
+        let grown = growth(x, r, 2);
+        assert_eq!(to_f64(grown), 80000.0);
+        fails(x, r, 2, grown);
+        // and once is still in range
+        verifies(x, r, 1, growth(x, r, 1));
+    }
+}
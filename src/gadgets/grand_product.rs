@@ -0,0 +1,117 @@
+//! b is a permutation of a, as multisets: for a random γ,
+//! Π(a_i + γ) = Π(b_i + γ) only if the two lists hold the same values
+//!
+//! the running product z starts at 1 and steps by z' = z·(a + γ) / (b + γ),
+//! ending on 1 again. γ has to be drawn after the lists are fixed: one the
+//! prover picks lets it match lists that differ, see the shuffle circuit
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct GrandProductConfig {
+    /// a, b, γ and z on each row, z alone on the last
+    pub advice: [Column<Advice>; 4],
+    pub selector: Selector,
+}
+
+#[derive(Debug)]
+pub struct GrandProductChip<F: Field> {
+    config: GrandProductConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> GrandProductChip<F> {
+    pub fn construct(config: GrandProductConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// the circuit enables a constant column for the z at either end
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+    ) -> GrandProductConfig {
+        let selector = meta.selector();
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("grand product", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, b, gamma, z] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let z_next = meta.query_advice(advice[3], Rotation::next());
+            vec![s * (z_next * (b + gamma.clone()) - z * (a + gamma))]
+        });
+
+        GrandProductConfig { advice, selector }
+    }
+
+    /// copies the lists in and constrains b to be a permutation of a under γ
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        gamma: &AssignedCell<F, F>,
+        a: &[AssignedCell<F, F>],
+        b: &[AssignedCell<F, F>],
+    ) -> Result<(), FiboError> {
+        if a.len() != b.len() {
+            return Err(FiboError::LengthMismatch {
+                a: a.len(),
+                b: b.len(),
+            });
+        }
+
+        let [col_a, col_b, col_gamma, col_z] = self.config.advice;
+        let mut at = (0, "z");
+        layouter
+            .assign_region(
+                || "grand product",
+                |mut region| {
+                    at = (0, "z");
+                    let mut z = region.assign_advice_from_constant(|| "z", col_z, 0, F::ONE)?;
+                    for (row, (a, b)) in a.iter().zip(b).enumerate() {
+                        at = (row, "selector");
+                        self.config.selector.enable(&mut region, row)?;
+                        at = (row, "a");
+                        a.copy_advice(|| "a", &mut region, col_a, row)?;
+                        at = (row, "b");
+                        b.copy_advice(|| "b", &mut region, col_b, row)?;
+                        at = (row, "gamma");
+                        gamma.copy_advice(|| "gamma", &mut region, col_gamma, row)?;
+
+                        // b + γ = 0 has no inverse, and any z' holds the
+                        // gate as well as 0 then
+                        let shift =
+                            |cell: &AssignedCell<F, F>| cell.value().copied() + gamma.value();
+                        let inv = shift(b).map(|d| Option::from(d.invert()).unwrap_or(F::ZERO));
+                        let next = z.value().copied() * shift(a) * inv;
+                        at = (row + 1, "z");
+                        z = region.assign_advice(|| "z", col_z, row + 1, || next)?;
+                    }
+                    at = (a.len(), "z");
+                    region.constrain_constant(z.cell(), F::ONE)
+                },
+            )
+            .map_err(|e| FiboError::assign("grand product", at, e))
+    }
+}
+
+impl<F: Field> Chip<F> for GrandProductChip<F> {
+    type Config = GrandProductConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
@@ -0,0 +1,260 @@
+//! is_zero(value) = 1 - value · value_inv, with value · (1 - value · value_inv) = 0
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct IsZeroConfig<F> {
+    pub value_inv: Column<Advice>,
+    /// 1 when the value is zero, 0 otherwise, for use in other gates
+    pub is_zero_expr: Expression<F>,
+}
+
+impl<F: Field> IsZeroConfig<F> {
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero_expr.clone()
+    }
+}
+
+pub struct IsZeroChip<F: Field> {
+    config: IsZeroConfig<F>,
+}
+
+impl<F: Field> IsZeroChip<F> {
+    pub fn construct(config: IsZeroConfig<F>) -> Self {
+        Self { config }
+    }
+
+    /// `q_enable` and `value` are queried in the gate "is zero", the inverse
+    /// being witnessed in `value_inv` on the same row
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+    ) -> IsZeroConfig<F> {
+        let mut is_zero_expr = Expression::Constant(F::ZERO);
+
+        meta.create_gate("is zero", |meta| {
+            let value = value(meta);
+            let q_enable = q_enable(meta);
+            let value_inv = meta.query_advice(value_inv, Rotation::cur());
+
+            is_zero_expr = Expression::Constant(F::ONE) - value.clone() * value_inv;
+            // either the value is zero, or value_inv is its inverse
+            vec![q_enable * value * is_zero_expr.clone()]
+        });
+
+        IsZeroConfig {
+            value_inv,
+            is_zero_expr,
+        }
+    }
+
+    /// witnesses the inverse of `value`, or 0 if there is none
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let value_inv = value.map(|value| value.invert().unwrap_or(F::ZERO));
+        region.assign_advice(|| "value inv", self.config.value_inv, offset, || value_inv)
+    }
+}
+
+impl<F: Field> Chip<F> for IsZeroChip<F> {
+    type Config = IsZeroConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EqualConfig<F> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub out: Column<Advice>,
+    pub selector: Selector,
+    pub is_zero: IsZeroConfig<F>,
+    pub instance: Column<Instance>,
+}
+
+/// exposes 1 if the private a and b are equal, 0 otherwise
+#[derive(Debug, Default)]
+pub struct EqualCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
+
+impl<F: Field> EqualCircuit<F> {
+    pub fn new(a: F, b: F) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for EqualCircuit<F> {
+    type Config = EqualConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let out = meta.advice_column();
+        let value_inv = meta.advice_column();
+        let selector = meta.selector();
+        let instance = meta.instance_column();
+
+        meta.enable_equality(out);
+        meta.enable_equality(instance);
+
+        let is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(selector),
+            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
+            value_inv,
+        );
+
+        meta.create_gate("equal", |meta| {
+            let s = meta.query_selector(selector);
+            let out = meta.query_advice(out, Rotation::cur());
+            vec![s * (out - is_zero.expr())]
+        });
+
+        EqualConfig {
+            a,
+            b,
+            out,
+            selector,
+            is_zero,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = IsZeroChip::construct(config.is_zero.clone());
+
+        let mut at = (0, "selector");
+        let out = layouter
+            .assign_region(
+                || "equal",
+                |mut region| {
+                    at = (0, "selector");
+                    config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    region.assign_advice(|| "a", config.a, 0, || self.a)?;
+                    at = (0, "b");
+                    region.assign_advice(|| "b", config.b, 0, || self.b)?;
+
+                    at = (0, "value inv");
+                    chip.assign(&mut region, 0, self.a - self.b)?;
+
+                    at = (0, "out");
+                    let out = (self.a - self.b).map(|diff| {
+                        if diff.is_zero_vartime() {
+                            F::ONE
+                        } else {
+                            F::ZERO
+                        }
+                    });
+                    region.assign_advice(|| "out", config.out, 0, || out)
+                },
+            )
+            .map_err(|e| FiboError::assign("equal", at, e))?;
+
+        layouter
+            .constrain_instance(out.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn equal() {
+        let circuit = EqualCircuit::new(Fp::from(7), Fp::from(7));
+        mock_ok!(circuit, 4, vec![vec![Fp::from(1)]]);
+        mock_fails!(circuit, 4, vec![vec![Fp::from(0)]]);
+    }
+
+    #[test]
+    fn not_equal() {
+        let circuit = EqualCircuit::new(Fp::from(7), Fp::from(9));
+        mock_ok!(circuit, 4, vec![vec![Fp::from(0)]]);
+        mock_fails!(circuit, 4, vec![vec![Fp::from(1)]]);
+    }
+
+    /// claims a != b are equal, witnessing 0 for the inverse of a - b
+    struct BogusInverse;
+
+    impl Circuit<Fp> for BogusInverse {
+        type Config = EqualConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            EqualCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let out = layouter.assign_region(
+                || "equal",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || Value::known(Fp::from(7)))?;
+                    region.assign_advice(|| "b", config.b, 0, || Value::known(Fp::from(9)))?;
+                    let inv = config.is_zero.value_inv;
+                    region.assign_advice(|| "value inv", inv, 0, || Value::known(Fp::zero()))?;
+                    region.assign_advice(|| "out", config.out, 0, || Value::known(Fp::one()))
+                },
+            )?;
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn bogus_inverse() {
+        // the prover too, for the explanation
+        let prover = MockProver::run(4, &BogusInverse, vec![vec![Fp::one()]]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert!(failures.iter().any(|failure| matches!(
+            failure,
+            halo2_proofs::dev::VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                if constraint.to_string().contains("is zero")
+        )));
+
+        let text = crate::failures::explain_failures(&prover, &failures);
+        assert!(text.contains("gate \"is zero\" in region \"equal\" #0, from row 0\n"));
+        assert!(text.contains("likely cause: the witness does not satisfy the gate"));
+    }
+}
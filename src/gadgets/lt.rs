@@ -0,0 +1,241 @@
+//! a < b for N bit values: a - b + 2^N has its bit N set iff a >= b
+//!
+//! the chip splits a - b + 2^N into top·2^N + low with low decomposed into N
+//! bits, and range checks a and b to N bits first, as the trick needs them in range
+
+use std::marker::PhantomData;
+
+use super::decompose::{bit, DecomposeChip, DecomposeConfig};
+use crate::error::FiboError;
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct LtConfig {
+    /// a, b, low, top and lt
+    pub advice: [Column<Advice>; 5],
+    pub selector: Selector,
+    pub decompose: DecomposeConfig,
+}
+
+pub struct LtChip<F: PrimeField, const N: usize> {
+    config: LtConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField, const N: usize> LtChip<F, N> {
+    pub fn construct(config: LtConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 5]) -> LtConfig {
+        let selector = meta.selector();
+        let decompose = DecomposeChip::<F, N>::configure(meta);
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("lt", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, b, low, top, lt] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let one = Expression::Constant(F::ONE);
+            let two_n = Expression::Constant(F::from(2).pow_vartime([N as u64]));
+            vec![
+                s.clone() * (a - b + two_n.clone() - top.clone() * two_n - low),
+                s.clone() * top.clone() * (one.clone() - top.clone()),
+                s * (one - top - lt),
+            ]
+        });
+
+        LtConfig {
+            advice,
+            selector,
+            decompose,
+        }
+    }
+
+    /// a cell holding 1 if a < b, 0 otherwise. a and b are range checked to N bits
+    pub fn lt(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        self.lt_with(layouter, a, b, |top| top)
+    }
+
+    /// as lt, the top bit witnessed by `top_of(top)`
+    pub(super) fn lt_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        top_of: impl FnOnce(Value<F>) -> Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let decompose = DecomposeChip::<F, N>::construct(self.config.decompose.clone());
+        decompose.assign(layouter.namespace(|| "range check a"), a)?;
+        decompose.assign(layouter.namespace(|| "range check b"), b)?;
+
+        let two_n = F::from(2).pow_vartime([N as u64]);
+        let diff = a.value().copied() - b.value() + Value::known(two_n);
+        let top = top_of(diff.map(|diff| if bit(&diff, N) { F::ONE } else { F::ZERO }));
+        let low = diff - top * Value::known(two_n);
+
+        let [col_a, col_b, col_low, col_top, col_lt] = self.config.advice;
+        let mut at = (0, "selector");
+        let (low, lt) = layouter
+            .assign_region(
+                || "lt",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    at = (0, "b");
+                    b.copy_advice(|| "b", &mut region, col_b, 0)?;
+
+                    at = (0, "low");
+                    let low = region.assign_advice(|| "low", col_low, 0, || low)?;
+                    at = (0, "top");
+                    region.assign_advice(|| "top", col_top, 0, || top)?;
+                    at = (0, "lt");
+                    let lt = Value::known(F::ONE) - top;
+                    let lt = region.assign_advice(|| "lt", col_lt, 0, || lt)?;
+                    Ok((low, lt))
+                },
+            )
+            .map_err(|e| FiboError::assign("lt", at, e))?;
+
+        decompose.assign(layouter.namespace(|| "range check low"), &low)?;
+        Ok(lt)
+    }
+}
+
+impl<F: PrimeField, const N: usize> Chip<F> for LtChip<F, N> {
+    type Config = LtConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LtCircuitConfig {
+    pub lt: LtConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes 1 if a < b for the private N bit values a and b, 0 otherwise
+#[derive(Debug, Default)]
+pub struct LtCircuit<F, const N: usize> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
+
+impl<F: PrimeField, const N: usize> LtCircuit<F, N> {
+    pub fn new(a: F, b: F) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+        }
+    }
+}
+
+impl<F: PrimeField, const N: usize> Circuit<F> for LtCircuit<F, N> {
+    type Config = LtCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        LtCircuitConfig {
+            lt: LtChip::<F, N>::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let [col_a, col_b, ..] = config.lt.advice;
+        let (a, b) = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", col_a, 0, || self.a)?;
+                    let b = region.assign_advice(|| "b", col_b, 0, || self.b)?;
+                    Ok((a, b))
+                },
+            )
+            .map_err(|e| FiboError::assign("load", (0, "a"), e))?;
+
+        let chip = LtChip::<F, N>::construct(config.lt);
+        let lt = chip.lt(layouter.namespace(|| "a < b"), &a, &b)?;
+        layouter
+            .constrain_instance(lt.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    fn verifies(a: Fp, b: Fp, lt: u64) {
+        mock_ok!(LtCircuit::<Fp, 8>::new(a, b), 6, vec![vec![Fp::from(lt)]]);
+    }
+
+    fn fails(a: Fp, b: Fp, lt: u64) {
+        mock_fails!(LtCircuit::<Fp, 8>::new(a, b), 6, vec![vec![Fp::from(lt)]]);
+    }
+
+    #[test]
+    fn compares() {
+        for (a, b) in [(3, 5), (0, 255), (254, 255)] {
+            let (a, b) = (Fp::from(a), Fp::from(b));
+            verifies(a, b, 1);
+            fails(a, b, 0);
+            // and the other way round
+            verifies(b, a, 0);
+            fails(b, a, 1);
+        }
+    }
+
+    #[test]
+    fn equal() {
+        for a in [0, 5, 255] {
+            verifies(Fp::from(a), Fp::from(a), 0);
+            fails(Fp::from(a), Fp::from(a), 1);
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        // 256 - 5 + 256 still splits into top and low, but 256 is no 8 bit value
+        fails(Fp::from(256), Fp::from(5), 0);
+        fails(Fp::from(5), Fp::from(300), 1);
+        // -1 - 5 + 256 = 250 would claim -1 < 5
+        fails(-Fp::one(), Fp::from(5), 1);
+    }
+}
@@ -0,0 +1,312 @@
+//! the mimc keyed permutation with x^5 rounds, x -> (x + key + c_i)^5, then
+//! x + key once more. x^5 permutes the pasta fields, 5 not dividing p - 1
+//!
+//! the round constants stand in for the hash derived ones of the paper
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// ceil(log_5 p), enough rounds for the degree to outgrow the field
+pub const ROUNDS: usize = 110;
+
+/// c_0 = 0 and c_i = i^3 + 42
+pub fn round_constants<F: PrimeField>() -> Vec<F> {
+    (0..ROUNDS)
+        .map(|i| match i {
+            0 => F::ZERO,
+            i => F::from(i as u64).pow_vartime([3]) + F::from(42),
+        })
+        .collect()
+}
+
+/// the digest of x under key, computed natively
+pub fn mimc<F: PrimeField>(x: F, key: F) -> F {
+    round_constants::<F>()
+        .into_iter()
+        .fold(x, |x, c| (x + key + c).pow_vartime([5]))
+        + key
+}
+
+#[derive(Debug, Clone)]
+pub struct MimcConfig {
+    pub state: Column<Advice>,
+    pub key: Column<Advice>,
+    pub round_constant: Column<Fixed>,
+    pub s_round: Selector,
+    pub s_out: Selector,
+}
+
+pub struct MimcChip<F: PrimeField> {
+    config: MimcConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> MimcChip<F> {
+    pub fn construct(config: MimcConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        [state, key]: [Column<Advice>; 2],
+    ) -> MimcConfig {
+        let round_constant = meta.fixed_column();
+        let s_round = meta.selector();
+        let s_out = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(state);
+        meta.enable_equality(key);
+
+        meta.create_gate("mimc round", |meta| {
+            let s = meta.query_selector(s_round);
+            let x = meta.query_advice(state, Rotation::cur());
+            let key = meta.query_advice(key, Rotation::cur());
+            let c = meta.query_fixed(round_constant);
+            let x_next = meta.query_advice(state, Rotation::next());
+
+            let t = x + key + c;
+            let t2 = t.clone() * t.clone();
+            vec![s * (t2.clone() * t2 * t - x_next)]
+        });
+        meta.create_gate("mimc out", |meta| {
+            let s = meta.query_selector(s_out);
+            let x = meta.query_advice(state, Rotation::cur());
+            let key = meta.query_advice(key, Rotation::cur());
+            let out = meta.query_advice(state, Rotation::next());
+            vec![s * (x + key - out)]
+        });
+
+        MimcConfig {
+            state,
+            key,
+            round_constant,
+            s_round,
+            s_out,
+        }
+    }
+
+    /// | row | state | key | round_constant | s_round | s_out |
+    /// |  0  |   x   | key |      c_0       |    1    |   0   |
+    /// |  1  |  x_1  | key |      c_1       |    1    |   0   |
+    /// | ... |       |     |                |         |       |
+    /// | 110 | x_110 | key |                |    0    |   1   |
+    /// | 111 |  out  |     |                |    0    |   0   |
+    ///
+    /// x and key copied in, and every row's key from the first. returns out
+    pub fn hash(
+        &self,
+        layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        key: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        self.hash_with(layouter, x, key, &round_constants())
+    }
+
+    /// as hash, the fixed column holding `constants` while the rounds
+    /// themselves follow round_constants
+    fn hash_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        key: &AssignedCell<F, F>,
+        constants: &[F],
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let MimcConfig {
+            state,
+            key: col_key,
+            round_constant,
+            s_round,
+            s_out,
+        } = self.config;
+        let rounds = round_constants::<F>();
+
+        let mut at = (0, "x");
+        layouter
+            .assign_region(
+                || "mimc",
+                |mut region| {
+                    at = (0, "x");
+                    let mut x_cell = x.copy_advice(|| "x", &mut region, state, 0)?;
+                    at = (0, "key");
+                    let first_key = key.copy_advice(|| "key", &mut region, col_key, 0)?;
+                    let mut key_cell = first_key.clone();
+
+                    for (row, (c, fixed)) in rounds.iter().zip(constants).enumerate() {
+                        at = (row, "selector");
+                        s_round.enable(&mut region, row)?;
+                        at = (row, "round_constant");
+                        region.assign_fixed(
+                            || "round constant",
+                            round_constant,
+                            row,
+                            || Value::known(*fixed),
+                        )?;
+
+                        at = (row + 1, "state");
+                        let x_next = (x_cell.value().copied() + key.value() + Value::known(*c))
+                            .map(|t| t.pow_vartime([5]));
+                        x_cell = region.assign_advice(|| "state", state, row + 1, || x_next)?;
+                        at = (row + 1, "key");
+                        key_cell =
+                            first_key.copy_advice(|| "key", &mut region, col_key, row + 1)?;
+                    }
+
+                    at = (ROUNDS, "selector");
+                    s_out.enable(&mut region, ROUNDS)?;
+                    at = (ROUNDS + 1, "state");
+                    let out = x_cell.value().copied() + key_cell.value();
+                    region.assign_advice(|| "out", state, ROUNDS + 1, || out)
+                },
+            )
+            .map_err(|e| FiboError::assign("mimc", at, e))
+    }
+}
+
+impl<F: PrimeField> Chip<F> for MimcChip<F> {
+    type Config = MimcConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PreimageConfig {
+    pub mimc: MimcConfig,
+    pub instance: Column<Instance>,
+}
+
+/// proves knowledge of an x and key hashing to the digest in the instance column
+#[derive(Debug, Default)]
+pub struct PreimageCircuit<F> {
+    pub x: Value<F>,
+    pub key: Value<F>,
+}
+
+impl<F: PrimeField> PreimageCircuit<F> {
+    pub fn new(x: F, key: F) -> Self {
+        Self {
+            x: Value::known(x),
+            key: Value::known(key),
+        }
+    }
+
+    /// the digest of x and key, with the fixed column holding `constants`
+    fn synthesize_with(
+        &self,
+        config: PreimageConfig,
+        mut layouter: impl Layouter<F>,
+        constants: &[F],
+    ) -> Result<(), FiboError> {
+        let MimcConfig { state, key, .. } = config.mimc;
+        let (x, key) = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    let x = region.assign_advice(|| "x", state, 0, || self.x)?;
+                    let key = region.assign_advice(|| "key", key, 0, || self.key)?;
+                    Ok((x, key))
+                },
+            )
+            .map_err(|e| FiboError::assign("load", (0, "x"), e))?;
+
+        let chip = MimcChip::construct(config.mimc);
+        let digest = chip.hash_with(layouter.namespace(|| "mimc"), &x, &key, constants)?;
+        layouter
+            .constrain_instance(digest.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for PreimageCircuit<F> {
+    type Config = PreimageConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 2].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        PreimageConfig {
+            mimc: MimcChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, &round_constants())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    /// the 112 rows of the hash and the load row fit in 2^7
+    const K: u32 = 7;
+
+    #[test]
+    fn digest() {
+        let (x, key) = (Fp::from(7), Fp::from(11));
+        let digest = mimc(x, key);
+        assert_ne!(digest, mimc(x + Fp::one(), key));
+        assert_ne!(digest, mimc(x, key + Fp::one()));
+
+        let circuit = PreimageCircuit::new(x, key);
+        mock_ok!(circuit, K, vec![vec![digest]]);
+        mock_fails!(circuit, K, vec![vec![digest + Fp::one()]]);
+        mock_fails!(circuit, K, vec![vec![mimc(x, Fp::zero())]]);
+    }
+
+    /// the preimage circuit with the round constants in the fixed column reversed
+    struct Reversed(PreimageCircuit<Fp>);
+
+    impl Circuit<Fp> for Reversed {
+        type Config = PreimageConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(PreimageCircuit::default())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            PreimageCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let mut constants = round_constants();
+            constants.reverse();
+            Ok(self.0.synthesize_with(config, layouter, &constants)?)
+        }
+    }
+
+    #[test]
+    fn reordered_constants() {
+        let (x, key) = (Fp::from(7), Fp::from(11));
+        mock_fails!(
+            Reversed(PreimageCircuit::new(x, key)),
+            K,
+            vec![vec![mimc(x, key)]]
+        );
+    }
+}
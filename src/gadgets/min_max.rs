@@ -0,0 +1,277 @@
+//! min and max of two N bit values: b < a swaps (a, b) into (min, max)
+
+use std::marker::PhantomData;
+
+use super::{
+    cond_swap::{CondSwapChip, CondSwapConfig},
+    lt::{LtChip, LtConfig},
+};
+use crate::error::FiboError;
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*};
+
+/// the two cells out of a comparison: (min, max)
+type MinMax<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
+
+#[derive(Debug, Clone)]
+pub struct MinMaxConfig {
+    pub lt: LtConfig,
+    pub swap: CondSwapConfig,
+}
+
+pub struct MinMaxChip<F: PrimeField, const N: usize> {
+    config: MinMaxConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField, const N: usize> MinMaxChip<F, N> {
+    pub fn construct(config: MinMaxConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// the comparison and the swap share the advice columns
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 5]) -> MinMaxConfig {
+        MinMaxConfig {
+            lt: LtChip::<F, N>::configure(meta, advice),
+            swap: CondSwapChip::configure(meta, advice),
+        }
+    }
+
+    /// (min, max) of a and b, each equal to one of them
+    pub fn min_max(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<MinMax<F>, FiboError> {
+        self.min_max_with(layouter, a, b, false)
+    }
+
+    /// as min_max, with the comparison claiming the opposite if `lie`
+    fn min_max_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        lie: bool,
+    ) -> Result<MinMax<F>, FiboError> {
+        let lt = LtChip::<F, N>::construct(self.config.lt.clone());
+        let b_lt_a = lt.lt_with(layouter.namespace(|| "b < a"), b, a, |top| {
+            if lie {
+                Value::known(F::ONE) - top
+            } else {
+                top
+            }
+        })?;
+
+        let swap = CondSwapChip::construct(self.config.swap.clone());
+        swap.assign(layouter.namespace(|| "swap"), a, b, &b_lt_a)
+    }
+
+    pub fn min(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        Ok(self.min_max(layouter, a, b)?.0)
+    }
+
+    pub fn max(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        Ok(self.min_max(layouter, a, b)?.1)
+    }
+}
+
+impl<F: PrimeField, const N: usize> Chip<F> for MinMaxChip<F, N> {
+    type Config = MinMaxConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Max3Config {
+    pub min_max: MinMaxConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes the largest of three private N bit values
+#[derive(Debug, Default)]
+pub struct Max3Circuit<F, const N: usize> {
+    pub values: [Value<F>; 3],
+}
+
+impl<F: PrimeField, const N: usize> Max3Circuit<F, N> {
+    pub fn new(values: [F; 3]) -> Self {
+        Self {
+            values: values.map(Value::known),
+        }
+    }
+
+    /// the max of the values, the last comparison lying if `lie`
+    fn synthesize_with(
+        &self,
+        config: Max3Config,
+        mut layouter: impl Layouter<F>,
+        lie: bool,
+    ) -> Result<(), FiboError> {
+        let advice = config.min_max.lt.advice;
+        let [a, b, c] = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    let [a, b, c] = self.values;
+                    Ok([
+                        region.assign_advice(|| "a", advice[0], 0, || a)?,
+                        region.assign_advice(|| "b", advice[1], 0, || b)?,
+                        region.assign_advice(|| "c", advice[2], 0, || c)?,
+                    ])
+                },
+            )
+            .map_err(|e| FiboError::assign("load", (0, "value"), e))?;
+
+        let chip = MinMaxChip::<F, N>::construct(config.min_max);
+        let max_ab = chip.max(layouter.namespace(|| "max a b"), &a, &b)?;
+        let (_, max) = chip.min_max_with(layouter.namespace(|| "max ab c"), &max_ab, &c, lie)?;
+        layouter
+            .constrain_instance(max.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))
+    }
+}
+
+impl<F: PrimeField, const N: usize> Circuit<F> for Max3Circuit<F, N> {
+    type Config = Max3Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        Max3Config {
+            min_max: MinMaxChip::<F, N>::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, false)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    fn max3(values: [u64; 3]) -> Max3Circuit<Fp, 8> {
+        Max3Circuit::new(values.map(Fp::from))
+    }
+
+    #[test]
+    fn distinct() {
+        for values in [[3, 9, 5], [9, 5, 3], [3, 5, 9], [5, 3, 9]] {
+            mock_ok!(max3(values), 7, vec![vec![Fp::from(9)]]);
+            mock_fails!(max3(values), 7, vec![vec![Fp::from(5)]]);
+        }
+    }
+
+    #[test]
+    fn ties() {
+        mock_ok!(max3([7, 7, 7]), 7, vec![vec![Fp::from(7)]]);
+        mock_ok!(max3([7, 7, 2]), 7, vec![vec![Fp::from(7)]]);
+        mock_ok!(max3([2, 7, 7]), 7, vec![vec![Fp::from(7)]]);
+        mock_fails!(max3([2, 7, 7]), 7, vec![vec![Fp::from(2)]]);
+    }
+
+    /// the max of three with the last comparison lying
+    struct Lying(Max3Circuit<Fp, 8>);
+
+    impl Circuit<Fp> for Lying {
+        type Config = Max3Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            Max3Circuit::<Fp, 8>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            Ok(self.0.synthesize_with(config, layouter, true)?)
+        }
+    }
+
+    #[test]
+    fn middle_as_max() {
+        // max(max(3, 9), 5) with 9 < 5 claimed makes 5 the max
+        mock_fails!(Lying(max3([3, 9, 5])), 7, vec![vec![Fp::from(5)]]);
+    }
+
+    #[test]
+    fn min() {
+        #[derive(Default)]
+        struct Min(Max3Circuit<Fp, 8>);
+
+        impl Circuit<Fp> for Min {
+            type Config = Max3Config;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                Self::default()
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                Max3Circuit::<Fp, 8>::configure(meta)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let [col_a, col_b, ..] = config.min_max.lt.advice;
+                let [a, b, _] = self.0.values;
+                let (a, b) = layouter.assign_region(
+                    || "load",
+                    |mut region| {
+                        let a = region.assign_advice(|| "a", col_a, 0, || a)?;
+                        let b = region.assign_advice(|| "b", col_b, 0, || b)?;
+                        Ok((a, b))
+                    },
+                )?;
+                let chip = MinMaxChip::<Fp, 8>::construct(config.min_max);
+                let min = chip.min(layouter.namespace(|| "min"), &a, &b)?;
+                layouter.constrain_instance(min.cell(), config.instance, 0)
+            }
+        }
+
+        mock_ok!(Min(max3([3, 9, 0])), 7, vec![vec![Fp::from(3)]]);
+        mock_ok!(Min(max3([9, 3, 0])), 7, vec![vec![Fp::from(3)]]);
+        mock_ok!(Min(max3([4, 4, 0])), 7, vec![vec![Fp::from(4)]]);
+        mock_fails!(Min(max3([9, 3, 0])), 7, vec![vec![Fp::from(9)]]);
+    }
+}
@@ -0,0 +1,23 @@
+//! small chips meant to be configured into other circuits
+
+pub mod accumulator;
+pub mod arith;
+pub mod boolean;
+pub mod byte_decompose;
+pub mod cond_swap;
+pub mod decompose;
+pub mod div_mod;
+pub mod fixed_point;
+pub mod grand_product;
+pub mod is_zero;
+pub mod lt;
+pub mod mimc;
+pub mod min_max;
+pub mod mux;
+pub mod range_check;
+pub mod range_lookup;
+pub mod relu;
+pub mod sbox;
+pub mod standard_plonk;
+pub mod u32_add;
+pub mod xor;
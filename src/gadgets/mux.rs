@@ -0,0 +1,263 @@
+//! out = b if sel else a, for a boolean sel: out = sel·b + (1 - sel)·a
+//!
+//! unlike the conditional swap only the selected value comes out, as one cell
+//! to chain into whatever follows
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct MuxConfig {
+    /// sel, a, b and out on one row
+    pub advice: [Column<Advice>; 4],
+    pub selector: Selector,
+}
+
+#[derive(Debug)]
+pub struct MuxChip<F: Field> {
+    config: MuxConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> MuxChip<F> {
+    pub fn construct(config: MuxConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 4]) -> MuxConfig {
+        let selector = meta.selector();
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("mux", |meta| {
+            let s = meta.query_selector(selector);
+            let [sel, a, b, out] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let not_sel = Expression::Constant(F::ONE) - sel.clone();
+            vec![
+                s.clone() * (sel.clone() * b + not_sel.clone() * a - out),
+                s * sel * not_sel,
+            ]
+        });
+
+        MuxConfig { advice, selector }
+    }
+
+    /// copies sel, a and b onto a row and returns b if sel else a
+    pub fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        sel: &AssignedCell<F, F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let [col_sel, col_a, col_b, col_out] = self.config.advice;
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "mux",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "sel");
+                    sel.copy_advice(|| "sel", &mut region, col_sel, 0)?;
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    at = (0, "b");
+                    b.copy_advice(|| "b", &mut region, col_b, 0)?;
+
+                    // out = a + sel·(b - a)
+                    at = (0, "out");
+                    let out = a.value().copied()
+                        + sel.value().copied() * (b.value().copied() - a.value());
+                    region.assign_advice(|| "out", col_out, 0, || out)
+                },
+            )
+            .map_err(|e| FiboError::assign("mux", at, e))
+    }
+
+    /// values[2·hi + lo] for the bits lo and hi, by three 2-to-1 selections
+    /// on the same columns
+    pub fn select4(
+        &self,
+        mut layouter: impl Layouter<F>,
+        [lo, hi]: [&AssignedCell<F, F>; 2],
+        [a, b, c, d]: [&AssignedCell<F, F>; 4],
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let low = self.select(layouter.namespace(|| "a or b"), lo, a, b)?;
+        let high = self.select(layouter.namespace(|| "c or d"), lo, c, d)?;
+        self.select(layouter.namespace(|| "low or high"), hi, &low, &high)
+    }
+}
+
+impl<F: Field> Chip<F> for MuxChip<F> {
+    type Config = MuxConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gadgets::standard_plonk::{StandardPlonkChip, StandardPlonkConfig},
+        mock_fails, mock_ok,
+    };
+    use halo2_proofs::{dev::VerifyFailure, pasta::Fp};
+
+    #[derive(Debug, Clone)]
+    struct SelectConfig {
+        mux: MuxConfig,
+        plonk: StandardPlonkConfig,
+        instance: Column<Instance>,
+    }
+
+    /// selects among 2 or 4 private values by the private selector bits,
+    /// exposing the selection, or the fibonacci step after it if `step`
+    struct SelectCircuit {
+        sels: Vec<u64>,
+        values: Vec<u64>,
+        step: bool,
+    }
+
+    impl Circuit<Fp> for SelectCircuit {
+        type Config = SelectConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                sels: self.sels.clone(),
+                values: self.values.clone(),
+                step: self.step,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            let plonk = StandardPlonkChip::configure(meta, instance);
+            let [a, b, c] = plonk.advice;
+            let out = meta.advice_column();
+            SelectConfig {
+                mux: MuxChip::configure(meta, [a, b, c, out]),
+                plonk,
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let column = config.mux.advice[1];
+            let (sels, values) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let mut row = 0;
+                    let mut load = |values: &[u64]| {
+                        values
+                            .iter()
+                            .map(|&v| {
+                                row += 1;
+                                let v = Value::known(Fp::from(v));
+                                region.assign_advice(|| "value", column, row - 1, || v)
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    };
+                    Ok((load(&self.sels)?, load(&self.values)?))
+                },
+            )?;
+
+            let chip = MuxChip::construct(config.mux);
+            let out = match values.as_slice() {
+                [a, b] => chip.select(layouter.namespace(|| "select"), &sels[0], a, b)?,
+                [a, b, c, d] => chip.select4(
+                    layouter.namespace(|| "select4"),
+                    [&sels[0], &sels[1]],
+                    [a, b, c, d],
+                )?,
+                _ => unreachable!(),
+            };
+
+            let plonk = StandardPlonkChip::construct(config.plonk);
+            let out = if self.step {
+                // the selection chained into the step after the last value
+                plonk.add(layouter.namespace(|| "step"), values.last().unwrap(), &out)?
+            } else {
+                out
+            };
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    fn circuit(sels: &[u64], values: &[u64], step: bool) -> SelectCircuit {
+        SelectCircuit {
+            sels: sels.to_vec(),
+            values: values.to_vec(),
+            step,
+        }
+    }
+
+    fn verifies(sels: &[u64], values: &[u64], out: u64) {
+        mock_ok!(circuit(sels, values, false), 4, vec![vec![Fp::from(out)]]);
+    }
+
+    fn fails(sels: &[u64], values: &[u64], out: u64) -> Vec<VerifyFailure> {
+        mock_fails!(circuit(sels, values, false), 4, vec![vec![Fp::from(out)]])
+    }
+
+    #[test]
+    fn two_to_one() {
+        verifies(&[0], &[3, 8], 3);
+        verifies(&[1], &[3, 8], 8);
+        fails(&[0], &[3, 8], 8);
+        fails(&[1], &[3, 8], 3);
+    }
+
+    #[test]
+    fn four_to_one() {
+        let values = [10, 20, 30, 40];
+        for i in 0..4 {
+            let sels = [i & 1, i >> 1];
+            verifies(&sels, &values, values[i as usize]);
+            let other = values[(i as usize + 1) % 4];
+            fails(&sels, &values, other);
+        }
+    }
+
+    #[test]
+    fn non_boolean_selector() {
+        // sel = 2 picks 2·8 - 3 = 13, which holds the first constraint alone
+        let failures = fails(&[2], &[3, 8], 13);
+        assert_eq!(failures.len(), 1, "{failures:?}");
+        assert!(matches!(
+            &failures[0],
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                if constraint.to_string().contains("mux")
+        ));
+    }
+
+    #[test]
+    fn chained_into_fibonacci() {
+        // select(1, 3, 8) = 8, then 8 + 8
+        mock_ok!(circuit(&[1], &[3, 8], true), 4, vec![vec![Fp::from(16)]]);
+        // select(0, 3, 8) = 3, then 8 + 3
+        mock_ok!(circuit(&[0], &[3, 8], true), 4, vec![vec![Fp::from(11)]]);
+        mock_fails!(circuit(&[0], &[3, 8], true), 4, vec![vec![Fp::from(16)]]);
+    }
+}
@@ -0,0 +1,170 @@
+//! value in 0..RANGE as the root of value·(value - 1)·...·(value - (RANGE - 1)),
+//! a gate of degree RANGE + 1 so only fit for small ranges
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct RangeCheckConfig {
+    pub value: Column<Advice>,
+    pub selector: Selector,
+}
+
+pub struct RangeCheckChip<F: Field, const RANGE: usize> {
+    config: RangeCheckConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const RANGE: usize> RangeCheckChip<F, RANGE> {
+    pub fn construct(config: RangeCheckConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> RangeCheckConfig {
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(value);
+
+        meta.create_gate("range check", |meta| {
+            let s = meta.query_selector(selector);
+            let value = meta.query_advice(value, Rotation::cur());
+
+            let mut i = F::ZERO;
+            let mut product = Expression::Constant(F::ONE);
+            for _ in 0..RANGE {
+                product = product * (value.clone() - Expression::Constant(i));
+                i += F::ONE;
+            }
+            vec![s * product]
+        });
+
+        RangeCheckConfig { value, selector }
+    }
+
+    /// assigns the value, constrained to be below RANGE
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "range check",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+                    at = (0, "value");
+                    region.assign_advice(|| "value", self.config.value, 0, || value)
+                },
+            )
+            .map_err(|e| FiboError::assign("range check", at, e))
+    }
+}
+
+impl<F: Field, const RANGE: usize> Chip<F> for RangeCheckChip<F, RANGE> {
+    type Config = RangeCheckConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves the private value is in 0..RANGE
+#[derive(Debug, Default)]
+pub struct RangeCircuit<F, const RANGE: usize> {
+    pub value: Value<F>,
+}
+
+/// a dice roll, 0 to 5
+pub type DiceCircuit<F> = RangeCircuit<F, 6>;
+
+impl<F: Field, const RANGE: usize> RangeCircuit<F, RANGE> {
+    pub fn new(value: F) -> Self {
+        Self {
+            value: Value::known(value),
+        }
+    }
+}
+
+impl<F: Field, const RANGE: usize> Circuit<F> for RangeCircuit<F, RANGE> {
+    type Config = RangeCheckConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckChip::<F, RANGE>::configure(meta, value)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = RangeCheckChip::<F, RANGE>::construct(config);
+        chip.assign(layouter.namespace(|| "value"), self.value)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    fn verifies<const RANGE: usize>(value: u64) {
+        mock_ok!(RangeCircuit::<Fp, RANGE>::new(Fp::from(value)), 4, vec![]);
+    }
+
+    fn fails<const RANGE: usize>(value: u64) {
+        mock_fails!(RangeCircuit::<Fp, RANGE>::new(Fp::from(value)), 4, vec![]);
+    }
+
+    #[test]
+    fn dice() {
+        for roll in 0..6 {
+            mock_ok!(DiceCircuit::new(Fp::from(roll)), 4, vec![]);
+        }
+        fails::<6>(6);
+        fails::<6>(100);
+        // -1 is no roll either
+        mock_fails!(DiceCircuit::new(-Fp::one()), 4, vec![]);
+    }
+
+    #[test]
+    fn bound() {
+        verifies::<3>(2);
+        fails::<3>(3);
+        verifies::<8>(7);
+        fails::<8>(8);
+    }
+
+    #[test]
+    fn degree_grows() {
+        fn degree<const RANGE: usize>() -> usize {
+            let mut meta = ConstraintSystem::<Fp>::default();
+            RangeCircuit::<Fp, RANGE>::configure(&mut meta);
+            meta.degree()
+        }
+        // the selector, times one factor per value in range
+        assert_eq!(degree::<2>(), 3);
+        assert_eq!(degree::<6>(), 7);
+        assert_eq!(degree::<16>(), 17);
+    }
+}
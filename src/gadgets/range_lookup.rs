@@ -0,0 +1,298 @@
+//! value < 2^num_bits for num_bits up to 8, by lookups into a table of 0..256
+//!
+//! a value of fewer bits is looked up a second time shifted left by 8 - num_bits,
+//! which only stays in the table if its top bits were zero
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// the bits of the largest value in the table
+pub const BITS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct RangeLookupConfig {
+    pub value: Column<Advice>,
+    /// value · shift
+    pub shifted: Column<Advice>,
+    /// 2^(BITS - num_bits)
+    pub shift: Column<Fixed>,
+    pub selector: Selector,
+    pub table: TableColumn,
+}
+
+pub struct RangeLookupChip<F: PrimeField> {
+    config: RangeLookupConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> RangeLookupChip<F> {
+    pub fn construct(config: RangeLookupConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> RangeLookupConfig {
+        let shifted = meta.advice_column();
+        let shift = meta.fixed_column();
+        let selector = meta.complex_selector();
+        let table = meta.lookup_table_column();
+
+        // enable the equality
+        meta.enable_equality(value);
+
+        meta.create_gate("shift", |meta| {
+            let s = meta.query_selector(selector);
+            let value = meta.query_advice(value, Rotation::cur());
+            let shifted = meta.query_advice(shifted, Rotation::cur());
+            let shift = meta.query_fixed(shift);
+            vec![s * (value * shift - shifted)]
+        });
+
+        // unselected rows look up 0, which is in the table
+        meta.lookup(|meta| {
+            let s = meta.query_selector(selector);
+            let value = meta.query_advice(value, Rotation::cur());
+            vec![(s * value, table)]
+        });
+        meta.lookup(|meta| {
+            let s = meta.query_selector(selector);
+            let shifted = meta.query_advice(shifted, Rotation::cur());
+            vec![(s * shifted, table)]
+        });
+
+        RangeLookupConfig {
+            value,
+            shifted,
+            shift,
+            selector,
+            table,
+        }
+    }
+
+    /// fills the table with 0..2^BITS, one value a row
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), FiboError> {
+        let mut at = (0, "table");
+        layouter
+            .assign_table(
+                || "load table",
+                |mut table| {
+                    for (offset, value) in (0..1u64 << BITS).enumerate() {
+                        at = (offset, "table");
+                        table.assign_cell(
+                            || "value",
+                            self.config.table,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| FiboError::assign("load table", at, e))
+    }
+
+    /// assigns the value, constrained to be below 2^num_bits for
+    /// 1 <= num_bits <= BITS
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        if !(1..=BITS).contains(&num_bits) {
+            return Err(FiboError::UnsupportedBits {
+                bits: num_bits,
+                max: BITS,
+            });
+        }
+        let shift = F::from(1 << (BITS - num_bits));
+
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "range lookup",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "shift");
+                    region.assign_fixed(
+                        || "shift",
+                        self.config.shift,
+                        0,
+                        || Value::known(shift),
+                    )?;
+                    at = (0, "shifted");
+                    let shifted = value * Value::known(shift);
+                    region.assign_advice(|| "shifted", self.config.shifted, 0, || shifted)?;
+
+                    at = (0, "value");
+                    region.assign_advice(|| "value", self.config.value, 0, || value)
+                },
+            )
+            .map_err(|e| FiboError::assign("range lookup", at, e))
+    }
+}
+
+impl<F: PrimeField> Chip<F> for RangeLookupChip<F> {
+    type Config = RangeLookupConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves each of the private values is below 2^num_bits
+#[derive(Debug, Default)]
+pub struct RangeLookupCircuit<F> {
+    pub values: Vec<Value<F>>,
+    pub num_bits: usize,
+}
+
+impl<F: PrimeField> RangeLookupCircuit<F> {
+    pub fn new(values: &[F], num_bits: usize) -> Self {
+        Self {
+            values: values.iter().copied().map(Value::known).collect(),
+            num_bits,
+        }
+    }
+
+    /// checks the values are bytes
+    pub fn bytes(values: &[F]) -> Self {
+        Self::new(values, BITS)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for RangeLookupCircuit<F> {
+    type Config = RangeLookupConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![Value::unknown(); self.values.len()],
+            num_bits: self.num_bits,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeLookupChip::configure(meta, value)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = RangeLookupChip::construct(config);
+        chip.load_table(layouter.namespace(|| "table"))?;
+
+        for value in &self.values {
+            chip.assign(layouter.namespace(|| "value"), *value, self.num_bits)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::min_k;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    fn values(values: &[u64]) -> Vec<Fp> {
+        values.iter().copied().map(Fp::from).collect()
+    }
+
+    #[test]
+    fn bytes() {
+        let circuit = RangeLookupCircuit::bytes(&values(&[0, 1, 128, 255]));
+        mock_ok!(circuit, 9, vec![]);
+
+        mock_fails!(RangeLookupCircuit::bytes(&values(&[0, 256, 1])), 9, vec![]);
+        mock_fails!(RangeLookupCircuit::bytes(&[-Fp::one()]), 9, vec![]);
+    }
+
+    #[test]
+    fn fewer_bits() {
+        mock_ok!(RangeLookupCircuit::new(&values(&[0, 15]), 4), 9, vec![]);
+        mock_fails!(RangeLookupCircuit::new(&values(&[16]), 4), 9, vec![]);
+        mock_ok!(RangeLookupCircuit::new(&values(&[1]), 1), 9, vec![]);
+        mock_fails!(RangeLookupCircuit::new(&values(&[2]), 1), 9, vec![]);
+    }
+
+    #[test]
+    fn unsupported_bits() {
+        for num_bits in [0, BITS + 1, 64] {
+            let circuit = RangeLookupCircuit::new(&values(&[1]), num_bits);
+            assert!(matches!(
+                MockProver::run(9, &circuit, vec![]),
+                Err(Error::Synthesis)
+            ));
+        }
+
+        let error = FiboError::UnsupportedBits { bits: 9, max: BITS };
+        assert_eq!(
+            error.to_string(),
+            "the table holds values of 1 to 8 bits, not 9"
+        );
+    }
+
+    /// the bytes circuit, with a value out of range left in an unselected row
+    struct Unselected(RangeLookupCircuit<Fp>);
+
+    impl Circuit<Fp> for Unselected {
+        type Config = RangeLookupConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            RangeLookupCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let value = config.value;
+            self.0.synthesize(config, layouter.namespace(|| "bytes"))?;
+            layouter.assign_region(
+                || "unselected",
+                |mut region| {
+                    region.assign_advice(|| "big", value, 0, || Value::known(Fp::from(1000)))
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn unselected_rows() {
+        let circuit = Unselected(RangeLookupCircuit::bytes(&values(&[7, 255])));
+        mock_ok!(circuit, 9, vec![]);
+    }
+
+    #[test]
+    fn table_fits() {
+        // the 256 table rows leave no room for the blinding rows at k = 8
+        assert_eq!(min_k::<Fp, RangeLookupCircuit<Fp>>(1 << BITS), 9);
+
+        let circuit = RangeLookupCircuit::bytes(&values(&[255]));
+        assert!(MockProver::run(8, &circuit, vec![]).is_err());
+        assert!(MockProver::run(9, &circuit, vec![]).is_ok());
+    }
+}
@@ -0,0 +1,307 @@
+//! max(x, 0) for signed BITS bit values, by a lookup into the table of every
+//! (x + 2^(BITS-1), max(x, 0))
+//!
+//! the input is offset encoded, so the table starts at the most negative value
+//! and its first row (0, 0) is what unselected rows look up. the output is a
+//! plain non negative value, fit for the unsigned fixed point chip
+
+use std::marker::PhantomData;
+
+use super::fixed_point::{FixedPointChip, FixedPointConfig};
+use crate::{error::FiboError, fibonacci::min_k};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// x + 2^(BITS-1), in 0..2^BITS for the values in range
+pub fn encode<const BITS: usize>(x: i64) -> u64 {
+    (x + (1 << (BITS - 1))) as u64
+}
+
+/// max(x, 0) of the offset encoded x
+pub fn relu<const BITS: usize>(encoded: u64) -> u64 {
+    encoded.saturating_sub(1 << (BITS - 1))
+}
+
+/// the low 64 bits of the value
+fn to_u64<F: PrimeField>(value: &F) -> u64 {
+    let repr = value.to_repr();
+    repr.as_ref()
+        .iter()
+        .take(8)
+        .rev()
+        .fold(0, |acc, byte| (acc << 8) | *byte as u64)
+}
+
+#[derive(Debug, Clone)]
+pub struct ReluConfig {
+    /// the encoded x and max(x, 0)
+    pub advice: [Column<Advice>; 2],
+    pub selector: Selector,
+    /// the encoded x and max(x, 0)
+    pub table: [TableColumn; 2],
+}
+
+#[derive(Debug)]
+pub struct ReluChip<F: PrimeField, const BITS: usize> {
+    config: ReluConfig,
+    _marker: PhantomData<F>,
+}
+
+/// the relu over the 16 bit values of i16
+pub type Relu16Chip<F> = ReluChip<F, 16>;
+
+impl<F: PrimeField, const BITS: usize> ReluChip<F, BITS> {
+    /// one row for each value
+    pub const TABLE_ROWS: usize = 1 << BITS;
+
+    pub fn construct(config: ReluConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 2]) -> ReluConfig {
+        let selector = meta.complex_selector();
+        let table = [(); 2].map(|_| meta.lookup_table_column());
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        // unselected rows look up (0, 0), the row of the most negative value
+        meta.lookup(|meta| {
+            let s = meta.query_selector(selector);
+            advice
+                .into_iter()
+                .zip(table)
+                .map(|(column, table)| {
+                    (
+                        s.clone() * meta.query_advice(column, Rotation::cur()),
+                        table,
+                    )
+                })
+                .collect()
+        });
+
+        ReluConfig {
+            advice,
+            selector,
+            table,
+        }
+    }
+
+    /// fills the table, once for all the relus of the circuit
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), FiboError> {
+        let [input, output] = self.config.table;
+        let mut at = (0, "table");
+        layouter
+            .assign_table(
+                || "relu table",
+                |mut table| {
+                    for offset in 0..Self::TABLE_ROWS {
+                        at = (offset, "table");
+                        let encoded = offset as u64;
+                        for (column, value) in [(input, encoded), (output, relu::<BITS>(encoded))] {
+                            table.assign_cell(
+                                || "relu",
+                                column,
+                                offset,
+                                || Value::known(F::from(value)),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| FiboError::assign("relu table", at, e))
+    }
+
+    /// max(x, 0) of the offset encoded x, which the table range checks
+    pub fn relu(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let [col_x, col_out] = self.config.advice;
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "relu",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "x");
+                    x.copy_advice(|| "x", &mut region, col_x, 0)?;
+
+                    at = (0, "out");
+                    let out = x.value().map(|x| F::from(relu::<BITS>(to_u64(x))));
+                    region.assign_advice(|| "out", col_out, 0, || out)
+                },
+            )
+            .map_err(|e| FiboError::assign("relu", at, e))
+    }
+}
+
+impl<F: PrimeField, const BITS: usize> Chip<F> for ReluChip<F, BITS> {
+    type Config = ReluConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// the bits of the fixed point numbers summing the activations
+const SUM_BITS: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct ReluSumConfig {
+    pub relu: ReluConfig,
+    pub fixed: FixedPointConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes Σ max(x_i, 0) of the private signed BITS bit values, offset encoded
+#[derive(Debug, Default)]
+pub struct ReluSumCircuit<F, const BITS: usize> {
+    pub values: Vec<Value<F>>,
+}
+
+impl<F: PrimeField, const BITS: usize> ReluSumCircuit<F, BITS> {
+    pub fn new(values: &[i64]) -> Self {
+        Self {
+            values: values
+                .iter()
+                .map(|&x| Value::known(F::from(encode::<BITS>(x))))
+                .collect(),
+        }
+    }
+
+    /// the smallest k fitting the table, which takes far more rows than the regions
+    pub fn min_k() -> u32 {
+        // the table is padded with one more row
+        min_k::<F, Self>(ReluChip::<F, BITS>::TABLE_ROWS + 1)
+    }
+}
+
+impl<F: PrimeField, const BITS: usize> Circuit<F> for ReluSumCircuit<F, BITS> {
+    type Config = ReluSumConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the number of values shapes the circuit
+        Self {
+            values: vec![Value::unknown(); self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        ReluSumConfig {
+            relu: ReluChip::<F, BITS>::configure(meta, [advice[0], advice[1]]),
+            fixed: FixedPointChip::<F, SUM_BITS>::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let relu = ReluChip::<F, BITS>::construct(config.relu.clone());
+        relu.load_table(layouter.namespace(|| "table"))?;
+        let fixed = FixedPointChip::<F, SUM_BITS>::construct(config.fixed);
+
+        let column = config.relu.advice[0];
+        let mut sum = None;
+        for (i, value) in self.values.iter().enumerate() {
+            let x = layouter
+                .assign_region(
+                    || "load",
+                    |mut region| region.assign_advice(|| "x", column, 0, || *value),
+                )
+                .map_err(|e| FiboError::assign("load", (0, "x"), e))?;
+            let out = relu.relu(layouter.namespace(|| format!("relu {i}")), &x)?;
+
+            sum = Some(match sum {
+                None => out,
+                Some(sum) => fixed.add(layouter.namespace(|| format!("sum {i}")), &sum, &out)?,
+            });
+        }
+
+        let sum = sum.ok_or(FiboError::TooFewRows { rows: 0, min: 1 })?;
+        layouter
+            .constrain_instance(sum.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::VerifyFailure, pasta::Fp};
+
+    fn verifies<const BITS: usize>(values: &[i64], sum: u64) {
+        mock_ok!(
+            ReluSumCircuit::<Fp, BITS>::new(values),
+            ReluSumCircuit::<Fp, BITS>::min_k(),
+            vec![vec![Fp::from(sum)]]
+        );
+    }
+
+    fn fails<const BITS: usize>(values: &[i64], sum: u64) -> Vec<VerifyFailure> {
+        mock_fails!(
+            ReluSumCircuit::<Fp, BITS>::new(values),
+            ReluSumCircuit::<Fp, BITS>::min_k(),
+            vec![vec![Fp::from(sum)]]
+        )
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(encode::<16>(-(1 << 15)), 0);
+        assert_eq!(encode::<16>(0), 1 << 15);
+        assert_eq!(relu::<16>(encode::<16>(-5)), 0);
+        assert_eq!(relu::<16>(encode::<16>(5)), 5);
+        assert_eq!(ReluSumCircuit::<Fp, 8>::min_k(), 9);
+    }
+
+    #[test]
+    fn activations() {
+        // negative, zero and positive
+        verifies::<8>(&[-3], 0);
+        verifies::<8>(&[0], 0);
+        verifies::<8>(&[7], 7);
+        verifies::<8>(&[-3, 0, 7, -100, 20], 27);
+        fails::<8>(&[-3, 0, 7, -100, 20], 127);
+        fails::<8>(&[-3], 3);
+    }
+
+    #[test]
+    fn bounds() {
+        let (min, max) = (-(1 << 15), (1 << 15) - 1);
+        verifies::<16>(&[min, max], max as u64);
+        // and 2^15 is no i16
+        fails::<16>(&[max + 1], max as u64 + 1);
+    }
+
+    #[test]
+    fn out_of_range() {
+        // 2^7 encodes to 2^8, past the end of the table
+        let failures = fails::<8>(&[1 << 7], 1 << 7);
+        assert!(failures
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Lookup { .. })));
+    }
+}
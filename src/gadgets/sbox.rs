@@ -0,0 +1,265 @@
+//! the x^5 s-box of poseidon-like hashes, on one row with a degree 6 gate or
+//! split over two rows into squarings and a multiplication of degree 3
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct SboxConfig {
+    /// x and y: (a, a^5) for the one row chip, (a, a^2) then (a^4, a^5) for
+    /// the split one
+    pub advice: [Column<Advice>; 2],
+    pub selector: Selector,
+}
+
+/// a^5 in a single gate, of degree 6 with its selector
+pub struct SboxChip<F: Field> {
+    config: SboxConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SboxChip<F> {
+    pub fn construct(config: SboxConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 2]) -> SboxConfig {
+        let selector = meta.selector();
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("sbox", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, out] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let a5 = a.clone() * a.clone() * a.clone() * a.clone() * a;
+            vec![s * (a5 - out)]
+        });
+
+        SboxConfig { advice, selector }
+    }
+
+    /// a^5
+    pub fn apply(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let [col_a, col_out] = self.config.advice;
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "sbox",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+
+                    at = (0, "out");
+                    let out = a.value().map(|a| a.pow_vartime([5]));
+                    region.assign_advice(|| "out", col_out, 0, || out)
+                },
+            )
+            .map_err(|e| FiboError::assign("sbox", at, e))
+    }
+}
+
+/// a^5 as a^2 = a·a, a^4 = a^2·a^2 and a^5 = a^4·a over two rows, of degree 3
+/// with the selector
+pub struct SplitSboxChip<F: Field> {
+    config: SboxConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SplitSboxChip<F> {
+    pub fn construct(config: SboxConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 2]) -> SboxConfig {
+        let selector = meta.selector();
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("split sbox", |meta| {
+            let s = meta.query_selector(selector);
+            let [x, y] = advice;
+            let a = meta.query_advice(x, Rotation::cur());
+            let a2 = meta.query_advice(y, Rotation::cur());
+            let a4 = meta.query_advice(x, Rotation::next());
+            let a5 = meta.query_advice(y, Rotation::next());
+            vec![
+                s.clone() * (a.clone() * a.clone() - a2.clone()),
+                s.clone() * (a2.clone() * a2 - a4.clone()),
+                s * (a4 * a - a5),
+            ]
+        });
+
+        SboxConfig { advice, selector }
+    }
+
+    /// | row |  x  |  y  | selector |
+    /// |  0  |  a  | a^2 |     1    |
+    /// |  1  | a^4 | a^5 |     0    |
+    pub fn apply(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let [x, y] = self.config.advice;
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "split sbox",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, x, 0)?;
+
+                    let a = a.value().copied();
+                    at = (0, "a^2");
+                    let a2 = a.map(|a| a.square());
+                    region.assign_advice(|| "a^2", y, 0, || a2)?;
+                    at = (1, "a^4");
+                    let a4 = a2.map(|a2| a2.square());
+                    region.assign_advice(|| "a^4", x, 1, || a4)?;
+                    at = (1, "a^5");
+                    region.assign_advice(|| "a^5", y, 1, || a4 * a)
+                },
+            )
+            .map_err(|e| FiboError::assign("split sbox", at, e))
+    }
+}
+
+impl<F: Field> Chip<F> for SboxChip<F> {
+    type Config = SboxConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> Chip<F> for SplitSboxChip<F> {
+    type Config = SboxConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    #[derive(Debug, Clone)]
+    struct Pow5Config {
+        sbox: SboxConfig,
+        instance: Column<Instance>,
+    }
+
+    /// exposes a^5 for the private a, on the split chip if SPLIT
+    #[derive(Default)]
+    struct Pow5<const SPLIT: bool> {
+        a: Value<Fp>,
+    }
+
+    impl<const SPLIT: bool> Circuit<Fp> for Pow5<SPLIT> {
+        type Config = Pow5Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 2].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let sbox = if SPLIT {
+                SplitSboxChip::configure(meta, advice)
+            } else {
+                SboxChip::configure(meta, advice)
+            };
+            Pow5Config { sbox, instance }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let a = layouter.assign_region(
+                || "load",
+                |mut region| region.assign_advice(|| "a", config.sbox.advice[0], 0, || self.a),
+            )?;
+            let out = if SPLIT {
+                SplitSboxChip::construct(config.sbox).apply(layouter.namespace(|| "sbox"), &a)?
+            } else {
+                SboxChip::construct(config.sbox).apply(layouter.namespace(|| "sbox"), &a)?
+            };
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    fn verifies<const SPLIT: bool>(a: Fp, out: Fp) {
+        mock_ok!(Pow5::<SPLIT> { a: Value::known(a) }, 4, vec![vec![out]]);
+    }
+
+    fn fails<const SPLIT: bool>(a: Fp, out: Fp) {
+        mock_fails!(Pow5::<SPLIT> { a: Value::known(a) }, 4, vec![vec![out]]);
+    }
+
+    #[test]
+    fn agree() {
+        for a in [Fp::zero(), Fp::one(), Fp::from(3), -Fp::from(7)] {
+            let out = a.pow_vartime([5]);
+            verifies::<false>(a, out);
+            verifies::<true>(a, out);
+        }
+        assert_eq!(Fp::from(3).pow_vartime([5]), Fp::from(243));
+        // a^4 and a^2 are no s-box outputs
+        for wrong in [81, 9] {
+            fails::<false>(Fp::from(3), Fp::from(wrong));
+            fails::<true>(Fp::from(3), Fp::from(wrong));
+        }
+    }
+
+    #[test]
+    fn split_degree() {
+        fn degree<const SPLIT: bool>() -> usize {
+            let mut meta = ConstraintSystem::<Fp>::default();
+            Pow5::<SPLIT>::configure(&mut meta);
+            meta.degree()
+        }
+        // the selector times a^5, against the selector times a product of two
+        assert_eq!(degree::<false>(), 6);
+        assert_eq!(degree::<true>(), 3);
+    }
+}
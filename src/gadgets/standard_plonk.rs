@@ -0,0 +1,300 @@
+//! the general purpose gate q_l·a + q_r·b + q_m·a·b + q_o·c + q_c = 0, the
+//! coefficients in fixed columns picking the operation of each row
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// the three cells of a row: (a, b, c)
+pub type Row<F> = [AssignedCell<F, F>; 3];
+
+#[derive(Debug, Clone)]
+pub struct StandardPlonkConfig {
+    /// a, b and c
+    pub advice: [Column<Advice>; 3],
+    /// q_l, q_r, q_m, q_o and q_c
+    pub fixed: [Column<Fixed>; 5],
+    pub instance: Column<Instance>,
+}
+
+#[derive(Debug)]
+pub struct StandardPlonkChip<F: Field> {
+    config: StandardPlonkConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> StandardPlonkChip<F> {
+    pub fn construct(config: StandardPlonkConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        instance: Column<Instance>,
+    ) -> StandardPlonkConfig {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let fixed = [(); 5].map(|_| meta.fixed_column());
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+
+        meta.create_gate("standard plonk", |meta| {
+            let [a, b, c] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let [q_l, q_r, q_m, q_o, q_c] = fixed.map(|column| meta.query_fixed(column));
+            vec![q_l * a.clone() + q_r * b.clone() + q_m * a * b + q_o * c + q_c]
+        });
+
+        StandardPlonkConfig {
+            advice,
+            fixed,
+            instance,
+        }
+    }
+
+    /// a row with the coefficients q = [q_l, q_r, q_m, q_o, q_c] and the values
+    /// [a, b, c], constrained by nothing but the gate
+    pub fn raw(
+        &self,
+        mut layouter: impl Layouter<F>,
+        q: [F; 5],
+        values: [Value<F>; 3],
+    ) -> Result<Row<F>, FiboError> {
+        self.row(layouter.namespace(|| "raw"), q, values, [None, None])
+    }
+
+    /// a row of the gate, a and b copied from `copies` where given
+    fn row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        q: [F; 5],
+        values: [Value<F>; 3],
+        copies: [Option<&AssignedCell<F, F>>; 2],
+    ) -> Result<Row<F>, FiboError> {
+        const NAMES: [&str; 5] = ["q_l", "q_r", "q_m", "q_o", "q_c"];
+        const ADVICE: [&str; 3] = ["a", "b", "c"];
+
+        let mut at = (0, "q_l");
+        layouter
+            .assign_region(
+                || "standard plonk",
+                |mut region| {
+                    for ((column, q), name) in self.config.fixed.into_iter().zip(q).zip(NAMES) {
+                        at = (0, name);
+                        region.assign_fixed(|| name, column, 0, || Value::known(q))?;
+                    }
+
+                    let [col_a, col_b, col_c] = self.config.advice;
+                    let mut cell = |i: usize, column| {
+                        at = (0, ADVICE[i]);
+                        match copies.get(i).copied().flatten() {
+                            Some(copy) => copy.copy_advice(|| ADVICE[i], &mut region, column, 0),
+                            None => region.assign_advice(|| ADVICE[i], column, 0, || values[i]),
+                        }
+                    };
+                    Ok([cell(0, col_a)?, cell(1, col_b)?, cell(2, col_c)?])
+                },
+            )
+            .map_err(|e| FiboError::assign("standard plonk", at, e))
+    }
+
+    /// a + b
+    pub fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let q = [F::ONE, F::ONE, F::ZERO, -F::ONE, F::ZERO];
+        let c = a.value().copied() + b.value();
+        let values = [a.value().copied(), b.value().copied(), c];
+        let [_, _, c] = self.row(layouter, q, values, [Some(a), Some(b)])?;
+        Ok(c)
+    }
+
+    /// a · b
+    pub fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let q = [F::ZERO, F::ZERO, F::ONE, -F::ONE, F::ZERO];
+        let c = a.value().copied() * b.value();
+        let values = [a.value().copied(), b.value().copied(), c];
+        let [_, _, c] = self.row(layouter, q, values, [Some(a), Some(b)])?;
+        Ok(c)
+    }
+
+    /// a + constant, the constant being part of the circuit
+    pub fn add_const(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        constant: F,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let q = [F::ONE, F::ZERO, F::ZERO, -F::ONE, constant];
+        let c = a.value().copied() + Value::known(constant);
+        let values = [a.value().copied(), Value::known(F::ZERO), c];
+        let [_, _, c] = self.row(layouter, q, values, [Some(a), None])?;
+        Ok(c)
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field> Chip<F> for StandardPlonkChip<F> {
+    type Config = StandardPlonkConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    #[derive(Clone, Copy)]
+    enum Op {
+        Add,
+        Mul,
+        AddConst(u64),
+    }
+
+    /// applies the op to the private a and b, exposing the result
+    struct OpCircuit {
+        a: u64,
+        b: u64,
+        op: Op,
+    }
+
+    impl Circuit<Fp> for OpCircuit {
+        type Config = StandardPlonkConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { ..*self }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            StandardPlonkChip::configure(meta, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = StandardPlonkChip::construct(config);
+            let values = [self.a, self.b, 0].map(|v| Value::known(Fp::from(v)));
+            let [a, b, _] = chip.raw(layouter.namespace(|| "load"), [Fp::zero(); 5], values)?;
+
+            let out = match self.op {
+                Op::Add => chip.add(layouter.namespace(|| "add"), &a, &b)?,
+                Op::Mul => chip.mul(layouter.namespace(|| "mul"), &a, &b)?,
+                Op::AddConst(k) => {
+                    chip.add_const(layouter.namespace(|| "add const"), &a, Fp::from(k))?
+                }
+            };
+            chip.expose_public(layouter.namespace(|| "out"), &out, 0)?;
+            Ok(())
+        }
+    }
+
+    fn verifies(a: u64, b: u64, op: Op, out: u64) {
+        mock_ok!(OpCircuit { a, b, op }, 4, vec![vec![Fp::from(out)]]);
+    }
+
+    fn fails(a: u64, b: u64, op: Op, out: u64) {
+        mock_fails!(OpCircuit { a, b, op }, 4, vec![vec![Fp::from(out)]]);
+    }
+
+    #[test]
+    fn add() {
+        verifies(3, 4, Op::Add, 7);
+        fails(3, 4, Op::Add, 12);
+    }
+
+    #[test]
+    fn mul() {
+        verifies(3, 4, Op::Mul, 12);
+        fails(3, 4, Op::Mul, 7);
+    }
+
+    #[test]
+    fn add_const() {
+        verifies(3, 4, Op::AddConst(10), 13);
+        fails(3, 4, Op::AddConst(10), 14);
+    }
+
+    /// the raw row 2·a·b + 5 = c for a = 1 and b = 3
+    struct Raw {
+        c: u64,
+    }
+
+    impl Circuit<Fp> for Raw {
+        type Config = StandardPlonkConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { c: self.c }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            OpCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = StandardPlonkChip::construct(config);
+            let q = [Fp::zero(), Fp::zero(), Fp::from(2), -Fp::one(), Fp::from(5)];
+            let values = [1, 3, self.c].map(|v| Value::known(Fp::from(v)));
+            chip.raw(layouter.namespace(|| "raw"), q, values)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn raw_cells() {
+        mock_ok!(Raw { c: 11 }, 4, vec![vec![]]);
+
+        let failures = mock_fails!(Raw { c: 12 }, 4, vec![vec![]]);
+        // the coefficients are dumped along with the advice cells
+        let dump = failures[0].to_string();
+        for cell in [
+            "Column('Advice', 2)@0 = 0xc",
+            "Column('Fixed', 2)@0 = 0x2",
+            "Column('Fixed', 3)@0 = -1",
+            "Column('Fixed', 4)@0 = 0x5",
+        ] {
+            assert!(dump.contains(cell), "{dump}");
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! c = (a + b) mod 2^32 with the carry witnessed: a + b = c + carry·2^32
+//!
+//! a, b and c are each decomposed into 4 bytes by the shared byte table, which
+//! the carry being boolean needs for the sum to be unique
+
+use std::marker::PhantomData;
+
+use super::byte_decompose::{load_table, to_bytes, ByteDecomposeChip, ByteDecomposeConfig};
+use crate::error::FiboError;
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// the bytes of a u32
+const BYTES: usize = 4;
+
+/// the low 32 bits of the value
+fn to_u32<F: PrimeField>(value: &F) -> u64 {
+    to_bytes::<F, BYTES>(*value)
+        .iter()
+        .rev()
+        .fold(0, |acc, byte| {
+            (acc << 8) | byte.to_repr().as_ref()[0] as u64
+        })
+}
+
+#[derive(Debug, Clone)]
+pub struct U32AddConfig {
+    /// a, b, c and carry
+    pub advice: [Column<Advice>; 4],
+    pub selector: Selector,
+    pub bytes: ByteDecomposeConfig,
+}
+
+#[derive(Debug)]
+pub struct U32AddChip<F: PrimeField> {
+    config: U32AddConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> U32AddChip<F> {
+    pub fn construct(config: U32AddConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// the bytes are laid out in the first two advice columns, looked up in
+    /// `table`, which is filled by `load_table`
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        table: TableColumn,
+    ) -> U32AddConfig {
+        let selector = meta.selector();
+        let bytes = ByteDecomposeChip::<F, BYTES>::configure(meta, [advice[0], advice[1]], table);
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("u32 add", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, b, c, carry] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let two_32 = Expression::Constant(F::from(1 << 32));
+            vec![
+                s.clone() * (a + b - c - carry.clone() * two_32),
+                s * carry.clone() * (Expression::Constant(F::ONE) - carry),
+            ]
+        });
+
+        U32AddConfig {
+            advice,
+            selector,
+            bytes,
+        }
+    }
+
+    /// (a + b) mod 2^32, a, b and the result range checked to 32 bits
+    pub fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        self.add_with(layouter, a, b, |carry| carry)
+    }
+
+    /// as add, the carry witnessed by `carry_of(carry)` and c made to match it
+    fn add_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        carry_of: impl FnOnce(Value<F>) -> Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let bytes = ByteDecomposeChip::<F, BYTES>::construct(self.config.bytes.clone());
+        bytes.assign(layouter.namespace(|| "range check a"), a)?;
+        bytes.assign(layouter.namespace(|| "range check b"), b)?;
+
+        let sum = a.value().zip(b.value()).map(|(a, b)| to_u32(a) + to_u32(b));
+        let carry = carry_of(sum.map(|sum| F::from(sum >> 32)));
+        let c = a.value().copied() + b.value() - carry * Value::known(F::from(1 << 32));
+
+        let [col_a, col_b, col_c, col_carry] = self.config.advice;
+        let mut at = (0, "selector");
+        let c = layouter
+            .assign_region(
+                || "u32 add",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    at = (0, "b");
+                    b.copy_advice(|| "b", &mut region, col_b, 0)?;
+
+                    at = (0, "carry");
+                    region.assign_advice(|| "carry", col_carry, 0, || carry)?;
+                    at = (0, "c");
+                    region.assign_advice(|| "c", col_c, 0, || c)
+                },
+            )
+            .map_err(|e| FiboError::assign("u32 add", at, e))?;
+
+        bytes.assign(layouter.namespace(|| "range check c"), &c)?;
+        Ok(c)
+    }
+}
+
+impl<F: PrimeField> Chip<F> for U32AddChip<F> {
+    type Config = U32AddConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChecksumConfig {
+    pub add: U32AddConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes the sum mod 2^32 of the private u32 values
+#[derive(Debug, Default)]
+pub struct ChecksumCircuit<F> {
+    pub values: Vec<Value<F>>,
+}
+
+impl<F: PrimeField> ChecksumCircuit<F> {
+    pub fn new(values: &[u32]) -> Self {
+        Self {
+            values: values
+                .iter()
+                .map(|&value| Value::known(F::from(value as u64)))
+                .collect(),
+        }
+    }
+
+    /// the sum of the values, the carry of the last addition witnessed by `carry_of`
+    fn synthesize_with(
+        &self,
+        config: ChecksumConfig,
+        mut layouter: impl Layouter<F>,
+        carry_of: impl FnOnce(Value<F>) -> Value<F>,
+    ) -> Result<(), FiboError> {
+        let (first, rest) = self
+            .values
+            .split_first()
+            .ok_or(FiboError::TooFewRows { rows: 0, min: 1 })?;
+        load_table(layouter.namespace(|| "table"), config.add.bytes.table)?;
+
+        let column = config.add.advice[0];
+        let mut sum = layouter
+            .assign_region(
+                || "load",
+                |mut region| region.assign_advice(|| "value", column, 0, || *first),
+            )
+            .map_err(|e| FiboError::assign("load", (0, "value"), e))?;
+
+        let chip = U32AddChip::construct(config.add);
+        let mut carry_of = Some(carry_of);
+        for (i, value) in rest.iter().enumerate() {
+            let value = layouter
+                .assign_region(
+                    || "load",
+                    |mut region| region.assign_advice(|| "value", column, 0, || *value),
+                )
+                .map_err(|e| FiboError::assign("load", (0, "value"), e))?;
+
+            let layouter = layouter.namespace(|| format!("add {}", i + 1));
+            sum = if i + 1 == rest.len() {
+                chip.add_with(layouter, &sum, &value, carry_of.take().unwrap())?
+            } else {
+                chip.add(layouter, &sum, &value)?
+            };
+        }
+
+        layouter
+            .constrain_instance(sum.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for ChecksumCircuit<F> {
+    type Config = ChecksumConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the number of values shapes the circuit
+        Self {
+            values: vec![Value::unknown(); self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let table = meta.lookup_table_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        ChecksumConfig {
+            add: U32AddChip::configure(meta, advice, table),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, |carry| carry)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn no_carry() {
+        mock_ok!(ChecksumCircuit::new(&[3, 4]), 9, vec![vec![Fp::from(7)]]);
+        mock_fails!(ChecksumCircuit::new(&[3, 4]), 9, vec![vec![Fp::from(8)]]);
+    }
+
+    #[test]
+    fn carry() {
+        mock_ok!(
+            ChecksumCircuit::new(&[u32::MAX, 1]),
+            9,
+            vec![vec![Fp::from(0)]]
+        );
+        mock_ok!(
+            ChecksumCircuit::new(&[1 << 31, 1 << 31, 5]),
+            9,
+            vec![vec![Fp::from(5)]]
+        );
+        mock_fails!(
+            ChecksumCircuit::new(&[u32::MAX, 1]),
+            9,
+            vec![vec![Fp::from(1)]]
+        );
+    }
+
+    #[test]
+    fn max() {
+        mock_ok!(
+            ChecksumCircuit::new(&[u32::MAX, u32::MAX]),
+            9,
+            vec![vec![Fp::from((u32::MAX - 1) as u64)]]
+        );
+    }
+
+    #[test]
+    fn checksum() {
+        let values = [0xdead_beef, 0xcafe_babe, 0x0bad_f00d, 0x1234_5678, 42];
+        let sum = values.iter().fold(0u32, |sum, v| sum.wrapping_add(*v));
+        mock_ok!(
+            ChecksumCircuit::new(&values),
+            9,
+            vec![vec![Fp::from(sum as u64)]]
+        );
+    }
+
+    /// the checksum with the carry of its last addition witnessed by `carry_of`
+    struct Tampered {
+        circuit: ChecksumCircuit<Fp>,
+        carry_of: fn(Value<Fp>) -> Value<Fp>,
+    }
+
+    impl Circuit<Fp> for Tampered {
+        type Config = ChecksumConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                circuit: self.circuit.without_witnesses(),
+                carry_of: self.carry_of,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            ChecksumCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            Ok(self
+                .circuit
+                .synthesize_with(config, layouter, self.carry_of)?)
+        }
+    }
+
+    #[test]
+    fn carry_of_two() {
+        // 2^33 - 2 = c + 2·2^32 makes c = -2, which is no u32, nor is 2 boolean
+        let circuit = Tampered {
+            circuit: ChecksumCircuit::new(&[u32::MAX, u32::MAX]),
+            carry_of: |_| Value::known(Fp::from(2)),
+        };
+        let failures = mock_fails!(circuit, 9, vec![vec![-Fp::from(2)]]);
+        assert!(
+            failures.iter().any(|f| f.to_string().contains("'u32 add'")),
+            "{failures:?}"
+        );
+
+        // and a carry of 0 leaves c = 2^33 - 2, out of range as well
+        let circuit = Tampered {
+            circuit: ChecksumCircuit::new(&[u32::MAX, u32::MAX]),
+            carry_of: |_| Value::known(Fp::zero()),
+        };
+        mock_fails!(circuit, 9, vec![vec![Fp::from((1 << 33) - 2)]]);
+    }
+}
@@ -0,0 +1,277 @@
+//! 4 bit xor by a lookup into the table of every (left, right, left ^ right)
+
+use std::marker::PhantomData;
+
+use crate::{error::FiboError, fibonacci::min_k};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// the bits of each operand
+pub const BITS: usize = 4;
+/// one row for each pair of operands
+pub const TABLE_ROWS: usize = 1 << (2 * BITS);
+
+/// the low byte of the value, which is all of it for an operand
+fn low_byte<F: PrimeField>(value: &F) -> u8 {
+    value.to_repr().as_ref()[0]
+}
+
+#[derive(Debug, Clone)]
+pub struct XorConfig {
+    /// a, b and out
+    pub advice: [Column<Advice>; 3],
+    pub selector: Selector,
+    /// left, right and left ^ right
+    pub table: [TableColumn; 3],
+}
+
+pub struct XorChip<F: PrimeField> {
+    config: XorConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> XorChip<F> {
+    pub fn construct(config: XorConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 3]) -> XorConfig {
+        let selector = meta.complex_selector();
+        let table = [(); 3].map(|_| meta.lookup_table_column());
+
+        // enable the equality
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        // unselected rows look up (0, 0, 0), which is in the table
+        meta.lookup(|meta| {
+            let s = meta.query_selector(selector);
+            advice
+                .into_iter()
+                .zip(table)
+                .map(|(column, table)| {
+                    (
+                        s.clone() * meta.query_advice(column, Rotation::cur()),
+                        table,
+                    )
+                })
+                .collect()
+        });
+
+        XorConfig {
+            advice,
+            selector,
+            table,
+        }
+    }
+
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), FiboError> {
+        let [left, right, out] = self.config.table;
+        let mut at = (0, "table");
+        layouter
+            .assign_table(
+                || "xor table",
+                |mut table| {
+                    for offset in 0..TABLE_ROWS {
+                        at = (offset, "table");
+                        let (l, r) = ((offset >> BITS) as u64, (offset % (1 << BITS)) as u64);
+                        for (column, value) in [(left, l), (right, r), (out, l ^ r)] {
+                            table.assign_cell(
+                                || "xor",
+                                column,
+                                offset,
+                                || Value::known(F::from(value)),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| FiboError::assign("xor table", at, e))
+    }
+
+    /// a ^ b, constrained by the table
+    pub fn assign_xor(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let [col_a, col_b, col_out] = self.config.advice;
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "xor",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+
+                    at = (0, "a");
+                    a.copy_advice(|| "a", &mut region, col_a, 0)?;
+                    at = (0, "b");
+                    b.copy_advice(|| "b", &mut region, col_b, 0)?;
+
+                    at = (0, "out");
+                    let out = a
+                        .value()
+                        .zip(b.value())
+                        .map(|(a, b)| F::from((low_byte(a) ^ low_byte(b)) as u64));
+                    region.assign_advice(|| "out", col_out, 0, || out)
+                },
+            )
+            .map_err(|e| FiboError::assign("xor", at, e))
+    }
+}
+
+impl<F: PrimeField> Chip<F> for XorChip<F> {
+    type Config = XorConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XorCircuitConfig {
+    pub xor: XorConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes a ^ b for the private nibbles a and b
+#[derive(Debug, Default)]
+pub struct XorCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
+
+impl<F: PrimeField> XorCircuit<F> {
+    pub fn new(a: F, b: F) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+        }
+    }
+
+    /// the smallest k fitting the table, which takes far more rows than the regions
+    pub fn min_k() -> u32 {
+        min_k::<F, Self>(TABLE_ROWS)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for XorCircuit<F> {
+    type Config = XorCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        XorCircuitConfig {
+            xor: XorChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = XorChip::construct(config.xor.clone());
+        chip.load_table(layouter.namespace(|| "table"))?;
+
+        let [col_a, col_b, _] = config.xor.advice;
+        let (a, b) = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", col_a, 0, || self.a)?;
+                    let b = region.assign_advice(|| "b", col_b, 0, || self.b)?;
+                    Ok((a, b))
+                },
+            )
+            .map_err(|e| FiboError::assign("load", (0, "a"), e))?;
+
+        let out = chip.assign_xor(layouter.namespace(|| "xor"), &a, &b)?;
+        layouter
+            .constrain_instance(out.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn k() {
+        // 256 table rows and the blinding rows overflow k = 8
+        assert_eq!(XorCircuit::<Fp>::min_k(), 9);
+        let circuit = XorCircuit::new(Fp::from(5), Fp::from(3));
+        assert!(MockProver::run(8, &circuit, vec![vec![Fp::from(6)]]).is_err());
+    }
+
+    /// the xor row of 5 and 3 with `out` witnessed, exposed as is
+    struct WrongOut {
+        out: u64,
+    }
+
+    impl Circuit<Fp> for WrongOut {
+        type Config = XorCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { out: self.out }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            XorCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = XorChip::construct(config.xor.clone());
+            chip.load_table(layouter.namespace(|| "table"))?;
+
+            let [col_a, col_b, col_out] = config.xor.advice;
+            let out = layouter.assign_region(
+                || "xor",
+                |mut region| {
+                    config.xor.selector.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", col_a, 0, || Value::known(Fp::from(5)))?;
+                    region.assign_advice(|| "b", col_b, 0, || Value::known(Fp::from(3)))?;
+                    let out = Value::known(Fp::from(self.out));
+                    region.assign_advice(|| "out", col_out, 0, || out)
+                },
+            )?;
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn wrong_out() {
+        mock_ok!(WrongOut { out: 6 }, 9, vec![vec![Fp::from(6)]]);
+        // 5 + 3, 5 | 3 and 5 & 3
+        for out in [8, 7, 1] {
+            mock_fails!(WrongOut { out }, 9, vec![vec![Fp::from(out)]]);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! mock_ok! and mock_fails!, which run a circuit under MockProver and, when it
+//! goes the other way than the test expects, panic with what
+//! failures::explain_failures makes of it
+//!
+//! ```ignore
+//! mock_ok!(MyCircuit::<Fp>::new(10), 4, vec![publics.to_instance_column()]);
+//! // failing in a gate or region of that name
+//! mock_fails!(MyCircuit::<Fp>::new(10), 4, vec![wrong], "fib step 10/10");
+//! ```
+//!
+//! and assert_vk_independent, checking keygen comes to the same verifying key
+//! whatever the witness
+
+use crate::failures::explain_failures;
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    pasta::{EqAffine, Fp},
+    plonk::{keygen_vk, Circuit},
+    poly::commitment::Params,
+};
+
+#[macro_export]
+macro_rules! mock_ok {
+    ($circuit:expr, $k:expr, $instances:expr $(,)?) => {
+        $crate::harness::mock_ok(&$circuit, $k, $instances)
+    };
+}
+
+/// evaluates to the failures, for further checks
+#[macro_export]
+macro_rules! mock_fails {
+    ($circuit:expr, $k:expr, $instances:expr $(,)?) => {
+        $crate::harness::mock_fails(&$circuit, $k, $instances, None)
+    };
+    ($circuit:expr, $k:expr, $instances:expr, $name:expr $(,)?) => {
+        $crate::harness::mock_fails(&$circuit, $k, $instances, Some($name))
+    };
+}
+
+fn run<C: Circuit<Fp>>(circuit: &C, k: u32, instances: Vec<Vec<Fp>>) -> MockProver<Fp> {
+    MockProver::run(k, circuit, instances)
+        .unwrap_or_else(|e| panic!("MockProver could not lay the circuit out at k = {k}: {e:?}"))
+}
+
+/// panics unless the circuit is satisfied
+pub fn mock_ok<C: Circuit<Fp>>(circuit: &C, k: u32, instances: Vec<Vec<Fp>>) {
+    let prover = run(circuit, k, instances);
+    if let Err(failures) = prover.verify() {
+        panic!(
+            "MockProver not satisfied, {}",
+            explain_failures(&prover, &failures)
+        );
+    }
+}
+
+/// panics unless the circuit fails, and if `name` is given, fails in a gate
+/// or a region of that name
+pub fn mock_fails<C: Circuit<Fp>>(
+    circuit: &C,
+    k: u32,
+    instances: Vec<Vec<Fp>>,
+    name: Option<&str>,
+) -> Vec<VerifyFailure> {
+    let prover = run(circuit, k, instances);
+    let Err(failures) = prover.verify() else {
+        panic!("MockProver satisfied, where the circuit should fail");
+    };
+    if let Some(name) = name {
+        // the headings quote the names of gates and regions
+        let text = explain_failures(&prover, &failures);
+        let quoted = format!("\"{name}\"");
+        assert!(
+            text.lines()
+                .any(|line| !line.starts_with(' ') && line.contains(&quoted)),
+            "no failure in a gate or region {quoted}, but {text}"
+        );
+    }
+    failures
+}
+
+/// the pinned verifying key of `circuit` at 2^k rows, pretty printed one
+/// field per line
+pub fn pinned_vk<C: Circuit<Fp>>(k: u32, circuit: &C) -> String {
+    let params: Params<EqAffine> = Params::new(k);
+    let vk = keygen_vk(&params, circuit).expect("keygen_vk");
+    format!("{:#?}\n", vk.pinned())
+}
+
+/// panics unless each of `circuits` has the verifying key of the circuit
+/// without witnesses, which is what keygen sees. a circuit whose layout
+/// branches on a witness would otherwise verify none of its proofs
+pub fn assert_vk_independent<C: Circuit<Fp>>(k: u32, circuits: &[C]) {
+    let Some(first) = circuits.first() else {
+        return;
+    };
+    let expected = pinned_vk(k, &first.without_witnesses());
+    for (i, circuit) in circuits.iter().enumerate() {
+        assert!(
+            pinned_vk(k, circuit) == expected,
+            "the verifying key of circuit {i} depends on its witness"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::assert_vk_independent;
+    use crate::fibonacci::{public_inputs::PublicInputs, row_based::MyCircuit, vectors::nth};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        pasta::Fp,
+        plonk::{Circuit, Column, ConstraintSystem, Error, Fixed},
+    };
+
+    fn claiming(out: Fp) -> Vec<Vec<Fp>> {
+        let mut publics = PublicInputs::new(Fp::one(), Fp::one(), 10);
+        publics.out = out;
+        vec![publics.to_instance_column()]
+    }
+
+    #[test]
+    fn ok() {
+        mock_ok!(MyCircuit::<Fp>::new(10), 4, claiming(nth(10)));
+    }
+
+    #[test]
+    fn fails() {
+        let failures = mock_fails!(
+            MyCircuit::<Fp>::new(10),
+            4,
+            claiming(Fp::from(65)),
+            "fib step 10/10"
+        );
+        assert_eq!(failures.len(), 2);
+        mock_fails!(MyCircuit::<Fp>::new(10), 4, claiming(Fp::from(65)));
+    }
+
+    #[test]
+    #[should_panic(expected = "likely cause: public input doesn't match witnessed value")]
+    fn ok_explains() {
+        mock_ok!(MyCircuit::<Fp>::new(10), 4, claiming(Fp::from(65)));
+    }
+
+    #[test]
+    #[should_panic(expected = "no failure in a gate or region \"first row\"")]
+    fn fails_elsewhere() {
+        mock_fails!(
+            MyCircuit::<Fp>::new(10),
+            4,
+            claiming(Fp::from(65)),
+            "first row"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "MockProver satisfied")]
+    fn fails_satisfied() {
+        mock_fails!(MyCircuit::<Fp>::new(10), 4, claiming(nth(10)));
+    }
+
+    /// sets a fixed cell only when it knows its witness, as a circuit skipping
+    /// a region for some values would
+    #[derive(Default)]
+    struct Branching(Option<Fp>);
+
+    impl Circuit<Fp> for Branching {
+        type Config = Column<Fixed>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.fixed_column()
+        }
+
+        fn synthesize(
+            &self,
+            fixed: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let Some(value) = self.0 else {
+                return Ok(());
+            };
+            layouter.assign_region(
+                || "known",
+                |mut region| region.assign_fixed(|| "value", fixed, 0, || Value::known(value)),
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the verifying key of circuit 1 depends on its witness")]
+    fn vk_branching() {
+        assert_vk_independent(4, &[Branching(None), Branching(Some(Fp::from(7)))]);
+    }
+}
@@ -0,0 +1,279 @@
+//! p(x) = y by horner's rule for a degree D polynomial with public coefficients,
+//! x private and y public
+//!
+//! the instance column holds the coefficients, highest degree first, then y
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// p(x) for the coefficients highest degree first, computed natively
+pub fn horner<F: Field>(coeffs: &[F], x: F) -> F {
+    coeffs.iter().fold(F::ZERO, |acc, coeff| acc * x + coeff)
+}
+
+/// the instance column of p(x) = y: the coefficients, then y
+pub fn instance<F: Field>(coeffs: &[F], y: F) -> Vec<F> {
+    coeffs.iter().copied().chain([y]).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct HornerConfig {
+    pub acc: Column<Advice>,
+    pub x: Column<Advice>,
+    pub coeff: Column<Advice>,
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct HornerChip<F: Field, const D: usize> {
+    config: HornerConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const D: usize> HornerChip<F, D> {
+    pub fn construct(config: HornerConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> HornerConfig {
+        let acc = meta.advice_column();
+        let x = meta.advice_column();
+        let coeff = meta.advice_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(acc);
+        meta.enable_equality(x);
+        meta.enable_equality(coeff);
+        meta.enable_equality(instance);
+
+        meta.create_gate("horner", |meta| {
+            let s = meta.query_selector(selector);
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let x = meta.query_advice(x, Rotation::cur());
+            let coeff_next = meta.query_advice(coeff, Rotation::next());
+            vec![s * (acc_cur * x + coeff_next - acc_next)]
+        });
+
+        HornerConfig {
+            acc,
+            x,
+            coeff,
+            selector,
+            instance,
+        }
+    }
+
+    /// | row |     acc     | x | coeff | selector |
+    /// |  0  |     c_D     | x |       |     1    |
+    /// |  1  | c_D·x + c_1 | x |  c_1  |     1    |
+    /// | ... |             |   |       |          |
+    /// |  D  |     p(x)    | x |  c_0  |     0    |
+    ///
+    /// with the coefficients copied from the instance and every x copied from
+    /// the first. returns the cell of p(x)
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        self.assign_with(layouter, |_| x)
+    }
+
+    /// as assign, the x of each row witnessed by `x_at(row)`
+    fn assign_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x_at: impl Fn(usize) -> Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let HornerConfig {
+            acc,
+            x,
+            coeff,
+            selector,
+            instance,
+        } = self.config;
+
+        let mut at = (0, "acc");
+        layouter
+            .assign_region(
+                || "horner",
+                |mut region| {
+                    at = (0, "acc");
+                    let mut acc_cell =
+                        region.assign_advice_from_instance(|| "c_D", instance, 0, acc, 0)?;
+                    at = (0, "x");
+                    let first_x = region.assign_advice(|| "x", x, 0, || x_at(0))?;
+                    let mut x_cell = first_x.clone();
+
+                    for row in 1..=D {
+                        at = (row - 1, "selector");
+                        selector.enable(&mut region, row - 1)?;
+
+                        at = (row, "coeff");
+                        let coeff_cell = region.assign_advice_from_instance(
+                            || "coeff",
+                            instance,
+                            row,
+                            coeff,
+                            row,
+                        )?;
+
+                        at = (row, "acc");
+                        let acc_val =
+                            acc_cell.value().copied() * x_cell.value() + coeff_cell.value();
+                        acc_cell = region.assign_advice(|| "acc", acc, row, || acc_val)?;
+
+                        // p(x) needs no x, the last row takes one all the same
+                        at = (row, "x");
+                        x_cell = region.assign_advice(|| "x", x, row, || x_at(row))?;
+                        region.constrain_equal(first_x.cell(), x_cell.cell())?;
+                    }
+
+                    Ok(acc_cell)
+                },
+            )
+            .map_err(|e| FiboError::assign("horner", at, e))
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field, const D: usize> Chip<F> for HornerChip<F, D> {
+    type Config = HornerConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves p(x) = y for the private x, the degree D polynomial and y being the
+/// instance
+#[derive(Debug, Default)]
+pub struct HornerCircuit<F, const D: usize> {
+    pub x: Value<F>,
+}
+
+impl<F: Field, const D: usize> HornerCircuit<F, D> {
+    pub fn new(x: F) -> Self {
+        Self { x: Value::known(x) }
+    }
+}
+
+impl<F: Field, const D: usize> Circuit<F> for HornerCircuit<F, D> {
+    type Config = HornerConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        HornerChip::<F, D>::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = HornerChip::<F, D>::construct(config);
+        let y = chip.assign(layouter.namespace(|| "horner"), self.x)?;
+        chip.expose_public(layouter.namespace(|| "y"), &y, D + 1)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::VerifyFailure, pasta::Fp};
+
+    /// 1·x^5 + 2·x^4 + 3·x^3 + 4·x^2 + 5·x + 6
+    fn coeffs() -> Vec<Fp> {
+        (1..=6).map(Fp::from).collect()
+    }
+
+    fn verifies<C: Circuit<Fp>>(circuit: &C, y: Fp) {
+        mock_ok!(*circuit, 4, vec![instance(&coeffs(), y)]);
+    }
+
+    fn fails<C: Circuit<Fp>>(circuit: &C, y: Fp) -> Vec<VerifyFailure> {
+        mock_fails!(*circuit, 4, vec![instance(&coeffs(), y)])
+    }
+
+    #[test]
+    fn degree_five() {
+        // 1, 4, 11, 26, 57, 120
+        let y = horner(&coeffs(), Fp::from(2));
+        assert_eq!(y, Fp::from(120));
+        let circuit = HornerCircuit::<Fp, 5>::new(Fp::from(2));
+        verifies(&circuit, y);
+        fails(&circuit, y + Fp::one());
+        // p(3)
+        fails(&circuit, Fp::from(543));
+    }
+
+    /// the horner rows with x changed to 3 from `row` on, the accumulator following
+    struct SwitchedX {
+        row: usize,
+    }
+
+    impl Circuit<Fp> for SwitchedX {
+        type Config = HornerConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { row: self.row }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            HornerCircuit::<Fp, 5>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = HornerChip::<Fp, 5>::construct(config);
+            let y = chip.assign_with(layouter.namespace(|| "horner"), |row| {
+                Value::known(Fp::from(if row < self.row { 2 } else { 3 }))
+            })?;
+            chip.expose_public(layouter.namespace(|| "y"), &y, 6)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn switched_x() {
+        // 1, 4, 11, 26, then x = 3: 83, 255
+        let failures = fails(&SwitchedX { row: 3 }, Fp::from(255));
+        // every gate holds, the x cells alone differ
+        assert!(failures
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+    }
+}
@@ -0,0 +1,313 @@
+//! ⟨a, b⟩ = c for private vectors a and b, the running sum in one region
+//! like two_column
+
+use std::marker::PhantomData;
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// ⟨a, b⟩, computed natively
+pub fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).map(|(a, b)| *a * b).sum()
+}
+
+/// where an entry of a vector comes from
+#[derive(Debug, Clone)]
+pub enum Entry<'a, F: Field> {
+    Private(Value<F>),
+    /// a row of the instance column
+    Instance(usize),
+    /// a cell assigned before, copy constrained to its copy
+    Copy(&'a AssignedCell<F, F>),
+}
+
+impl<F: Field> Entry<'_, F> {
+    /// the entry at `offset` in `column`, a private or copied value witnessed
+    /// as `value_of(value)`
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        instance: Column<Instance>,
+        column: Column<Advice>,
+        offset: usize,
+        value_of: impl FnOnce(Value<F>) -> Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        match self {
+            Entry::Private(value) => {
+                let value = value_of(*value);
+                region.assign_advice(|| "entry", column, offset, || value)
+            }
+            Entry::Instance(row) => {
+                region.assign_advice_from_instance(|| "entry", instance, *row, column, offset)
+            }
+            Entry::Copy(cell) => {
+                let value = value_of(cell.value().copied());
+                let copy = region.assign_advice(|| "entry", column, offset, || value)?;
+                region.constrain_equal(cell.cell(), copy.cell())?;
+                Ok(copy)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InnerProductConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct InnerProductChip<F: Field> {
+    config: InnerProductConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> InnerProductChip<F> {
+    pub fn construct(config: InnerProductConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> InnerProductConfig {
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let acc = meta.advice_column();
+        let selector = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(acc);
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        meta.create_gate("inner product", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            vec![s * (acc_cur + a * b - acc_next)]
+        });
+
+        InnerProductConfig {
+            a,
+            b,
+            acc,
+            selector,
+            instance,
+        }
+    }
+
+    /// | row |  a  |  b  |      acc      | selector |
+    /// |  0  | a_0 | b_0 |       0       |     1    |
+    /// |  1  | a_1 | b_1 |    a_0·b_0    |     1    |
+    /// | ... |     |     |               |          |
+    /// |  n  |     |     |     ⟨a, b⟩    |     0    |
+    ///
+    /// the first acc being the constant 0. returns the last acc
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &[Value<F>],
+        b: &[Value<F>],
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let private = |values: &[Value<F>]| values.iter().copied().map(Entry::Private).collect();
+        let (a, b): (Vec<_>, Vec<_>) = (private(a), private(b));
+        self.assign_entries(layouter, &a, &b)
+    }
+
+    /// as assign, each entry loaded from where it says
+    pub fn assign_entries(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &[Entry<F>],
+        b: &[Entry<F>],
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        self.assign_entries_with(layouter, a, b, |_, b| b)
+    }
+
+    /// as assign_entries, the i-th b witnessed by `b_at(i, b)`
+    pub(crate) fn assign_entries_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &[Entry<F>],
+        b: &[Entry<F>],
+        b_at: impl Fn(usize, Value<F>) -> Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        if a.len() != b.len() {
+            return Err(FiboError::LengthMismatch {
+                a: a.len(),
+                b: b.len(),
+            });
+        }
+
+        let InnerProductConfig {
+            a: col_a,
+            b: col_b,
+            acc,
+            selector,
+            instance,
+        } = self.config;
+
+        let mut at = (0, "acc");
+        layouter
+            .assign_region(
+                || "inner product",
+                |mut region| {
+                    at = (0, "acc");
+                    let mut acc_cell =
+                        region.assign_advice_from_constant(|| "acc", acc, 0, F::ZERO)?;
+
+                    for (row, (a, b)) in a.iter().zip(b).enumerate() {
+                        at = (row, "selector");
+                        selector.enable(&mut region, row)?;
+
+                        at = (row, "a");
+                        let a = a.assign(&mut region, instance, col_a, row, |a| a)?;
+                        at = (row, "b");
+                        let b = b.assign(&mut region, instance, col_b, row, |b| b_at(row, b))?;
+
+                        at = (row + 1, "acc");
+                        let acc_val = acc_cell.value().copied() + a.value().copied() * b.value();
+                        acc_cell = region.assign_advice(|| "acc", acc, row + 1, || acc_val)?;
+                    }
+
+                    Ok(acc_cell)
+                },
+            )
+            .map_err(|e| FiboError::assign("inner product", at, e))
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), FiboError> {
+        layouter
+            .constrain_instance(cell.cell(), self.config.instance, row)
+            .map_err(|e| FiboError::expose(row, e))
+    }
+}
+
+impl<F: Field> Chip<F> for InnerProductChip<F> {
+    type Config = InnerProductConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves ⟨a, b⟩ = c for the private a and b, c being the instance
+#[derive(Debug, Default)]
+pub struct InnerProductCircuit<F> {
+    pub a: Vec<Value<F>>,
+    pub b: Vec<Value<F>>,
+}
+
+impl<F: Field> InnerProductCircuit<F> {
+    /// synthesize fails unless a and b are of the same length
+    pub fn new(a: &[F], b: &[F]) -> Self {
+        Self {
+            a: a.iter().copied().map(Value::known).collect(),
+            b: b.iter().copied().map(Value::known).collect(),
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for InnerProductCircuit<F> {
+    type Config = InnerProductConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: vec![Value::unknown(); self.a.len()],
+            b: vec![Value::unknown(); self.b.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        InnerProductChip::configure(meta, instance, constant)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = InnerProductChip::construct(config);
+        let c = chip.assign(layouter.namespace(|| "inner product"), &self.a, &self.b)?;
+        chip.expose_public(layouter.namespace(|| "c"), &c, 0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    fn verifies(a: &[Fp], b: &[Fp], c: Fp) {
+        mock_ok!(InnerProductCircuit::new(a, b), 5, vec![vec![c]]);
+    }
+
+    fn fails(a: &[Fp], b: &[Fp], c: Fp) {
+        mock_fails!(InnerProductCircuit::new(a, b), 5, vec![vec![c]]);
+    }
+
+    fn vector(values: impl IntoIterator<Item = u64>) -> Vec<Fp> {
+        values.into_iter().map(Fp::from).collect()
+    }
+
+    #[test]
+    fn one() {
+        let (a, b) = (vector([6]), vector([7]));
+        verifies(&a, &b, Fp::from(42));
+        fails(&a, &b, Fp::from(13));
+    }
+
+    #[test]
+    fn sixteen() {
+        let (a, b) = (vector(1..=16), vector((1..=16).rev()));
+        // the sum of i·(17 - i)
+        let c = inner_product(&a, &b);
+        assert_eq!(c, Fp::from(816));
+        verifies(&a, &b, c);
+
+        let mut perturbed = a.clone();
+        perturbed[7] += Fp::one();
+        fails(&perturbed, &b, c);
+    }
+
+    #[test]
+    fn empty() {
+        verifies(&[], &[], Fp::zero());
+        fails(&[], &[], Fp::one());
+    }
+
+    #[test]
+    fn lengths() {
+        let circuit = InnerProductCircuit::new(&vector([1, 2]), &vector([3]));
+        assert!(matches!(
+            MockProver::run(5, &circuit, vec![vec![Fp::from(3)]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
@@ -0,0 +1,52 @@
+//! renders circuit layouts with plotters, to see how the floor planner
+//! places the regions. --features=dev-graph
+
+use halo2_proofs::{arithmetic::Field, dev::CircuitLayout, plonk::Circuit};
+use plotters::prelude::*;
+use std::{error::Error, path::Path};
+
+/// draws `circuit` laid out in 2^k rows into a png at `path`, with labelled
+/// regions and equality constrained cells
+pub fn render<F: Field, C: Circuit<F>>(
+    k: u32,
+    circuit: &C,
+    title: &str,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(path.as_ref(), (1024, 3096)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root = root.titled(title, ("sans-serif", 60))?;
+
+    CircuitLayout::default()
+        .show_labels(true)
+        .mark_equality_cells(true)
+        .show_equality_constraints(true)
+        .render(k, circuit, &root)?;
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{row_based, single_column};
+    use halo2_proofs::pasta::Fp;
+    use std::fs;
+
+    #[test]
+    fn render_fibonacci() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let row_based_png = dir.path().join("row_based.png");
+        let circuit = row_based::MyCircuit::<Fp>::new(10);
+        render(4, &circuit, "row based", &row_based_png).unwrap();
+
+        let single_column_png = dir.path().join("single_column.png");
+        let circuit = single_column::MyCircuit::<Fp>::new(10);
+        render(4, &circuit, "single column", &single_column_png).unwrap();
+
+        for png in [row_based_png, single_column_png] {
+            assert!(fs::metadata(png).unwrap().len() > 0);
+        }
+    }
+}
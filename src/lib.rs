@@ -1,2 +1,39 @@
+pub mod age;
+pub mod balance;
+pub mod commit_reveal;
+pub mod cost;
+pub mod encoding;
+pub mod envelope;
+pub mod error;
+pub mod factorial;
+pub mod failures;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod fibonacci;
-mod square;
\ No newline at end of file
+pub mod gadgets;
+pub mod harness;
+pub mod horner;
+pub mod inner_product;
+#[cfg(feature = "dev-graph")]
+pub mod layout;
+pub mod linear_model;
+pub mod matvec;
+pub mod membership;
+pub mod memory;
+#[cfg(feature = "memstats")]
+pub mod memstats;
+pub mod merkle;
+pub mod params_cache;
+pub mod planner;
+pub mod poseidon;
+pub mod proving;
+pub mod shuffle;
+pub mod sorted;
+pub mod square;
+pub mod sudoku;
+pub mod summary;
+pub mod sweep;
+pub mod trace;
+pub mod util;
+pub mod voting;
+pub mod witness;
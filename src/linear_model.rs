@@ -0,0 +1,366 @@
+//! a linear model: w·x + b = y for the private features x and the public y,
+//! the weights w and the bias b in a fixed column
+//!
+//! the weights are part of the circuit rather than of the witness, so keygen
+//! commits to them in the vk and a proof made with other weights fails to
+//! verify against it. Circuit::configure takes no self, so they are assigned
+//! at synthesis, which keygen runs as well
+
+use crate::error::FiboError;
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+/// the weights and the bias of a model of L features
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Model<F, const L: usize> {
+    pub weights: [F; L],
+    pub bias: F,
+}
+
+impl<F: Field, const L: usize> Model<F, L> {
+    pub fn new(weights: [F; L], bias: F) -> Self {
+        Self { weights, bias }
+    }
+
+    /// w·x + b
+    pub fn evaluate(&self, x: &[F; L]) -> F {
+        self.weights
+            .iter()
+            .zip(x)
+            .fold(self.bias, |acc, (w, x)| acc + *w * x)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LinearConfig {
+    pub x: Column<Advice>,
+    /// the running sum, from b to w·x + b
+    pub acc: Column<Advice>,
+    /// b, then w_i on the row of x_i
+    pub weight: Column<Fixed>,
+    /// the first row: acc = b
+    pub s_bias: Selector,
+    /// every other row: acc = acc_prev + w_i·x_i
+    pub s_dot: Selector,
+}
+
+#[derive(Debug)]
+pub struct LinearChip<F: Field, const L: usize> {
+    config: LinearConfig,
+    model: Model<F, L>,
+}
+
+impl<F: Field, const L: usize> LinearChip<F, L> {
+    pub fn construct(config: LinearConfig, model: Model<F, L>) -> Self {
+        Self { config, model }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        [x, acc]: [Column<Advice>; 2],
+        weight: Column<Fixed>,
+    ) -> LinearConfig {
+        let s_bias = meta.selector();
+        let s_dot = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(x);
+        meta.enable_equality(acc);
+
+        meta.create_gate("bias", |meta| {
+            let s = meta.query_selector(s_bias);
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let bias = meta.query_fixed(weight);
+            vec![s * (acc - bias)]
+        });
+
+        meta.create_gate("dot", |meta| {
+            let s = meta.query_selector(s_dot);
+            let x = meta.query_advice(x, Rotation::cur());
+            let w = meta.query_fixed(weight);
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (acc_prev + w * x - acc)]
+        });
+
+        LinearConfig {
+            x,
+            acc,
+            weight,
+            s_bias,
+            s_dot,
+        }
+    }
+
+    /// | row |   x   |     acc     | weight | s_bias | s_dot |
+    /// |  0  |       |      b      |   b    |   1    |   0   |
+    /// |  1  |  x_0  | b + w_0·x_0 |  w_0   |   0    |   1   |
+    ///                         .
+    ///                         .
+    /// |  L  | x_L-1 |  w·x + b    | w_L-1  |   0    |   1   |
+    ///
+    /// each x_i is copied in, so the features may come from anywhere
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        x: &[AssignedCell<F, F>; L],
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        self.assign_with(layouter, x, |_, x| x)
+    }
+
+    /// as assign, x_i witnessed by `x_at(i, x_i)` rather than copied
+    fn assign_with(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &[AssignedCell<F, F>; L],
+        x_at: impl Fn(usize, Value<F>) -> Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let mut at = (0, "bias");
+        layouter
+            .assign_region(
+                || "linear",
+                |mut region| {
+                    at = (0, "s_bias");
+                    self.config.s_bias.enable(&mut region, 0)?;
+                    at = (0, "bias");
+                    let bias = Value::known(self.model.bias);
+                    region.assign_fixed(|| "bias", self.config.weight, 0, || bias)?;
+                    let mut acc_value = bias;
+                    let mut acc = region.assign_advice(|| "acc", self.config.acc, 0, || bias)?;
+
+                    for (i, (x, w)) in x.iter().zip(self.model.weights).enumerate() {
+                        let row = i + 1;
+                        at = (row, "s_dot");
+                        self.config.s_dot.enable(&mut region, row)?;
+
+                        at = (row, "weight");
+                        region.assign_fixed(
+                            || "weight",
+                            self.config.weight,
+                            row,
+                            || Value::known(w),
+                        )?;
+
+                        at = (row, "x");
+                        let value = x_at(i, x.value().copied());
+                        let cell = region.assign_advice(|| "x", self.config.x, row, || value)?;
+                        region.constrain_equal(cell.cell(), x.cell())?;
+
+                        at = (row, "acc");
+                        acc_value = acc_value + Value::known(w) * value;
+                        acc = region.assign_advice(|| "acc", self.config.acc, row, || acc_value)?;
+                    }
+
+                    Ok(acc)
+                },
+            )
+            .map_err(|e| FiboError::assign("linear", at, e))
+    }
+}
+
+impl<F: Field, const L: usize> Chip<F> for LinearChip<F, L> {
+    type Config = LinearConfig;
+    type Loaded = Model<F, L>;
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &self.model
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LinearCircuitConfig {
+    pub linear: LinearConfig,
+    pub instance: Column<Instance>,
+}
+
+/// proves w·x + b = y for the private x and the y in the instance column,
+/// the model being part of the circuit
+#[derive(Debug)]
+pub struct LinearCircuit<F, const L: usize> {
+    pub model: Model<F, L>,
+    pub x: [Value<F>; L],
+}
+
+impl<F: Field, const L: usize> LinearCircuit<F, L> {
+    pub fn new(model: Model<F, L>, x: [F; L]) -> Self {
+        Self {
+            model,
+            x: x.map(Value::known),
+        }
+    }
+
+    /// the circuit of the model alone, for keygen
+    pub fn keygen(model: Model<F, L>) -> Self {
+        Self {
+            model,
+            x: [Value::unknown(); L],
+        }
+    }
+
+    /// the features loaded and dotted, x_i witnessed by `x_at`
+    fn synthesize_with(
+        &self,
+        config: LinearCircuitConfig,
+        mut layouter: impl Layouter<F>,
+        x_at: impl Fn(usize, Value<F>) -> Value<F>,
+    ) -> Result<(), FiboError> {
+        let column = config.linear.x;
+        let mut at = (0, "x");
+        let x = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    let mut cells = Vec::with_capacity(L);
+                    for (row, x) in self.x.iter().enumerate() {
+                        at = (row, "x");
+                        cells.push(region.assign_advice(|| "x", column, row, || *x)?);
+                    }
+                    Ok(cells)
+                },
+            )
+            .map_err(|e| FiboError::assign("load", at, e))?;
+        let x: [AssignedCell<F, F>; L] = x.try_into().unwrap();
+
+        let chip = LinearChip::construct(config.linear, self.model);
+        let y = chip.assign_with(layouter.namespace(|| "w·x + b"), &x, x_at)?;
+        layouter
+            .constrain_instance(y.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))
+    }
+}
+
+impl<F: Field, const L: usize> Circuit<F> for LinearCircuit<F, L> {
+    type Config = LinearCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the model shapes the circuit
+        Self::keygen(self.model)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 2].map(|_| meta.advice_column());
+        let weight = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        LinearCircuitConfig {
+            linear: LinearChip::<F, L>::configure(meta, advice, weight),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, |_, x| x)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock_fails, mock_ok,
+        proving::{keygen, prove, verify},
+    };
+    use halo2_proofs::{
+        dev::VerifyFailure,
+        pasta::{EqAffine, Fp},
+        poly::commitment::Params,
+    };
+    use rand_core::OsRng;
+
+    const K: u32 = 4;
+
+    fn model() -> Model<Fp, 4> {
+        Model::new([3, 2, 5, 1].map(Fp::from), Fp::from(7))
+    }
+
+    fn features() -> [Fp; 4] {
+        [1, 2, 3, 4].map(Fp::from)
+    }
+
+    #[test]
+    fn evaluate() {
+        // 3 + 4 + 15 + 4 + 7
+        assert_eq!(model().evaluate(&features()), Fp::from(33));
+        let negative = Model::new([-Fp::one(); 4], Fp::zero());
+        assert_eq!(negative.evaluate(&features()), -Fp::from(10));
+    }
+
+    #[test]
+    fn mock() {
+        let circuit = LinearCircuit::new(model(), features());
+        mock_ok!(circuit, K, vec![vec![Fp::from(33)]]);
+        mock_fails!(circuit, K, vec![vec![Fp::from(34)]]);
+    }
+
+    #[test]
+    fn empty() {
+        let circuit = LinearCircuit::new(Model::new([], Fp::from(7)), []);
+        mock_ok!(circuit, K, vec![vec![Fp::from(7)]]);
+    }
+
+    /// the model with x_2 witnessed as 4 where it is copied from 3
+    struct SwappedX(LinearCircuit<Fp, 4>);
+
+    impl Circuit<Fp> for SwappedX {
+        type Config = LinearCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            LinearCircuit::<Fp, 4>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let x_at = |i, x| if i == 2 { Value::known(Fp::from(4)) } else { x };
+            Ok(self.0.synthesize_with(config, layouter, x_at)?)
+        }
+    }
+
+    #[test]
+    fn swapped_x() {
+        // 33 + 5·(4 - 3): the gates hold, the copy of x_2 does not
+        let circuit = SwappedX(LinearCircuit::new(model(), features()));
+        let failures = mock_fails!(circuit, K, vec![vec![Fp::from(38)]]);
+        assert!(failures
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+    }
+
+    #[test]
+    fn vk_pins_weights() {
+        let params: Params<EqAffine> = Params::new(K);
+        let pk = keygen(&params, &LinearCircuit::keygen(model())).unwrap();
+
+        let y = model().evaluate(&features());
+        let proof = prove(
+            &params,
+            &pk,
+            LinearCircuit::new(model(), features()),
+            &[&[y]],
+            OsRng,
+        )
+        .unwrap();
+        assert!(verify(&params, pk.get_vk(), &proof, &[&[y]]).is_ok());
+
+        // a proof of the same y under other weights, against the vk of the first
+        let other = Model::new([3, 2, 5, 1].map(Fp::from), Fp::from(8));
+        let features = [1, 2, 3, 3].map(Fp::from);
+        assert_eq!(other.evaluate(&features), y);
+        let other_pk = keygen(&params, &LinearCircuit::keygen(other)).unwrap();
+        let circuit = LinearCircuit::new(other, features);
+        let proof = prove(&params, &other_pk, circuit, &[&[y]], OsRng).unwrap();
+        assert!(verify(&params, other_pk.get_vk(), &proof, &[&[y]]).is_ok());
+        assert!(verify(&params, pk.get_vk(), &proof, &[&[y]]).is_err());
+    }
+}
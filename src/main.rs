@@ -0,0 +1,490 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use fibo::{
+    commit_reveal::{self, commit, CommitRevealCircuit},
+    encoding::{fp_from_hex, from_hex, to_hex},
+    envelope::{self, ProofEnvelope},
+    failures::explain_failures,
+    fibonacci::{self, public_inputs::PublicInputs, reference, row_based, single_column},
+    params_cache,
+    proving::{self, Proof},
+    summary::describe,
+    trace,
+    witness::{parse_fp, WitnessFile},
+};
+use halo2_proofs::{dev::MockProver, pasta::Fp, plonk::Circuit};
+use rand_core::OsRng;
+use std::{
+    error::Error,
+    fmt::Debug,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+};
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(about = "prove the n-th fibonacci number with halo2")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// run the circuit under MockProver
+    Mock {
+        #[command(flatten)]
+        shape: Shape,
+        #[command(flatten)]
+        seeds: Seeds,
+        /// claimed public inputs a,b,out, computed from the seeds if absent
+        #[arg(long, value_delimiter = ',')]
+        public: Option<Vec<u64>>,
+        /// a json or toml file of a, b, n and optionally out, in place of the
+        /// seeds, n and public
+        #[arg(long, conflicts_with = "public")]
+        input: Option<PathBuf>,
+    },
+    /// create a real proof and write it to a json envelope
+    Prove {
+        #[command(flatten)]
+        shape: Shape,
+        #[command(flatten)]
+        seeds: Seeds,
+        #[arg(long, default_value = "proof.json")]
+        out: PathBuf,
+        /// json writes the envelope to out, hex prints the proof instead
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// the report of the proof on stderr as json, rather than a table
+        #[arg(long)]
+        json_report: bool,
+        #[arg(long, default_value = "params")]
+        params_dir: PathBuf,
+    },
+    /// summarize the constraint system of a layout
+    Describe {
+        #[arg(long, value_enum, default_value_t = Layout::ThreeCol)]
+        layout: Layout,
+        #[arg(long, default_value_t = 4)]
+        k: u32,
+    },
+    /// verify the proof of an envelope against the public inputs it claims
+    Verify {
+        #[arg(long, default_value = "proof.json")]
+        proof: PathBuf,
+        /// json reads the envelope at proof, hex the proof argument, the
+        /// public inputs and the shape
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// the proof as 0x prefixed hex, with --format hex
+        proof_hex: Option<String>,
+        /// a json list of envelope paths, relative to it, to verify as one
+        /// batch instead of the proof
+        #[arg(long, conflicts_with = "proof_hex")]
+        batch: Option<PathBuf>,
+        /// public inputs a,b,out. with --format json, decimal or 0x hex
+        /// numbers the envelope must prove if they are given; with --format
+        /// hex, hex field elements
+        #[arg(long, value_delimiter = ',')]
+        public: Vec<String>,
+        #[command(flatten)]
+        shape: Shape,
+        #[arg(long, default_value = "params")]
+        params_dir: PathBuf,
+    },
+    /// commit to x, then prove knowledge of it and verify the proof
+    CommitReveal {
+        #[arg(long)]
+        x: u64,
+        #[arg(long, default_value = "params")]
+        params_dir: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Layout {
+    /// row_based: three advice columns, one region per row
+    ThreeCol,
+    /// single_column: one advice column and rotations
+    OneCol,
+}
+
+impl Layout {
+    /// the name of the circuit in a proof envelope
+    fn circuit(self) -> &'static str {
+        match self {
+            Layout::ThreeCol => "row_based",
+            Layout::OneCol => "single_column",
+        }
+    }
+
+    fn from_circuit(circuit: &str) -> Option<Self> {
+        [Layout::ThreeCol, Layout::OneCol]
+            .into_iter()
+            .find(|layout| layout.circuit() == circuit)
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// a proof envelope in a json file
+    Json,
+    /// 0x prefixed hex, field elements as their 32 byte little endian repr
+    Hex,
+}
+
+/// what determines the circuit, hence its keys
+#[derive(Args)]
+struct Shape {
+    #[arg(long, value_enum, default_value_t = Layout::ThreeCol)]
+    layout: Layout,
+    /// a number, or auto for the least k fitting n
+    #[arg(long, default_value = "4")]
+    k: KArg,
+    /// index of the fibonacci number to prove
+    #[arg(long, default_value_t = 10)]
+    n: usize,
+}
+
+/// the largest k --k auto tries
+const MAX_K: u32 = 20;
+
+#[derive(Clone, Copy)]
+enum KArg {
+    Auto,
+    Exact(u32),
+}
+
+impl FromStr for KArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(KArg::Auto),
+            s => s
+                .parse()
+                .map(KArg::Exact)
+                .map_err(|_| format!("expected a number or auto, got {s}")),
+        }
+    }
+}
+
+impl Shape {
+    /// k, or the least k fitting the n-th number in the layout, for an n
+    /// either layout can prove
+    fn k(&self, n: usize) -> Result<u32, Box<dyn Error>> {
+        fibonacci::check_n(n)?;
+        let KArg::Exact(k) = self.k else {
+            let k = match self.layout {
+                Layout::ThreeCol => row_based::MyCircuit::<Fp>::with_min_k(n, MAX_K)?.1,
+                Layout::OneCol => single_column::MyCircuit::<Fp>::with_min_k(n, MAX_K)?.1,
+            };
+            eprintln!("k = {k}");
+            return Ok(k);
+        };
+        Ok(k)
+    }
+}
+
+#[derive(Args)]
+struct Seeds {
+    #[arg(long, default_value_t = 1)]
+    a: u64,
+    #[arg(long, default_value_t = 1)]
+    b: u64,
+}
+
+impl Seeds {
+    fn publics(&self, n: usize) -> PublicInputs<Fp> {
+        PublicInputs::new(Fp::from(self.a), Fp::from(self.b), n)
+    }
+}
+
+fn to_publics(public: &[u64]) -> Result<PublicInputs<Fp>, Box<dyn Error>> {
+    match *public {
+        [a, b, out] => Ok(PublicInputs {
+            a: Fp::from(a),
+            b: Fp::from(b),
+            out: Fp::from(out),
+        }),
+        _ => Err(format!("expected 3 public inputs a,b,out, got {}", public.len()).into()),
+    }
+}
+
+fn mock<C: Circuit<Fp>>(k: u32, circuit: &C, publics: &PublicInputs<Fp>) -> CliResult {
+    let instance = vec![publics.to_instance_column()];
+    let prover = tracing::info_span!("mock").in_scope(|| MockProver::run(k, circuit, instance))?;
+    match prover.verify() {
+        Ok(()) => {
+            println!("MockProver satisfied");
+            Ok(())
+        }
+        Err(failures) => {
+            println!(
+                "MockProver not satisfied, {}",
+                explain_failures(&prover, &failures)
+            );
+            Err(format!("{} constraint(s) failed", failures.len()).into())
+        }
+    }
+}
+
+/// how prove writes its proof and its report
+struct Output<'a> {
+    out: &'a Path,
+    format: Format,
+    json_report: bool,
+}
+
+fn prove<C: Circuit<Fp> + Debug>(
+    shape: &Shape,
+    circuit: C,
+    publics: &PublicInputs<Fp>,
+    params_dir: &Path,
+    output: Output,
+) -> CliResult {
+    let k = shape.k(shape.n)?;
+    let params = params_cache::load_or_generate(k, params_dir)?;
+    let instance = publics.to_instance_column();
+    let (proof, report) = proving::prove_with_report(k, &params, circuit, &[&instance], OsRng)?;
+    if output.json_report {
+        eprintln!("{}", serde_json::to_string(&report)?);
+    } else {
+        eprint!("{}", report);
+    }
+
+    let Output { out, format, .. } = output;
+    match format {
+        Format::Json => {
+            let circuit = shape.layout.circuit();
+            let envelope = ProofEnvelope::new(circuit, k, shape.n, &[instance], &proof);
+            envelope.save_json(out)?;
+            println!("proof written to {}", out.display());
+        }
+        Format::Hex => println!("{}", to_hex(proof.as_bytes())),
+    }
+    Ok(())
+}
+
+fn verify<C: Circuit<Fp>>(envelope: &ProofEnvelope, circuit: &C, params_dir: &Path) -> CliResult {
+    let params = params_cache::load_or_generate(envelope.k, params_dir)?;
+    let pk = proving::keygen(&params, circuit)?;
+    let instances = envelope.instances()?;
+    let instances: Vec<&[Fp]> = instances.iter().map(Vec::as_slice).collect();
+    proving::verify(&params, pk.get_vk(), &envelope.proof()?, &instances)
+        .map_err(|e| format!("verification failed: {}", e))?;
+    println!("proof verified");
+    Ok(())
+}
+
+/// verifies the envelopes of a manifest, all of one circuit, at once
+fn verify_batch<C: Circuit<Fp>>(
+    envelopes: &[ProofEnvelope],
+    circuit: &C,
+    params_dir: &Path,
+) -> CliResult {
+    let params = params_cache::load_or_generate(envelopes[0].k, params_dir)?;
+    let pk = proving::keygen(&params, circuit)?;
+    // load_manifest accepts one instance column
+    let items = envelopes
+        .iter()
+        .map(|envelope| Ok((envelope.proof()?, envelope.instances()?.remove(0))))
+        .collect::<Result<Vec<_>, envelope::EnvelopeError>>()?;
+    proving::verify_batch(&params, pk.get_vk(), &items)
+        .map_err(|e| format!("verification failed: {}", e))?;
+    println!("{} proofs verified", items.len());
+    Ok(())
+}
+
+/// the public inputs of --format hex, each a 32 byte little endian field
+/// element
+fn parse_hex_public(public: &[String]) -> Result<Vec<Fp>, Box<dyn Error>> {
+    public
+        .iter()
+        .map(|value| fp_from_hex(value).map_err(|e| format!("public input: {e}").into()))
+        .collect()
+}
+
+/// fails unless the envelope proves the public inputs the verifier expects,
+/// if it gave any
+fn check_public(envelope: &ProofEnvelope, public: &[String]) -> CliResult {
+    if public.is_empty() {
+        return Ok(());
+    }
+    let public = public
+        .iter()
+        .map(|value| parse_fp("public", value))
+        .collect::<Result<Vec<_>, _>>()?;
+    if envelope.instances()? != [public] {
+        return Err("the envelope proves other public inputs than --public".into());
+    }
+    Ok(())
+}
+
+/// the envelope of a proof and public inputs given in hex
+fn hex_envelope(
+    shape: &Shape,
+    proof: Option<&str>,
+    public: &[String],
+) -> Result<ProofEnvelope, Box<dyn Error>> {
+    let proof = proof.ok_or("--format hex takes the proof as an argument")?;
+    let proof = Proof::from_bytes(&from_hex(proof).map_err(|e| format!("proof: {e}"))?);
+    let instance = parse_hex_public(public)?;
+    let envelope = ProofEnvelope::new(
+        shape.layout.circuit(),
+        shape.k(shape.n)?,
+        shape.n,
+        &[instance],
+        &proof,
+    );
+    envelope.validate()?;
+    Ok(envelope)
+}
+
+fn commit_reveal(x: u64, params_dir: &Path) -> CliResult {
+    let params = params_cache::load_or_generate(commit_reveal::K, params_dir)?;
+    let pk = proving::keygen(&params, &CommitRevealCircuit::default())?;
+
+    let x = Fp::from(x);
+    let c = commit(x);
+    println!("commitment {:?}", c);
+    let proof = proving::prove(&params, &pk, CommitRevealCircuit::new(x), &[&[c]], OsRng)?;
+    proving::verify(&params, pk.get_vk(), &proof, &[&[c]])
+        .map_err(|e| format!("verification failed: {}", e))?;
+    println!("proof verified");
+    Ok(())
+}
+
+fn run(cli: Cli) -> CliResult {
+    match cli.command {
+        Command::Mock {
+            shape,
+            seeds,
+            public,
+            input,
+        } => {
+            let (n, publics) = match (input, public) {
+                (Some(input), _) => {
+                    let file = WitnessFile::load(&input)?;
+                    if file.out.is_none() {
+                        println!("out {:?}", file.publics().out);
+                    }
+                    (file.n, file.publics())
+                }
+                (None, Some(public)) => (shape.n, to_publics(&public)?),
+                (None, None) => (shape.n, seeds.publics(shape.n)),
+            };
+            let max_exact = reference::max_exact_index();
+            if n > max_exact {
+                eprintln!(
+                    "warning: n = {n} is past {max_exact}, the last n below the modulus, so the output is mod p"
+                );
+            }
+            let k = shape.k(n)?;
+            match shape.layout {
+                Layout::ThreeCol => {
+                    let circuit = row_based::MyCircuit::<Fp>::new(n);
+                    mock(k, &circuit, &publics)
+                }
+                Layout::OneCol => {
+                    let circuit = single_column::MyCircuit::<Fp>::new(n);
+                    mock(k, &circuit, &publics)
+                }
+            }
+        }
+        Command::Prove {
+            shape,
+            seeds,
+            out,
+            format,
+            json_report,
+            params_dir,
+        } => {
+            let publics = seeds.publics(shape.n);
+            let output = Output {
+                out: &out,
+                format,
+                json_report,
+            };
+            match shape.layout {
+                Layout::ThreeCol => {
+                    let circuit = row_based::MyCircuit::<Fp>::new(shape.n);
+                    prove(&shape, circuit, &publics, &params_dir, output)
+                }
+                Layout::OneCol => {
+                    let circuit = single_column::MyCircuit::<Fp>::new(shape.n);
+                    prove(&shape, circuit, &publics, &params_dir, output)
+                }
+            }
+        }
+        Command::Describe { layout, k } => {
+            let summary = match layout {
+                Layout::ThreeCol => describe::<Fp, row_based::MyCircuit<Fp>>(k)?,
+                Layout::OneCol => describe::<Fp, single_column::MyCircuit<Fp>>(k)?,
+            };
+            print!("{}", summary);
+            Ok(())
+        }
+        Command::Verify {
+            batch: Some(manifest),
+            params_dir,
+            ..
+        } => {
+            let envelopes = envelope::load_manifest(&manifest)?;
+            let first = envelopes.first().ok_or("the manifest lists no proofs")?;
+            match Layout::from_circuit(&first.circuit).unwrap() {
+                Layout::ThreeCol => {
+                    let circuit = row_based::MyCircuit::<Fp>::new(first.n);
+                    verify_batch(&envelopes, &circuit, &params_dir)
+                }
+                Layout::OneCol => {
+                    let circuit = single_column::MyCircuit::<Fp>::new(first.n);
+                    verify_batch(&envelopes, &circuit, &params_dir)
+                }
+            }
+        }
+        Command::Verify {
+            proof,
+            format,
+            proof_hex,
+            public,
+            shape,
+            params_dir,
+            ..
+        } => {
+            let envelope = match format {
+                Format::Json => {
+                    let envelope = ProofEnvelope::load_json(&proof)?;
+                    check_public(&envelope, &public)?;
+                    envelope
+                }
+                Format::Hex => hex_envelope(&shape, proof_hex.as_deref(), &public)?,
+            };
+            // load_json only accepts the circuits of the layouts
+            match Layout::from_circuit(&envelope.circuit).unwrap() {
+                Layout::ThreeCol => {
+                    let circuit = row_based::MyCircuit::<Fp>::new(envelope.n);
+                    verify(&envelope, &circuit, &params_dir)
+                }
+                Layout::OneCol => {
+                    let circuit = single_column::MyCircuit::<Fp>::new(envelope.n);
+                    verify(&envelope, &circuit, &params_dir)
+                }
+            }
+        }
+        Command::CommitReveal { x, params_dir } => commit_reveal(x, &params_dir),
+    }
+}
+
+fn main() -> ExitCode {
+    trace::init_tracing();
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
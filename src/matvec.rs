@@ -0,0 +1,179 @@
+//! M·x = y for a public R×C matrix M, a private x and a public y, each entry
+//! of y an inner product of a row of M with the same x cells
+//!
+//! the instance column holds M row by row, then y
+
+use crate::{
+    error::FiboError,
+    inner_product::{Entry, InnerProductChip, InnerProductConfig},
+};
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*};
+
+/// M·x, computed natively
+pub fn mat_vec<F: Field, const R: usize, const C: usize>(m: &[[F; C]; R], x: &[F; C]) -> [F; R] {
+    m.map(|row| row.iter().zip(x).map(|(m, x)| *m * x).sum())
+}
+
+/// the instance column of M·x = y: M row by row, then y
+pub fn instance<F: Field, const R: usize, const C: usize>(m: &[[F; C]; R], y: &[F; R]) -> Vec<F> {
+    m.iter().flatten().chain(y).copied().collect()
+}
+
+/// proves M·x = y for the private x, M and y being the instance
+#[derive(Debug)]
+pub struct MatVecCircuit<F, const R: usize, const C: usize> {
+    pub x: [Value<F>; C],
+}
+
+impl<F: Field, const R: usize, const C: usize> Default for MatVecCircuit<F, R, C> {
+    fn default() -> Self {
+        Self {
+            x: [Value::unknown(); C],
+        }
+    }
+}
+
+impl<F: Field, const R: usize, const C: usize> MatVecCircuit<F, R, C> {
+    pub fn new(x: [F; C]) -> Self {
+        Self {
+            x: x.map(Value::known),
+        }
+    }
+
+    /// the rows of M·x, the x of row i witnessed by `x_at(i, j, x_j)`
+    fn synthesize_with(
+        &self,
+        config: InnerProductConfig,
+        mut layouter: impl Layouter<F>,
+        x_at: impl Fn(usize, usize, Value<F>) -> Value<F>,
+    ) -> Result<(), FiboError> {
+        let mut at = (0, "x");
+        let x = layouter
+            .assign_region(
+                || "load x",
+                |mut region| {
+                    let mut x = Vec::with_capacity(C);
+                    for (offset, value) in self.x.iter().enumerate() {
+                        at = (offset, "x");
+                        x.push(region.assign_advice(|| "x", config.b, offset, || *value)?);
+                    }
+                    Ok(x)
+                },
+            )
+            .map_err(|e| FiboError::assign("load x", at, e))?;
+
+        let chip = InnerProductChip::construct(config);
+        let x: Vec<_> = x.iter().map(Entry::Copy).collect();
+        for i in 0..R {
+            let m_i: Vec<_> = (0..C).map(|j| Entry::Instance(i * C + j)).collect();
+            let y_i = chip.assign_entries_with(
+                layouter.namespace(|| format!("row {i}")),
+                &m_i,
+                &x,
+                |j, x_j| x_at(i, j, x_j),
+            )?;
+            chip.expose_public(layouter.namespace(|| "y"), &y_i, R * C + i)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: Field, const R: usize, const C: usize> Circuit<F> for MatVecCircuit<F, R, C> {
+    type Config = InnerProductConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        InnerProductChip::configure(meta, instance, constant)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, |_, _, x| x)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibonacci::min_k, mock_fails, mock_ok};
+    use halo2_proofs::{dev::VerifyFailure, pasta::Fp};
+
+    fn matrix<const R: usize, const C: usize>(m: [[u64; C]; R]) -> [[Fp; C]; R] {
+        m.map(|row| row.map(Fp::from))
+    }
+
+    fn m() -> [[Fp; 3]; 3] {
+        matrix([[1, 2, 3], [4, 5, 6], [7, 8, 10]])
+    }
+
+    /// the instance of M·x = y for the 3 by 3 M
+    fn y(y: [u64; 3]) -> Vec<Vec<Fp>> {
+        vec![instance(&m(), &y.map(Fp::from))]
+    }
+
+    #[test]
+    fn three_by_three() {
+        let x = [1, 2, 3].map(Fp::from);
+        assert_eq!(mat_vec(&m(), &x), [14, 32, 53].map(Fp::from));
+
+        let circuit = MatVecCircuit::<Fp, 3, 3>::new(x);
+        mock_ok!(circuit, 5, y([14, 32, 53]));
+        mock_fails!(circuit, 5, y([14, 33, 53]));
+    }
+
+    #[test]
+    fn eight_by_eight() {
+        let m: [[Fp; 8]; 8] =
+            std::array::from_fn(|i| std::array::from_fn(|j| Fp::from((i * 8 + j) as u64)));
+        let x = std::array::from_fn(|j| Fp::from(j as u64 + 1));
+        let circuit = MatVecCircuit::<Fp, 8, 8>::new(x);
+        let k = min_k::<Fp, MatVecCircuit<Fp, 8, 8>>(8 + 8 * 9);
+        mock_ok!(circuit, k, vec![instance(&m, &mat_vec(&m, &x))]);
+    }
+
+    /// M·x with the second row using x + (1, 0, 0)
+    struct SecondRow(MatVecCircuit<Fp, 3, 3>);
+
+    impl Circuit<Fp> for SecondRow {
+        type Config = InnerProductConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(MatVecCircuit::default())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MatVecCircuit::<Fp, 3, 3>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            Ok(self.0.synthesize_with(config, layouter, |i, j, x| {
+                if (i, j) == (1, 0) {
+                    x + Value::known(Fp::one())
+                } else {
+                    x
+                }
+            })?)
+        }
+    }
+
+    #[test]
+    fn shared_x() {
+        // the second row is 4·2 + 5·2 + 6·3 = 36, consistent with its own x
+        let circuit = SecondRow(MatVecCircuit::new([1, 2, 3].map(Fp::from)));
+        let failures = mock_fails!(circuit, 5, y([14, 36, 53]));
+        // only the copy of x_0 gives it away
+        assert!(failures
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+    }
+}
@@ -0,0 +1,253 @@
+//! a private value is in a public allowlist, the allowlist being a lookup
+//! table fixed into the circuit
+//!
+//! the lookup takes (s, s·value) into a table of (1, entry) rows and the
+//! single row (0, 0), which unselected rows hit, so 0 is only a member when
+//! it is on the list
+
+use std::marker::PhantomData;
+
+use crate::{
+    error::FiboError,
+    fibonacci::{min_k, usable_rows},
+};
+use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct MembershipConfig {
+    pub value: Column<Advice>,
+    pub selector: Selector,
+    /// the flag and the entry of each table row
+    pub table: [TableColumn; 2],
+}
+
+pub struct MembershipChip<F: Field> {
+    config: MembershipConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> MembershipChip<F> {
+    pub fn construct(config: MembershipConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> MembershipConfig {
+        let selector = meta.complex_selector();
+        let table = [(); 2].map(|_| meta.lookup_table_column());
+
+        meta.lookup(|meta| {
+            let s = meta.query_selector(selector);
+            let value = meta.query_advice(value, Rotation::cur());
+            vec![(s.clone(), table[0]), (s * value, table[1])]
+        });
+
+        MembershipConfig {
+            value,
+            selector,
+            table,
+        }
+    }
+
+    /// the (0, 0) row, then (1, entry) for each entry of the allowlist
+    pub fn load_table(
+        &self,
+        mut layouter: impl Layouter<F>,
+        allowlist: &[F],
+    ) -> Result<(), FiboError> {
+        if allowlist.is_empty() {
+            return Err(FiboError::TooFewRows { rows: 0, min: 1 });
+        }
+
+        let [flag, entry] = self.config.table;
+        let mut at = (0, "table");
+        layouter
+            .assign_table(
+                || "allowlist",
+                |mut table| {
+                    let rows = [(F::ZERO, F::ZERO)]
+                        .into_iter()
+                        .chain(allowlist.iter().map(|value| (F::ONE, *value)));
+                    for (offset, (f, value)) in rows.enumerate() {
+                        at = (offset, "table");
+                        table.assign_cell(|| "flag", flag, offset, || Value::known(f))?;
+                        table.assign_cell(|| "entry", entry, offset, || Value::known(value))?;
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| FiboError::assign("allowlist", at, e))
+    }
+
+    /// assigns the value, constrained to be on the allowlist
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, FiboError> {
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "member",
+                |mut region| {
+                    at = (0, "selector");
+                    self.config.selector.enable(&mut region, 0)?;
+                    at = (0, "value");
+                    region.assign_advice(|| "value", self.config.value, 0, || value)
+                },
+            )
+            .map_err(|e| FiboError::assign("member", at, e))
+    }
+}
+
+impl<F: Field> Chip<F> for MembershipChip<F> {
+    type Config = MembershipConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// proves the private id is on the allowlist without revealing which entry it is
+#[derive(Debug, Default)]
+pub struct MembershipCircuit<F> {
+    pub allowlist: Vec<F>,
+    pub id: Value<F>,
+}
+
+impl<F: Field> MembershipCircuit<F> {
+    pub fn new(allowlist: Vec<F>, id: F) -> Self {
+        Self {
+            allowlist,
+            id: Value::known(id),
+        }
+    }
+
+    /// same as new, but checks the table is neither empty nor larger than 2^k fits
+    pub fn with_k(allowlist: Vec<F>, id: F, k: u32) -> Result<Self, FiboError> {
+        if allowlist.is_empty() {
+            return Err(FiboError::TooFewRows { rows: 0, min: 1 });
+        }
+        let rows = Self::table_rows(&allowlist);
+        let max = usable_rows::<F, Self>(k) - 1;
+        if rows > max {
+            return Err(FiboError::TableTooLarge { rows, k, max });
+        }
+        Ok(Self::new(allowlist, id))
+    }
+
+    /// the smallest k fitting the table
+    pub fn min_k(&self) -> u32 {
+        // halo2 pads the table from the row after it, which has to be usable too
+        min_k::<F, Self>(Self::table_rows(&self.allowlist) + 1)
+    }
+
+    /// the entries and the (0, 0) row
+    fn table_rows(allowlist: &[F]) -> usize {
+        allowlist.len() + 1
+    }
+}
+
+impl<F: Field> Circuit<F> for MembershipCircuit<F> {
+    type Config = MembershipConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            allowlist: self.allowlist.clone(),
+            id: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        MembershipChip::configure(meta, value)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = MembershipChip::construct(config);
+        chip.load_table(layouter.namespace(|| "allowlist"), &self.allowlist)?;
+        chip.assign(layouter.namespace(|| "id"), self.id)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    fn allowlist() -> Vec<Fp> {
+        [1001, 1002, 2024, 31337].map(Fp::from).to_vec()
+    }
+
+    fn verifies(allowlist: Vec<Fp>, id: Fp) {
+        let circuit = MembershipCircuit::new(allowlist, id);
+        mock_ok!(circuit, circuit.min_k(), vec![]);
+    }
+
+    fn fails(allowlist: Vec<Fp>, id: Fp) {
+        let circuit = MembershipCircuit::new(allowlist, id);
+        mock_fails!(circuit, circuit.min_k(), vec![]);
+    }
+
+    #[test]
+    fn member() {
+        for id in allowlist() {
+            verifies(allowlist(), id);
+        }
+    }
+
+    #[test]
+    fn non_member() {
+        fails(allowlist(), Fp::from(1003));
+        // 0 is in the table for the unselected rows, but not on the list
+        fails(allowlist(), Fp::zero());
+        verifies(vec![Fp::zero()], Fp::zero());
+    }
+
+    #[test]
+    fn empty_table() {
+        let result = MembershipCircuit::with_k(vec![], Fp::one(), 4);
+        assert!(matches!(
+            result,
+            Err(FiboError::TooFewRows { rows: 0, min: 1 })
+        ));
+
+        let circuit = MembershipCircuit::new(vec![], Fp::one());
+        assert!(matches!(
+            MockProver::run(4, &circuit, vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn table_too_large() {
+        let allowlist: Vec<_> = (0..16).map(Fp::from).collect();
+        let error = MembershipCircuit::with_k(allowlist.clone(), Fp::one(), 4).unwrap_err();
+        assert!(matches!(
+            error,
+            FiboError::TableTooLarge { rows: 17, k: 4, .. }
+        ));
+        assert!(
+            error.to_string().contains("17 rows does not fit in 2^4"),
+            "{error}"
+        );
+
+        let circuit = MembershipCircuit::with_k(allowlist, Fp::one(), 5).unwrap();
+        assert_eq!(circuit.min_k(), 5);
+        assert!(MockProver::run(4, &circuit, vec![]).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+//! a small memory: (address, value) writes in one part of a region, reads of
+//! them in another, each read checked to be one of the writes
+//!
+//! the table is witnessed, so this is a dynamic lookup. halo2_proofs 0.3 only
+//! looks up into fixed table columns and has no lookup_any, so the lookup is
+//! written out as the log-derivative argument: with t = addr + α·value + α²·counter,
+//!
+//!   Σ_writes m / (γ - t) = Σ_reads 1 / (γ - t)
+//!
+//! for the number m of reads of each write, kept by a running sum that ends on
+//! 0. the fixed tag column is 1 on the writes, marking the table rows
+//!
+//! the counter of a write is its position among the writes, from 1, and a
+//! read names the counter of the write it saw. nothing makes that the last
+//! write to the address: a stale read passes, under the old counter, which is
+//! public along with the value read
+//!
+//! INSECURE: α and γ come from the instance column, chosen by the prover, as
+//! in the shuffle circuit
+
+use crate::{error::FiboError, fibonacci::min_k};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// instance row of the compression challenge α
+pub const ALPHA_ROW: usize = 0;
+/// instance row of the shift challenge γ
+pub const GAMMA_ROW: usize = 1;
+/// instance row of the first read's value, its counter following, then the next read's
+pub const READS_ROW: usize = 2;
+
+/// the value and counter of the last write to the address, if any
+pub fn latest(writes: &[(u64, u64)], addr: u64) -> Option<(u64, u64)> {
+    writes
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, (a, _))| *a == addr)
+        .map(|(i, (_, value))| (*value, i as u64 + 1))
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryConfig {
+    /// addr, value, counter, m, inv, sum, α and γ
+    pub advice: [Column<Advice>; 8],
+    /// 1 on the writes, 0 on the reads
+    pub tag: Column<Fixed>,
+    /// on the writes and the reads: inv·(γ - t) = 1, and the running sum
+    pub s_entry: Selector,
+    /// on the writes but the first: the counter is one more than the one before
+    pub s_count: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// proves each read is (addr, value, counter) of one of the writes, and
+/// exposes the value and counter of each read
+#[derive(Debug, Default)]
+pub struct MemoryCircuit<F> {
+    /// (addr, value)
+    pub writes: Vec<[Value<F>; 2]>,
+    /// (addr, value, counter)
+    pub reads: Vec<[Value<F>; 3]>,
+}
+
+impl<F: PrimeField> MemoryCircuit<F> {
+    /// reads of the last write to each address, or of (0, 0) where there is none
+    pub fn new(writes: &[(u64, u64)], addrs: &[u64]) -> Self {
+        let reads: Vec<_> = addrs
+            .iter()
+            .map(|&addr| {
+                let (value, counter) = latest(writes, addr).unwrap_or_default();
+                (addr, value, counter)
+            })
+            .collect();
+        Self::with_reads(writes, &reads)
+    }
+
+    /// the reads as given, (addr, value, counter)
+    pub fn with_reads(writes: &[(u64, u64)], reads: &[(u64, u64, u64)]) -> Self {
+        let known = |v| Value::known(F::from(v));
+        Self {
+            writes: writes.iter().map(|&(a, v)| [known(a), known(v)]).collect(),
+            reads: reads
+                .iter()
+                .map(|&(a, v, c)| [known(a), known(v), known(c)])
+                .collect(),
+        }
+    }
+
+    /// the instance column for the challenges and the (value, counter) of each read
+    pub fn instance(alpha: F, gamma: F, reads: &[(u64, u64)]) -> Vec<F> {
+        let reads = reads
+            .iter()
+            .flat_map(|&(value, counter)| [F::from(value), F::from(counter)]);
+        [alpha, gamma].into_iter().chain(reads).collect()
+    }
+
+    /// the smallest k fitting the writes, the reads and the row starting the sum
+    pub fn min_k(&self) -> u32 {
+        min_k::<F, Self>(1 + self.writes.len() + self.reads.len())
+    }
+
+    fn assign(
+        &self,
+        config: MemoryConfig,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), FiboError> {
+        let [col_addr, col_value, col_counter, col_m, col_inv, col_sum, col_alpha, col_gamma] =
+            config.advice;
+        let instance = config.instance;
+
+        // the writes with their counters, then the reads
+        let writes = self.writes.iter().enumerate().map(|(i, [addr, value])| {
+            let counter = Value::known(F::from(i as u64 + 1));
+            (true, [*addr, *value, counter])
+        });
+        let reads = self.reads.iter().map(|read| (false, *read));
+        let entries: Vec<_> = writes.chain(reads).collect();
+
+        let mut at = (0, "sum");
+        let exposed = layouter
+            .assign_region(
+                || "memory",
+                |mut region| {
+                    at = (0, "sum");
+                    let mut sum =
+                        region.assign_advice_from_constant(|| "sum", col_sum, 0, F::ZERO)?;
+                    let mut exposed = vec![];
+
+                    for (i, &(write, [addr, value, counter])) in entries.iter().enumerate() {
+                        let row = i + 1;
+                        at = (row, "selector");
+                        config.s_entry.enable(&mut region, row)?;
+                        at = (row, "alpha");
+                        let alpha = region.assign_advice_from_instance(
+                            || "alpha",
+                            instance,
+                            ALPHA_ROW,
+                            col_alpha,
+                            row,
+                        )?;
+                        at = (row, "gamma");
+                        let gamma = region.assign_advice_from_instance(
+                            || "gamma",
+                            instance,
+                            GAMMA_ROW,
+                            col_gamma,
+                            row,
+                        )?;
+                        let alpha = alpha.value().copied();
+                        let t = addr + alpha * value + alpha * alpha * counter;
+                        let inv = (gamma.value().copied() - t)
+                            .map(|d| Option::from(d.invert()).unwrap_or(F::ZERO));
+
+                        at = (row, "addr");
+                        region.assign_advice(|| "addr", col_addr, row, || addr)?;
+                        at = (row, "value");
+                        let value_cell =
+                            region.assign_advice(|| "value", col_value, row, || value)?;
+                        at = (row, "counter");
+                        let counter_cell = if write && i == 0 {
+                            region.assign_advice_from_constant(
+                                || "counter",
+                                col_counter,
+                                row,
+                                F::ONE,
+                            )?
+                        } else {
+                            region.assign_advice(|| "counter", col_counter, row, || counter)?
+                        };
+
+                        at = (row, "tag");
+                        let tag = Value::known(if write { F::ONE } else { F::ZERO });
+                        region.assign_fixed(|| "tag", config.tag, row, || tag)?;
+                        let m = if write {
+                            if i > 0 {
+                                at = (row, "s_count");
+                                config.s_count.enable(&mut region, row)?;
+                            }
+                            // the number of reads of this write
+                            let t_of = |[a, v, c]: [Value<F>; 3]| a + alpha * v + alpha * alpha * c;
+                            self.reads.iter().fold(Value::known(F::ZERO), |m, read| {
+                                m + t_of(*read).zip(t).map(|(r, t)| F::from((r == t) as u64))
+                            })
+                        } else {
+                            exposed.push((value_cell, counter_cell));
+                            Value::known(F::ZERO)
+                        };
+                        at = (row, "m");
+                        region.assign_advice(|| "m", col_m, row, || m)?;
+                        at = (row, "inv");
+                        region.assign_advice(|| "inv", col_inv, row, || inv)?;
+
+                        // + m/(γ - t) for a write, - 1/(γ - t) for a read
+                        let term = if write { m * inv } else { -inv };
+                        at = (row, "sum");
+                        let next = sum.value().copied() + term;
+                        sum = region.assign_advice(|| "sum", col_sum, row, || next)?;
+                    }
+
+                    at = (entries.len(), "sum");
+                    region.constrain_constant(sum.cell(), F::ZERO)?;
+                    Ok(exposed)
+                },
+            )
+            .map_err(|e| FiboError::assign("memory", at, e))?;
+
+        for (i, (value, counter)) in exposed.iter().enumerate() {
+            let row = READS_ROW + 2 * i;
+            layouter
+                .constrain_instance(value.cell(), instance, row)
+                .map_err(|e| FiboError::expose(row, e))?;
+            layouter
+                .constrain_instance(counter.cell(), instance, row + 1)
+                .map_err(|e| FiboError::expose(row + 1, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for MemoryCircuit<F> {
+    type Config = MemoryConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the number of writes and reads shape the circuit
+        Self {
+            writes: vec![[Value::unknown(); 2]; self.writes.len()],
+            reads: vec![[Value::unknown(); 3]; self.reads.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 8].map(|_| meta.advice_column());
+        let tag = meta.fixed_column();
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        let s_entry = meta.selector();
+        let s_count = meta.selector();
+
+        // enable the equality
+        for column in [advice[1], advice[2], advice[5], advice[6], advice[7]] {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        let [addr, value, counter, m, inv, sum, alpha, gamma] = advice;
+        meta.create_gate("entry", |meta| {
+            let s = meta.query_selector(s_entry);
+            let tag = meta.query_fixed(tag);
+            let [addr, value, counter, m, inv, sum, alpha, gamma] =
+                [addr, value, counter, m, inv, sum, alpha, gamma]
+                    .map(|column| meta.query_advice(column, Rotation::cur()));
+            let sum_prev = meta.query_advice(advice[5], Rotation::prev());
+            let t = addr + alpha.clone() * value + alpha.clone() * alpha * counter;
+            let one = Expression::Constant(F::ONE);
+            vec![
+                s.clone() * (inv.clone() * (gamma - t) - one.clone()),
+                s * (sum - sum_prev - tag.clone() * m * inv.clone() + (one - tag) * inv),
+            ]
+        });
+
+        meta.create_gate("counter", |meta| {
+            let s = meta.query_selector(s_count);
+            let prev = meta.query_advice(counter, Rotation::prev());
+            let counter = meta.query_advice(counter, Rotation::cur());
+            vec![s * (prev + Expression::Constant(F::ONE) - counter)]
+        });
+
+        MemoryConfig {
+            advice,
+            tag,
+            s_entry,
+            s_count,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.assign(config, layouter)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    fn writes() -> Vec<(u64, u64)> {
+        vec![(0x10, 7), (0x20, 9), (0x30, 0), (0x10, 42)]
+    }
+
+    fn verifies(circuit: &MemoryCircuit<Fp>, reads: &[(u64, u64)]) {
+        let alpha = Fp::from(0x2545f4914f6cdd1d);
+        let gamma = Fp::from(0x9e3779b97f4a7c15);
+        let instance = MemoryCircuit::instance(alpha, gamma, reads);
+        mock_ok!(*circuit, circuit.min_k(), vec![instance]);
+    }
+
+    fn fails(circuit: &MemoryCircuit<Fp>, reads: &[(u64, u64)]) {
+        let alpha = Fp::from(0x2545f4914f6cdd1d);
+        let gamma = Fp::from(0x9e3779b97f4a7c15);
+        let instance = MemoryCircuit::instance(alpha, gamma, reads);
+        mock_fails!(*circuit, circuit.min_k(), vec![instance]);
+    }
+
+    #[test]
+    fn latest_write() {
+        assert_eq!(latest(&writes(), 0x10), Some((42, 4)));
+        assert_eq!(latest(&writes(), 0x30), Some((0, 3)));
+        assert_eq!(latest(&writes(), 0x40), None);
+    }
+
+    #[test]
+    fn reads_match_writes() {
+        let circuit = MemoryCircuit::new(&writes(), &[0x20, 0x10, 0x30, 0x20]);
+        verifies(&circuit, &[(9, 2), (42, 4), (0, 3), (9, 2)]);
+        // the value read is public
+        fails(&circuit, &[(9, 2), (43, 4), (0, 3), (9, 2)]);
+        // and no reads at all
+        verifies(&MemoryCircuit::new(&writes(), &[]), &[]);
+    }
+
+    #[test]
+    fn unwritten_address() {
+        let circuit = MemoryCircuit::new(&writes(), &[0x40]);
+        fails(&circuit, &[(0, 0)]);
+        // nor a value never written to a written address
+        let circuit = MemoryCircuit::with_reads(&writes(), &[(0x20, 8, 2)]);
+        fails(&circuit, &[(8, 2)]);
+    }
+
+    #[test]
+    fn stale_read() {
+        // 7 was written to 0x10 before 42: as the first write it passes,
+        // under its counter for everyone to see
+        let stale = MemoryCircuit::with_reads(&writes(), &[(0x10, 7, 1)]);
+        verifies(&stale, &[(7, 1)]);
+        // but not claimed as the last write
+        let stale = MemoryCircuit::with_reads(&writes(), &[(0x10, 7, 4)]);
+        fails(&stale, &[(7, 4)]);
+    }
+}
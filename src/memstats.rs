@@ -0,0 +1,96 @@
+//! a global allocator counting the bytes allocated, to find the peak of
+//! proving. a binary opts in with
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOC: fibo::memstats::CountingAlloc = fibo::memstats::CountingAlloc;
+//! ```
+//!
+//! without which the counters stay at 0
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::proving;
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::Circuit,
+    poly::commitment::Params,
+};
+use rand_core::OsRng;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// the system allocator, keeping count of the bytes live and their peak
+pub struct CountingAlloc;
+
+impl CountingAlloc {
+    fn grow(bytes: usize) {
+        let current = CURRENT.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn shrink(bytes: usize) {
+        CURRENT.fetch_sub(bytes, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            Self::shrink(layout.size());
+            Self::grow(new_size);
+        }
+        new
+    }
+}
+
+/// the bytes live now
+pub fn current() -> usize {
+    CURRENT.load(Ordering::Relaxed)
+}
+
+/// runs `f`, returning the most bytes live at once meanwhile beyond those
+/// live before. allocations of other threads count as well
+pub fn peak_of<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = current();
+    PEAK.store(before, Ordering::Relaxed);
+    let value = f();
+    (value, PEAK.load(Ordering::Relaxed) - before)
+}
+
+/// the peak bytes of generating params of 2^k rows and the keys of `circuit`,
+/// then proving it against `instances`
+pub fn prover_peak<C: Circuit<Fp>>(k: u32, circuit: C, instances: &[&[Fp]]) -> usize {
+    let ((), peak) = peak_of(|| {
+        let params: Params<EqAffine> = Params::new(k);
+        let pk = proving::keygen(&params, &circuit).expect("keygen");
+        proving::prove(&params, &pk, circuit, instances, OsRng).expect("proving");
+    });
+    peak
+}
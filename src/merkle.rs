@@ -0,0 +1,385 @@
+//! inclusion of a private leaf in a tree of depth DEPTH with a public root,
+//! a node hashing its children as mimc of the left keyed by the right
+//!
+//! each level swaps the node and its sibling into (left, right) by the path
+//! bit, set when the node is a right child, then hashes them
+
+use crate::{
+    error::FiboError,
+    gadgets::{
+        cond_swap::{CondSwapChip, CondSwapConfig},
+        mimc::{mimc, MimcChip, MimcConfig},
+    },
+};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*};
+
+/// the siblings from the leaf up, and the path bits, set for a right child
+pub type Path<F, const DEPTH: usize> = ([F; DEPTH], [bool; DEPTH]);
+
+/// the parent of two nodes, computed natively
+pub fn hash_pair<F: PrimeField>(left: F, right: F) -> F {
+    mimc(left, right)
+}
+
+/// a tree over 2^depth leaves, computed natively
+#[derive(Debug, Clone)]
+pub struct MerkleTree<F> {
+    /// the leaves first, the root last
+    levels: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> MerkleTree<F> {
+    pub fn new(leaves: Vec<F>) -> Self {
+        assert!(
+            leaves.len().is_power_of_two(),
+            "the leaves are no power of two"
+        );
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels.last().unwrap();
+            let parents = level
+                .chunks(2)
+                .map(|pair| hash_pair(pair[0], pair[1]))
+                .collect();
+            levels.push(parents);
+        }
+        Self { levels }
+    }
+
+    pub fn root(&self) -> F {
+        self.levels.last().unwrap()[0]
+    }
+
+    pub fn leaf(&self, index: usize) -> F {
+        self.levels[0][index]
+    }
+
+    /// the path of the leaf at `index`, DEPTH being the depth of the tree
+    pub fn path<const DEPTH: usize>(&self, index: usize) -> Path<F, DEPTH> {
+        assert_eq!(self.levels.len() - 1, DEPTH, "the tree has another depth");
+        let siblings = std::array::from_fn(|level| self.levels[level][(index >> level) ^ 1]);
+        let bits = std::array::from_fn(|level| (index >> level) & 1 == 1);
+        (siblings, bits)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MerkleConfig {
+    pub swap: CondSwapConfig,
+    /// on the first two columns of the swap
+    pub mimc: MimcConfig,
+    pub instance: Column<Instance>,
+}
+
+/// proves the private leaf is in the tree with the root in the instance column
+#[derive(Debug)]
+pub struct MerkleCircuit<F, const DEPTH: usize> {
+    pub leaf: Value<F>,
+    pub siblings: [Value<F>; DEPTH],
+    /// 0 or 1, witnessed as field elements
+    pub bits: [Value<F>; DEPTH],
+}
+
+impl<F: PrimeField, const DEPTH: usize> Default for MerkleCircuit<F, DEPTH> {
+    fn default() -> Self {
+        Self {
+            leaf: Value::unknown(),
+            siblings: [Value::unknown(); DEPTH],
+            bits: [Value::unknown(); DEPTH],
+        }
+    }
+}
+
+impl<F: PrimeField, const DEPTH: usize> MerkleCircuit<F, DEPTH> {
+    pub fn new(leaf: F, (siblings, bits): Path<F, DEPTH>) -> Self {
+        Self {
+            leaf: Value::known(leaf),
+            siblings: siblings.map(Value::known),
+            bits: bits.map(|bit| Value::known(if bit { F::ONE } else { F::ZERO })),
+        }
+    }
+}
+
+impl<F: PrimeField, const DEPTH: usize> Circuit<F> for MerkleCircuit<F, DEPTH> {
+    type Config = MerkleConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        MerkleConfig {
+            swap: CondSwapChip::configure(meta, advice),
+            mimc: MimcChip::configure(meta, [advice[0], advice[1]]),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let [col_sibling, col_bit, col_leaf, ..] = config.swap.advice;
+        let mut at = (0, "leaf");
+        let (leaf, path) = layouter
+            .assign_region(
+                || "load path",
+                |mut region| {
+                    at = (0, "leaf");
+                    let leaf = region.assign_advice(|| "leaf", col_leaf, 0, || self.leaf)?;
+                    let mut path = Vec::with_capacity(DEPTH);
+                    for (level, (sibling, bit)) in self.siblings.iter().zip(self.bits).enumerate() {
+                        at = (level, "sibling");
+                        let sibling =
+                            region.assign_advice(|| "sibling", col_sibling, level, || *sibling)?;
+                        at = (level, "bit");
+                        let bit = region.assign_advice(|| "bit", col_bit, level, || bit)?;
+                        path.push((sibling, bit));
+                    }
+                    Ok((leaf, path))
+                },
+            )
+            .map_err(|e| FiboError::assign("load path", at, e))?;
+
+        let swap = CondSwapChip::construct(config.swap);
+        let mimc = MimcChip::construct(config.mimc);
+        let mut node = leaf;
+        for (level, (sibling, bit)) in path.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("level {level}"));
+            let (left, right) = swap.assign(layouter.namespace(|| "swap"), &node, sibling, bit)?;
+            node = mimc.hash(layouter.namespace(|| "hash"), &left, &right)?;
+        }
+
+        layouter
+            .constrain_instance(node.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+/// the leaves of the tree an 8 leaf root circuit hashes
+pub const LEAVES: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct MerkleRootConfig {
+    pub mimc: MimcConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes the root of the tree over 8 private leaves, every internal node
+/// hashed in the circuit
+#[derive(Debug)]
+pub struct MerkleRootCircuit<F> {
+    pub leaves: [Value<F>; LEAVES],
+}
+
+impl<F: PrimeField> Default for MerkleRootCircuit<F> {
+    fn default() -> Self {
+        Self {
+            leaves: [Value::unknown(); LEAVES],
+        }
+    }
+}
+
+impl<F: PrimeField> MerkleRootCircuit<F> {
+    pub fn new(leaves: [F; LEAVES]) -> Self {
+        Self {
+            leaves: leaves.map(Value::known),
+        }
+    }
+
+    /// the root, the internal node `bogus` (counted level by level from the
+    /// leaves up) witnessed once more as one more than its hash and copy
+    /// constrained to it
+    fn synthesize_with(
+        &self,
+        config: MerkleRootConfig,
+        mut layouter: impl Layouter<F>,
+        bogus: Option<usize>,
+    ) -> Result<(), FiboError> {
+        let MimcConfig { state, key, .. } = config.mimc;
+        let mut at = (0, "leaf");
+        let mut level = layouter
+            .assign_region(
+                || "load leaves",
+                |mut region| {
+                    let mut leaves = Vec::with_capacity(LEAVES);
+                    for (i, leaf) in self.leaves.iter().enumerate() {
+                        at = (i / 2, "leaf");
+                        let column = if i % 2 == 0 { state } else { key };
+                        leaves.push(region.assign_advice(|| "leaf", column, i / 2, || *leaf)?);
+                    }
+                    Ok(leaves)
+                },
+            )
+            .map_err(|e| FiboError::assign("load leaves", at, e))?;
+
+        let chip = MimcChip::construct(config.mimc);
+        let mut node = 0;
+        while level.len() > 1 {
+            let mut parents = Vec::with_capacity(level.len() / 2);
+            for pair in level.chunks(2) {
+                let mut layouter = layouter.namespace(|| format!("node {node}"));
+                let mut parent = chip.hash(layouter.namespace(|| "hash"), &pair[0], &pair[1])?;
+                if bogus == Some(node) {
+                    parent = layouter
+                        .assign_region(
+                            || "bogus node",
+                            |mut region| {
+                                let value = parent.value().copied() + Value::known(F::ONE);
+                                let cell = region.assign_advice(|| "node", state, 0, || value)?;
+                                region.constrain_equal(parent.cell(), cell.cell())?;
+                                Ok(cell)
+                            },
+                        )
+                        .map_err(|e| FiboError::assign("bogus node", (0, "node"), e))?;
+                }
+                parents.push(parent);
+                node += 1;
+            }
+            level = parents;
+        }
+
+        layouter
+            .constrain_instance(level[0].cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for MerkleRootCircuit<F> {
+    type Config = MerkleRootConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 2].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        MerkleRootConfig {
+            mimc: MimcChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.synthesize_with(config, layouter, None)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibonacci::min_k, mock_fails, mock_ok};
+    use halo2_proofs::{dev::VerifyFailure, pasta::Fp};
+
+    /// each level takes a swap row and the 112 rows of its hash
+    const K: u32 = 9;
+
+    fn tree(offset: u64) -> MerkleTree<Fp> {
+        MerkleTree::new((0..8).map(|i| Fp::from(offset + i)).collect())
+    }
+
+    #[test]
+    fn fits() {
+        // a swap row and the mimc rows per level
+        assert_eq!(min_k::<Fp, MerkleCircuit<Fp, 3>>(3 * 113), K);
+    }
+
+    #[test]
+    fn included() {
+        let tree = tree(100);
+        for index in [0, 3, 6, 7] {
+            let circuit = MerkleCircuit::<_, 3>::new(tree.leaf(index), tree.path(index));
+            mock_ok!(circuit, K, vec![vec![tree.root()]]);
+        }
+    }
+
+    #[test]
+    fn wrong_sibling() {
+        let tree = tree(100);
+        let (mut siblings, bits) = tree.path::<3>(5);
+        siblings[1] += Fp::one();
+        let circuit = MerkleCircuit::new(tree.leaf(5), (siblings, bits));
+        mock_fails!(circuit, K, vec![vec![tree.root()]]);
+    }
+
+    #[test]
+    fn wrong_bit() {
+        let tree = tree(100);
+        let (siblings, mut bits) = tree.path::<3>(5);
+        bits[0] = !bits[0];
+        let circuit = MerkleCircuit::new(tree.leaf(5), (siblings, bits));
+        mock_fails!(circuit, K, vec![vec![tree.root()]]);
+    }
+
+    #[test]
+    fn other_tree() {
+        let (tree, other) = (tree(100), tree(200));
+        let circuit = MerkleCircuit::<_, 3>::new(tree.leaf(2), tree.path(2));
+        mock_fails!(circuit, K, vec![vec![other.root()]]);
+    }
+
+    /// the 7 hashes of 112 rows
+    const ROOT_K: u32 = 10;
+
+    fn leaves() -> [Fp; LEAVES] {
+        std::array::from_fn(|i| Fp::from(i as u64 * 10))
+    }
+
+    #[test]
+    fn root() {
+        let root = MerkleTree::new(leaves().to_vec()).root();
+        let circuit = MerkleRootCircuit::new(leaves());
+        mock_ok!(circuit, ROOT_K, vec![vec![root]]);
+        mock_fails!(circuit, ROOT_K, vec![vec![tree(100).root()]]);
+
+        let mut swapped = leaves();
+        swapped.swap(0, 1);
+        mock_fails!(MerkleRootCircuit::new(swapped), ROOT_K, vec![vec![root]]);
+    }
+
+    /// the root circuit with the first node of the second level bogus
+    struct Bogus(MerkleRootCircuit<Fp>);
+
+    impl Circuit<Fp> for Bogus {
+        type Config = MerkleRootConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(MerkleRootCircuit::default())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MerkleRootCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            Ok(self.0.synthesize_with(config, layouter, Some(4))?)
+        }
+    }
+
+    #[test]
+    fn bogus_node() {
+        // the root the bogus node hashes to
+        let [a, b, c, d] = std::array::from_fn(|i| hash_pair(leaves()[2 * i], leaves()[2 * i + 1]));
+        let root = hash_pair(hash_pair(a, b) + Fp::one(), hash_pair(c, d));
+        let circuit = Bogus(MerkleRootCircuit::new(leaves()));
+        // the hashes all hold, the copy of the node alone breaks
+        let failures = mock_fails!(circuit, ROOT_K, vec![vec![root]]);
+        assert!(failures
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+    }
+}
@@ -0,0 +1,104 @@
+//! caches the IPA params on disk, `Params::new(k)` being slow for larger k
+
+use halo2_proofs::{pasta::EqAffine, poly::commitment::Params};
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// the file holding the params of size 2^k inside `dir`
+pub fn params_path(dir: impl AsRef<Path>, k: u32) -> PathBuf {
+    dir.as_ref().join(format!("params_k{}.bin", k))
+}
+
+/// loads the params of size 2^k cached in `dir`, generating and caching them
+/// if the file is missing, corrupt or holds params for another k
+pub fn load_or_generate(k: u32, dir: impl AsRef<Path>) -> io::Result<Params<EqAffine>> {
+    let path = params_path(&dir, k);
+    if let Ok(params) = load(k, &path) {
+        return Ok(params);
+    }
+
+    let params = Params::new(k);
+    fs::create_dir_all(&dir)?;
+    let mut writer = BufWriter::new(File::create(&path)?);
+    params.write(&mut writer)?;
+    writer.flush()?;
+    Ok(params)
+}
+
+fn load(k: u32, path: &Path) -> io::Result<Params<EqAffine>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    // params start with their k, check it before reading 2^k points
+    let mut header = [0u8; 4];
+    reader.read_exact(&mut header)?;
+    if u32::from_le_bytes(header) != k {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} does not hold params for k = {}", path.display(), k),
+        ));
+    }
+
+    let mut reader = io::Cursor::new(header).chain(reader);
+    let params = Params::read(&mut reader)?;
+    if reader.read(&mut [0u8])? != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("trailing bytes in {}", path.display()),
+        ));
+    }
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_bytes(params: &Params<EqAffine>) -> Vec<u8> {
+        let mut bytes = vec![];
+        params.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn cached_params() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let generated = load_or_generate(4, dir.path()).unwrap();
+        assert!(params_path(dir.path(), 4).exists());
+        let loaded = load_or_generate(4, dir.path()).unwrap();
+        assert_eq!(to_bytes(&generated), to_bytes(&loaded));
+    }
+
+    #[test]
+    fn wrong_k_is_regenerated() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // a k = 4 file sitting where the k = 6 params belong
+        let params = load_or_generate(4, dir.path()).unwrap();
+        fs::copy(params_path(dir.path(), 4), params_path(dir.path(), 6)).unwrap();
+
+        let params6 = load_or_generate(6, dir.path()).unwrap();
+        assert_ne!(to_bytes(&params), to_bytes(&params6));
+        assert_eq!(
+            fs::read(params_path(dir.path(), 6)).unwrap()[..4],
+            6u32.to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn corrupt_file_is_regenerated() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let params = load_or_generate(4, dir.path()).unwrap();
+        let path = params_path(dir.path(), 4);
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+
+        let reloaded = load_or_generate(4, dir.path()).unwrap();
+        assert_eq!(to_bytes(&params), to_bytes(&reloaded));
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+    }
+}
@@ -0,0 +1,86 @@
+//! runs a circuit under another floor planner, to compare how they lay it out
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{floor_planner::V1, Layouter},
+    plonk::{Circuit, ConstraintSystem, Error, FloorPlanner},
+};
+use std::marker::PhantomData;
+
+/// the circuit C, laid out by the floor planner P instead of its own
+#[derive(Debug, Default)]
+pub struct WithPlanner<C, P> {
+    pub circuit: C,
+    _planner: PhantomData<P>,
+}
+
+/// the circuit C laid out by the V1 floor planner
+pub type WithV1<C> = WithPlanner<C, V1>;
+
+impl<C, P> WithPlanner<C, P> {
+    pub fn new(circuit: C) -> Self {
+        Self {
+            circuit,
+            _planner: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, C: Circuit<F>, P: FloorPlanner> Circuit<F> for WithPlanner<C, P> {
+    type Config = C::Config;
+    type FloorPlanner = P;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.circuit.without_witnesses())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.circuit.synthesize(config, layouter)
+    }
+}
+
+/// the name of the floor planner laying out C, without its module path
+pub fn planner_name<F: Field, C: Circuit<F>>() -> &'static str {
+    let name = std::any::type_name::<C::FloorPlanner>();
+    name.rsplit("::").next().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_ok;
+    use crate::{
+        cost::measure,
+        fibonacci::{public_inputs::PublicInputs, row_based, single_column},
+    };
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn v1_rows() {
+        let one = Fp::from(1);
+        let publics = PublicInputs::new(one, one, 10).to_instance_column();
+
+        let simple = row_based::MyCircuit::<Fp>::new(10);
+        let v1 = WithV1::new(row_based::MyCircuit::<Fp>::new(10));
+        mock_ok!(v1, 4, vec![publics.clone()]);
+        assert!(measure(4, &v1).unwrap().rows <= measure(4, &simple).unwrap().rows);
+
+        let simple = single_column::MyCircuit::<Fp>::new(10);
+        let v1 = WithV1::new(single_column::MyCircuit::<Fp>::new(10));
+        mock_ok!(v1, 4, vec![publics]);
+        assert!(measure(4, &v1).unwrap().rows <= measure(4, &simple).unwrap().rows);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            planner_name::<Fp, row_based::MyCircuit<Fp>>(),
+            "SimpleFloorPlanner"
+        );
+        assert_eq!(planner_name::<Fp, WithV1<row_based::MyCircuit<Fp>>>(), "V1");
+    }
+}
@@ -0,0 +1,158 @@
+//! hashing two private field elements with the poseidon chip of halo2_gadgets,
+//! the P128Pow5T3 spec over the pallas base field
+
+use halo2_gadgets::poseidon::{
+    primitives::{self as poseidon, ConstantLength, P128Pow5T3},
+    Hash, Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{circuit::*, pasta::Fp, plonk::*};
+
+use crate::error::FiboError;
+
+/// the state words of the permutation
+pub const WIDTH: usize = 3;
+/// the words absorbed at a time
+pub const RATE: usize = 2;
+
+/// the config of the pow5 chip for P128Pow5T3
+pub type PoseidonConfig = Pow5Config<Fp, WIDTH, RATE>;
+
+/// the poseidon hash of L elements, computed natively
+pub fn poseidon_hash<const L: usize>(message: [Fp; L]) -> Fp {
+    poseidon::Hash::<_, P128Pow5T3, ConstantLength<L>, WIDTH, RATE>::init().hash(message)
+}
+
+/// the pow5 chip on the state columns, with its own partial sbox column and
+/// two sets of round constant columns, the second also taking constants
+pub fn configure(
+    meta: &mut ConstraintSystem<Fp>,
+    state: [Column<Advice>; WIDTH],
+) -> PoseidonConfig {
+    let partial_sbox = meta.advice_column();
+    let rc_a = [(); WIDTH].map(|_| meta.fixed_column());
+    let rc_b = [(); WIDTH].map(|_| meta.fixed_column());
+    meta.enable_constant(rc_b[0]);
+
+    Pow5Chip::configure::<P128Pow5T3>(meta, state, partial_sbox, rc_a, rc_b)
+}
+
+/// the digest of the L cells
+pub fn hash<const L: usize>(
+    config: &PoseidonConfig,
+    mut layouter: impl Layouter<Fp>,
+    message: [AssignedCell<Fp, Fp>; L],
+) -> Result<AssignedCell<Fp, Fp>, Error> {
+    let chip = Pow5Chip::construct(config.clone());
+    let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<L>, WIDTH, RATE>::init(
+        chip,
+        layouter.namespace(|| "init"),
+    )?;
+    hasher.hash(layouter.namespace(|| "hash"), message)
+}
+
+#[derive(Debug, Clone)]
+pub struct PoseidonCircuitConfig {
+    /// the chip's state columns, which the message is loaded into
+    pub state: [Column<Advice>; WIDTH],
+    pub poseidon: PoseidonConfig,
+    pub instance: Column<Instance>,
+}
+
+/// exposes the poseidon digest of two private elements
+#[derive(Debug, Default)]
+pub struct PoseidonCircuit {
+    pub message: [Value<Fp>; 2],
+}
+
+impl PoseidonCircuit {
+    pub fn new(message: [Fp; 2]) -> Self {
+        Self {
+            message: message.map(Value::known),
+        }
+    }
+}
+
+impl Circuit<Fp> for PoseidonCircuit {
+    type Config = PoseidonCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let state = [(); WIDTH].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        PoseidonCircuitConfig {
+            state,
+            poseidon: configure(meta, state),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let state = config.state;
+        let mut at = (0, "a");
+        let message = layouter
+            .assign_region(
+                || "load message",
+                |mut region| {
+                    at = (0, "a");
+                    let a = region.assign_advice(|| "a", state[0], 0, || self.message[0])?;
+                    at = (0, "b");
+                    let b = region.assign_advice(|| "b", state[1], 0, || self.message[1])?;
+                    Ok([a, b])
+                },
+            )
+            .map_err(|e| FiboError::assign("load message", at, e))?;
+
+        let digest = hash(&config.poseidon, layouter.namespace(|| "poseidon"), message)?;
+        layouter
+            .constrain_instance(digest.cell(), config.instance, 0)
+            .map_err(|e| FiboError::expose(0, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+
+    fn verifies(message: [u64; 2], digest: Fp) {
+        mock_ok!(
+            PoseidonCircuit::new(message.map(Fp::from)),
+            6,
+            vec![vec![digest]]
+        );
+    }
+
+    fn fails(message: [u64; 2], digest: Fp) {
+        mock_fails!(
+            PoseidonCircuit::new(message.map(Fp::from)),
+            6,
+            vec![vec![digest]]
+        );
+    }
+
+    #[test]
+    fn digest() {
+        let digest = poseidon_hash([Fp::from(1), Fp::from(2)]);
+        verifies([1, 2], digest);
+        // the order of the message counts
+        assert_ne!(digest, poseidon_hash([Fp::from(2), Fp::from(1)]));
+        fails([2, 1], digest);
+    }
+
+    #[test]
+    fn flipped_digest() {
+        let digest = poseidon_hash([Fp::from(1), Fp::from(2)]);
+        fails([1, 2], digest + Fp::one());
+        fails([1, 2], -digest);
+    }
+}
@@ -0,0 +1,471 @@
+//! the real proving pipeline over pasta: params, keygen, create_proof, verify_proof
+
+use crate::{
+    cost::DebugFieldError,
+    fibonacci::{public_inputs::PublicInputs, row_based},
+};
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::*,
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::{OsRng, RngCore};
+use rayon::{prelude::*, ThreadPoolBuildError};
+use serde::Serialize;
+use std::{
+    fmt::{self, Debug},
+    fs, io,
+    path::Path,
+    time::Instant,
+};
+use thiserror::Error as ThisError;
+
+/// a serialized proof, as written by the blake2b transcript
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof(Vec<u8>);
+
+impl Proof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.clone()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn write_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, &self.0)
+    }
+
+    pub fn read_from(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read(path).map(Self)
+    }
+}
+
+/// generates the verifying and proving keys of `circuit`. halo2_proofs 0.3 can
+/// not serialize either key, so every command reruns this and only the params
+/// are cached, by params_cache
+pub fn keygen<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    circuit: &C,
+) -> Result<ProvingKey<EqAffine>, Error> {
+    let vk = tracing::info_span!("keygen_vk").in_scope(|| keygen_vk(params, circuit))?;
+    tracing::info_span!("keygen_pk").in_scope(|| keygen_pk(params, vk, circuit))
+}
+
+/// proves `circuit` against its instance columns, one slice per column. the
+/// blinding comes from `rng`, so a seeded rng makes the proof reproducible
+pub fn prove<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    instances: &[&[Fp]],
+    rng: impl RngCore,
+) -> Result<Proof, Error> {
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    tracing::info_span!("create_proof")
+        .in_scope(|| create_proof(params, pk, &[circuit], &[instances], rng, &mut transcript))?;
+    Ok(Proof(transcript.finalize()))
+}
+
+/// where the time of a proof went, and what came of it
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProveReport {
+    pub keygen_ms: f64,
+    /// laying the circuit out on its own, which the prover does again
+    pub synthesize_ms: f64,
+    pub prove_ms: f64,
+    pub proof_bytes: usize,
+    pub k: u32,
+    /// rows assigned by the floor planner, over all columns
+    pub rows_used: usize,
+}
+
+impl fmt::Display for ProveReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "k             {}", self.k)?;
+        writeln!(f, "rows used     {} of {}", self.rows_used, 1 << self.k)?;
+        writeln!(f, "keygen        {:.1} ms", self.keygen_ms)?;
+        writeln!(f, "synthesize    {:.1} ms", self.synthesize_ms)?;
+        writeln!(f, "prove         {:.1} ms", self.prove_ms)?;
+        writeln!(f, "proof size    {} bytes", self.proof_bytes)
+    }
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+/// why prove_with_report failed
+#[derive(Debug, ThisError)]
+pub enum ReportError {
+    #[error(transparent)]
+    Proving(#[from] Error),
+    /// the rows used could not be read from halo2's cost
+    #[error(transparent)]
+    Cost(#[from] DebugFieldError),
+}
+
+/// generates the keys of `circuit` and proves it as `prove` does, timing
+/// each step. `params` are those of size 2^k
+pub fn prove_with_report<C: Circuit<Fp> + Debug>(
+    k: u32,
+    params: &Params<EqAffine>,
+    circuit: C,
+    instances: &[&[Fp]],
+    rng: impl RngCore,
+) -> Result<(Proof, ProveReport), ReportError> {
+    let start = Instant::now();
+    let rows_used = crate::cost::measure(k, &circuit)?.rows;
+    let synthesize_ms = elapsed_ms(start);
+
+    let start = Instant::now();
+    let pk = keygen(params, &circuit)?;
+    let keygen_ms = elapsed_ms(start);
+
+    let start = Instant::now();
+    let proof = prove(params, &pk, circuit, instances, rng)?;
+    let prove_ms = elapsed_ms(start);
+
+    let report = ProveReport {
+        keygen_ms,
+        synthesize_ms,
+        prove_ms,
+        proof_bytes: proof.as_bytes().len(),
+        k,
+        rows_used,
+    };
+    Ok((proof, report))
+}
+
+/// verifies `proof` against the instance columns it claims. the proof may
+/// come from anywhere, e.g. read back from a file written by another process
+pub fn verify(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &Proof,
+    instances: &[&[Fp]],
+) -> Result<(), Error> {
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof.as_bytes());
+    tracing::info_span!("verify_proof")
+        .in_scope(|| verify_proof(params, vk, strategy, &[instances], &mut transcript))
+}
+
+/// why a batch of proofs did not verify
+#[derive(Debug, ThisError)]
+pub enum BatchError {
+    /// the first proof which does not verify on its own
+    #[error("proof {index} does not verify: {source}")]
+    Invalid {
+        index: usize,
+        #[source]
+        source: Error,
+    },
+    /// the accumulated check failed while every proof verifies on its own,
+    /// which only a faulty verifier would do
+    #[error("the batch does not verify, though each of its proofs does")]
+    Batch,
+}
+
+/// verifies proofs of one circuit, each against its single instance column,
+/// folding them into one multi-scalar multiplication rather than checking
+/// each. a bad batch is verified again proof by proof to find the one at fault
+pub fn verify_batch(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    items: &[(Proof, Vec<Fp>)],
+) -> Result<(), BatchError> {
+    let mut batch = BatchVerifier::new();
+    for (proof, instance) in items {
+        batch.add_proof(vec![vec![instance.clone()]], proof.to_bytes());
+    }
+    if tracing::info_span!("verify_batch").in_scope(|| batch.finalize(params, vk)) {
+        return Ok(());
+    }
+
+    for (index, (proof, instance)) in items.iter().enumerate() {
+        verify(params, vk, proof, &[instance])
+            .map_err(|source| BatchError::Invalid { index, source })?;
+    }
+    Err(BatchError::Batch)
+}
+
+/// a proof of a batch and the instance column it is against, or why its
+/// witness could not be proven
+pub type BatchProof = Result<(Proof, Vec<Fp>), Error>;
+
+/// proves the row based circuit of the n-th number from each of the (a, b)
+/// `seeds`, on a pool of `parallelism` threads sharing `pk`, which must be the
+/// key of row_based::MyCircuit::new(n). each result is at the index of its
+/// seeds, with the instance column [a, b, out] the proof is against
+///
+/// the seeds go through in chunks of `parallelism`, so no more than that many
+/// proofs are in memory before being handed over. the blinding of the i-th
+/// proof comes from `rng(i)`, so seeded rngs make the batch reproducible.
+/// `progress` is called with the index of each proof done, from the thread
+/// which did it. fails only if the pool can not be started
+pub fn prove_batch<R: RngCore>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    n: usize,
+    seeds: &[(Fp, Fp)],
+    parallelism: usize,
+    rng: impl Fn(usize) -> R + Sync,
+    progress: impl Fn(usize) + Sync,
+) -> Result<Vec<BatchProof>, ThreadPoolBuildError> {
+    let publics = |&(a, b): &(Fp, Fp)| PublicInputs::new(a, b, n);
+    let witnesses: Vec<_> = seeds.iter().map(publics).collect();
+    prove_witnesses(params, pk, n, &witnesses, parallelism, rng, progress)
+}
+
+/// as prove_batch, from the public inputs of each proof. a witness which does
+/// not satisfy the circuit, as a claimed output that is not the n-th number,
+/// fails on its own with ConstraintSystemFailure
+fn prove_witnesses<R: RngCore>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    n: usize,
+    witnesses: &[PublicInputs<Fp>],
+    parallelism: usize,
+    rng: impl Fn(usize) -> R + Sync,
+    progress: impl Fn(usize) + Sync,
+) -> Result<Vec<BatchProof>, ThreadPoolBuildError> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(parallelism)
+        .build()?;
+
+    let k = params.k();
+    let prove_one = |i: usize, publics: &PublicInputs<Fp>| {
+        let instance = publics.to_instance_column();
+        let circuit = row_based::MyCircuit::<Fp>::new(n);
+        // create_proof takes any witness, and an unsatisfied one would only
+        // show up as a proof which does not verify
+        let satisfied = MockProver::run(k, &circuit, vec![instance.clone()])?
+            .verify()
+            .is_ok();
+        let result = if satisfied {
+            prove(params, pk, circuit, &[&instance], rng(i)).map(|proof| (proof, instance))
+        } else {
+            Err(Error::ConstraintSystemFailure)
+        };
+        progress(i);
+        result
+    };
+
+    // 0 threads being as many as there are cores
+    let size = pool.current_num_threads();
+    let mut results = Vec::with_capacity(witnesses.len());
+    for (chunk, witnesses) in witnesses.chunks(size).enumerate() {
+        let mut done = vec![];
+        pool.install(|| {
+            witnesses
+                .par_iter()
+                .enumerate()
+                .map(|(i, publics)| prove_one(chunk * size + i, publics))
+                .collect_into_vec(&mut done)
+        });
+        results.append(&mut done);
+    }
+    Ok(results)
+}
+
+/// generates fresh params and keys for `circuit`, proves it against the
+/// instance columns and verifies the resulting proof
+pub fn prove_and_verify<C: Circuit<Fp>>(
+    k: u32,
+    circuit: C,
+    instances: &[&[Fp]],
+) -> Result<(), Error> {
+    let params: Params<EqAffine> = Params::new(k);
+    let pk = keygen(&params, &circuit)?;
+    let proof = prove(&params, &pk, circuit, instances, OsRng)?;
+    verify(&params, pk.get_vk(), &proof, instances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{single_column::MyCircuit, vectors};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    fn fibo_proof() -> (Params<EqAffine>, ProvingKey<EqAffine>, Proof, Vec<Fp>) {
+        let params: Params<EqAffine> = Params::new(4);
+        let circuit = MyCircuit::<Fp>::new(10);
+        let pk = keygen(&params, &circuit).unwrap();
+
+        let a = Fp::from(1);
+        let publics = PublicInputs::new(a, a, 10).to_instance_column();
+        let proof = prove(&params, &pk, circuit, &[&publics], OsRng).unwrap();
+        (params, pk, proof, publics)
+    }
+
+    fn seeded_proof(seed: u64) -> (Params<EqAffine>, ProvingKey<EqAffine>, Proof, Vec<Fp>) {
+        let params: Params<EqAffine> = Params::new(4);
+        let circuit = MyCircuit::<Fp>::new(10);
+        let pk = keygen(&params, &circuit).unwrap();
+
+        let a = Fp::from(1);
+        let publics = PublicInputs::new(a, a, 10).to_instance_column();
+        let rng = ChaCha20Rng::seed_from_u64(seed);
+        let proof = prove(&params, &pk, circuit, &[&publics], rng).unwrap();
+        (params, pk, proof, publics)
+    }
+
+    #[test]
+    fn same_seed() {
+        // nothing but the rng may vary between two proofs of the same witness
+        let (_, _, first, _) = seeded_proof(7);
+        let (_, _, second, _) = seeded_proof(7);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn other_seed() {
+        let (params, pk, first, publics) = seeded_proof(7);
+        let (_, _, second, _) = seeded_proof(8);
+        assert_ne!(first, second);
+        assert!(verify(&params, pk.get_vk(), &first, &[&publics]).is_ok());
+        assert!(verify(&params, pk.get_vk(), &second, &[&publics]).is_ok());
+    }
+
+    #[test]
+    fn proof_file_round_trip() {
+        let (params, pk, proof, publics) = fibo_proof();
+        assert_eq!(Proof::from_bytes(&proof.to_bytes()), proof);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proof.bin");
+        proof.write_to(&path).unwrap();
+        let loaded = Proof::read_from(&path).unwrap();
+        assert!(verify(&params, pk.get_vk(), &loaded, &[&publics]).is_ok());
+    }
+
+    #[test]
+    fn report() {
+        let params: Params<EqAffine> = Params::new(4);
+        let a = Fp::from(1);
+        let publics = PublicInputs::new(a, a, 10).to_instance_column();
+
+        let circuit = row_based::MyCircuit::<Fp>::new(10);
+        let (proof, report) = prove_with_report(4, &params, circuit, &[&publics], OsRng).unwrap();
+        assert_eq!(report.proof_bytes, proof.as_bytes().len());
+        assert_eq!(report.k, 4);
+        // one row per step, from the 3rd number to the 10th
+        assert_eq!(report.rows_used, 8);
+        assert!(report.keygen_ms > 0.0 && report.prove_ms > 0.0);
+        assert!(report.to_string().contains("rows used     8 of 16"));
+
+        let circuit = MyCircuit::<Fp>::new(10);
+        let (proof, report) = prove_with_report(4, &params, circuit, &[&publics], OsRng).unwrap();
+        assert_eq!(report.proof_bytes, proof.as_bytes().len());
+        assert_eq!(report.rows_used, 10);
+        assert!(report.synthesize_ms > 0.0);
+    }
+
+    #[test]
+    fn batch() {
+        use std::sync::Mutex;
+
+        let params: Params<EqAffine> = Params::new(4);
+        let pk = keygen(&params, &row_based::MyCircuit::<Fp>::new(10)).unwrap();
+        let mut witnesses: Vec<_> = (0..8)
+            .map(|i| PublicInputs::new(Fp::from(i), Fp::from(i + 1), 10))
+            .collect();
+        // the 6th claims another output
+        witnesses[5].out += Fp::one();
+
+        let done = Mutex::new(vec![]);
+        let progress = |i| done.lock().unwrap().push(i);
+        let results =
+            prove_witnesses(&params, &pk, 10, &witnesses, 3, |_| OsRng, progress).unwrap();
+        assert_eq!(results.len(), 8);
+        for (i, result) in results.iter().enumerate() {
+            if i == 5 {
+                assert!(matches!(result, Err(Error::ConstraintSystemFailure)));
+                continue;
+            }
+            // in the order of the witnesses
+            let (proof, instance) = result.as_ref().unwrap();
+            assert_eq!(*instance, witnesses[i].to_instance_column());
+            assert!(verify(&params, pk.get_vk(), proof, &[instance]).is_ok());
+        }
+
+        let mut done = done.into_inner().unwrap();
+        done.sort();
+        assert_eq!(done, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn batch_seeds() {
+        let params: Params<EqAffine> = Params::new(4);
+        let pk = keygen(&params, &row_based::MyCircuit::<Fp>::new(10)).unwrap();
+        let seeds = [(Fp::one(), Fp::one()), (Fp::from(2), Fp::from(3))];
+        let rng = |i| ChaCha20Rng::seed_from_u64(i as u64);
+        let batch = || prove_batch(&params, &pk, 10, &seeds, 2, rng, |_| {}).unwrap();
+
+        let first = batch();
+        for ((a, b), result) in seeds.iter().zip(&first) {
+            // the output is the 10th number of the seeds
+            let (proof, instance) = result.as_ref().unwrap();
+            assert_eq!(
+                *instance,
+                PublicInputs::new(*a, *b, 10).to_instance_column()
+            );
+            assert!(verify(&params, pk.get_vk(), proof, &[instance]).is_ok());
+        }
+        assert_eq!(first[0].as_ref().unwrap().1[2], vectors::nth(10));
+
+        // the same rngs give the same proofs
+        let proofs = |batch: Vec<BatchProof>| -> Vec<_> {
+            batch.into_iter().map(|result| result.unwrap().0).collect()
+        };
+        assert_eq!(proofs(first), proofs(batch()));
+    }
+
+    #[test]
+    fn batch_verify() {
+        let params: Params<EqAffine> = Params::new(4);
+        let pk = keygen(&params, &row_based::MyCircuit::<Fp>::new(10)).unwrap();
+        let seeds: Vec<_> = (0..10).map(|i| (Fp::from(i), Fp::one())).collect();
+        let mut items: Vec<_> = prove_batch(&params, &pk, 10, &seeds, 0, |_| OsRng, |_| {})
+            .unwrap()
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert!(verify_batch(&params, pk.get_vk(), &items).is_ok());
+        assert!(verify_batch(&params, pk.get_vk(), &[]).is_ok());
+
+        // the 8th claims another output, and the 4th loses a byte
+        items[7].1[2] += Fp::one();
+        assert!(matches!(
+            verify_batch(&params, pk.get_vk(), &items),
+            Err(BatchError::Invalid { index: 7, .. })
+        ));
+        let bytes = items[3].0.to_bytes();
+        items[3].0 = Proof::from_bytes(&bytes[..bytes.len() - 1]);
+        assert!(matches!(
+            verify_batch(&params, pk.get_vk(), &items),
+            Err(BatchError::Invalid { index: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn truncated_proof_file() {
+        let (params, pk, proof, publics) = fibo_proof();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proof.bin");
+        let bytes = proof.to_bytes();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+
+        let truncated = Proof::read_from(&path).unwrap();
+        assert!(verify(&params, pk.get_vk(), &truncated, &[&publics]).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! a private list b is a shuffle of a private list a, duplicates and all,
+//! by the grand product chip
+//!
+//! INSECURE: the challenge γ is read from the instance column, so the prover
+//! chooses it, and for lists that differ it can solve
+//! Π(a_i + γ) = Π(b_i + γ) for a γ. a sound circuit draws γ from the
+//! transcript after the lists are committed, which takes a backend with
+//! challenge phases
+
+use crate::{
+    error::FiboError,
+    fibonacci::min_k,
+    gadgets::grand_product::{GrandProductChip, GrandProductConfig},
+};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*};
+
+#[derive(Debug, Clone)]
+pub struct ShuffleConfig {
+    pub grand_product: GrandProductConfig,
+    /// the challenge
+    pub instance: Column<Instance>,
+}
+
+/// proves b holds the values of a in some order, under the challenge in the
+/// instance column
+#[derive(Debug, Default)]
+pub struct ShuffleCircuit<F> {
+    pub a: Vec<Value<F>>,
+    pub b: Vec<Value<F>>,
+}
+
+impl<F: PrimeField> ShuffleCircuit<F> {
+    pub fn new(a: &[u64], b: &[u64]) -> Self {
+        let known = |values: &[u64]| values.iter().map(|&v| Value::known(F::from(v))).collect();
+        Self {
+            a: known(a),
+            b: known(b),
+        }
+    }
+
+    /// the instance column for the challenge
+    pub fn instance(gamma: F) -> Vec<F> {
+        vec![gamma]
+    }
+
+    /// the smallest k fitting the lists and the running product
+    pub fn min_k(&self) -> u32 {
+        min_k::<F, Self>(2 * self.a.len() + 1)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for ShuffleCircuit<F> {
+    type Config = ShuffleConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the length shapes the circuit
+        Self {
+            a: vec![Value::unknown(); self.a.len()],
+            b: vec![Value::unknown(); self.b.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        ShuffleConfig {
+            grand_product: GrandProductChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let [col_a, col_b, col_gamma, _] = config.grand_product.advice;
+        let mut at = (0, "gamma");
+        let (gamma, a, b) = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    at = (0, "gamma");
+                    let gamma = region.assign_advice_from_instance(
+                        || "gamma",
+                        config.instance,
+                        0,
+                        col_gamma,
+                        0,
+                    )?;
+                    let mut load = |values: &[Value<F>], column, name| {
+                        values
+                            .iter()
+                            .enumerate()
+                            .map(|(row, &v)| {
+                                at = (row, name);
+                                region.assign_advice(|| name, column, row, || v)
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    };
+                    let a = load(&self.a, col_a, "a")?;
+                    let b = load(&self.b, col_b, "b")?;
+                    Ok((gamma, a, b))
+                },
+            )
+            .map_err(|e| FiboError::assign("load", at, e))?;
+
+        let chip = GrandProductChip::construct(config.grand_product);
+        chip.assign(layouter.namespace(|| "a ~ b"), &gamma, &a, &b)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    fn gamma() -> Fp {
+        Fp::from(0x9e3779b97f4a7c15)
+    }
+
+    fn verifies(a: &[u64], b: &[u64], gamma: Fp) {
+        let circuit = ShuffleCircuit::new(a, b);
+        mock_ok!(
+            circuit,
+            circuit.min_k(),
+            vec![ShuffleCircuit::instance(gamma)]
+        );
+    }
+
+    fn fails(a: &[u64], b: &[u64], gamma: Fp) {
+        let circuit = ShuffleCircuit::new(a, b);
+        mock_fails!(
+            circuit,
+            circuit.min_k(),
+            vec![ShuffleCircuit::instance(gamma)]
+        );
+    }
+
+    #[test]
+    fn permutation() {
+        verifies(&[3, 1, 4, 5, 9], &[9, 5, 4, 3, 1], gamma());
+        verifies(&[3, 1, 4, 5, 9], &[3, 1, 4, 5, 9], gamma());
+        verifies(&[], &[], gamma());
+    }
+
+    #[test]
+    fn element_changed() {
+        fails(&[3, 1, 4, 5, 9], &[9, 5, 4, 3, 2], gamma());
+    }
+
+    #[test]
+    fn duplicates() {
+        verifies(&[2, 7, 2, 2], &[2, 2, 7, 2], gamma());
+        // the same values, but not as often
+        fails(&[2, 7, 2, 2], &[2, 7, 7, 2], gamma());
+        fails(&[2, 7, 7, 2], &[2, 7, 2, 2], gamma());
+    }
+
+    #[test]
+    fn chosen_challenge() {
+        // (0 + γ)(3 + γ) = (1 + γ)(1 + γ) at γ = 1
+        verifies(&[0, 3], &[1, 1], Fp::one());
+        fails(&[0, 3], &[1, 1], gamma());
+    }
+
+    #[test]
+    fn lengths() {
+        let circuit = ShuffleCircuit::<Fp>::new(&[1, 2], &[1]);
+        assert!(matches!(
+            MockProver::run(4, &circuit, vec![ShuffleCircuit::instance(gamma())]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
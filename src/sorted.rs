@@ -0,0 +1,228 @@
+//! a private list is the sorted private input: the same multiset, by the
+//! grand product chip, and in order, by the lt chip on each neighbouring pair
+//!
+//! the lt chip range checks the values to BITS bits. the challenge comes from
+//! the instance column, as INSECURE as the shuffle circuit's
+
+use crate::{
+    error::FiboError,
+    fibonacci::min_k,
+    gadgets::{
+        grand_product::{GrandProductChip, GrandProductConfig},
+        lt::{LtChip, LtConfig},
+    },
+};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*};
+
+#[derive(Debug, Clone)]
+pub struct SortedConfig {
+    /// the grand product lives in the first four of the comparison columns
+    pub lt: LtConfig,
+    pub grand_product: GrandProductConfig,
+    /// the challenge
+    pub instance: Column<Instance>,
+}
+
+/// proves the output holds the input's values, smallest first, under the
+/// challenge in the instance column
+#[derive(Debug, Default)]
+pub struct SortedCircuit<F, const BITS: usize> {
+    pub input: Vec<Value<F>>,
+    pub output: Vec<Value<F>>,
+}
+
+impl<F: PrimeField, const BITS: usize> SortedCircuit<F, BITS> {
+    pub fn new(input: &[u64], output: &[u64]) -> Self {
+        let known = |values: &[u64]| values.iter().map(|&v| Value::known(F::from(v))).collect();
+        Self {
+            input: known(input),
+            output: known(output),
+        }
+    }
+
+    /// the input with its sorted copy as the output
+    pub fn sorting(input: &[u64]) -> Self {
+        let mut output = input.to_vec();
+        output.sort_unstable();
+        Self::new(input, &output)
+    }
+
+    /// the instance column for the challenge
+    pub fn instance(gamma: F) -> Vec<F> {
+        vec![gamma]
+    }
+
+    /// the smallest k fitting the lists, the running product and the comparisons
+    pub fn min_k(&self) -> u32 {
+        let n = self.input.len();
+        // each comparison range checks a, b and the difference, then takes a row
+        let comparisons = n.saturating_sub(1) * (3 * BITS + 1);
+        min_k::<F, Self>(n + (n + 1) + comparisons)
+    }
+}
+
+impl<F: PrimeField, const BITS: usize> Circuit<F> for SortedCircuit<F, BITS> {
+    type Config = SortedConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the length shapes the circuit
+        Self {
+            input: vec![Value::unknown(); self.input.len()],
+            output: vec![Value::unknown(); self.output.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        SortedConfig {
+            lt: LtChip::<F, BITS>::configure(meta, advice),
+            grand_product: GrandProductChip::configure(
+                meta,
+                [advice[0], advice[1], advice[2], advice[3]],
+            ),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let [col_input, col_output, col_gamma, _] = config.grand_product.advice;
+        let mut at = (0, "gamma");
+        let (gamma, input, output) = layouter
+            .assign_region(
+                || "load",
+                |mut region| {
+                    at = (0, "gamma");
+                    let gamma = region.assign_advice_from_instance(
+                        || "gamma",
+                        config.instance,
+                        0,
+                        col_gamma,
+                        0,
+                    )?;
+                    let mut load = |values: &[Value<F>], column, name| {
+                        values
+                            .iter()
+                            .enumerate()
+                            .map(|(row, &v)| {
+                                at = (row, name);
+                                region.assign_advice(|| name, column, row, || v)
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    };
+                    let input = load(&self.input, col_input, "input")?;
+                    let output = load(&self.output, col_output, "output")?;
+                    Ok((gamma, input, output))
+                },
+            )
+            .map_err(|e| FiboError::assign("load", at, e))?;
+
+        let grand_product = GrandProductChip::construct(config.grand_product);
+        grand_product.assign(
+            layouter.namespace(|| "input ~ output"),
+            &gamma,
+            &input,
+            &output,
+        )?;
+
+        let lt = LtChip::<F, BITS>::construct(config.lt);
+        for (i, pair) in output.windows(2).enumerate() {
+            let descending = lt.lt(
+                layouter.namespace(|| format!("out[{}] < out[{i}]", i + 1)),
+                &pair[1],
+                &pair[0],
+            )?;
+            layouter
+                .assign_region(
+                    || "in order",
+                    |mut region| region.constrain_constant(descending.cell(), F::ZERO),
+                )
+                .map_err(|e| FiboError::assign("in order", (0, "lt"), e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::VerifyFailure, pasta::Fp};
+
+    type Sorted = SortedCircuit<Fp, 8>;
+
+    fn gamma() -> Fp {
+        Fp::from(0x9e3779b97f4a7c15)
+    }
+
+    fn verifies(circuit: &Sorted) {
+        mock_ok!(*circuit, circuit.min_k(), vec![Sorted::instance(gamma())]);
+    }
+
+    fn fails(circuit: &Sorted) -> Vec<VerifyFailure> {
+        mock_fails!(*circuit, circuit.min_k(), vec![Sorted::instance(gamma())])
+    }
+
+    /// whether a copy into the column failed: the last running product for
+    /// the multiset check, the comparisons' results for the order
+    fn failed_on(failures: &[VerifyFailure], column: Column<Advice>) -> bool {
+        let column = Column::<Any>::from(column).into();
+        failures.iter().any(|failure| {
+            matches!(failure, VerifyFailure::Permutation { column: c, .. } if *c == column)
+        })
+    }
+
+    fn columns() -> (Column<Advice>, Column<Advice>) {
+        let config = Sorted::configure(&mut ConstraintSystem::default());
+        (config.grand_product.advice[3], config.lt.advice[4])
+    }
+
+    #[test]
+    fn sorted() {
+        let input: Vec<u64> = (0..32).map(|i| (i * 37 + 11) % 256).collect();
+        let circuit = Sorted::sorting(&input);
+        assert_eq!(circuit.min_k(), 10);
+        verifies(&circuit);
+    }
+
+    #[test]
+    fn inversion() {
+        // the right values, 4 and 5 swapped
+        let circuit = Sorted::new(&[5, 3, 8, 1, 4], &[1, 3, 5, 4, 8]);
+        let failures = fails(&circuit);
+        let (z, lt) = columns();
+        assert!(failed_on(&failures, lt), "{failures:?}");
+        assert!(!failed_on(&failures, z), "{failures:?}");
+    }
+
+    #[test]
+    fn dropped_element() {
+        // in order, but 8 dropped for a second 5
+        let circuit = Sorted::new(&[5, 3, 8, 1, 4], &[1, 3, 4, 5, 5]);
+        let failures = fails(&circuit);
+        let (z, lt) = columns();
+        assert!(failed_on(&failures, z), "{failures:?}");
+        assert!(!failed_on(&failures, lt), "{failures:?}");
+    }
+
+    #[test]
+    fn ties() {
+        verifies(&Sorted::sorting(&[7, 2, 7, 2, 7]));
+        verifies(&Sorted::sorting(&[0, 0, 0]));
+        verifies(&Sorted::sorting(&[255]));
+    }
+
+    #[test]
+    fn out_of_range() {
+        // in order as field elements, but 256 needs a ninth bit
+        fails(&Sorted::sorting(&[256, 3]));
+    }
+}
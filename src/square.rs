@@ -4,7 +4,7 @@ use halo2_proofs::{arithmetic::Field, circuit::*, plonk::*, poly::Rotation};
 use std::marker::PhantomData;
 type ResultNum<N> = Result<N, Error>;
 
-trait NumericInstructions<F: Field>: Chip<F> {
+pub trait NumericInstructions<F: Field>: Chip<F> {
     // a number
     type Num;
 
@@ -28,7 +28,7 @@ trait NumericInstructions<F: Field>: Chip<F> {
 
 /// the chip to implement the instructions
 /// chips store their own config, as well as type markers if necessary
-struct FieldChip<F: Field> {
+pub struct FieldChip<F: Field> {
     config: FieldConfig,
     _marker: PhantomData<F>,
 }
@@ -50,7 +50,7 @@ impl<F: Field> Chip<F> for FieldChip<F> {
 /// chip state is stored in config. it's generated
 /// by the chip during configuration, and then stored inside the chip
 #[derive(Clone, Debug)]
-struct FieldConfig {
+pub struct FieldConfig {
     advice: [Column<Advice>; 2],
 
     /// public input column
@@ -61,14 +61,14 @@ struct FieldConfig {
 }
 
 impl<F: Field> FieldChip<F> {
-    fn construct(config: <Self as Chip<F>>::Config) -> Self {
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
         Self {
             config,
             _marker: PhantomData,
         }
     }
 
-    fn configure(
+    pub fn configure(
         meta: &mut ConstraintSystem<F>,
         advice: [Column<Advice>; 2],
         instance: Column<Instance>,
@@ -101,7 +101,7 @@ impl<F: Field> FieldChip<F> {
 }
 
 #[derive(Clone)]
-struct Number<F: Field>(AssignedCell<F, F>);
+pub struct Number<F: Field>(pub AssignedCell<F, F>);
 
 impl<F: Field> NumericInstructions<F> for FieldChip<F> {
     type Num = Number<F>;
@@ -172,10 +172,10 @@ impl<F: Field> NumericInstructions<F> for FieldChip<F> {
 }
 
 #[derive(Default)]
-struct MyCircuit<F: Field> {
-    constant: F,
-    a: Value<F>,
-    b: Value<F>,
+pub struct MyCircuit<F: Field> {
+    pub constant: F,
+    pub a: Value<F>,
+    pub b: Value<F>,
 }
 
 impl<F: Field> Circuit<F> for MyCircuit<F> {
@@ -219,7 +219,8 @@ mod tests {
     #[test]
     fn test() {
         use super::*;
-        use halo2_proofs::{dev::MockProver, pasta::Fp};
+        use crate::{mock_fails, mock_ok};
+        use halo2_proofs::pasta::Fp;
 
         // the number of rows cannot exceed 2^k.
         let k = 8;
@@ -238,18 +239,19 @@ mod tests {
             b: Value::known(b),
         };
 
-        let dot_string = halo2_proofs::dev::circuit_dot_graph(&circuit);
-        println!("{:?}", dot_string);
+        #[cfg(feature = "dev-graph")]
+        {
+            let dot_string = halo2_proofs::dev::circuit_dot_graph(&circuit);
+            println!("{:?}", dot_string);
+        }
         // arrange the public input
         let mut public_inputs = vec![c];
 
         // given the correct public input
-        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
-        assert_eq!(prover.verify(), Ok(()));
+        mock_ok!(circuit, k, vec![public_inputs.clone()]);
 
         // try some other public inputs
         public_inputs[0] += Fp::one();
-        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
-        assert!(prover.verify().is_err());
+        mock_fails!(circuit, k, vec![public_inputs]);
     }
 }
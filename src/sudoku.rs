@@ -0,0 +1,303 @@
+//! a private 9×9 grid is a solution of the public puzzle, the givens in the
+//! instance column row by row with 0 for a blank
+//!
+//! the circuit holds each digit less one, range checked to 0..9, so the nine
+//! pairwise distinct digits of a row, column or box are 1 to 9
+
+use crate::{
+    error::FiboError,
+    gadgets::range_check::{RangeCheckChip, RangeCheckConfig},
+};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// a grid row by row, 0 for a blank
+pub type Grid = [[u8; 9]; 9];
+
+/// the 27 rows, columns and boxes as (row, column) pairs
+pub fn units() -> Vec<[(usize, usize); 9]> {
+    let rows = (0..9).map(|r| std::array::from_fn(|i| (r, i)));
+    let columns = (0..9).map(|c| std::array::from_fn(|i| (i, c)));
+    let boxes = (0..9).map(|b| std::array::from_fn(|i| (b / 3 * 3 + i / 3, b % 3 * 3 + i % 3)));
+    rows.chain(columns).chain(boxes).collect()
+}
+
+/// every unit holds 1 to 9
+pub fn is_solved(grid: &Grid) -> bool {
+    units().iter().all(|unit| {
+        let mut digits: Vec<_> = unit.iter().map(|&(r, c)| grid[r][c]).collect();
+        digits.sort();
+        digits == (1..=9).collect::<Vec<_>>()
+    })
+}
+
+/// the solution agrees with every given of the puzzle
+pub fn is_consistent(puzzle: &Grid, solution: &Grid) -> bool {
+    puzzle
+        .iter()
+        .flatten()
+        .zip(solution.iter().flatten())
+        .all(|(given, digit)| *given == 0 || given == digit)
+}
+
+/// the first solution of the puzzle by backtracking, if there is one
+pub fn solve(puzzle: &Grid) -> Option<Grid> {
+    let mut grid = *puzzle;
+    fill(&mut grid).then_some(grid)
+}
+
+fn fill(grid: &mut Grid) -> bool {
+    let Some(blank) = (0..81).find(|i| grid[i / 9][i % 9] == 0) else {
+        return true;
+    };
+    let (r, c) = (blank / 9, blank % 9);
+    for digit in 1..=9 {
+        let clashes = (0..9).any(|i| {
+            grid[r][i] == digit
+                || grid[i][c] == digit
+                || grid[r / 3 * 3 + i / 3][c / 3 * 3 + i % 3] == digit
+        });
+        if !clashes {
+            grid[r][c] = digit;
+            if fill(grid) {
+                return true;
+            }
+        }
+    }
+    grid[r][c] = 0;
+    false
+}
+
+/// the instance column of the puzzle
+pub fn instance<F: PrimeField>(puzzle: &Grid) -> Vec<F> {
+    puzzle
+        .iter()
+        .flatten()
+        .map(|&given| F::from(given as u64))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct SudokuConfig {
+    /// on the first advice column, which holds the digits less one
+    pub range: RangeCheckConfig,
+    /// the other digit of a pair, or the given of a digit
+    pub other: Column<Advice>,
+    /// the inverse of the difference of a pair
+    pub inv: Column<Advice>,
+    pub s_given: Selector,
+    pub s_distinct: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// proves the private grid solves the puzzle in the instance column
+#[derive(Debug)]
+pub struct SudokuCircuit<F> {
+    /// the digits 1 to 9
+    pub grid: [[Value<F>; 9]; 9],
+}
+
+impl<F: PrimeField> Default for SudokuCircuit<F> {
+    fn default() -> Self {
+        Self {
+            grid: [[Value::unknown(); 9]; 9],
+        }
+    }
+}
+
+impl<F: PrimeField> SudokuCircuit<F> {
+    pub fn new(grid: &Grid) -> Self {
+        Self {
+            grid: grid.map(|row| row.map(|digit| Value::known(F::from(digit as u64)))),
+        }
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for SudokuCircuit<F> {
+    type Config = SudokuConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let digit = meta.advice_column();
+        let other = meta.advice_column();
+        let inv = meta.advice_column();
+        let instance = meta.instance_column();
+        let s_given = meta.selector();
+        let s_distinct = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(other);
+        meta.enable_equality(instance);
+
+        let range = RangeCheckChip::<F, 9>::configure(meta, digit);
+
+        // a blank's given is 0, anything goes
+        meta.create_gate("given", |meta| {
+            let s = meta.query_selector(s_given);
+            let digit = meta.query_advice(digit, Rotation::cur());
+            let given = meta.query_advice(other, Rotation::cur());
+            let one = Expression::Constant(F::ONE);
+            vec![s * given.clone() * (digit + one - given)]
+        });
+        meta.create_gate("distinct", |meta| {
+            let s = meta.query_selector(s_distinct);
+            let a = meta.query_advice(digit, Rotation::cur());
+            let b = meta.query_advice(other, Rotation::cur());
+            let inv = meta.query_advice(inv, Rotation::cur());
+            let one = Expression::Constant(F::ONE);
+            vec![s * ((a - b) * inv - one)]
+        });
+
+        SudokuConfig {
+            range,
+            other,
+            inv,
+            s_given,
+            s_distinct,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let digit_col = config.range.value;
+        let range = RangeCheckChip::<F, 9>::construct(config.range.clone());
+
+        let mut digits = Vec::with_capacity(81);
+        for (i, digit) in self.grid.iter().flatten().enumerate() {
+            let digit = *digit - Value::known(F::ONE);
+            digits.push(range.assign(layouter.namespace(|| format!("digit {i}")), digit)?);
+        }
+
+        let mut at = (0, "selector");
+        layouter
+            .assign_region(
+                || "givens",
+                |mut region| {
+                    for (i, digit) in digits.iter().enumerate() {
+                        at = (i, "selector");
+                        config.s_given.enable(&mut region, i)?;
+                        at = (i, "digit");
+                        digit.copy_advice(|| "digit", &mut region, digit_col, i)?;
+                        at = (i, "given");
+                        region.assign_advice_from_instance(
+                            || "given",
+                            config.instance,
+                            i,
+                            config.other,
+                            i,
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| FiboError::assign("givens", at, e))?;
+
+        for (u, unit) in units().iter().enumerate() {
+            let mut at = (0, "selector");
+            layouter
+                .assign_region(
+                    || format!("unit {u}"),
+                    |mut region| {
+                        let mut offset = 0;
+                        for i in 0..9 {
+                            for j in i + 1..9 {
+                                let (a, b) = (unit[i], unit[j]);
+                                let (a, b) = (&digits[a.0 * 9 + a.1], &digits[b.0 * 9 + b.1]);
+
+                                at = (offset, "selector");
+                                config.s_distinct.enable(&mut region, offset)?;
+                                at = (offset, "a");
+                                a.copy_advice(|| "a", &mut region, digit_col, offset)?;
+                                at = (offset, "b");
+                                b.copy_advice(|| "b", &mut region, config.other, offset)?;
+
+                                at = (offset, "inv");
+                                let inv = (a.value().copied() - b.value())
+                                    .map(|diff| diff.invert().unwrap_or(F::ZERO));
+                                region.assign_advice(|| "inv", config.inv, offset, || inv)?;
+                                offset += 1;
+                            }
+                        }
+                        Ok(())
+                    },
+                )
+                .map_err(|e| FiboError::assign("unit", at, e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::pasta::Fp;
+
+    /// the 81 range checks, the 81 givens and 36 pairs in each of 27 units
+    const K: u32 = 11;
+
+    const PUZZLE: Grid = [
+        [5, 3, 0, 0, 7, 0, 0, 0, 0],
+        [6, 0, 0, 1, 9, 5, 0, 0, 0],
+        [0, 9, 8, 0, 0, 0, 0, 6, 0],
+        [8, 0, 0, 0, 6, 0, 0, 0, 3],
+        [4, 0, 0, 8, 0, 3, 0, 0, 1],
+        [7, 0, 0, 0, 2, 0, 0, 0, 6],
+        [0, 6, 0, 0, 0, 0, 2, 8, 0],
+        [0, 0, 0, 4, 1, 9, 0, 0, 5],
+        [0, 0, 0, 0, 8, 0, 0, 7, 9],
+    ];
+
+    fn verifies(puzzle: &Grid, solution: &Grid) {
+        mock_ok!(
+            SudokuCircuit::<Fp>::new(solution),
+            K,
+            vec![instance(puzzle)]
+        );
+    }
+
+    fn fails(puzzle: &Grid, solution: &Grid) {
+        mock_fails!(
+            SudokuCircuit::<Fp>::new(solution),
+            K,
+            vec![instance(puzzle)]
+        );
+    }
+
+    #[test]
+    fn solved() {
+        let solution = solve(&PUZZLE).unwrap();
+        assert!(is_solved(&solution));
+        assert!(is_consistent(&PUZZLE, &solution));
+        assert_eq!(solution[0], [5, 3, 4, 6, 7, 8, 9, 1, 2]);
+        verifies(&PUZZLE, &solution);
+    }
+
+    #[test]
+    fn duplicate_in_row() {
+        let mut grid = solve(&PUZZLE).unwrap();
+        // (0, 2) is blank, its 4 becomes the 3 next to it
+        grid[0][2] = grid[0][1];
+        assert!(!is_solved(&grid));
+        assert!(is_consistent(&PUZZLE, &grid));
+        fails(&PUZZLE, &grid);
+    }
+
+    #[test]
+    fn ignores_givens() {
+        // swapping the digits 1 and 2 keeps the grid solved
+        let grid = solve(&PUZZLE)
+            .unwrap()
+            .map(|row| row.map(|digit| [0, 2, 1, 3, 4, 5, 6, 7, 8, 9][digit as usize]));
+        assert!(is_solved(&grid));
+        assert!(!is_consistent(&PUZZLE, &grid));
+        fails(&PUZZLE, &grid);
+    }
+}
@@ -0,0 +1,107 @@
+//! what configure puts into a ConstraintSystem, to see what a change to a chip did
+
+use crate::{
+    cost::{debug_field, DebugFieldError},
+    planner::planner_name,
+};
+use halo2_proofs::{
+    dev::CircuitGates,
+    pasta::group::ff::PrimeField,
+    plonk::{Circuit, ConstraintSystem},
+};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitSummary {
+    pub k: u32,
+    pub gates: Vec<String>,
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    /// selectors before they get compressed into fixed columns
+    pub selectors: usize,
+    pub minimum_rows: usize,
+    /// rows left to the circuit in 2^k once the blinding rows are reserved
+    pub usable_rows: usize,
+    pub degree: usize,
+    pub floor_planner: &'static str,
+}
+
+/// runs configure of `C` on a fresh ConstraintSystem and summarizes it
+pub fn describe<F: PrimeField, C: Circuit<F>>(k: u32) -> Result<CircuitSummary, DebugFieldError> {
+    let mut meta = ConstraintSystem::<F>::default();
+    C::configure(&mut meta);
+
+    // the counts are only exposed through the Debug output of the pinned system
+    let pinned = format!("{:?}", meta.pinned());
+    let field = |field| {
+        debug_field(&pinned, field).ok_or(DebugFieldError {
+            of: "PinnedConstraintSystem",
+            field,
+        })
+    };
+
+    // and the gate names through the Debug output of CircuitGates
+    let gates = format!("{:?}", CircuitGates::collect::<F, C>());
+    let gates = gates
+        .split("Gate { name: \"")
+        .skip(1)
+        .map(|gate| Some(gate[..gate.find('"')?].to_string()))
+        .collect::<Option<_>>()
+        .ok_or(DebugFieldError {
+            of: "CircuitGates",
+            field: "name",
+        })?;
+
+    Ok(CircuitSummary {
+        k,
+        gates,
+        advice_columns: field("num_advice_columns")?,
+        fixed_columns: field("num_fixed_columns")?,
+        instance_columns: field("num_instance_columns")?,
+        selectors: field("num_selectors")?,
+        minimum_rows: meta.minimum_rows(),
+        usable_rows: (1 << k) - (meta.blinding_factors() + 1),
+        degree: meta.degree(),
+        floor_planner: planner_name::<F, C>(),
+    })
+}
+
+impl fmt::Display for CircuitSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<18} {}", "gates", self.gates.join(", "))?;
+        writeln!(f, "{:<18} {}", "advice columns", self.advice_columns)?;
+        writeln!(f, "{:<18} {}", "fixed columns", self.fixed_columns)?;
+        writeln!(f, "{:<18} {}", "instance columns", self.instance_columns)?;
+        writeln!(f, "{:<18} {}", "selectors", self.selectors)?;
+        writeln!(f, "{:<18} {}", "degree", self.degree)?;
+        writeln!(f, "{:<18} {}", "minimum rows", self.minimum_rows)?;
+        writeln!(f, "{:<18} {}", "floor planner", self.floor_planner)?;
+        writeln!(
+            f,
+            "{:<18} {} (k = {})",
+            "usable rows", self.usable_rows, self.k
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{row_based, single_column};
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn describe_fibonacci() {
+        let summary = describe::<Fp, row_based::MyCircuit<Fp>>(4).unwrap();
+        assert_eq!(summary.advice_columns, 3);
+        assert_eq!(summary.selectors, 1);
+        assert_eq!(summary.gates, vec!["add"]);
+        assert_eq!(summary.usable_rows, 10);
+        assert_eq!(summary.floor_planner, "SimpleFloorPlanner");
+
+        let summary = describe::<Fp, single_column::MyCircuit<Fp>>(4).unwrap();
+        assert_eq!(summary.advice_columns, 1);
+        assert_eq!(summary.gates, vec!["add"]);
+    }
+}
@@ -0,0 +1,161 @@
+//! proves a fibonacci layout at every k from the least it fits in up to a
+//! cap, the sequence as long as each size holds, and times each step. the
+//! rows are the csv the k_sweep binary prints
+
+use std::{fmt::Debug, io, path::Path, time::Instant};
+
+use crate::{
+    cost::{self, DebugFieldError},
+    fibonacci::public_inputs::PublicInputs,
+    params_cache, proving,
+};
+use halo2_proofs::{
+    pasta::Fp,
+    plonk::{Circuit, Error},
+};
+use rand_core::OsRng;
+use thiserror::Error;
+
+pub const CSV_HEADER: &str = "k,n,rows_used,keygen_ms,prove_ms,verify_ms,proof_bytes";
+
+#[derive(Debug, Error)]
+pub enum SweepError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Cost(#[from] DebugFieldError),
+    #[error("k = {k}: {source}")]
+    Proving {
+        k: u32,
+        #[source]
+        source: Error,
+    },
+}
+
+/// what proving the longest sequence fitting 2^k rows costs
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepRow {
+    pub k: u32,
+    pub n: usize,
+    /// rows assigned by the floor planner, over all columns
+    pub rows_used: usize,
+    pub keygen_ms: f64,
+    pub prove_ms: f64,
+    pub verify_ms: f64,
+    pub proof_bytes: usize,
+}
+
+impl SweepRow {
+    /// a line of the csv, in the order of CSV_HEADER
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{:.3},{:.3},{:.3},{}",
+            self.k,
+            self.n,
+            self.rows_used,
+            self.keygen_ms,
+            self.prove_ms,
+            self.verify_ms,
+            self.proof_bytes
+        )
+    }
+}
+
+/// a layout of the sequence: its circuit of the n-th number, and the largest
+/// n fitting 2^k rows
+pub struct Layout<C> {
+    pub circuit: fn(usize) -> C,
+    pub max_n: fn(u32) -> usize,
+}
+
+/// sweeps k from the least the layout fits in up to `max_k`, with the
+/// params cached in `params_dir`, calling `row` as each k is done
+pub fn sweep<C: Circuit<Fp> + Debug>(
+    layout: &Layout<C>,
+    max_k: u32,
+    params_dir: &Path,
+    mut row: impl FnMut(&SweepRow),
+) -> Result<Vec<SweepRow>, SweepError> {
+    // the shortest sequence, 1, 1, 2
+    let instances = [PublicInputs::new(Fp::one(), Fp::one(), 3).to_instance_column()];
+    let min_k = cost::find_min_k(&(layout.circuit)(3), &instances, max_k)
+        .map_err(|source| SweepError::Proving { k: max_k, source })?;
+
+    let mut rows = vec![];
+    for k in min_k..=max_k {
+        let sweep_row = sweep_k(layout, k, params_dir)?;
+        row(&sweep_row);
+        rows.push(sweep_row);
+    }
+    Ok(rows)
+}
+
+fn sweep_k<C: Circuit<Fp> + Debug>(
+    layout: &Layout<C>,
+    k: u32,
+    params_dir: &Path,
+) -> Result<SweepRow, SweepError> {
+    let proving = |source| SweepError::Proving { k, source };
+    let ms = |start: Instant| start.elapsed().as_secs_f64() * 1000.0;
+
+    let params = params_cache::load_or_generate(k, params_dir)?;
+    let n = (layout.max_n)(k);
+    let instance = PublicInputs::new(Fp::one(), Fp::one(), n).to_instance_column();
+    let rows_used = cost::measure(k, &(layout.circuit)(n))?.rows;
+
+    let start = Instant::now();
+    let pk = proving::keygen(&params, &(layout.circuit)(n)).map_err(proving)?;
+    let keygen_ms = ms(start);
+
+    let start = Instant::now();
+    let proof =
+        proving::prove(&params, &pk, (layout.circuit)(n), &[&instance], OsRng).map_err(proving)?;
+    let prove_ms = ms(start);
+
+    let start = Instant::now();
+    proving::verify(&params, pk.get_vk(), &proof, &[&instance]).map_err(proving)?;
+    let verify_ms = ms(start);
+
+    Ok(SweepRow {
+        k,
+        n,
+        rows_used,
+        keygen_ms,
+        prove_ms,
+        verify_ms,
+        proof_bytes: proof.as_bytes().len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{row_based, single_column};
+
+    #[test]
+    fn sweep_fills_each_k() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = Layout {
+            circuit: single_column::MyCircuit::<Fp>::new,
+            max_n: single_column::max_rows::<Fp>,
+        };
+        let mut seen = vec![];
+        let rows = sweep(&layout, 5, dir.path(), |row| seen.push(row.k)).unwrap();
+        assert_eq!(seen, [4, 5]);
+        // the one column holds a number per row, filling what halo2 leaves
+        assert_eq!(rows[0].n, 10);
+        assert_eq!(rows[1].rows_used, rows[1].n);
+        assert!(rows[0].proof_bytes > 0 && rows[1].verify_ms > 0.0);
+        assert!(rows[0].to_csv().starts_with("4,10,10,"));
+        assert_eq!(rows[0].to_csv().split(',').count(), 7);
+
+        let layout = Layout {
+            circuit: row_based::MyCircuit::<Fp>::new,
+            max_n: row_based::max_n::<Fp>,
+        };
+        assert!(matches!(
+            sweep(&layout, 3, dir.path(), |_| {}),
+            Err(SweepError::Proving { k: 3, .. })
+        ));
+    }
+}
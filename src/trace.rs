@@ -0,0 +1,365 @@
+//! tracing spans of synthesis and proving, and a subscriber printing them
+//!
+//! configure, synthesize and each region of the row based and single column
+//! circuits open a span, as do keygen_vk, keygen_pk, create_proof and
+//! verify_proof in proving. halo2 synthesizes a circuit once for each of those
+//! passes but the last, so every synthesize span records the pass it is in,
+//! the name of the span around it, and the floor planner then runs each
+//! region closure twice within it
+
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{self, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    thread::{self, ThreadId},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    level_filters::LevelFilter,
+    span, Event, Metadata, Span, Subscriber,
+};
+
+/// the name of the span the caller is in, "none" outside any or without a
+/// subscriber
+pub fn pass() -> &'static str {
+    Span::current().metadata().map_or("none", |m| m.name())
+}
+
+/// installs a subscriber printing spans and events to stderr, filtered by
+/// RUST_LOG as in `info` or `fibo=debug,halo2_proofs=off`. returns whether it
+/// was installed, which it is not without RUST_LOG or if another was first
+pub fn init_tracing() -> bool {
+    let Ok(directives) = env::var("RUST_LOG") else {
+        return false;
+    };
+    let subscriber = TreeSubscriber::new(Filter::parse(&directives), Sink::Stderr);
+    tracing::subscriber::set_global_default(subscriber).is_ok()
+}
+
+/// RUST_LOG directives: a level for every target prefix, "" for all
+#[derive(Debug, Default)]
+pub struct Filter(Vec<(String, LevelFilter)>);
+
+impl Filter {
+    /// skips what does not parse
+    pub fn parse(directives: &str) -> Self {
+        let directive = |d: &str| {
+            let (target, level) = d.rsplit_once('=').unwrap_or(("", d));
+            Some((target.trim().to_owned(), level.trim().parse().ok()?))
+        };
+        let mut directives: Vec<_> = directives.split(',').filter_map(directive).collect();
+        // the most specific target first
+        directives.sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Self(directives)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.0
+            .iter()
+            .find(|(target, _)| metadata.target().starts_with(target.as_str()))
+            .is_some_and(|(_, level)| level >= metadata.level())
+    }
+}
+
+/// where TreeSubscriber writes the paths of spans and events
+#[derive(Debug)]
+pub enum Sink {
+    Stderr,
+    /// kept, for taken
+    Capture(Mutex<Vec<String>>),
+}
+
+#[derive(Debug)]
+struct SpanEntry {
+    metadata: &'static Metadata<'static>,
+    label: String,
+    parent: Option<u64>,
+    refs: usize,
+}
+
+/// spans by id, and the spans each thread is in
+#[derive(Debug, Default)]
+struct State {
+    spans: HashMap<u64, SpanEntry>,
+    stacks: HashMap<ThreadId, Vec<u64>>,
+}
+
+impl State {
+    fn current(&self) -> Option<u64> {
+        self.stacks.get(&thread::current().id())?.last().copied()
+    }
+
+    /// the labels from the root down to `id`, with the depth
+    fn path(&self, mut id: Option<u64>) -> (String, usize) {
+        let mut labels = vec![];
+        while let Some(entry) = id.and_then(|id| self.spans.get(&id)) {
+            labels.push(entry.label.as_str());
+            id = entry.parent;
+        }
+        labels.reverse();
+        (labels.join(" > "), labels.len())
+    }
+}
+
+/// a subscriber tracking the nesting of spans, and writing each new span and
+/// event with the path of spans to it
+#[derive(Debug)]
+pub struct TreeSubscriber {
+    filter: Filter,
+    sink: Sink,
+    next_id: AtomicU64,
+    state: Mutex<State>,
+}
+
+impl TreeSubscriber {
+    pub fn new(filter: Filter, sink: Sink) -> Self {
+        Self {
+            filter,
+            sink,
+            next_id: AtomicU64::new(1),
+            state: Mutex::default(),
+        }
+    }
+
+    /// what a capturing subscriber has written so far
+    pub fn captured(&self) -> Vec<String> {
+        match &self.sink {
+            Sink::Stderr => vec![],
+            Sink::Capture(lines) => lines.lock().unwrap().clone(),
+        }
+    }
+
+    fn write(&self, path: String, depth: usize, leaf: &str) {
+        match &self.sink {
+            Sink::Stderr => eprintln!("{:indent$}{leaf}", "", indent = 2 * depth),
+            Sink::Capture(lines) => lines.lock().unwrap().push(path),
+        }
+    }
+}
+
+/// `name{field=value, ...}`
+struct Label(String);
+
+impl Label {
+    fn new(name: &str) -> Self {
+        Self(name.to_owned())
+    }
+
+    fn finish(mut self) -> String {
+        if self.0.ends_with(", ") {
+            self.0.truncate(self.0.len() - 2);
+            self.0.push('}');
+        }
+        self.0
+    }
+}
+
+impl Visit for Label {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.0.ends_with(", ") {
+            self.0.push('{');
+        }
+        write!(self.0, "{}={:?}, ", field.name(), value).unwrap();
+    }
+}
+
+impl Subscriber for TreeSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.filter.enabled(metadata)
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut label = Label::new(attrs.metadata().name());
+        attrs.record(&mut label);
+
+        let mut state = self.state.lock().unwrap();
+        let parent = match attrs.parent() {
+            Some(parent) => Some(parent.into_u64()),
+            None if attrs.is_contextual() => state.current(),
+            None => None,
+        };
+        let label = label.finish();
+        state.spans.insert(
+            id,
+            SpanEntry {
+                metadata: attrs.metadata(),
+                label: label.clone(),
+                parent,
+                refs: 1,
+            },
+        );
+        let (path, depth) = state.path(Some(id));
+        drop(state);
+
+        self.write(path, depth - 1, &label);
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut label = Label::new(event.metadata().level().as_str());
+        event.record(&mut label);
+        let label = label.finish();
+
+        let state = self.state.lock().unwrap();
+        let parent = event.parent().map(span::Id::into_u64).or(state.current());
+        let (path, depth) = state.path(parent);
+        drop(state);
+
+        let path = if path.is_empty() {
+            label.clone()
+        } else {
+            format!("{path} > {label}")
+        };
+        self.write(path, depth, &label);
+    }
+
+    fn enter(&self, span: &span::Id) {
+        let mut state = self.state.lock().unwrap();
+        let stack = state.stacks.entry(thread::current().id()).or_default();
+        stack.push(span.into_u64());
+    }
+
+    fn exit(&self, span: &span::Id) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(stack) = state.stacks.get_mut(&thread::current().id()) {
+            if let Some(i) = stack.iter().rposition(|&id| id == span.into_u64()) {
+                stack.remove(i);
+            }
+        }
+    }
+
+    fn current_span(&self) -> tracing_core::span::Current {
+        let state = self.state.lock().unwrap();
+        match state
+            .current()
+            .and_then(|id| Some((id, state.spans.get(&id)?)))
+        {
+            Some((id, entry)) => {
+                tracing_core::span::Current::new(span::Id::from_u64(id), entry.metadata)
+            }
+            None => tracing_core::span::Current::none(),
+        }
+    }
+
+    fn clone_span(&self, id: &span::Id) -> span::Id {
+        let mut state = self.state.lock().unwrap();
+        if let Some(entry) = state.spans.get_mut(&id.into_u64()) {
+            entry.refs += 1;
+        }
+        id.clone()
+    }
+
+    /// a closed span stays while it is the parent of an open one, so paths
+    /// can still be walked up from the children
+    fn try_close(&self, id: span::Id) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(entry) = state.spans.get_mut(&id.into_u64()) else {
+            return false;
+        };
+        entry.refs -= 1;
+        let closed = entry.refs == 0;
+
+        // then the parents only kept for it
+        let mut id = closed.then_some(id.into_u64());
+        while let Some(closing) = id {
+            let entry = &state.spans[&closing];
+            let has_children = state.spans.values().any(|e| e.parent == Some(closing));
+            if entry.refs > 0 || has_children {
+                break;
+            }
+            id = state.spans.remove(&closing).unwrap().parent;
+        }
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fibonacci::{public_inputs::PublicInputs, row_based::MyCircuit},
+        proving,
+    };
+    use halo2_proofs::{
+        pasta::{EqAffine, Fp},
+        poly::commitment::Params,
+    };
+    use rand_core::OsRng;
+    use std::sync::Arc;
+
+    fn capture(directives: &str, f: impl FnOnce()) -> Vec<String> {
+        let subscriber = Arc::new(TreeSubscriber::new(
+            Filter::parse(directives),
+            Sink::Capture(Mutex::default()),
+        ));
+        tracing::subscriber::with_default(subscriber.clone(), f);
+        subscriber.captured()
+    }
+
+    #[test]
+    fn filter() {
+        let filter = Filter::parse("info,fibo::trace=trace, halo2_proofs = off,bogus=loud");
+        assert_eq!(
+            filter.0,
+            [
+                ("halo2_proofs".to_owned(), LevelFilter::OFF),
+                ("fibo::trace".to_owned(), LevelFilter::TRACE),
+                ("".to_owned(), LevelFilter::INFO),
+            ]
+        );
+        assert!(Filter::parse("off")
+            .0
+            .iter()
+            .all(|(_, l)| *l == LevelFilter::OFF));
+    }
+
+    #[test]
+    fn passes() {
+        let params: Params<EqAffine> = Params::new(4);
+        let publics = PublicInputs::new(Fp::one(), Fp::one(), 10).to_instance_column();
+        let paths = capture("fibo=debug", || {
+            let circuit = MyCircuit::<Fp>::new(10);
+            let pk = proving::keygen(&params, &circuit).unwrap();
+            let proof = proving::prove(&params, &pk, circuit, &[&publics], OsRng).unwrap();
+            proving::verify(&params, pk.get_vk(), &proof, &[&publics]).unwrap();
+        });
+
+        // each pass synthesizes the circuit but verify_proof
+        for pass in ["keygen_vk", "keygen_pk", "create_proof"] {
+            let synthesize = format!("{pass} > synthesize{{pass={pass}}}");
+            assert!(paths.contains(&synthesize), "{synthesize} in {paths:#?}");
+            let first_row = format!("{synthesize} > region{{name=first row}}");
+            // the floor planner measures the region, then assigns it
+            assert_eq!(paths.iter().filter(|p| **p == first_row).count(), 2);
+        }
+        assert!(paths.contains(&"verify_proof".to_owned()));
+        assert!(!paths.iter().any(|p| p.starts_with("verify_proof > ")));
+
+        assert!(paths.contains(&"keygen_vk > configure".to_owned()));
+    }
+
+    #[test]
+    fn pass_label() {
+        assert_eq!(pass(), "none");
+        let paths = capture("info", || {
+            let _span = tracing::info_span!("outer").entered();
+            assert_eq!(pass(), "outer");
+            tracing::info!(pass = pass(), "inside");
+        });
+        assert_eq!(paths, ["outer", "outer > INFO{message=inside, pass=outer}"]);
+    }
+}
@@ -0,0 +1,142 @@
+//! what the examples share to assign cells
+
+use std::ops::Deref;
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{AssignedCell, Region, Value},
+    plonk::{Advice, Column, Error},
+};
+
+/// an assigned advice cell holding a field element. derefs to the
+/// AssignedCell, so value() and cell() work on it as on the cell itself
+#[derive(Debug, Clone)]
+pub struct ACell<F: Field>(pub AssignedCell<F, F>);
+
+impl<F: Field> ACell<F> {
+    /// the value, owned rather than borrowed from the cell
+    pub fn value_copied(&self) -> Value<F> {
+        self.0.value().copied()
+    }
+
+    /// copies the cell to `offset` of `column`, constrained equal to it
+    pub fn copy_to(
+        &self,
+        region: &mut Region<'_, F>,
+        column: Column<Advice>,
+        offset: usize,
+        annotation: &str,
+    ) -> Result<Self, Error> {
+        self.0
+            .copy_advice(|| annotation, region, column, offset)
+            .map(Self)
+    }
+}
+
+impl<F: Field> Deref for ACell<F> {
+    type Target = AssignedCell<F, F>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<F: Field> From<AssignedCell<F, F>> for ACell<F> {
+    fn from(cell: AssignedCell<F, F>) -> Self {
+        Self(cell)
+    }
+}
+
+impl<F: Field> From<ACell<F>> for AssignedCell<F, F> {
+    fn from(cell: ACell<F>) -> Self {
+        cell.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        pasta::Fp,
+        plonk::{Circuit, ConstraintSystem},
+    };
+
+    /// a cell copied down a column, then the copy witnessed anew with `copy`
+    /// to check copy_to constrains the two equal
+    struct Copies {
+        value: Fp,
+        copy: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for Copies {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                value: self.value,
+                copy: self.copy,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = meta.advice_column();
+            meta.enable_equality(advice);
+            advice
+        }
+
+        fn synthesize(
+            &self,
+            advice: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copies",
+                |mut region| {
+                    let cell: ACell<Fp> = region
+                        .assign_advice(|| "value", advice, 0, || Value::known(self.value))?
+                        .into();
+                    let copy = cell.copy_to(&mut region, advice, 1, "copy")?;
+                    copy.value_copied()
+                        .zip(cell.value_copied())
+                        .assert_if_known(|(copy, cell)| copy == cell);
+
+                    // the same cell through deref, and after a round trip
+                    let raw: AssignedCell<Fp, Fp> = copy.clone().into();
+                    assert_eq!(format!("{:?}", raw.cell()), format!("{:?}", copy.cell()));
+
+                    let Some(other) = self.copy else {
+                        return Ok(());
+                    };
+                    let other =
+                        region.assign_advice(|| "other", advice, 2, || Value::known(other))?;
+                    region.constrain_equal(copy.cell(), other.cell())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn copy_to() {
+        let value = Fp::from(7);
+        mock_ok!(Copies { value, copy: None }, 4, vec![]);
+        mock_ok!(
+            Copies {
+                value,
+                copy: Some(value)
+            },
+            4,
+            vec![]
+        );
+        mock_fails!(
+            Copies {
+                value,
+                copy: Some(value + Fp::one())
+            },
+            4,
+            vec![]
+        );
+    }
+}
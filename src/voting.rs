@@ -0,0 +1,219 @@
+//! a private vote: the tally of the hidden boolean votes is public, and so,
+//! optionally, is the number of votes counted
+//!
+//! the votes are summed by the accumulator chip, each row of it also holding
+//! an index, 1 on the first vote and one more on each vote after, as the
+//! factorial table does. the last index is the number of votes
+
+use crate::{
+    error::FiboError,
+    gadgets::accumulator::{AccumulatorChip, AccumulatorConfig},
+};
+use halo2_proofs::{circuit::*, pasta::group::ff::PrimeField, plonk::*, poly::Rotation};
+
+/// instance row of the tally
+pub const TALLY_ROW: usize = 0;
+/// instance row of the number of votes, if public
+pub const COUNT_ROW: usize = 1;
+
+#[derive(Debug, Clone)]
+pub struct VotingConfig {
+    pub accumulator: AccumulatorConfig,
+    pub idx: Column<Advice>,
+    /// on every vote: the vote is boolean
+    pub s_vote: Selector,
+    /// on every vote but the first: the index is one more than the one before
+    pub s_count: Selector,
+    pub instance: Column<Instance>,
+}
+
+/// proves the private votes add up to the tally in the instance column
+#[derive(Debug, Default)]
+pub struct VotingCircuit<F> {
+    pub votes: Vec<Value<F>>,
+    /// whether the number of votes follows the tally in the instance column
+    pub public_count: bool,
+}
+
+impl<F: PrimeField> VotingCircuit<F> {
+    pub fn new(votes: &[u64]) -> Self {
+        Self {
+            votes: votes.iter().map(|&v| Value::known(F::from(v))).collect(),
+            public_count: false,
+        }
+    }
+
+    /// the number of votes made public as well
+    pub fn with_public_count(self) -> Self {
+        Self {
+            public_count: true,
+            ..self
+        }
+    }
+
+    /// the instance column for the tally, followed by the number of votes if public
+    pub fn instance(&self, tally: u64) -> Vec<F> {
+        let mut instance = vec![F::from(tally)];
+        if self.public_count {
+            instance.push(F::from(self.votes.len() as u64));
+        }
+        instance
+    }
+
+    fn tally(&self, config: VotingConfig, mut layouter: impl Layouter<F>) -> Result<(), FiboError> {
+        if self.votes.is_empty() {
+            return Err(FiboError::TooFewRows { rows: 0, min: 1 });
+        }
+
+        let VotingConfig {
+            idx,
+            s_vote,
+            s_count,
+            instance,
+            ..
+        } = config;
+        let mut count = None;
+        let chip = AccumulatorChip::construct(config.accumulator);
+        let (_, tally) = chip.assign_rows(
+            layouter.namespace(|| "votes"),
+            &self.votes,
+            |region, row| {
+                s_vote.enable(region, row)?;
+                let cell = if row == 0 {
+                    region.assign_advice_from_constant(|| "idx", idx, 0, F::ONE)?
+                } else {
+                    s_count.enable(region, row)?;
+                    let idx_value = Value::known(F::from(row as u64 + 1));
+                    region.assign_advice(|| "idx", idx, row, || idx_value)?
+                };
+                count = Some(cell);
+                Ok(())
+            },
+        )?;
+
+        layouter
+            .constrain_instance(tally.cell(), instance, TALLY_ROW)
+            .map_err(|e| FiboError::expose(TALLY_ROW, e))?;
+        if self.public_count {
+            layouter
+                .constrain_instance(count.unwrap().cell(), instance, COUNT_ROW)
+                .map_err(|e| FiboError::expose(COUNT_ROW, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for VotingCircuit<F> {
+    type Config = VotingConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the number of votes and whether it is public shape the circuit
+        Self {
+            votes: vec![Value::unknown(); self.votes.len()],
+            public_count: self.public_count,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 2].map(|_| meta.advice_column());
+        let idx = meta.advice_column();
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        let s_vote = meta.selector();
+        let s_count = meta.selector();
+
+        // enable the equality
+        meta.enable_equality(idx);
+        meta.enable_equality(instance);
+
+        let accumulator = AccumulatorChip::configure(meta, advice, constant);
+
+        meta.create_gate("vote", |meta| {
+            let s = meta.query_selector(s_vote);
+            let vote = meta.query_advice(accumulator.value, Rotation::cur());
+            vec![s * vote.clone() * (Expression::Constant(F::ONE) - vote)]
+        });
+
+        meta.create_gate("count", |meta| {
+            let s = meta.query_selector(s_count);
+            let idx_prev = meta.query_advice(idx, Rotation::prev());
+            let idx = meta.query_advice(idx, Rotation::cur());
+            vec![s * (idx_prev + Expression::Constant(F::ONE) - idx)]
+        });
+
+        VotingConfig {
+            accumulator,
+            idx,
+            s_vote,
+            s_count,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(self.tally(config, layouter)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_fails, mock_ok};
+    use halo2_proofs::{dev::VerifyFailure, pasta::Fp};
+
+    fn verifies(votes: &[u64], tally: u64) {
+        let circuit = VotingCircuit::new(votes);
+        mock_ok!(circuit, 5, vec![circuit.instance(tally)]);
+    }
+
+    fn fails(votes: &[u64], tally: u64) {
+        let circuit = VotingCircuit::new(votes);
+        mock_fails!(circuit, 5, vec![circuit.instance(tally)]);
+    }
+
+    #[test]
+    fn all_zeros() {
+        verifies(&[0; 10], 0);
+        fails(&[0; 10], 1);
+    }
+
+    #[test]
+    fn all_ones() {
+        verifies(&[1; 10], 10);
+        fails(&[1; 10], 9);
+    }
+
+    #[test]
+    fn mixed() {
+        let votes = [1, 0, 1, 1, 0, 0, 1];
+        verifies(&votes, 4);
+        // off by one either way
+        fails(&votes, 3);
+        fails(&votes, 5);
+    }
+
+    #[test]
+    fn vote_of_two() {
+        // 1 + 2 + 0 = 3 adds up, but 2 is no vote
+        let circuit = VotingCircuit::<Fp>::new(&[1, 2, 0]);
+        let failures = mock_fails!(circuit, 5, vec![circuit.instance(3)]);
+        assert_eq!(failures.len(), 1, "{failures:?}");
+        assert!(matches!(
+            &failures[0],
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                if constraint.to_string().contains("vote")
+        ));
+    }
+
+    #[test]
+    fn public_count() {
+        let circuit = VotingCircuit::<Fp>::new(&[1, 0, 1]).with_public_count();
+        assert_eq!(circuit.instance(2), vec![Fp::from(2), Fp::from(3)]);
+        mock_ok!(circuit, 5, vec![circuit.instance(2)]);
+
+        // three votes counted as four
+        let instance = vec![Fp::from(2), Fp::from(4)];
+        mock_fails!(circuit, 5, vec![instance]);
+    }
+}
@@ -0,0 +1,269 @@
+//! the seeds, n and optionally the claimed output of a fibonacci circuit, read
+//! from a json or toml file
+//!
+//! a, b and out are decimal or 0x prefixed hex numbers, as strings or, for
+//! small values, bare integers, and must be below the modulus of Fp. of toml
+//! only flat `key = value` lines and # comments are read, which is all the
+//! format needs
+//!
+//! ```toml
+//! a = "1"
+//! b = "0x1"
+//! n = 10
+//! # out = "55", computed if absent
+//! ```
+
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use crate::fibonacci::public_inputs::PublicInputs;
+use halo2_proofs::pasta::{group::ff::PrimeField, Fp};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WitnessError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("malformed json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("line {line}: {message}")]
+    Toml { line: usize, message: String },
+    #[error("the witness file is neither .json nor .toml")]
+    UnknownFormat,
+    #[error("missing field `{0}`")]
+    Missing(&'static str),
+    #[error("field `{field}` is not a number: {value}")]
+    NotNumeric { field: &'static str, value: String },
+    #[error("field `{field}` is not below the modulus of Fp: {value}")]
+    Overflow { field: &'static str, value: String },
+}
+
+/// the parsed file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WitnessFile {
+    pub a: Fp,
+    pub b: Fp,
+    /// index of the fibonacci number to prove
+    pub n: usize,
+    /// the claimed output, the honest one if absent
+    pub out: Option<Fp>,
+}
+
+impl WitnessFile {
+    /// reads a .json or .toml file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, WitnessError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(&text),
+            Some("toml") => Self::from_toml(&text),
+            _ => Err(WitnessError::UnknownFormat),
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, WitnessError> {
+        let fields: BTreeMap<String, serde_json::Value> = serde_json::from_str(text)?;
+        let fields = fields
+            .into_iter()
+            .map(|(key, value)| match value {
+                serde_json::Value::String(value) => (key, value),
+                value => (key, value.to_string()),
+            })
+            .collect();
+        Self::from_fields(&fields)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, WitnessError> {
+        let mut fields = BTreeMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: &str| WitnessError::Toml {
+                line: i + 1,
+                message: message.to_owned(),
+            };
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected key = value"))?;
+            let value = value.trim();
+            let value = match value.strip_prefix('"') {
+                Some(quoted) => quoted
+                    .strip_suffix('"')
+                    .ok_or_else(|| error("unterminated string"))?,
+                None => value,
+            };
+            fields.insert(key.trim().to_owned(), value.to_owned());
+        }
+        Self::from_fields(&fields)
+    }
+
+    fn from_fields(fields: &BTreeMap<String, String>) -> Result<Self, WitnessError> {
+        let field = |name| fields.get(name).ok_or(WitnessError::Missing(name));
+        let n = field("n")?;
+        Ok(Self {
+            a: parse_fp("a", field("a")?)?,
+            b: parse_fp("b", field("b")?)?,
+            n: n.parse().map_err(|_| WitnessError::NotNumeric {
+                field: "n",
+                value: n.clone(),
+            })?,
+            out: fields
+                .get("out")
+                .map(|out| parse_fp("out", out))
+                .transpose()?,
+        })
+    }
+
+    /// the claimed public inputs, out computed from the seeds if absent
+    pub fn publics(&self) -> PublicInputs<Fp> {
+        let honest = PublicInputs::new(self.a, self.b, self.n);
+        PublicInputs {
+            out: self.out.unwrap_or(honest.out),
+            ..honest
+        }
+    }
+}
+
+/// a decimal or 0x prefixed hex number below the modulus
+pub fn parse_fp(field: &'static str, value: &str) -> Result<Fp, WitnessError> {
+    let not_numeric = || WitnessError::NotNumeric {
+        field,
+        value: value.to_owned(),
+    };
+    let overflow = || WitnessError::Overflow {
+        field,
+        value: value.to_owned(),
+    };
+
+    let (radix, digits) = match value.strip_prefix("0x") {
+        Some(digits) => (16, digits),
+        None => (10, value),
+    };
+    if digits.is_empty() {
+        return Err(not_numeric());
+    }
+
+    // the little endian u64 limbs of the 256 bit repr
+    let mut limbs = [0u64; 4];
+    for c in digits.chars() {
+        let digit = c.to_digit(radix).ok_or_else(not_numeric)?;
+        let mut carry = digit as u128;
+        for limb in &mut limbs {
+            let wide = *limb as u128 * radix as u128 + carry;
+            *limb = wide as u64;
+            carry = wide >> 64;
+        }
+        if carry != 0 {
+            return Err(overflow());
+        }
+    }
+
+    let mut repr = [0u8; 32];
+    for (bytes, limb) in repr.chunks_mut(8).zip(limbs) {
+        bytes.copy_from_slice(&limb.to_le_bytes());
+    }
+    Option::from(Fp::from_repr(repr)).ok_or_else(overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::vectors;
+
+    /// p - 1, the largest element
+    const MAX: &str = "0x40000000000000000000000000000000224698fc094cf91b992d30ed00000000";
+
+    #[test]
+    fn decimal() {
+        let file = WitnessFile::from_toml("a = \"2\"\nb = 3\nn = 10\nout = \"144\"\n").unwrap();
+        assert_eq!(file.a, Fp::from(2));
+        assert_eq!(file.b, Fp::from(3));
+        assert_eq!(file.n, 10);
+        assert_eq!(
+            file.publics(),
+            PublicInputs::new(Fp::from(2), Fp::from(3), 10)
+        );
+    }
+
+    #[test]
+    fn hex() {
+        let file = WitnessFile::from_json("{\"a\": \"0x1\", \"b\": \"0xff\", \"n\": 3}").unwrap();
+        assert_eq!(file.a, Fp::one());
+        assert_eq!(file.b, Fp::from(255));
+        assert_eq!(parse_fp("a", MAX).unwrap(), -Fp::one());
+        assert_eq!(parse_fp("a", "0xDEAD").unwrap(), Fp::from(0xdead));
+    }
+
+    #[test]
+    fn overflow() {
+        // p, and a number past 256 bits
+        let p = "0x40000000000000000000000000000000224698fc094cf91b992d30ed00000001";
+        assert!(matches!(
+            parse_fp("out", p),
+            Err(WitnessError::Overflow { field: "out", .. })
+        ));
+        let wide = format!("0x1{}", "0".repeat(64));
+        assert!(matches!(
+            parse_fp("a", &wide),
+            Err(WitnessError::Overflow { .. })
+        ));
+        let decimal = "1".repeat(80);
+        assert!(matches!(
+            parse_fp("b", &decimal),
+            Err(WitnessError::Overflow { field: "b", .. })
+        ));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            WitnessFile::from_toml("a = 1\nn = 10\n"),
+            Err(WitnessError::Missing("b"))
+        ));
+        assert!(matches!(
+            WitnessFile::from_toml("a = 1\nb = one\nn = 10\n"),
+            Err(WitnessError::NotNumeric { field: "b", .. })
+        ));
+        assert!(matches!(
+            WitnessFile::from_toml("a = 1\nb = 1\nn = -3\n"),
+            Err(WitnessError::NotNumeric { field: "n", .. })
+        ));
+        assert!(matches!(
+            parse_fp("a", "0x"),
+            Err(WitnessError::NotNumeric { .. })
+        ));
+        assert!(matches!(
+            WitnessFile::from_toml("a = 1\nb\n"),
+            Err(WitnessError::Toml { line: 2, .. })
+        ));
+        assert!(matches!(
+            WitnessFile::from_json("{\"a\": 1,"),
+            Err(WitnessError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn out_computed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fib.toml");
+        fs::write(
+            &path,
+            "# the 10th from 1, 1\na = \"1\"\nb = \"1\"\nn = 10\n",
+        )
+        .unwrap();
+        let file = WitnessFile::load(&path).unwrap();
+        assert_eq!(file.out, None);
+        assert_eq!(file.publics().out, vectors::nth(10));
+
+        assert!(matches!(
+            WitnessFile::load(dir.path().join("fib.yaml")),
+            Err(WitnessError::Io(_))
+        ));
+        fs::write(dir.path().join("fib.yaml"), "a: 1").unwrap();
+        assert!(matches!(
+            WitnessFile::load(dir.path().join("fib.yaml")),
+            Err(WitnessError::UnknownFormat)
+        ));
+    }
+}
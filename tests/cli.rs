@@ -0,0 +1,409 @@
+use assert_cmd::Command;
+use fibo::{encoding::fp_to_hex, fibonacci::vectors::nth};
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+
+fn fibo() -> Command {
+    Command::cargo_bin("fibo").unwrap()
+}
+
+#[test]
+fn mock_satisfied() {
+    for layout in ["three-col", "one-col"] {
+        fibo()
+            .args(["mock", "--k", "4", "--a", "1", "--b", "1", "--n", "10"])
+            .args(["--layout", layout])
+            .assert()
+            .success()
+            .stdout(contains("MockProver satisfied"));
+    }
+}
+
+#[test]
+fn mock_wrong_output() {
+    fibo()
+        .args(["mock", "--public", "1,1,65"])
+        .assert()
+        .failure()
+        .stdout(contains("not satisfied"))
+        .stdout(contains(
+            "instance column 0\n  copy constraint not satisfied at row 2",
+        ));
+}
+
+#[test]
+fn mock_past_exact_bound() {
+    fibo()
+        .args(["mock", "--k", "auto", "--n", "400"])
+        .assert()
+        .success()
+        .stdout(contains("MockProver satisfied"))
+        .stderr(contains("warning: n = 400 is past 367"));
+    fibo()
+        .args(["mock", "--k", "auto", "--n", "367"])
+        .assert()
+        .success()
+        .stderr(contains("warning").not());
+}
+
+#[test]
+fn mock_too_short() {
+    for n in ["0", "1", "2"] {
+        for k in ["4", "auto"] {
+            fibo()
+                .args(["mock", "--k", k, "--n", n])
+                .assert()
+                .failure()
+                .stderr(contains(format!(
+                    "error: the table needs at least 3 rows, got {n}"
+                )));
+        }
+    }
+}
+
+#[test]
+fn mock_k_auto() {
+    fibo()
+        .args(["mock", "--k", "auto", "--n", "100"])
+        .assert()
+        .success()
+        .stderr(contains("k = 7"))
+        .stdout(contains("MockProver satisfied"));
+    fibo()
+        .args(["mock", "--k", "many"])
+        .assert()
+        .failure()
+        .stderr(contains("expected a number or auto"));
+}
+
+#[test]
+fn mock_input_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let toml = dir.path().join("fib.toml");
+    std::fs::write(&toml, "a = \"1\"\nb = \"0x1\"\nn = 10\n").unwrap();
+    // out is computed and printed, in the Debug form of Fp
+    fibo()
+        .args(["mock", "--input"])
+        .arg(&toml)
+        .assert()
+        .success()
+        .stdout(contains(format!("out {:?}", nth(10))))
+        .stdout(contains("MockProver satisfied"));
+
+    let json = dir.path().join("fib.json");
+    std::fs::write(&json, r#"{"a": "1", "b": "1", "n": 10, "out": "56"}"#).unwrap();
+    fibo()
+        .args(["mock", "--input"])
+        .arg(&json)
+        .assert()
+        .failure()
+        .stdout(contains("not satisfied"));
+
+    std::fs::write(&json, r#"{"a": "1", "n": 10}"#).unwrap();
+    fibo()
+        .args(["mock", "--input"])
+        .arg(&json)
+        .assert()
+        .failure()
+        .stderr(contains("missing field `b`"));
+}
+
+#[test]
+fn prove_and_verify() {
+    let dir = tempfile::tempdir().unwrap();
+    let proof = dir.path().join("proof.json");
+    let params = dir.path().join("params");
+
+    for layout in ["three-col", "one-col"] {
+        fibo()
+            .args(["prove", "--layout", layout, "--out"])
+            .arg(&proof)
+            .arg("--params-dir")
+            .arg(&params)
+            .assert()
+            .success();
+
+        // the envelope names the circuit, so verify takes nothing else
+        fibo()
+            .args(["verify", "--proof"])
+            .arg(&proof)
+            .arg("--params-dir")
+            .arg(&params)
+            .assert()
+            .success()
+            .stdout(contains("proof verified"));
+    }
+
+    // --public pins the statement the envelope must prove
+    let verify = |public: &str| {
+        let mut command = fibo();
+        command
+            .args(["verify", "--proof"])
+            .arg(&proof)
+            .args(["--public", public])
+            .arg("--params-dir")
+            .arg(&params);
+        command.assert()
+    };
+    verify("1,1,55")
+        .success()
+        .stdout(contains("proof verified"));
+    // the statement as 0x numbers, as the witness file takes them
+    verify("0x1,0x1,0x37")
+        .success()
+        .stdout(contains("proof verified"));
+    verify("1,1,65").failure().stderr(contains(
+        "the envelope proves other public inputs than --public",
+    ));
+}
+
+#[test]
+fn verify_decimal_public() {
+    let dir = tempfile::tempdir().unwrap();
+    fibo()
+        .current_dir(dir.path())
+        .args(["prove", "--out", "proof.json", "--params-dir", "params"])
+        .assert()
+        .success();
+
+    fibo()
+        .current_dir(dir.path())
+        .args(["verify", "--proof", "proof.json", "--public", "1,1,55"])
+        .args(["--params-dir", "params"])
+        .assert()
+        .success()
+        .stdout(contains("proof verified"));
+    fibo()
+        .current_dir(dir.path())
+        .args(["verify", "--proof", "proof.json", "--public", "1,1,x"])
+        .args(["--params-dir", "params"])
+        .assert()
+        .failure()
+        .stderr(contains("field `public` is not a number: x"));
+}
+
+#[test]
+fn verify_tampered_envelope() {
+    let dir = tempfile::tempdir().unwrap();
+    let proof = dir.path().join("proof.json");
+    let params = dir.path().join("params");
+
+    fibo()
+        .args(["prove", "--n", "5", "--out"])
+        .arg(&proof)
+        .arg("--params-dir")
+        .arg(&params)
+        .assert()
+        .success();
+
+    // out = 5 claimed as 6, each in the little endian hex of its repr
+    let envelope = std::fs::read_to_string(&proof).unwrap();
+    let claimed = envelope.replace("\"0x05", "\"0x06");
+    assert_ne!(claimed, envelope);
+    std::fs::write(&proof, claimed).unwrap();
+    fibo()
+        .args(["verify", "--proof"])
+        .arg(&proof)
+        .arg("--params-dir")
+        .arg(&params)
+        .assert()
+        .failure()
+        .stderr(contains("verification failed"));
+
+    std::fs::write(&proof, envelope.replace("row_based", "tribonacci")).unwrap();
+    fibo()
+        .args(["verify", "--proof"])
+        .arg(&proof)
+        .assert()
+        .failure()
+        .stderr(contains("unknown circuit \"tribonacci\""));
+}
+
+#[test]
+fn verify_batch() {
+    let dir = tempfile::tempdir().unwrap();
+    let params = dir.path().join("params");
+
+    for (i, b) in ["1", "2", "3"].into_iter().enumerate() {
+        fibo()
+            .args(["prove", "--b", b, "--out"])
+            .arg(dir.path().join(format!("{i}.json")))
+            .arg("--params-dir")
+            .arg(&params)
+            .assert()
+            .success();
+    }
+    let manifest = dir.path().join("manifest.json");
+    std::fs::write(&manifest, "[\"0.json\", \"1.json\", \"2.json\"]").unwrap();
+    let verify = || {
+        let mut verify = fibo();
+        verify
+            .args(["verify", "--batch"])
+            .arg(&manifest)
+            .arg("--params-dir")
+            .arg(&params);
+        verify
+    };
+    verify()
+        .assert()
+        .success()
+        .stdout(contains("3 proofs verified"));
+
+    // the second claims the first's public inputs
+    let first = std::fs::read_to_string(dir.path().join("0.json")).unwrap();
+    let second = std::fs::read_to_string(dir.path().join("1.json")).unwrap();
+    let instances = |envelope: &str| {
+        let start = envelope.find("\"instances\"").unwrap();
+        let end = envelope.find("\"proof\"").unwrap();
+        envelope[start..end].to_owned()
+    };
+    let claimed = second.replace(&instances(&second), &instances(&first));
+    std::fs::write(dir.path().join("1.json"), claimed).unwrap();
+    verify()
+        .assert()
+        .failure()
+        .stderr(contains("proof 1 does not verify"));
+}
+
+#[test]
+fn prove_report() {
+    let dir = tempfile::tempdir().unwrap();
+    let prove = |extra: &[&str]| {
+        let mut command = fibo();
+        command
+            .args(["prove", "--out"])
+            .arg(dir.path().join("proof.json"))
+            .arg("--params-dir")
+            .arg(dir.path().join("params"))
+            .args(extra);
+        command.output().unwrap()
+    };
+
+    let table = String::from_utf8(prove(&[]).stderr).unwrap();
+    assert!(table.contains("rows used     8 of 16"), "{table}");
+
+    let output = prove(&["--json-report"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(report["k"], 4);
+    assert_eq!(report["rows_used"], 8);
+    assert!(report["prove_ms"].as_f64().unwrap() > 0.0);
+
+    // the envelope holds the proof in padded base64, 4 characters per 3 bytes
+    let envelope = std::fs::read_to_string(dir.path().join("proof.json")).unwrap();
+    let envelope: serde_json::Value = serde_json::from_str(&envelope).unwrap();
+    let base64 = envelope["proof"].as_str().unwrap();
+    let bytes = report["proof_bytes"].as_u64().unwrap() as usize;
+    assert_eq!(bytes.div_ceil(3) * 4, base64.len());
+}
+
+/// the hex of the repr of a small field element
+fn fp_hex(value: u8) -> String {
+    format!("0x{value:02x}{}", "00".repeat(31))
+}
+
+#[test]
+fn prove_and_verify_hex() {
+    let dir = tempfile::tempdir().unwrap();
+    let params = dir.path().join("params");
+    let output = fibo()
+        .args([
+            "prove",
+            "--layout",
+            "one-col",
+            "--format",
+            "hex",
+            "--params-dir",
+        ])
+        .arg(&params)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let proof = String::from_utf8(output.stdout).unwrap().trim().to_owned();
+    assert!(proof.starts_with("0x"));
+
+    let verify = |public: &[String], proof: &str| {
+        let mut command = fibo();
+        command
+            .args([
+                "verify", "--layout", "one-col", "--format", "hex", "--public",
+            ])
+            .arg(public.join(","))
+            .arg(proof)
+            .arg("--params-dir")
+            .arg(&params);
+        command.assert()
+    };
+    let honest = [fp_hex(1), fp_hex(1), fp_to_hex(&nth(10))];
+    verify(&honest, &proof)
+        .success()
+        .stdout(contains("proof verified"));
+    verify(&[fp_hex(1), fp_hex(1), fp_hex(56)], &proof)
+        .failure()
+        .stderr(contains("verification failed"));
+
+    verify(&honest, &proof[..proof.len() - 1])
+        .failure()
+        .stderr(contains("proof: hex has an odd number of digits"));
+    verify(&honest, &proof[2..])
+        .failure()
+        .stderr(contains("proof: hex must start with 0x"));
+    let mut bad = proof.clone();
+    bad.replace_range(4..5, "z");
+    verify(&honest, &bad)
+        .failure()
+        .stderr(contains("proof: 'z' at 4 is not a hex digit"));
+    verify(&honest[..2], &proof)
+        .failure()
+        .stderr(contains("takes instance columns of [3] rows, got [2]"));
+    verify(&[fp_hex(1), fp_hex(1), "0x37".to_owned()], &proof)
+        .failure()
+        .stderr(contains("public input: a field element is 32 bytes, got 1"));
+    // decimal public inputs are for envelopes
+    verify(&["1".to_owned(), "1".to_owned(), "55".to_owned()], &proof)
+        .failure()
+        .stderr(contains("public input: hex must start with 0x: 1"));
+}
+
+#[test]
+fn describe() {
+    fibo()
+        .args(["describe", "--layout", "one-col"])
+        .assert()
+        .success()
+        .stdout(contains("advice columns     1"));
+}
+
+#[test]
+fn commit_reveal() {
+    let dir = tempfile::tempdir().unwrap();
+    fibo()
+        .args(["commit-reveal", "--x", "42", "--params-dir"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(contains("commitment 0x"))
+        .stdout(contains("proof verified"));
+}
+
+#[test]
+fn k_sweep() {
+    let dir = tempfile::tempdir().unwrap();
+    for circuit in ["row-based", "single-column"] {
+        let output = Command::cargo_bin("k_sweep")
+            .unwrap()
+            .args(["--circuit", circuit, "--max-k", "5", "--params-dir"])
+            .arg(dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        // a header, then k = 4 and 5
+        let csv = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "k,n,rows_used,keygen_ms,prove_ms,verify_ms,proof_bytes"
+        );
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("4,") && lines[2].starts_with("5,"));
+    }
+}
@@ -0,0 +1,63 @@
+use fibo::fibonacci::{row_based, single_column, vectors, Step};
+use halo2_proofs::{arithmetic::Field, circuit::*, dev::MockProver, pasta::Fp, plonk::*};
+
+/// an external circuit embedding the row based chip: proves the 5th number
+#[derive(Default)]
+struct EmbeddedCircuit<F> {
+    a: Value<F>,
+    b: Value<F>,
+}
+
+impl<F: Field> Circuit<F> for EmbeddedCircuit<F> {
+    type Config = row_based::FiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        row_based::FiboChip::configure(meta, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = row_based::FiboChip::construct(config);
+
+        let (_, b, c) =
+            chip.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
+        let step = |index| Step { index, of: 5 };
+        let d = chip.assign_row(layouter.namespace(|| "next row"), step(4), &b, &c)?;
+        let e = chip.assign_row(layouter.namespace(|| "next row"), step(5), &c, &d)?;
+
+        chip.expose_public(layouter.namespace(|| "out"), &e, 0)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn embed_row_based_chip() {
+    let circuit = EmbeddedCircuit {
+        a: Value::known(Fp::from(1)),
+        b: Value::known(Fp::from(1)),
+    };
+
+    let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(5)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(6)]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn single_column_circuit() {
+    let circuit = single_column::MyCircuit::<Fp>::new(10);
+    let publics = vec![Fp::from(1), Fp::from(1), vectors::nth(10)];
+
+    let prover = MockProver::run(4, &circuit, vec![publics]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
@@ -0,0 +1,178 @@
+//! the pinned verifying key of each example circuit against a checked-in copy
+//! under tests/golden, so a change to configure shows up as a failing test.
+//! UPDATE_GOLDEN=1 cargo test --test golden rewrites the copies
+
+use std::{env, fs, path::PathBuf};
+
+use fibo::{
+    factorial::FactorialCircuit,
+    fibonacci::{
+        doubling::DoublingCircuit, fibo3, linrec::LinRecCircuit, product::ProductCircuit,
+        row_based, single_column, tribonacci, two_column,
+    },
+    gadgets::{
+        decompose::U64Circuit, div_mod::DivisibleCircuit, is_zero::EqualCircuit, lt::LtCircuit,
+        min_max::Max3Circuit, range_check::DiceCircuit, range_lookup::RangeLookupCircuit,
+    },
+    harness::pinned_vk,
+    shuffle::ShuffleCircuit,
+};
+use halo2_proofs::{pasta::Fp, plonk::Circuit};
+
+/// lines of context around the first difference
+const CONTEXT: usize = 3;
+
+/// the first differing line of the two, with the lines around it
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+    let first = (0..expected.len().max(actual.len()))
+        .find(|&i| expected.get(i) != actual.get(i))
+        .unwrap_or(0);
+
+    let mut out = format!("first difference at line {}\n", first + 1);
+    let from = first.saturating_sub(CONTEXT);
+    for line in &expected[from..first] {
+        out += &format!("  {line}\n");
+    }
+    for line in expected.iter().skip(first).take(CONTEXT + 1) {
+        out += &format!("- {line}\n");
+    }
+    for line in actual.iter().skip(first).take(CONTEXT + 1) {
+        out += &format!("+ {line}\n");
+    }
+    out
+}
+
+/// compares the pinned key to tests/golden/<name>.txt, or writes it there
+/// under UPDATE_GOLDEN=1
+fn check<C: Circuit<Fp>>(name: &str, k: u32, circuit: &C) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden"]
+        .iter()
+        .collect::<PathBuf>()
+        .join(format!("{name}.txt"));
+    let actual = pinned_vk(k, circuit);
+
+    if env::var_os("UPDATE_GOLDEN").is_some_and(|v| v == "1") {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "{}: {e}\nrun UPDATE_GOLDEN=1 cargo test --test golden to create it",
+            path.display()
+        )
+    });
+    if expected != actual {
+        panic!(
+            "the verifying key of {name} changed\n{}\nif the change to the circuit is \
+             intended, run UPDATE_GOLDEN=1 cargo test --test golden and commit {}",
+            diff(&expected, &actual),
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn example1() {
+    check("example1", 4, &row_based::MyCircuit::<Fp>::new(10));
+}
+
+#[test]
+fn fibo2() {
+    check("fibo2", 4, &single_column::MyCircuit::<Fp>::new(10));
+}
+
+#[test]
+fn fibo3() {
+    check("fibo3", 4, &fibo3::MyCircuit);
+}
+
+#[test]
+fn tribonacci() {
+    check("tribonacci", 5, &tribonacci::TriboCircuit::<Fp>::new(10));
+}
+
+#[test]
+fn doubling() {
+    let k = DoublingCircuit::<Fp, 8>::min_k();
+    check("doubling", k, &DoublingCircuit::<Fp, 8>::default());
+}
+
+#[test]
+fn shuffle() {
+    let circuit = ShuffleCircuit::<Fp>::new(&[1, 2, 3, 4], &[4, 3, 2, 1]);
+    check("shuffle", circuit.min_k(), &circuit);
+}
+
+#[test]
+fn two_column() {
+    check("two_column", 4, &two_column::MyCircuit::<Fp>::new(10));
+}
+
+#[test]
+fn linrec() {
+    let one = Fp::one();
+    check("linrec", 4, &LinRecCircuit::new(one, one, one, one, 10));
+}
+
+#[test]
+fn product() {
+    let circuit = ProductCircuit::new(Fp::from(2), Fp::from(3), 6);
+    check("product", 4, &circuit);
+}
+
+#[test]
+fn factorial() {
+    check("factorial", 4, &FactorialCircuit::<Fp>::new(5));
+}
+
+#[test]
+fn is_zero() {
+    check("is_zero", 4, &EqualCircuit::new(Fp::from(3), Fp::from(3)));
+}
+
+#[test]
+fn cond_swap() {
+    // the max of three is two compare and swaps
+    check(
+        "cond_swap",
+        7,
+        &Max3Circuit::<Fp, 8>::new([3, 9, 5].map(Fp::from)),
+    );
+}
+
+#[test]
+fn range_check() {
+    check("range_check", 4, &DiceCircuit::new(Fp::from(3)));
+}
+
+#[test]
+fn range_lookup() {
+    let values = [0, 1, 255].map(Fp::from);
+    check("range_lookup", 9, &RangeLookupCircuit::bytes(&values));
+}
+
+#[test]
+fn decompose() {
+    check("decompose", 7, &U64Circuit::new(Fp::from(u64::MAX)));
+}
+
+#[test]
+fn lt() {
+    check("lt", 6, &LtCircuit::<Fp, 8>::new(Fp::from(3), Fp::from(5)));
+}
+
+#[test]
+fn div_mod() {
+    check("div_mod", 7, &DivisibleCircuit::<Fp, 16>::new(Fp::from(91)));
+}
+
+#[test]
+fn diff_shows_the_change() {
+    let diff = diff("a\nb\nc\n", "a\nx\nc\n");
+    assert!(diff.starts_with("first difference at line 2\n"));
+    assert!(diff.contains("  a\n- b\n- c\n+ x\n+ c\n"));
+}
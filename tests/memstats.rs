@@ -0,0 +1,38 @@
+//! the estimate of cost::estimate_prover_memory against the peak the counting
+//! allocator sees, for the fibonacci circuits
+
+use fibo::{
+    cost::estimate_prover_memory,
+    fibonacci::{public_inputs::PublicInputs, row_based, single_column},
+    memstats::{prover_peak, CountingAlloc},
+};
+use halo2_proofs::{
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn within_2x<C: Circuit<Fp>>(k: u32, circuit: impl Fn(usize) -> C) {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    let estimate = estimate_prover_memory(k, &cs).unwrap();
+
+    let n = single_column::max_rows::<Fp>(k);
+    let publics = PublicInputs::new(Fp::one(), Fp::one(), n).to_instance_column();
+    let peak = prover_peak(k, circuit(n), &[&publics]);
+    assert!(
+        peak / 2 <= estimate && estimate <= 2 * peak,
+        "k = {k}: estimated {estimate} bytes, the peak was {peak}"
+    );
+}
+
+// one test, as the peak counts the allocations of every thread. below k = 10
+// the allocations of anything but the polynomials weigh in, and above it the
+// test takes minutes unoptimized
+#[test]
+fn estimate_within_2x() {
+    within_2x(10, row_based::MyCircuit::<Fp>::new);
+    within_2x(10, single_column::MyCircuit::<Fp>::new);
+}
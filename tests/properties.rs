@@ -0,0 +1,75 @@
+use fibo::{
+    fibonacci::{public_inputs::PublicInputs, row_based, single_column},
+    gadgets::{decompose::RoundTripCircuit, xor::XorCircuit},
+};
+use halo2_proofs::{dev::MockProver, pasta::Fp};
+use proptest::prelude::*;
+
+const N: usize = 10;
+
+fn publics(a: u64, b: u64) -> PublicInputs<Fp> {
+    PublicInputs::new(Fp::from(a), Fp::from(b), N)
+}
+
+fn row_based(publics: PublicInputs<Fp>) -> bool {
+    let circuit = row_based::MyCircuit::new(N);
+    let prover = MockProver::run(4, &circuit, vec![publics.to_instance_column()]).unwrap();
+    prover.verify().is_ok()
+}
+
+fn single_column(publics: PublicInputs<Fp>) -> bool {
+    let circuit = single_column::MyCircuit::<Fp>::new(N);
+    let prover = MockProver::run(4, &circuit, vec![publics.to_instance_column()]).unwrap();
+    prover.verify().is_ok()
+}
+
+fn round_trip(value: u64) -> bool {
+    let circuit = RoundTripCircuit::<_, 64>::new(Fp::from(value));
+    let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+    prover.verify().is_ok()
+}
+
+fn xor(a: u64, b: u64, out: u64) -> bool {
+    let circuit = XorCircuit::new(Fp::from(a), Fp::from(b));
+    let k = XorCircuit::<Fp>::min_k();
+    let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(out)]]).unwrap();
+    prover.verify().is_ok()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(200))]
+
+    #[test]
+    fn row_based_random_seeds(a: u64, b: u64, delta in 1u64..) {
+        prop_assert!(row_based(publics(a, b)));
+
+        let mut wrong = publics(a, b);
+        wrong.out += Fp::from(delta);
+        prop_assert!(!row_based(wrong));
+    }
+
+    #[test]
+    fn single_column_random_seeds(a: u64, b: u64, delta in 1u64..) {
+        prop_assert!(single_column(publics(a, b)));
+
+        let mut wrong = publics(a, b);
+        wrong.out += Fp::from(delta);
+        prop_assert!(!single_column(wrong));
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn bits_round_trip(value: u64) {
+        prop_assert!(round_trip(value));
+    }
+
+    #[test]
+    fn xor_nibbles(a in 0u64..16, b in 0u64..16, wrong in 0u64..16) {
+        prop_assert!(xor(a, b, a ^ b));
+        prop_assume!(wrong != a ^ b);
+        prop_assert!(!xor(a, b, wrong));
+    }
+}